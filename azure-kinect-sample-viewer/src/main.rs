@@ -1,3 +1,4 @@
+use azure_kinect::playback_camera::PlaybackCamera;
 use azure_kinect::*;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
@@ -10,15 +11,36 @@ fn main() {
 }
 
 fn main2() -> Result<(), Box<dyn std::error::Error>> {
-    let factory = Factory::new()?;
-    let device = factory.device_open(0)?;
-    let camera_config = DeviceConfiguration::builder()
-        .color_format(ImageFormat::BGRA32)
-        .color_resolution(ColorResolution::_720p)
-        .depth_mode(DepthMode::NFov2x2Binned)
-        .camera_fps(Fps::_30fps)
-        .build();
-    let camera = device.start_cameras(&camera_config)?;
+    //  Views the recording given as the argument, or the first device
+    match std::env::args().nth(1) {
+        Some(path) => {
+            let factory = FactoryRecord::new()?;
+            #[cfg_attr(feature = "depth-view", allow(unused_mut))]
+            let mut playback = factory.playback_open(&path)?;
+            #[cfg(not(feature = "depth-view"))]
+            playback.set_color_conversion(ImageFormat::BGRA32)?;
+            let camera = PlaybackCamera::new(playback)?;
+            camera.set_looping(true);
+            view(&camera)
+        }
+        None => {
+            let factory = Factory::new()?;
+            let device = factory.device_open(0)?;
+            let camera = device.start_cameras(
+                &DeviceConfiguration::builder()
+                    .color_format(ImageFormat::BGRA32)
+                    .color_resolution(ColorResolution::_720p)
+                    .depth_mode(DepthMode::NFov2x2Binned)
+                    .camera_fps(Fps::_30fps)
+                    .build(),
+            )?;
+            view(&DeviceSource::new(&camera, None))
+        }
+    }
+}
+
+fn view<S: SensorSource>(source: &S) -> Result<(), Box<dyn std::error::Error>> {
+    let camera_config = source.get_device_configuration()?;
 
     #[cfg(feature = "depth-view")]
     let image_dimension = camera_config.depth_mode().get_dimension();
@@ -30,7 +52,10 @@ fn main2() -> Result<(), Box<dyn std::error::Error>> {
 
     let window = video_subsystem
         .window(
-            "azure-kinect-sample-viewer",
+            &format!(
+                "azure-kinect-sample-viewer - {}",
+                source.get_serial_number().unwrap_or_default()
+            ),
             image_dimension.width as u32,
             image_dimension.height as u32,
         )
@@ -61,23 +86,21 @@ fn main2() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        if let Ok(capture) = camera.get_capture(1) {
+        if let Ok(capture) = source.get_capture(1) {
             texture.with_lock(None, |buffer: &mut [u8], pitch: usize| {
                 #[cfg(feature = "depth-view")]
                 {
                     let depth_image = capture.get_depth_image();
-                    let width = depth_image.get_width_pixels();
-                    unsafe {
-                        for y in 0..depth_image.get_height_pixels() as usize {
-                            let p = depth_image
-                                .get_buffer()
-                                .add(y * depth_image.get_stride_bytes() as usize)
-                                as *const u16;
-                            let p2 = buffer.as_mut_ptr().add(y * pitch) as *mut u32;
-                            for x in 0..width as isize {
-                                let value = *p.offset(x);
-                                *p2.offset(x) =
-                                    get_depth_color(value, camera_config.depth_mode().get_range())
+                    if let Ok(rows) = depth_image.depth16() {
+                        for (row, line) in rows.zip(buffer.chunks_mut(pitch)) {
+                            for (value, pixel) in row.iter().zip(line.chunks_exact_mut(4)) {
+                                pixel.copy_from_slice(
+                                    &get_depth_color(
+                                        *value,
+                                        camera_config.depth_mode().get_range(),
+                                    )
+                                    .to_ne_bytes(),
+                                );
                             }
                         }
                     }
@@ -86,16 +109,11 @@ fn main2() -> Result<(), Box<dyn std::error::Error>> {
                 #[cfg(not(feature = "depth-view"))]
                 {
                     let image = capture.get_color_image();
-                    let width = image.get_width_pixels();
-                    for y in 0..image.get_height_pixels() as usize {
-                        unsafe {
-                            std::ptr::copy_nonoverlapping(
-                                image
-                                    .get_buffer()
-                                    .add(y * image.get_stride_bytes() as usize),
-                                buffer.as_mut_ptr().add(y * pitch),
-                                (width * 4) as usize,
-                            );
+                    if let Ok(rows) = image.bgra32() {
+                        for (row, line) in rows.zip(buffer.chunks_mut(pitch)) {
+                            for (value, pixel) in row.iter().zip(line.chunks_exact_mut(4)) {
+                                pixel.copy_from_slice(value);
+                            }
                         }
                     }
                 }
@@ -1,8 +1,15 @@
 use crate::loader::Module;
 use crate::Error;
 
-const K4A_LIBNAME: &'static str = "k4a.dll";
-const K4ARECORD_LIBNAME: &'static str = "k4arecord.dll";
+#[cfg(windows)]
+const K4A_LIBNAME: &str = "k4a.dll";
+#[cfg(windows)]
+const K4ARECORD_LIBNAME: &str = "k4arecord.dll";
+
+#[cfg(unix)]
+const K4A_LIBNAME: &str = "libk4a.so.1.4";
+#[cfg(unix)]
+const K4ARECORD_LIBNAME: &str = "libk4arecord.so.1.4";
 
 macro_rules! proc_address {
     ($m:ident, $proc_name:ident) => {
@@ -12,11 +19,12 @@ macro_rules! proc_address {
 
 pub struct Api {
     #[allow(dead_code)]
-    module_k4a: Module, //  Module manages a dynamic link library
+    module_k4a: Option<Module>, //  Module manages a dynamic link library. None if funcs are not backed by one.
     pub funcs: super::k4a::Funcs,
 }
 
 impl Api {
+    #[allow(clippy::missing_transmute_annotations)]
     pub(crate) fn with_module(module_k4a: Module) -> Result<Api, Error> {
         unsafe {
             let funcs = super::k4a::Funcs {
@@ -153,7 +161,19 @@ impl Api {
                 ),
             };
 
-            Ok(Api { module_k4a, funcs })
+            Ok(Api {
+                module_k4a: Some(module_k4a),
+                funcs,
+            })
+        }
+    }
+
+    /// Creates an Api from a function table that is not loaded from the SDK library,
+    /// e.g. the in-process fake SDK in [`crate::mock`] or a custom backend.
+    pub fn with_funcs(funcs: super::k4a::Funcs) -> Api {
+        Api {
+            module_k4a: None,
+            funcs,
         }
     }
 
@@ -179,11 +199,12 @@ impl Api {
 
 pub struct ApiRecord {
     #[allow(dead_code)]
-    pub(crate) module_k4arecord: Module, //  Module manages a dynamic link library
+    pub(crate) module_k4arecord: Option<Module>, //  Module manages a dynamic link library. None if funcs are not backed by one.
     pub funcs: super::k4arecord::Funcs,
 }
 
 impl ApiRecord {
+    #[allow(clippy::missing_transmute_annotations)]
     pub(crate) fn with_module(module_k4arecord: Module) -> Result<ApiRecord, Error> {
         unsafe {
             let funcs = super::k4arecord::Funcs {
@@ -320,12 +341,21 @@ impl ApiRecord {
             };
 
             Ok(ApiRecord {
-                module_k4arecord,
+                module_k4arecord: Some(module_k4arecord),
                 funcs,
             })
         }
     }
 
+    /// Creates an ApiRecord from a function table that is not loaded from the SDK library,
+    /// e.g. the in-process fake SDK in [`crate::mock`] or a custom backend.
+    pub fn with_funcs(funcs: super::k4arecord::Funcs) -> ApiRecord {
+        ApiRecord {
+            module_k4arecord: None,
+            funcs,
+        }
+    }
+
     pub fn new() -> Result<ApiRecord, Error> {
         Self::with_library_directory(
             std::env::current_exe()
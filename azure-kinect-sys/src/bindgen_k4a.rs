@@ -30,7 +30,7 @@ fn bindgen_test_layout__k4a_device_t() {
         concat!("Alignment of ", stringify!(_k4a_device_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_device_t>()))._rsvd as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_device_t, _rsvd),
         0usize,
         concat!(
             "Offset of field: ",
@@ -59,7 +59,7 @@ fn bindgen_test_layout__k4a_capture_t() {
         concat!("Alignment of ", stringify!(_k4a_capture_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_capture_t>()))._rsvd as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_capture_t, _rsvd),
         0usize,
         concat!(
             "Offset of field: ",
@@ -88,7 +88,7 @@ fn bindgen_test_layout__k4a_image_t() {
         concat!("Alignment of ", stringify!(_k4a_image_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_image_t>()))._rsvd as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_image_t, _rsvd),
         0usize,
         concat!(
             "Offset of field: ",
@@ -117,7 +117,7 @@ fn bindgen_test_layout__k4a_transformation_t() {
         concat!("Alignment of ", stringify!(_k4a_transformation_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_transformation_t>()))._rsvd as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_transformation_t, _rsvd),
         0usize,
         concat!(
             "Offset of field: ",
@@ -773,10 +773,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         concat!("Alignment of ", stringify!(_k4a_device_configuration_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).color_format as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, color_format),
         0usize,
         concat!(
             "Offset of field: ",
@@ -786,10 +783,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).color_resolution as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, color_resolution),
         4usize,
         concat!(
             "Offset of field: ",
@@ -799,9 +793,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).depth_mode as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, depth_mode),
         8usize,
         concat!(
             "Offset of field: ",
@@ -811,9 +803,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).camera_fps as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, camera_fps),
         12usize,
         concat!(
             "Offset of field: ",
@@ -823,10 +813,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).synchronized_images_only
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, synchronized_images_only),
         16usize,
         concat!(
             "Offset of field: ",
@@ -836,10 +823,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).depth_delay_off_color_usec
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, depth_delay_off_color_usec),
         20usize,
         concat!(
             "Offset of field: ",
@@ -849,10 +833,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).wired_sync_mode as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, wired_sync_mode),
         24usize,
         concat!(
             "Offset of field: ",
@@ -862,10 +843,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>()))
-                .subordinate_delay_off_master_usec as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, subordinate_delay_off_master_usec),
         28usize,
         concat!(
             "Offset of field: ",
@@ -875,10 +853,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).disable_streaming_indicator
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, disable_streaming_indicator),
         32usize,
         concat!(
             "Offset of field: ",
@@ -941,9 +916,7 @@ fn bindgen_test_layout__k4a_calibration_extrinsics_t() {
         concat!("Alignment of ", stringify!(_k4a_calibration_extrinsics_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_extrinsics_t>())).rotation as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_extrinsics_t, rotation),
         0usize,
         concat!(
             "Offset of field: ",
@@ -953,10 +926,7 @@ fn bindgen_test_layout__k4a_calibration_extrinsics_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_extrinsics_t>())).translation as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_extrinsics_t, translation),
         36usize,
         concat!(
             "Offset of field: ",
@@ -1057,10 +1027,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).cx as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, cx),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1070,10 +1037,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).cy as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, cy),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1083,10 +1047,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).fx as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, fx),
         8usize,
         concat!(
             "Offset of field: ",
@@ -1096,10 +1057,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).fy as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, fy),
         12usize,
         concat!(
             "Offset of field: ",
@@ -1109,10 +1067,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k1 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k1),
         16usize,
         concat!(
             "Offset of field: ",
@@ -1122,10 +1077,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k2 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k2),
         20usize,
         concat!(
             "Offset of field: ",
@@ -1135,10 +1087,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k3 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k3),
         24usize,
         concat!(
             "Offset of field: ",
@@ -1148,10 +1097,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k4 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k4),
         28usize,
         concat!(
             "Offset of field: ",
@@ -1161,10 +1107,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k5 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k5),
         32usize,
         concat!(
             "Offset of field: ",
@@ -1174,10 +1117,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k6 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k6),
         36usize,
         concat!(
             "Offset of field: ",
@@ -1187,10 +1127,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).codx
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, codx),
         40usize,
         concat!(
             "Offset of field: ",
@@ -1200,10 +1137,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).cody
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, cody),
         44usize,
         concat!(
             "Offset of field: ",
@@ -1213,10 +1147,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).p2 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, p2),
         48usize,
         concat!(
             "Offset of field: ",
@@ -1226,10 +1157,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).p1 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, p1),
         52usize,
         concat!(
             "Offset of field: ",
@@ -1239,10 +1167,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).metric_radius
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, metric_radius),
         56usize,
         concat!(
             "Offset of field: ",
@@ -1271,10 +1196,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t>())).param as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t, param),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1284,10 +1206,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t>())).v as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t, v),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1345,9 +1264,7 @@ fn bindgen_test_layout__k4a_calibration_intrinsics_t() {
         concat!("Alignment of ", stringify!(_k4a_calibration_intrinsics_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_intrinsics_t>())).type_ as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_intrinsics_t, type_),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1357,10 +1274,7 @@ fn bindgen_test_layout__k4a_calibration_intrinsics_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_intrinsics_t>())).parameter_count as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_intrinsics_t, parameter_count),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1370,10 +1284,7 @@ fn bindgen_test_layout__k4a_calibration_intrinsics_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_intrinsics_t>())).parameters as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_intrinsics_t, parameters),
         8usize,
         concat!(
             "Offset of field: ",
@@ -1445,9 +1356,7 @@ fn bindgen_test_layout__k4a_calibration_camera_t() {
         concat!("Alignment of ", stringify!(_k4a_calibration_camera_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_camera_t>())).extrinsics as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_camera_t, extrinsics),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1457,9 +1366,7 @@ fn bindgen_test_layout__k4a_calibration_camera_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_camera_t>())).intrinsics as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_camera_t, intrinsics),
         48usize,
         concat!(
             "Offset of field: ",
@@ -1469,10 +1376,7 @@ fn bindgen_test_layout__k4a_calibration_camera_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_camera_t>())).resolution_width as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_camera_t, resolution_width),
         116usize,
         concat!(
             "Offset of field: ",
@@ -1482,10 +1386,7 @@ fn bindgen_test_layout__k4a_calibration_camera_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_camera_t>())).resolution_height as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_camera_t, resolution_height),
         120usize,
         concat!(
             "Offset of field: ",
@@ -1495,9 +1396,7 @@ fn bindgen_test_layout__k4a_calibration_camera_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_camera_t>())).metric_radius as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_camera_t, metric_radius),
         124usize,
         concat!(
             "Offset of field: ",
@@ -1564,10 +1463,7 @@ fn bindgen_test_layout__k4a_calibration_t() {
         concat!("Alignment of ", stringify!(_k4a_calibration_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_t>())).depth_camera_calibration as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_t, depth_camera_calibration),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1577,10 +1473,7 @@ fn bindgen_test_layout__k4a_calibration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_t>())).color_camera_calibration as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_t, color_camera_calibration),
         128usize,
         concat!(
             "Offset of field: ",
@@ -1590,7 +1483,7 @@ fn bindgen_test_layout__k4a_calibration_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_calibration_t>())).extrinsics as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_calibration_t, extrinsics),
         256usize,
         concat!(
             "Offset of field: ",
@@ -1600,7 +1493,7 @@ fn bindgen_test_layout__k4a_calibration_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_calibration_t>())).depth_mode as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_calibration_t, depth_mode),
         1024usize,
         concat!(
             "Offset of field: ",
@@ -1610,9 +1503,7 @@ fn bindgen_test_layout__k4a_calibration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_t>())).color_resolution as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_t, color_resolution),
         1028usize,
         concat!(
             "Offset of field: ",
@@ -1670,7 +1561,7 @@ fn bindgen_test_layout__k4a_version_t() {
         concat!("Alignment of ", stringify!(_k4a_version_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_version_t>())).major as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_version_t, major),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1680,7 +1571,7 @@ fn bindgen_test_layout__k4a_version_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_version_t>())).minor as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_version_t, minor),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1690,7 +1581,7 @@ fn bindgen_test_layout__k4a_version_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_version_t>())).iteration as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_version_t, iteration),
         8usize,
         concat!(
             "Offset of field: ",
@@ -1744,7 +1635,7 @@ fn bindgen_test_layout__k4a_hardware_version_t() {
         concat!("Alignment of ", stringify!(_k4a_hardware_version_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_hardware_version_t>())).rgb as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_hardware_version_t, rgb),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1754,7 +1645,7 @@ fn bindgen_test_layout__k4a_hardware_version_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_hardware_version_t>())).depth as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_hardware_version_t, depth),
         12usize,
         concat!(
             "Offset of field: ",
@@ -1764,7 +1655,7 @@ fn bindgen_test_layout__k4a_hardware_version_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_hardware_version_t>())).audio as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_hardware_version_t, audio),
         24usize,
         concat!(
             "Offset of field: ",
@@ -1774,9 +1665,7 @@ fn bindgen_test_layout__k4a_hardware_version_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_hardware_version_t>())).depth_sensor as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_hardware_version_t, depth_sensor),
         36usize,
         concat!(
             "Offset of field: ",
@@ -1786,9 +1675,7 @@ fn bindgen_test_layout__k4a_hardware_version_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_hardware_version_t>())).firmware_build as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_hardware_version_t, firmware_build),
         48usize,
         concat!(
             "Offset of field: ",
@@ -1798,10 +1685,7 @@ fn bindgen_test_layout__k4a_hardware_version_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_hardware_version_t>())).firmware_signature as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_hardware_version_t, firmware_signature),
         52usize,
         concat!(
             "Offset of field: ",
@@ -1865,7 +1749,7 @@ fn bindgen_test_layout_k4a_float2_t__xy() {
         concat!("Alignment of ", stringify!(k4a_float2_t__xy))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float2_t__xy>())).x as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float2_t__xy, x),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1875,7 +1759,7 @@ fn bindgen_test_layout_k4a_float2_t__xy() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float2_t__xy>())).y as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float2_t__xy, y),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1898,7 +1782,7 @@ fn bindgen_test_layout_k4a_float2_t() {
         concat!("Alignment of ", stringify!(k4a_float2_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float2_t>())).xy as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float2_t, xy),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1908,7 +1792,7 @@ fn bindgen_test_layout_k4a_float2_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float2_t>())).v as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float2_t, v),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1966,7 +1850,7 @@ fn bindgen_test_layout_k4a_float3_t__xyz() {
         concat!("Alignment of ", stringify!(k4a_float3_t__xyz))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float3_t__xyz>())).x as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float3_t__xyz, x),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1976,7 +1860,7 @@ fn bindgen_test_layout_k4a_float3_t__xyz() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float3_t__xyz>())).y as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float3_t__xyz, y),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1986,7 +1870,7 @@ fn bindgen_test_layout_k4a_float3_t__xyz() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float3_t__xyz>())).z as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float3_t__xyz, z),
         8usize,
         concat!(
             "Offset of field: ",
@@ -2009,7 +1893,7 @@ fn bindgen_test_layout_k4a_float3_t() {
         concat!("Alignment of ", stringify!(k4a_float3_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float3_t>())).xyz as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float3_t, xyz),
         0usize,
         concat!(
             "Offset of field: ",
@@ -2019,7 +1903,7 @@ fn bindgen_test_layout_k4a_float3_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float3_t>())).v as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float3_t, v),
         0usize,
         concat!(
             "Offset of field: ",
@@ -2072,7 +1956,7 @@ fn bindgen_test_layout__k4a_imu_sample_t() {
         concat!("Alignment of ", stringify!(_k4a_imu_sample_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_imu_sample_t>())).temperature as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_imu_sample_t, temperature),
         0usize,
         concat!(
             "Offset of field: ",
@@ -2082,7 +1966,7 @@ fn bindgen_test_layout__k4a_imu_sample_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_imu_sample_t>())).acc_sample as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_imu_sample_t, acc_sample),
         4usize,
         concat!(
             "Offset of field: ",
@@ -2092,9 +1976,7 @@ fn bindgen_test_layout__k4a_imu_sample_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_imu_sample_t>())).acc_timestamp_usec as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_imu_sample_t, acc_timestamp_usec),
         16usize,
         concat!(
             "Offset of field: ",
@@ -2104,7 +1986,7 @@ fn bindgen_test_layout__k4a_imu_sample_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_imu_sample_t>())).gyro_sample as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_imu_sample_t, gyro_sample),
         24usize,
         concat!(
             "Offset of field: ",
@@ -2114,9 +1996,7 @@ fn bindgen_test_layout__k4a_imu_sample_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_imu_sample_t>())).gyro_timestamp_usec as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_imu_sample_t, gyro_timestamp_usec),
         40usize,
         concat!(
             "Offset of field: ",
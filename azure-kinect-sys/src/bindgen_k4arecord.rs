@@ -27,7 +27,7 @@ fn bindgen_test_layout__k4a_device_t() {
         concat!("Alignment of ", stringify!(_k4a_device_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_device_t>()))._rsvd as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_device_t, _rsvd),
         0usize,
         concat!(
             "Offset of field: ",
@@ -56,7 +56,7 @@ fn bindgen_test_layout__k4a_capture_t() {
         concat!("Alignment of ", stringify!(_k4a_capture_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_capture_t>()))._rsvd as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_capture_t, _rsvd),
         0usize,
         concat!(
             "Offset of field: ",
@@ -399,10 +399,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         concat!("Alignment of ", stringify!(_k4a_device_configuration_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).color_format as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, color_format),
         0usize,
         concat!(
             "Offset of field: ",
@@ -412,10 +409,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).color_resolution as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, color_resolution),
         4usize,
         concat!(
             "Offset of field: ",
@@ -425,9 +419,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).depth_mode as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, depth_mode),
         8usize,
         concat!(
             "Offset of field: ",
@@ -437,9 +429,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).camera_fps as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, camera_fps),
         12usize,
         concat!(
             "Offset of field: ",
@@ -449,10 +439,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).synchronized_images_only
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, synchronized_images_only),
         16usize,
         concat!(
             "Offset of field: ",
@@ -462,10 +449,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).depth_delay_off_color_usec
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, depth_delay_off_color_usec),
         20usize,
         concat!(
             "Offset of field: ",
@@ -475,10 +459,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).wired_sync_mode as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, wired_sync_mode),
         24usize,
         concat!(
             "Offset of field: ",
@@ -488,10 +469,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>()))
-                .subordinate_delay_off_master_usec as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, subordinate_delay_off_master_usec),
         28usize,
         concat!(
             "Offset of field: ",
@@ -501,10 +479,7 @@ fn bindgen_test_layout__k4a_device_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_device_configuration_t>())).disable_streaming_indicator
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_device_configuration_t, disable_streaming_indicator),
         32usize,
         concat!(
             "Offset of field: ",
@@ -567,9 +542,7 @@ fn bindgen_test_layout__k4a_calibration_extrinsics_t() {
         concat!("Alignment of ", stringify!(_k4a_calibration_extrinsics_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_extrinsics_t>())).rotation as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_extrinsics_t, rotation),
         0usize,
         concat!(
             "Offset of field: ",
@@ -579,10 +552,7 @@ fn bindgen_test_layout__k4a_calibration_extrinsics_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_extrinsics_t>())).translation as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_extrinsics_t, translation),
         36usize,
         concat!(
             "Offset of field: ",
@@ -683,10 +653,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).cx as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, cx),
         0usize,
         concat!(
             "Offset of field: ",
@@ -696,10 +663,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).cy as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, cy),
         4usize,
         concat!(
             "Offset of field: ",
@@ -709,10 +673,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).fx as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, fx),
         8usize,
         concat!(
             "Offset of field: ",
@@ -722,10 +683,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).fy as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, fy),
         12usize,
         concat!(
             "Offset of field: ",
@@ -735,10 +693,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k1 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k1),
         16usize,
         concat!(
             "Offset of field: ",
@@ -748,10 +703,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k2 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k2),
         20usize,
         concat!(
             "Offset of field: ",
@@ -761,10 +713,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k3 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k3),
         24usize,
         concat!(
             "Offset of field: ",
@@ -774,10 +723,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k4 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k4),
         28usize,
         concat!(
             "Offset of field: ",
@@ -787,10 +733,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k5 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k5),
         32usize,
         concat!(
             "Offset of field: ",
@@ -800,10 +743,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).k6 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, k6),
         36usize,
         concat!(
             "Offset of field: ",
@@ -813,10 +753,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).codx
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, codx),
         40usize,
         concat!(
             "Offset of field: ",
@@ -826,10 +763,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).cody
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, cody),
         44usize,
         concat!(
             "Offset of field: ",
@@ -839,10 +773,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).p2 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, p2),
         48usize,
         concat!(
             "Offset of field: ",
@@ -852,10 +783,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).p1 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, p1),
         52usize,
         concat!(
             "Offset of field: ",
@@ -865,10 +793,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t__param() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t__param>())).metric_radius
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t__param, metric_radius),
         56usize,
         concat!(
             "Offset of field: ",
@@ -897,10 +822,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t>())).param as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t, param),
         0usize,
         concat!(
             "Offset of field: ",
@@ -910,10 +832,7 @@ fn bindgen_test_layout_k4a_calibration_intrinsic_parameters_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<k4a_calibration_intrinsic_parameters_t>())).v as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(k4a_calibration_intrinsic_parameters_t, v),
         0usize,
         concat!(
             "Offset of field: ",
@@ -971,9 +890,7 @@ fn bindgen_test_layout__k4a_calibration_intrinsics_t() {
         concat!("Alignment of ", stringify!(_k4a_calibration_intrinsics_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_intrinsics_t>())).type_ as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_intrinsics_t, type_),
         0usize,
         concat!(
             "Offset of field: ",
@@ -983,10 +900,7 @@ fn bindgen_test_layout__k4a_calibration_intrinsics_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_intrinsics_t>())).parameter_count as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_intrinsics_t, parameter_count),
         4usize,
         concat!(
             "Offset of field: ",
@@ -996,10 +910,7 @@ fn bindgen_test_layout__k4a_calibration_intrinsics_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_intrinsics_t>())).parameters as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_intrinsics_t, parameters),
         8usize,
         concat!(
             "Offset of field: ",
@@ -1071,9 +982,7 @@ fn bindgen_test_layout__k4a_calibration_camera_t() {
         concat!("Alignment of ", stringify!(_k4a_calibration_camera_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_camera_t>())).extrinsics as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_camera_t, extrinsics),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1083,9 +992,7 @@ fn bindgen_test_layout__k4a_calibration_camera_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_camera_t>())).intrinsics as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_camera_t, intrinsics),
         48usize,
         concat!(
             "Offset of field: ",
@@ -1095,10 +1002,7 @@ fn bindgen_test_layout__k4a_calibration_camera_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_camera_t>())).resolution_width as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_camera_t, resolution_width),
         116usize,
         concat!(
             "Offset of field: ",
@@ -1108,10 +1012,7 @@ fn bindgen_test_layout__k4a_calibration_camera_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_camera_t>())).resolution_height as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_camera_t, resolution_height),
         120usize,
         concat!(
             "Offset of field: ",
@@ -1121,9 +1022,7 @@ fn bindgen_test_layout__k4a_calibration_camera_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_camera_t>())).metric_radius as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_camera_t, metric_radius),
         124usize,
         concat!(
             "Offset of field: ",
@@ -1190,10 +1089,7 @@ fn bindgen_test_layout__k4a_calibration_t() {
         concat!("Alignment of ", stringify!(_k4a_calibration_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_t>())).depth_camera_calibration as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_t, depth_camera_calibration),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1203,10 +1099,7 @@ fn bindgen_test_layout__k4a_calibration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_t>())).color_camera_calibration as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_t, color_camera_calibration),
         128usize,
         concat!(
             "Offset of field: ",
@@ -1216,7 +1109,7 @@ fn bindgen_test_layout__k4a_calibration_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_calibration_t>())).extrinsics as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_calibration_t, extrinsics),
         256usize,
         concat!(
             "Offset of field: ",
@@ -1226,7 +1119,7 @@ fn bindgen_test_layout__k4a_calibration_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_calibration_t>())).depth_mode as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_calibration_t, depth_mode),
         1024usize,
         concat!(
             "Offset of field: ",
@@ -1236,9 +1129,7 @@ fn bindgen_test_layout__k4a_calibration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_calibration_t>())).color_resolution as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_calibration_t, color_resolution),
         1028usize,
         concat!(
             "Offset of field: ",
@@ -1304,7 +1195,7 @@ fn bindgen_test_layout_k4a_float3_t__xyz() {
         concat!("Alignment of ", stringify!(k4a_float3_t__xyz))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float3_t__xyz>())).x as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float3_t__xyz, x),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1314,7 +1205,7 @@ fn bindgen_test_layout_k4a_float3_t__xyz() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float3_t__xyz>())).y as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float3_t__xyz, y),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1324,7 +1215,7 @@ fn bindgen_test_layout_k4a_float3_t__xyz() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float3_t__xyz>())).z as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float3_t__xyz, z),
         8usize,
         concat!(
             "Offset of field: ",
@@ -1347,7 +1238,7 @@ fn bindgen_test_layout_k4a_float3_t() {
         concat!("Alignment of ", stringify!(k4a_float3_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float3_t>())).xyz as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float3_t, xyz),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1357,7 +1248,7 @@ fn bindgen_test_layout_k4a_float3_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<k4a_float3_t>())).v as *const _ as usize },
+        ::std::mem::offset_of!(k4a_float3_t, v),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1410,7 +1301,7 @@ fn bindgen_test_layout__k4a_imu_sample_t() {
         concat!("Alignment of ", stringify!(_k4a_imu_sample_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_imu_sample_t>())).temperature as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_imu_sample_t, temperature),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1420,7 +1311,7 @@ fn bindgen_test_layout__k4a_imu_sample_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_imu_sample_t>())).acc_sample as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_imu_sample_t, acc_sample),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1430,9 +1321,7 @@ fn bindgen_test_layout__k4a_imu_sample_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_imu_sample_t>())).acc_timestamp_usec as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_imu_sample_t, acc_timestamp_usec),
         16usize,
         concat!(
             "Offset of field: ",
@@ -1442,7 +1331,7 @@ fn bindgen_test_layout__k4a_imu_sample_t() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_imu_sample_t>())).gyro_sample as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_imu_sample_t, gyro_sample),
         24usize,
         concat!(
             "Offset of field: ",
@@ -1452,9 +1341,7 @@ fn bindgen_test_layout__k4a_imu_sample_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_imu_sample_t>())).gyro_timestamp_usec as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_imu_sample_t, gyro_timestamp_usec),
         40usize,
         concat!(
             "Offset of field: ",
@@ -1499,7 +1386,7 @@ fn bindgen_test_layout__k4a_record_t() {
         concat!("Alignment of ", stringify!(_k4a_record_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_record_t>()))._rsvd as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_record_t, _rsvd),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1528,7 +1415,7 @@ fn bindgen_test_layout__k4a_playback_t() {
         concat!("Alignment of ", stringify!(_k4a_playback_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_k4a_playback_t>()))._rsvd as *const _ as usize },
+        ::std::mem::offset_of!(_k4a_playback_t, _rsvd),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1557,9 +1444,7 @@ fn bindgen_test_layout__k4a_playback_data_block_t() {
         concat!("Alignment of ", stringify!(_k4a_playback_data_block_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_playback_data_block_t>()))._rsvd as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_playback_data_block_t, _rsvd),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1652,10 +1537,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         concat!("Alignment of ", stringify!(_k4a_record_configuration_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).color_format as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, color_format),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1665,10 +1547,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).color_resolution as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, color_resolution),
         4usize,
         concat!(
             "Offset of field: ",
@@ -1678,9 +1557,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).depth_mode as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, depth_mode),
         8usize,
         concat!(
             "Offset of field: ",
@@ -1690,9 +1567,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).camera_fps as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, camera_fps),
         12usize,
         concat!(
             "Offset of field: ",
@@ -1702,10 +1577,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).color_track_enabled as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, color_track_enabled),
         16usize,
         concat!(
             "Offset of field: ",
@@ -1715,10 +1587,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).depth_track_enabled as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, depth_track_enabled),
         17usize,
         concat!(
             "Offset of field: ",
@@ -1728,10 +1597,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).ir_track_enabled as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, ir_track_enabled),
         18usize,
         concat!(
             "Offset of field: ",
@@ -1741,10 +1607,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).imu_track_enabled as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, imu_track_enabled),
         19usize,
         concat!(
             "Offset of field: ",
@@ -1754,10 +1617,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).depth_delay_off_color_usec
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, depth_delay_off_color_usec),
         20usize,
         concat!(
             "Offset of field: ",
@@ -1767,10 +1627,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).wired_sync_mode as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, wired_sync_mode),
         24usize,
         concat!(
             "Offset of field: ",
@@ -1780,10 +1637,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>()))
-                .subordinate_delay_off_master_usec as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, subordinate_delay_off_master_usec),
         28usize,
         concat!(
             "Offset of field: ",
@@ -1793,10 +1647,7 @@ fn bindgen_test_layout__k4a_record_configuration_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_configuration_t>())).start_timestamp_offset_usec
-                as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_configuration_t, start_timestamp_offset_usec),
         32usize,
         concat!(
             "Offset of field: ",
@@ -1856,9 +1707,7 @@ fn bindgen_test_layout__k4a_record_video_settings_t() {
         concat!("Alignment of ", stringify!(_k4a_record_video_settings_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_video_settings_t>())).width as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_video_settings_t, width),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1868,9 +1717,7 @@ fn bindgen_test_layout__k4a_record_video_settings_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_video_settings_t>())).height as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_video_settings_t, height),
         8usize,
         concat!(
             "Offset of field: ",
@@ -1880,9 +1727,7 @@ fn bindgen_test_layout__k4a_record_video_settings_t() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_video_settings_t>())).frame_rate as *const _ as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_video_settings_t, frame_rate),
         16usize,
         concat!(
             "Offset of field: ",
@@ -1932,10 +1777,7 @@ fn bindgen_test_layout__k4a_record_subtitle_settings_t() {
         concat!("Alignment of ", stringify!(_k4a_record_subtitle_settings_t))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<_k4a_record_subtitle_settings_t>())).high_freq_data as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(_k4a_record_subtitle_settings_t, high_freq_data),
         0usize,
         concat!(
             "Offset of field: ",
@@ -3,9 +3,13 @@ pub mod k4arecord;
 
 pub mod api;
 pub mod loader;
+#[cfg(feature = "mock")]
+pub mod mock;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum Error {
     Failed,
-    Win32Error(u32),
+    /// The shared library could not be loaded or a symbol could not be resolved.
+    /// Holds the message reported by the platform loader (`GetLastError` / `dlerror`).
+    LoadLibrary(String),
 }
@@ -1,7 +1,10 @@
 use crate::Error;
 use std::ffi::c_void;
+#[cfg(unix)]
+use std::os::raw::c_char;
 use std::ptr;
 
+#[cfg(windows)]
 #[link(name = "kernel32")]
 extern "system" {
     fn GetLastError() -> u32;
@@ -15,16 +18,36 @@ extern "system" {
     fn GetModuleHandleW(lpModuleName: *const u16) -> *const c_void;
 }
 
+#[cfg(unix)]
+const RTLD_NOW: i32 = 0x0002;
+#[cfg(all(unix, any(target_os = "macos", target_os = "ios")))]
+const RTLD_NOLOAD: i32 = 0x0010;
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+const RTLD_NOLOAD: i32 = 0x0004;
+
+#[cfg(unix)]
+#[cfg_attr(any(target_os = "linux", target_os = "android"), link(name = "dl"))]
+extern "C" {
+    fn dlopen(filename: *const c_char, flag: i32) -> *const c_void;
+    fn dlsym(handle: *const c_void, symbol: *const c_char) -> *const c_void;
+    fn dlclose(handle: *const c_void) -> i32;
+    fn dlerror() -> *const c_char;
+}
+
 pub struct Module {
     handle: *const c_void,
     require_free_library: bool,
 }
 
+//  A loaded library can be used and freed from any thread.
+unsafe impl Send for Module {}
+unsafe impl Sync for Module {}
+
 impl Drop for Module {
     fn drop(&mut self) {
-        if self.require_free_library && self.handle != ptr::null() {
+        if self.require_free_library && !self.handle.is_null() {
             unsafe {
-                FreeLibrary(self.handle);
+                Self::free_library(self.handle);
             }
             self.handle = ptr::null();
         }
@@ -38,6 +61,17 @@ impl Module {
             require_free_library,
         }
     }
+}
+
+#[cfg(windows)]
+impl Module {
+    unsafe fn free_library(handle: *const c_void) {
+        FreeLibrary(handle);
+    }
+
+    fn last_error() -> Error {
+        Error::LoadLibrary(format!("Win32 error {}", unsafe { GetLastError() }))
+    }
 
     pub fn load_library(lib_dir: &str, dll_file_name: &str) -> Result<Module, Error> {
         let full_path =
@@ -58,7 +92,7 @@ impl Module {
             if p != ptr::null() {
                 Ok(Module::new(p, true))
             } else {
-                Err(Error::Win32Error(GetLastError()))
+                Err(Self::last_error())
             }
         }
     }
@@ -75,7 +109,7 @@ impl Module {
             if p != ptr::null() {
                 Ok(Module::new(p, false))
             } else {
-                Err(Error::Win32Error(GetLastError()))
+                Err(Self::last_error())
             }
         }
     }
@@ -86,7 +120,71 @@ impl Module {
             if p != ptr::null() {
                 Ok(p)
             } else {
-                Err(Error::Win32Error(GetLastError()))
+                Err(Self::last_error())
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Module {
+    unsafe fn free_library(handle: *const c_void) {
+        dlclose(handle);
+    }
+
+    fn last_error() -> Error {
+        let message = unsafe { dlerror() };
+        Error::LoadLibrary(if message.is_null() {
+            String::from("unknown dlerror")
+        } else {
+            unsafe { std::ffi::CStr::from_ptr(message) }
+                .to_string_lossy()
+                .into_owned()
+        })
+    }
+
+    fn open(file_name: &str, flag: i32) -> Result<*const c_void, Error> {
+        let file_name = std::ffi::CString::new(file_name).map_err(|_| Error::Failed)?;
+        let p = unsafe { dlopen(file_name.as_ptr(), flag) };
+        if !p.is_null() {
+            Ok(p)
+        } else {
+            Err(Self::last_error())
+        }
+    }
+
+    /// Loads `dll_file_name` from `lib_dir`. If the library does not exist there,
+    /// falls back to the dynamic linker search path (LD_LIBRARY_PATH, ld.so.cache, ...)
+    /// where the SDK packages install it.
+    pub fn load_library(lib_dir: &str, dll_file_name: &str) -> Result<Module, Error> {
+        let full_path = std::path::Path::new(if !lib_dir.is_empty() { lib_dir } else { "." })
+            .join(dll_file_name);
+
+        let p = if full_path.exists() {
+            Self::open(full_path.to_str().ok_or(Error::Failed)?, RTLD_NOW)?
+        } else {
+            Self::open(dll_file_name, RTLD_NOW)?
+        };
+        Ok(Module::new(p, true))
+    }
+
+    pub fn get_module(module_name: &str) -> Result<Module, Error> {
+        //  RTLD_NOLOAD only succeeds if the library is already loaded, but still takes a reference.
+        Ok(Module::new(
+            Self::open(module_name, RTLD_NOW | RTLD_NOLOAD)?,
+            true,
+        ))
+    }
+
+    pub fn get_proc_address(&self, proc_name: *const u8) -> Result<*const c_void, Error> {
+        unsafe {
+            //  Clear any stale error so that a null symbol can be told apart from a failure.
+            dlerror();
+            let p = dlsym(self.handle, proc_name as *const c_char);
+            if !p.is_null() {
+                Ok(p)
+            } else {
+                Err(Self::last_error())
             }
         }
     }
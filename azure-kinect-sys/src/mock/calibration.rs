@@ -0,0 +1,861 @@
+//  Factory calibration of the fake devices and the calibration functions.
+//
+//  The raw calibration uses the JSON layout of the device EEPROM. Mode specific calibration is
+//  derived the same way as in the SDK (scale normalized intrinsics to the binned sensor resolution
+//  and shift by the crop offset). Projection ignores lens distortion.
+
+use super::{mock_error, write_buffer};
+use crate::k4a::*;
+use std::os::raw::{c_char, c_int};
+
+#[derive(Clone, Copy)]
+pub(super) struct Rt {
+    pub(super) rotation: [f32; 9],
+    //  Meters, as stored in the factory calibration.
+    pub(super) translation: [f32; 3],
+}
+
+const IDENTITY: Rt = Rt {
+    rotation: [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
+    translation: [0.0, 0.0, 0.0],
+};
+
+#[derive(Clone, Copy)]
+pub(super) struct FactoryCamera {
+    //  cx, cy, fx, fy normalized by the sensor size, k1..k6, codx, cody, p2, p1
+    pub(super) model_parameters: [f32; 14],
+    pub(super) metric_radius: f32,
+    pub(super) rt: Rt,
+    pub(super) sensor_width: i32,
+    pub(super) sensor_height: i32,
+}
+
+#[derive(Clone, Copy)]
+pub(super) struct FactoryCalibration {
+    pub(super) depth: FactoryCamera,
+    pub(super) color: FactoryCamera,
+    pub(super) gyro: Rt,
+    pub(super) accel: Rt,
+}
+
+impl FactoryCalibration {
+    pub(super) fn new() -> FactoryCalibration {
+        //  About 6 degrees tilt between the depth and the color camera.
+        let (s, c) = (6.0f32.to_radians().sin(), 6.0f32.to_radians().cos());
+        let imu_rotation = [0.0, 0.0, -1.0, -1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        FactoryCalibration {
+            depth: FactoryCamera {
+                model_parameters: [
+                    0.5,
+                    0.5,
+                    0.492_187_5,
+                    0.492_187_5,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                ],
+                metric_radius: 1.74,
+                rt: IDENTITY,
+                sensor_width: 1024,
+                sensor_height: 1024,
+            },
+            color: FactoryCamera {
+                model_parameters: [
+                    0.5,
+                    0.5,
+                    0.445_312_5,
+                    0.593_75,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                ],
+                metric_radius: 0.0,
+                rt: Rt {
+                    rotation: [1.0, 0.0, 0.0, 0.0, c, s, 0.0, -s, c],
+                    translation: [-0.032, -0.002, 0.004],
+                },
+                sensor_width: 4096,
+                sensor_height: 3072,
+            },
+            gyro: Rt {
+                rotation: imu_rotation,
+                translation: [-0.051, 0.003, 0.001],
+            },
+            accel: Rt {
+                rotation: imu_rotation,
+                translation: [-0.051, 0.004, 0.001],
+            },
+        }
+    }
+
+    /// Serializes to the JSON layout of the device factory calibration.
+    pub(super) fn to_json(self, serial_number: &str) -> String {
+        fn floats(v: &[f32]) -> String {
+            v.iter()
+                .map(|f| format!("{}", f))
+                .collect::<Vec<_>>()
+                .join(",")
+        }
+        fn rt(rt: &Rt) -> String {
+            format!(
+                "{{\"Rotation\":[{}],\"Translation\":[{}]}}",
+                floats(&rt.rotation),
+                floats(&rt.translation)
+            )
+        }
+        fn camera(camera: &FactoryCamera, location: &str, purpose: &str) -> String {
+            format!(
+                "{{\"Intrinsics\":{{\"ModelParameterCount\":14,\"ModelParameters\":[{}],\
+                 \"ModelType\":\"CALIBRATION_LensDistortionModelBrownConrady\"}},\
+                 \"Location\":\"{}\",\"Purpose\":\"{}\",\"MetricRadius\":{},\"Rt\":{},\
+                 \"SensorHeight\":{},\"SensorWidth\":{},\"Shutter\":\"CALIBRATION_ShutterTypeUndefined\",\
+                 \"ThermalAdjustmentParams\":{{\"Params\":[{}]}}}}",
+                floats(&camera.model_parameters),
+                location,
+                purpose,
+                camera.metric_radius,
+                rt(&camera.rt),
+                camera.sensor_height,
+                camera.sensor_width,
+                floats(&[0.0; 12])
+            )
+        }
+        fn inertial(sensor: &Rt, id: &str, sensor_type: &str) -> String {
+            format!(
+                "{{\"BiasTemperatureModel\":[{}],\"BiasUncertainty\":[{}],\"Id\":\"{}\",\
+                 \"MixingMatrixTemperatureModel\":[{}],\"ModelTypeMask\":16,\"Noise\":[{}],\
+                 \"Rt\":{},\"SecondOrderScaling\":[{}],\"SensorType\":\"{}\",\
+                 \"TemperatureBounds\":[5,60],\"TemperatureC\":0}}",
+                floats(&[0.0; 12]),
+                floats(&[0.0001; 3]),
+                id,
+                floats(
+                    &(0..36)
+                        .map(|i| if i % 16 == 0 { 1.0 } else { 0.0 })
+                        .collect::<Vec<_>>()
+                ),
+                floats(&[0.00095, 0.00095, 0.00095, 0.0, 0.0, 0.0]),
+                rt(sensor),
+                floats(&[0.0; 9]),
+                sensor_type
+            )
+        }
+        format!(
+            "{{\"CalibrationInformation\":{{\"Cameras\":[{},{}],\"InertialSensors\":[{},{}],\
+             \"Metadata\":{{\"SerialId\":\"{}\",\"FactoryCalDate\":\"1/1/2020 12:00:00 AM GMT\",\
+             \"Version\":{{\"Major\":1,\"Minor\":2}},\"DeviceName\":\"AzureKinect-PV\",\"Notes\":\"\"}}}}}}",
+            camera(
+                &self.depth,
+                "CALIBRATION_CameraLocationD0",
+                "CALIBRATION_CameraPurposeDepth"
+            ),
+            camera(
+                &self.color,
+                "CALIBRATION_CameraLocationPV0",
+                "CALIBRATION_CameraPurposePhotoVideo"
+            ),
+            inertial(
+                &self.gyro,
+                "CALIBRATION_InertialSensorId_LSM6DSM",
+                "CALIBRATION_InertialSensorType_Gyro"
+            ),
+            inertial(
+                &self.accel,
+                "CALIBRATION_InertialSensorId_LSM6DSM",
+                "CALIBRATION_InertialSensorType_Accelerometer"
+            ),
+            serial_number
+        )
+    }
+
+    /// Parses the JSON layout of the device factory calibration.
+    pub(super) fn from_json(json: &str) -> Option<FactoryCalibration> {
+        let root = json::parse(json)?;
+        let info = root.get("CalibrationInformation")?;
+        let mut calibration = FactoryCalibration::new();
+        let mut found = [false; 4];
+        for camera in info.get("Cameras")?.as_array()? {
+            let intrinsics = camera.get("Intrinsics")?;
+            let parsed = FactoryCamera {
+                model_parameters: {
+                    let mut p = [0.0f32; 14];
+                    for (d, s) in p
+                        .iter_mut()
+                        .zip(intrinsics.get("ModelParameters")?.as_array()?)
+                    {
+                        *d = s.as_f32()?;
+                    }
+                    p
+                },
+                metric_radius: camera.get("MetricRadius").and_then(|v| v.as_f32())?,
+                rt: parse_rt(camera.get("Rt")?)?,
+                sensor_width: camera.get("SensorWidth")?.as_f32()? as i32,
+                sensor_height: camera.get("SensorHeight")?.as_f32()? as i32,
+            };
+            match camera.get("Location")?.as_str()? {
+                "CALIBRATION_CameraLocationD0" => {
+                    calibration.depth = parsed;
+                    found[0] = true;
+                }
+                "CALIBRATION_CameraLocationPV0" => {
+                    calibration.color = parsed;
+                    found[1] = true;
+                }
+                _ => {}
+            }
+        }
+        for sensor in info.get("InertialSensors")?.as_array()? {
+            let rt = parse_rt(sensor.get("Rt")?)?;
+            match sensor.get("SensorType")?.as_str()? {
+                "CALIBRATION_InertialSensorType_Gyro" => {
+                    calibration.gyro = rt;
+                    found[2] = true;
+                }
+                "CALIBRATION_InertialSensorType_Accelerometer" => {
+                    calibration.accel = rt;
+                    found[3] = true;
+                }
+                _ => {}
+            }
+        }
+        if found.iter().all(|f| *f) {
+            Some(calibration)
+        } else {
+            None
+        }
+    }
+
+    /// Derives the calibration of a depth mode and color resolution.
+    pub(super) fn calibration(
+        &self,
+        depth_mode: k4a_depth_mode_t,
+        color_resolution: k4a_color_resolution_t,
+    ) -> Option<k4a_calibration_t> {
+        //  (binned sensor resolution, crop offset, output resolution)
+        let depth_mode_info = match depth_mode {
+            k4a_depth_mode_t_K4A_DEPTH_MODE_OFF => ([1024, 1024], [0, 0], [0, 0]),
+            k4a_depth_mode_t_K4A_DEPTH_MODE_NFOV_2X2BINNED => ([512, 512], [96, 90], [320, 288]),
+            k4a_depth_mode_t_K4A_DEPTH_MODE_NFOV_UNBINNED => ([1024, 1024], [192, 180], [640, 576]),
+            k4a_depth_mode_t_K4A_DEPTH_MODE_WFOV_2X2BINNED => ([512, 512], [0, 0], [512, 512]),
+            k4a_depth_mode_t_K4A_DEPTH_MODE_WFOV_UNBINNED
+            | k4a_depth_mode_t_K4A_DEPTH_MODE_PASSIVE_IR => ([1024, 1024], [0, 0], [1024, 1024]),
+            _ => return None,
+        };
+        let color_mode_info = match color_resolution {
+            k4a_color_resolution_t_K4A_COLOR_RESOLUTION_OFF => ([4096, 3072], [0, 0], [0, 0]),
+            k4a_color_resolution_t_K4A_COLOR_RESOLUTION_720P => {
+                ([1280, 960], [0, 120], [1280, 720])
+            }
+            k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1080P => {
+                ([1920, 1440], [0, 180], [1920, 1080])
+            }
+            k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1440P => {
+                ([2560, 1920], [0, 240], [2560, 1440])
+            }
+            k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1536P => {
+                ([2048, 1536], [0, 0], [2048, 1536])
+            }
+            k4a_color_resolution_t_K4A_COLOR_RESOLUTION_2160P => {
+                ([3840, 2880], [0, 360], [3840, 2160])
+            }
+            k4a_color_resolution_t_K4A_COLOR_RESOLUTION_3072P => {
+                ([4096, 3072], [0, 0], [4096, 3072])
+            }
+            _ => return None,
+        };
+
+        let sensors = [self.depth.rt, self.color.rt, self.gyro, self.accel];
+        let mut extrinsics = [[to_extrinsics(&IDENTITY); 4]; 4];
+        for (source, row) in extrinsics.iter_mut().enumerate() {
+            for (target, e) in row.iter_mut().enumerate() {
+                *e = compose(&sensors[source], &sensors[target]);
+            }
+        }
+
+        Some(k4a_calibration_t {
+            depth_camera_calibration: mode_specific(&self.depth, depth_mode_info),
+            color_camera_calibration: mode_specific(&self.color, color_mode_info),
+            extrinsics,
+            depth_mode,
+            color_resolution,
+        })
+    }
+}
+
+fn parse_rt(value: &json::Value) -> Option<Rt> {
+    let mut rt = IDENTITY;
+    for (d, s) in rt
+        .rotation
+        .iter_mut()
+        .zip(value.get("Rotation")?.as_array()?)
+    {
+        *d = s.as_f32()?;
+    }
+    for (d, s) in rt
+        .translation
+        .iter_mut()
+        .zip(value.get("Translation")?.as_array()?)
+    {
+        *d = s.as_f32()?;
+    }
+    Some(rt)
+}
+
+fn to_extrinsics(rt: &Rt) -> k4a_calibration_extrinsics_t {
+    k4a_calibration_extrinsics_t {
+        rotation: rt.rotation,
+        translation: [
+            rt.translation[0] * 1000.0,
+            rt.translation[1] * 1000.0,
+            rt.translation[2] * 1000.0,
+        ],
+    }
+}
+
+/// Returns the transformation from `source` to `target`, both given relative to the depth camera.
+fn compose(source: &Rt, target: &Rt) -> k4a_calibration_extrinsics_t {
+    let (rs, rt) = (&source.rotation, &target.rotation);
+    let mut r = [0.0f32; 9];
+    for i in 0..3 {
+        for j in 0..3 {
+            //  target.R * transpose(source.R)
+            r[i * 3 + j] = (0..3).map(|k| rt[i * 3 + k] * rs[j * 3 + k]).sum();
+        }
+    }
+    let mut t = [0.0f32; 3];
+    for (i, t) in t.iter_mut().enumerate() {
+        *t = target.translation[i]
+            - (0..3)
+                .map(|k| r[i * 3 + k] * source.translation[k])
+                .sum::<f32>();
+    }
+    to_extrinsics(&Rt {
+        rotation: r,
+        translation: t,
+    })
+}
+
+fn mode_specific(
+    camera: &FactoryCamera,
+    (binned, crop, output): ([i32; 2], [i32; 2], [i32; 2]),
+) -> k4a_calibration_camera_t {
+    let p = &camera.model_parameters;
+    let mut v = [0.0f32; 15];
+    v[..14].copy_from_slice(p);
+    v[0] = p[0] * binned[0] as f32 - crop[0] as f32 - 0.5;
+    v[1] = p[1] * binned[1] as f32 - crop[1] as f32 - 0.5;
+    v[2] = p[2] * binned[0] as f32;
+    v[3] = p[3] * binned[1] as f32;
+    v[14] = camera.metric_radius;
+    k4a_calibration_camera_t {
+        extrinsics: to_extrinsics(&camera.rt),
+        intrinsics: k4a_calibration_intrinsics_t {
+            type_: k4a_calibration_model_type_t_K4A_CALIBRATION_LENS_DISTORTION_MODEL_BROWN_CONRADY,
+            parameter_count: 14,
+            parameters: k4a_calibration_intrinsic_parameters_t { v },
+        },
+        resolution_width: output[0],
+        resolution_height: output[1],
+        metric_radius: camera.metric_radius,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_calibration_get_from_raw(
+    raw_calibration: *mut c_char,
+    raw_calibration_size: usize,
+    depth_mode: k4a_depth_mode_t,
+    color_resolution: k4a_color_resolution_t,
+    calibration: *mut k4a_calibration_t,
+) -> k4a_result_t {
+    if raw_calibration.is_null() || calibration.is_null() {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let raw = std::slice::from_raw_parts(raw_calibration as *const u8, raw_calibration_size);
+    let raw = match raw.iter().position(|c| *c == 0) {
+        Some(end) => &raw[..end],
+        None => raw,
+    };
+    match std::str::from_utf8(raw)
+        .ok()
+        .and_then(FactoryCalibration::from_json)
+        .and_then(|factory| factory.calibration(depth_mode, color_resolution))
+    {
+        Some(c) => {
+            *calibration = c;
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        None => {
+            mock_error!("failed to parse the raw calibration");
+            k4a_result_t_K4A_RESULT_FAILED
+        }
+    }
+}
+
+pub(super) fn write_raw(
+    factory: &FactoryCalibration,
+    serial_number: &str,
+    data: *mut u8,
+    data_size: *mut usize,
+) -> k4a_buffer_result_t {
+    let mut json = factory.to_json(serial_number).into_bytes();
+    json.push(0);
+    unsafe { write_buffer(&json, data, data_size) }
+}
+
+fn camera(
+    calibration: &k4a_calibration_t,
+    camera: k4a_calibration_type_t,
+) -> Option<&k4a_calibration_camera_t> {
+    match camera {
+        k4a_calibration_type_t_K4A_CALIBRATION_TYPE_DEPTH => {
+            Some(&calibration.depth_camera_calibration)
+        }
+        k4a_calibration_type_t_K4A_CALIBRATION_TYPE_COLOR => {
+            Some(&calibration.color_camera_calibration)
+        }
+        _ => None,
+    }
+}
+
+fn is_valid_type(camera: k4a_calibration_type_t) -> bool {
+    (k4a_calibration_type_t_K4A_CALIBRATION_TYPE_DEPTH
+        ..k4a_calibration_type_t_K4A_CALIBRATION_TYPE_NUM)
+        .contains(&camera)
+}
+
+pub(super) fn transform_3d(
+    calibration: &k4a_calibration_t,
+    p: [f32; 3],
+    source: k4a_calibration_type_t,
+    target: k4a_calibration_type_t,
+) -> [f32; 3] {
+    let e = &calibration.extrinsics[source as usize][target as usize];
+    let r = &e.rotation;
+    [
+        r[0] * p[0] + r[1] * p[1] + r[2] * p[2] + e.translation[0],
+        r[3] * p[0] + r[4] * p[1] + r[5] * p[2] + e.translation[1],
+        r[6] * p[0] + r[7] * p[1] + r[8] * p[2] + e.translation[2],
+    ]
+}
+
+pub(super) fn unproject(
+    camera: &k4a_calibration_camera_t,
+    p: [f32; 2],
+    depth: f32,
+) -> ([f32; 3], bool) {
+    let v = unsafe { &camera.intrinsics.parameters.v };
+    let valid = p[0] >= -0.5
+        && p[1] >= -0.5
+        && p[0] <= camera.resolution_width as f32 - 0.5
+        && p[1] <= camera.resolution_height as f32 - 0.5;
+    (
+        [
+            (p[0] - v[0]) / v[2] * depth,
+            (p[1] - v[1]) / v[3] * depth,
+            depth,
+        ],
+        valid,
+    )
+}
+
+pub(super) fn project(camera: &k4a_calibration_camera_t, p: [f32; 3]) -> ([f32; 2], bool) {
+    let v = unsafe { &camera.intrinsics.parameters.v };
+    if p[2] <= 0.0 {
+        return ([0.0, 0.0], false);
+    }
+    let q = [p[0] / p[2] * v[2] + v[0], p[1] / p[2] * v[3] + v[1]];
+    let valid = q[0] >= -0.5
+        && q[1] >= -0.5
+        && q[0] <= camera.resolution_width as f32 - 0.5
+        && q[1] <= camera.resolution_height as f32 - 0.5;
+    (q, valid)
+}
+
+pub(super) unsafe extern "C" fn k4a_calibration_3d_to_3d(
+    calibration: *const k4a_calibration_t,
+    source_point3d_mm: *const k4a_float3_t,
+    source_camera: k4a_calibration_type_t,
+    target_camera: k4a_calibration_type_t,
+    target_point3d_mm: *mut k4a_float3_t,
+) -> k4a_result_t {
+    if calibration.is_null()
+        || source_point3d_mm.is_null()
+        || target_point3d_mm.is_null()
+        || !is_valid_type(source_camera)
+        || !is_valid_type(target_camera)
+    {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    (*target_point3d_mm).v = transform_3d(
+        &*calibration,
+        (*source_point3d_mm).v,
+        source_camera,
+        target_camera,
+    );
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_calibration_2d_to_3d(
+    calibration: *const k4a_calibration_t,
+    source_point2d: *const k4a_float2_t,
+    source_depth_mm: f32,
+    source_camera: k4a_calibration_type_t,
+    target_camera: k4a_calibration_type_t,
+    target_point3d_mm: *mut k4a_float3_t,
+    valid: *mut c_int,
+) -> k4a_result_t {
+    if calibration.is_null()
+        || source_point2d.is_null()
+        || target_point3d_mm.is_null()
+        || valid.is_null()
+        || !is_valid_type(target_camera)
+    {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let source = match camera(&*calibration, source_camera) {
+        Some(source) => source,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let (p, ok) = unproject(source, (*source_point2d).v, source_depth_mm);
+    (*target_point3d_mm).v = transform_3d(&*calibration, p, source_camera, target_camera);
+    *valid = ok as c_int;
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_calibration_3d_to_2d(
+    calibration: *const k4a_calibration_t,
+    source_point3d_mm: *const k4a_float3_t,
+    source_camera: k4a_calibration_type_t,
+    target_camera: k4a_calibration_type_t,
+    target_point2d: *mut k4a_float2_t,
+    valid: *mut c_int,
+) -> k4a_result_t {
+    if calibration.is_null()
+        || source_point3d_mm.is_null()
+        || target_point2d.is_null()
+        || valid.is_null()
+        || !is_valid_type(source_camera)
+    {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let target = match camera(&*calibration, target_camera) {
+        Some(target) => target,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let p = transform_3d(
+        &*calibration,
+        (*source_point3d_mm).v,
+        source_camera,
+        target_camera,
+    );
+    let (q, ok) = project(target, p);
+    (*target_point2d).v = q;
+    *valid = ok as c_int;
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_calibration_2d_to_2d(
+    calibration: *const k4a_calibration_t,
+    source_point2d: *const k4a_float2_t,
+    source_depth_mm: f32,
+    source_camera: k4a_calibration_type_t,
+    target_camera: k4a_calibration_type_t,
+    target_point2d: *mut k4a_float2_t,
+    valid: *mut c_int,
+) -> k4a_result_t {
+    if calibration.is_null()
+        || source_point2d.is_null()
+        || target_point2d.is_null()
+        || valid.is_null()
+    {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let (source, target) = match (
+        camera(&*calibration, source_camera),
+        camera(&*calibration, target_camera),
+    ) {
+        (Some(source), Some(target)) => (source, target),
+        _ => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let (p, ok) = unproject(source, (*source_point2d).v, source_depth_mm);
+    let (q, ok2) = project(
+        target,
+        transform_3d(&*calibration, p, source_camera, target_camera),
+    );
+    (*target_point2d).v = q;
+    *valid = (ok && ok2) as c_int;
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_calibration_color_2d_to_depth_2d(
+    calibration: *const k4a_calibration_t,
+    source_point2d: *const k4a_float2_t,
+    depth_image: k4a_image_t,
+    target_point2d: *mut k4a_float2_t,
+    valid: *mut c_int,
+) -> k4a_result_t {
+    let depth = match super::image::image(depth_image) {
+        Some(depth) if depth.format == k4a_image_format_t_K4A_IMAGE_FORMAT_DEPTH16 => depth,
+        _ => {
+            mock_error!("depth_image must be a DEPTH16 image");
+            return k4a_result_t_K4A_RESULT_FAILED;
+        }
+    };
+    if calibration.is_null()
+        || source_point2d.is_null()
+        || target_point2d.is_null()
+        || valid.is_null()
+    {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let calibration = &*calibration;
+    let color = &calibration.color_camera_calibration;
+    let data = depth.data();
+
+    //  Walk along the epipolar line and keep the depth pixel that best agrees with its own depth.
+    let mut best: Option<([f32; 2], f32)> = None;
+    let mut depth_mm = 200.0f32;
+    while depth_mm <= 12000.0 {
+        let (p, _) = unproject(color, (*source_point2d).v, depth_mm);
+        let p = transform_3d(
+            calibration,
+            p,
+            k4a_calibration_type_t_K4A_CALIBRATION_TYPE_COLOR,
+            k4a_calibration_type_t_K4A_CALIBRATION_TYPE_DEPTH,
+        );
+        let (q, ok) = project(&calibration.depth_camera_calibration, p);
+        if ok {
+            let (x, y) = (q[0].round() as i32, q[1].round() as i32);
+            if x >= 0 && y >= 0 && x < depth.width && y < depth.height {
+                let offset = (y * depth.stride + x * 2) as usize;
+                let d = u16::from_le_bytes([data[offset], data[offset + 1]]) as f32;
+                let error = (d - p[2]).abs();
+                if d > 0.0 && error < p[2] * 0.02 && best.is_none_or(|(_, e)| error < e) {
+                    best = Some((q, error));
+                }
+            }
+        }
+        depth_mm += 2.0;
+    }
+    match best {
+        Some((q, _)) => {
+            (*target_point2d).v = q;
+            *valid = 1;
+        }
+        None => {
+            (*target_point2d).v = [0.0, 0.0];
+            *valid = 0;
+        }
+    }
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+//  Minimal JSON reader for the factory calibration.
+mod json {
+    #[allow(dead_code)]
+    pub(super) enum Value {
+        Null,
+        Bool(bool),
+        Number(f64),
+        String(String),
+        Array(Vec<Value>),
+        Object(Vec<(String, Value)>),
+    }
+
+    impl Value {
+        pub(super) fn get(&self, key: &str) -> Option<&Value> {
+            match self {
+                Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+                _ => None,
+            }
+        }
+
+        pub(super) fn as_array(&self) -> Option<&Vec<Value>> {
+            match self {
+                Value::Array(a) => Some(a),
+                _ => None,
+            }
+        }
+
+        pub(super) fn as_str(&self) -> Option<&str> {
+            match self {
+                Value::String(s) => Some(s),
+                _ => None,
+            }
+        }
+
+        pub(super) fn as_f32(&self) -> Option<f32> {
+            match self {
+                Value::Number(n) => Some(*n as f32),
+                _ => None,
+            }
+        }
+    }
+
+    pub(super) fn parse(s: &str) -> Option<Value> {
+        let mut parser = Parser {
+            s: s.as_bytes(),
+            pos: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos == parser.s.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    struct Parser<'a> {
+        s: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> Parser<'a> {
+        fn skip_whitespace(&mut self) {
+            while self.pos < self.s.len() && self.s[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+        }
+
+        fn peek(&mut self) -> Option<u8> {
+            self.skip_whitespace();
+            self.s.get(self.pos).copied()
+        }
+
+        fn expect(&mut self, c: u8) -> Option<()> {
+            if self.peek()? == c {
+                self.pos += 1;
+                Some(())
+            } else {
+                None
+            }
+        }
+
+        fn literal(&mut self, literal: &str, value: Value) -> Option<Value> {
+            if self.s[self.pos..].starts_with(literal.as_bytes()) {
+                self.pos += literal.len();
+                Some(value)
+            } else {
+                None
+            }
+        }
+
+        fn value(&mut self) -> Option<Value> {
+            match self.peek()? {
+                b'{' => {
+                    self.pos += 1;
+                    let mut members = Vec::new();
+                    if self.peek()? == b'}' {
+                        self.pos += 1;
+                        return Some(Value::Object(members));
+                    }
+                    loop {
+                        let key = self.string()?;
+                        self.expect(b':')?;
+                        members.push((key, self.value()?));
+                        match self.peek()? {
+                            b',' => self.pos += 1,
+                            b'}' => {
+                                self.pos += 1;
+                                return Some(Value::Object(members));
+                            }
+                            _ => return None,
+                        }
+                    }
+                }
+                b'[' => {
+                    self.pos += 1;
+                    let mut elements = Vec::new();
+                    if self.peek()? == b']' {
+                        self.pos += 1;
+                        return Some(Value::Array(elements));
+                    }
+                    loop {
+                        elements.push(self.value()?);
+                        match self.peek()? {
+                            b',' => self.pos += 1,
+                            b']' => {
+                                self.pos += 1;
+                                return Some(Value::Array(elements));
+                            }
+                            _ => return None,
+                        }
+                    }
+                }
+                b'"' => self.string().map(Value::String),
+                b't' => self.literal("true", Value::Bool(true)),
+                b'f' => self.literal("false", Value::Bool(false)),
+                b'n' => self.literal("null", Value::Null),
+                _ => {
+                    let start = self.pos;
+                    while self.pos < self.s.len()
+                        && matches!(
+                            self.s[self.pos],
+                            b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'
+                        )
+                    {
+                        self.pos += 1;
+                    }
+                    std::str::from_utf8(&self.s[start..self.pos])
+                        .ok()?
+                        .parse()
+                        .ok()
+                        .map(Value::Number)
+                }
+            }
+        }
+
+        fn string(&mut self) -> Option<String> {
+            self.expect(b'"')?;
+            let mut out = String::new();
+            loop {
+                let c = *self.s.get(self.pos)?;
+                self.pos += 1;
+                match c {
+                    b'"' => return Some(out),
+                    b'\\' => {
+                        let e = *self.s.get(self.pos)?;
+                        self.pos += 1;
+                        out.push(match e {
+                            b'n' => '\n',
+                            b't' => '\t',
+                            b'r' => '\r',
+                            b'u' => {
+                                let hex = std::str::from_utf8(self.s.get(self.pos..self.pos + 4)?)
+                                    .ok()?;
+                                self.pos += 4;
+                                std::char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+                            }
+                            e => e as char,
+                        });
+                    }
+                    _ => {
+                        //  Multi-byte UTF-8 sequences are copied through unchanged.
+                        let start = self.pos - 1;
+                        let mut end = self.pos;
+                        while end < self.s.len() && (self.s[end] & 0xC0) == 0x80 {
+                            end += 1;
+                        }
+                        out.push_str(std::str::from_utf8(&self.s[start..end]).ok()?);
+                        self.pos = end;
+                    }
+                }
+            }
+        }
+    }
+}
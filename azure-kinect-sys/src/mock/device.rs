@@ -0,0 +1,870 @@
+//  Fake devices: cameras and IMU paced in real time, color controls, version and sync jacks.
+
+use super::calibration::FactoryCalibration;
+use super::image::{create_capture, create_image};
+use super::{mock_error, mock_info};
+use crate::k4a::*;
+use std::os::raw::c_char;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+//  Device timestamps of the first frame, like a device that has been running for a while.
+const DEVICE_TIMESTAMP_BASE_USEC: u64 = 200_000;
+const IMU_PERIOD_USEC: u64 = 625;
+//  Captures not read by then are dropped, oldest first.
+const CAPTURE_QUEUE_LENGTH: u64 = 2;
+const IMU_QUEUE_LENGTH: u64 = 1000;
+
+struct Streaming {
+    config: k4a_device_configuration_t,
+    period_usec: u64,
+    started: Instant,
+    next_frame: u64,
+}
+
+struct ImuStreaming {
+    started: Instant,
+    next_sample: u64,
+}
+
+struct ColorControl {
+    command: k4a_color_control_command_t,
+    supports_auto: bool,
+    min: i32,
+    max: i32,
+    step: i32,
+    default: i32,
+    default_mode: k4a_color_control_mode_t,
+}
+
+const AUTO: k4a_color_control_mode_t = k4a_color_control_mode_t_K4A_COLOR_CONTROL_MODE_AUTO;
+const MANUAL: k4a_color_control_mode_t = k4a_color_control_mode_t_K4A_COLOR_CONTROL_MODE_MANUAL;
+
+const COLOR_CONTROLS: [ColorControl; 10] = [
+    ColorControl {
+        command: k4a_color_control_command_t_K4A_COLOR_CONTROL_EXPOSURE_TIME_ABSOLUTE,
+        supports_auto: true,
+        min: 500,
+        max: 133_330,
+        step: 100,
+        default: 16_670,
+        default_mode: AUTO,
+    },
+    ColorControl {
+        command: k4a_color_control_command_t_K4A_COLOR_CONTROL_AUTO_EXPOSURE_PRIORITY,
+        supports_auto: false,
+        min: 0,
+        max: 0,
+        step: 0,
+        default: 0,
+        default_mode: MANUAL,
+    },
+    ColorControl {
+        command: k4a_color_control_command_t_K4A_COLOR_CONTROL_BRIGHTNESS,
+        supports_auto: false,
+        min: 0,
+        max: 255,
+        step: 1,
+        default: 128,
+        default_mode: MANUAL,
+    },
+    ColorControl {
+        command: k4a_color_control_command_t_K4A_COLOR_CONTROL_CONTRAST,
+        supports_auto: false,
+        min: 0,
+        max: 10,
+        step: 1,
+        default: 5,
+        default_mode: MANUAL,
+    },
+    ColorControl {
+        command: k4a_color_control_command_t_K4A_COLOR_CONTROL_SATURATION,
+        supports_auto: false,
+        min: 0,
+        max: 63,
+        step: 1,
+        default: 32,
+        default_mode: MANUAL,
+    },
+    ColorControl {
+        command: k4a_color_control_command_t_K4A_COLOR_CONTROL_SHARPNESS,
+        supports_auto: false,
+        min: 0,
+        max: 4,
+        step: 1,
+        default: 2,
+        default_mode: MANUAL,
+    },
+    ColorControl {
+        command: k4a_color_control_command_t_K4A_COLOR_CONTROL_WHITEBALANCE,
+        supports_auto: true,
+        min: 2500,
+        max: 12500,
+        step: 10,
+        default: 4500,
+        default_mode: AUTO,
+    },
+    ColorControl {
+        command: k4a_color_control_command_t_K4A_COLOR_CONTROL_BACKLIGHT_COMPENSATION,
+        supports_auto: false,
+        min: 0,
+        max: 1,
+        step: 1,
+        default: 0,
+        default_mode: MANUAL,
+    },
+    ColorControl {
+        command: k4a_color_control_command_t_K4A_COLOR_CONTROL_GAIN,
+        supports_auto: false,
+        min: 0,
+        max: 255,
+        step: 1,
+        default: 128,
+        default_mode: MANUAL,
+    },
+    ColorControl {
+        command: k4a_color_control_command_t_K4A_COLOR_CONTROL_POWERLINE_FREQUENCY,
+        supports_auto: false,
+        min: 1,
+        max: 2,
+        step: 1,
+        default: 2,
+        default_mode: MANUAL,
+    },
+];
+
+fn color_control(command: k4a_color_control_command_t) -> Option<(usize, &'static ColorControl)> {
+    COLOR_CONTROLS
+        .iter()
+        .enumerate()
+        .find(|(_, c)| c.command == command)
+}
+
+struct DeviceState {
+    cameras: Option<Streaming>,
+    imu: Option<ImuStreaming>,
+    color_controls: [(k4a_color_control_mode_t, i32); 10],
+}
+
+pub(super) struct MockDevice {
+    index: u32,
+    pub(super) serial_number: String,
+    pub(super) factory_calibration: FactoryCalibration,
+    state: Mutex<DeviceState>,
+}
+
+pub(super) unsafe fn device<'a>(device_handle: k4a_device_t) -> Option<&'a MockDevice> {
+    let device = (device_handle as *const MockDevice).as_ref();
+    if device.is_none() {
+        mock_error!("invalid device handle");
+    }
+    device
+}
+
+/// Serial number of the device at `index`.
+pub(super) fn serial_number(index: u32) -> String {
+    format!("{:012}", 1_000_000 + index)
+}
+
+pub(super) unsafe extern "C" fn k4a_device_get_installed_count() -> u32 {
+    super::DEVICE_COUNT
+}
+
+pub(super) unsafe extern "C" fn k4a_device_open(
+    index: u32,
+    device_handle: *mut k4a_device_t,
+) -> k4a_result_t {
+    if device_handle.is_null() {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    if index >= super::DEVICE_COUNT {
+        mock_error!(
+            "device index {} is out of range, {} devices are installed",
+            index,
+            super::DEVICE_COUNT
+        );
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let mut color_controls = [(AUTO, 0); 10];
+    for (state, control) in color_controls.iter_mut().zip(COLOR_CONTROLS.iter()) {
+        *state = (control.default_mode, control.default);
+    }
+    let device = MockDevice {
+        index,
+        serial_number: serial_number(index),
+        factory_calibration: FactoryCalibration::new(),
+        state: Mutex::new(DeviceState {
+            cameras: None,
+            imu: None,
+            color_controls,
+        }),
+    };
+    mock_info!("opened device {} ({})", index, device.serial_number);
+    *device_handle = Box::into_raw(Box::new(device)) as k4a_device_t;
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_device_close(device_handle: k4a_device_t) {
+    if !device_handle.is_null() {
+        drop(Box::from_raw(device_handle as *mut MockDevice));
+    }
+}
+
+fn validate_configuration(config: &k4a_device_configuration_t) -> Result<(), String> {
+    let period = super::frame_period_usec(config.camera_fps)
+        .ok_or_else(|| format!("invalid camera_fps {}", config.camera_fps))?;
+    let color = super::color_resolution(config.color_resolution);
+    let depth = super::depth_mode_resolution(config.depth_mode);
+    if config.color_resolution != k4a_color_resolution_t_K4A_COLOR_RESOLUTION_OFF && color.is_none()
+    {
+        return Err(format!(
+            "invalid color_resolution {}",
+            config.color_resolution
+        ));
+    }
+    if config.depth_mode != k4a_depth_mode_t_K4A_DEPTH_MODE_OFF && depth.is_none() {
+        return Err(format!("invalid depth_mode {}", config.depth_mode));
+    }
+    if color.is_none() && depth.is_none() {
+        return Err(String::from(
+            "at least one of the color and depth cameras must be enabled",
+        ));
+    }
+    if period < 33_334
+        && (config.depth_mode == k4a_depth_mode_t_K4A_DEPTH_MODE_WFOV_UNBINNED
+            || config.color_resolution == k4a_color_resolution_t_K4A_COLOR_RESOLUTION_3072P)
+    {
+        return Err(String::from(
+            "30 fps is not supported by WFOV_UNBINNED and 3072P",
+        ));
+    }
+    if color.is_some() {
+        match config.color_format {
+            k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_MJPG
+            | k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_BGRA32 => {}
+            k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_NV12
+            | k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_YUY2 => {
+                if config.color_resolution != k4a_color_resolution_t_K4A_COLOR_RESOLUTION_720P {
+                    return Err(String::from("NV12 and YUY2 are only supported with 720P"));
+                }
+            }
+            _ => return Err(format!("invalid color_format {}", config.color_format)),
+        }
+    }
+    if config.synchronized_images_only && (color.is_none() || depth.is_none()) {
+        return Err(String::from(
+            "synchronized_images_only requires both the color and depth cameras",
+        ));
+    }
+    if config.wired_sync_mode > k4a_wired_sync_mode_t_K4A_WIRED_SYNC_MODE_SUBORDINATE {
+        return Err(format!(
+            "invalid wired_sync_mode {}",
+            config.wired_sync_mode
+        ));
+    }
+    if config.wired_sync_mode != k4a_wired_sync_mode_t_K4A_WIRED_SYNC_MODE_SUBORDINATE
+        && config.subordinate_delay_off_master_usec != 0
+    {
+        return Err(String::from(
+            "subordinate_delay_off_master_usec requires the subordinate mode",
+        ));
+    }
+    if config.depth_delay_off_color_usec.unsigned_abs() as u64 >= period {
+        return Err(String::from(
+            "depth_delay_off_color_usec must be less than a frame period",
+        ));
+    }
+    Ok(())
+}
+
+pub(super) unsafe extern "C" fn k4a_device_start_cameras(
+    device_handle: k4a_device_t,
+    config: *const k4a_device_configuration_t,
+) -> k4a_result_t {
+    let device = match device(device_handle) {
+        Some(device) => device,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    if config.is_null() {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let config = *config;
+    if let Err(message) = validate_configuration(&config) {
+        mock_error!("{}", message);
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let (sync_in, sync_out) = sync_jack(device.index);
+    if (config.wired_sync_mode == k4a_wired_sync_mode_t_K4A_WIRED_SYNC_MODE_MASTER && !sync_out)
+        || (config.wired_sync_mode == k4a_wired_sync_mode_t_K4A_WIRED_SYNC_MODE_SUBORDINATE
+            && !sync_in)
+    {
+        mock_error!("the sync jack required by wired_sync_mode is not connected");
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let mut state = device.state.lock().unwrap();
+    if state.cameras.is_some() {
+        mock_error!("cameras are already started");
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    state.cameras = Some(Streaming {
+        config,
+        period_usec: super::frame_period_usec(config.camera_fps).unwrap(),
+        started: Instant::now(),
+        next_frame: 0,
+    });
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_device_stop_cameras(device_handle: k4a_device_t) {
+    if let Some(device) = device(device_handle) {
+        let mut state = device.state.lock().unwrap();
+        state.cameras = None;
+        state.imu = None;
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_device_start_imu(device_handle: k4a_device_t) -> k4a_result_t {
+    let device = match device(device_handle) {
+        Some(device) => device,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let mut state = device.state.lock().unwrap();
+    if state.cameras.is_none() {
+        mock_error!("the cameras must be started before the IMU");
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    if state.imu.is_some() {
+        mock_error!("the IMU is already started");
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    state.imu = Some(ImuStreaming {
+        started: Instant::now(),
+        next_sample: 0,
+    });
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_device_stop_imu(device_handle: k4a_device_t) {
+    if let Some(device) = device(device_handle) {
+        device.state.lock().unwrap().imu = None;
+    }
+}
+
+/// Waits until item `next` of a stream with the given period is available, skipping items that
+/// fell out of the queue. Returns the index of the item or None on timeout.
+fn wait_for_item(
+    started: Instant,
+    period_usec: u64,
+    next: u64,
+    queue_length: u64,
+    timeout_in_ms: i32,
+) -> Option<u64> {
+    let elapsed = started.elapsed().as_micros() as u64;
+    //  Item n is complete one period after it started.
+    let available = (elapsed / period_usec).checked_sub(1);
+    match available {
+        Some(latest) if latest >= next => Some(next.max((latest + 1).saturating_sub(queue_length))),
+        _ => {
+            let ready_at = Duration::from_micros((next + 1) * period_usec);
+            let wait = ready_at.saturating_sub(started.elapsed());
+            if timeout_in_ms >= 0 && wait > Duration::from_millis(timeout_in_ms as u64) {
+                if timeout_in_ms > 0 {
+                    thread::sleep(Duration::from_millis(timeout_in_ms as u64));
+                }
+                None
+            } else {
+                thread::sleep(wait);
+                Some(next)
+            }
+        }
+    }
+}
+
+fn system_timestamp_nsec() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// Fills `image` with a frame pattern. Depth is a tilted plane with an invalid border.
+fn fill_image(image: k4a_image_t, format: k4a_image_format_t, frame: u64) {
+    let image = unsafe { super::image::image(image) }.unwrap();
+    let (width, height, stride) = (
+        image.width as usize,
+        image.height as usize,
+        image.stride as usize,
+    );
+    let data = image.data_mut();
+    match format {
+        k4a_image_format_t_K4A_IMAGE_FORMAT_DEPTH16 => {
+            for y in 0..height {
+                for x in 0..width {
+                    let border = x < 4 || y < 4 || x + 4 >= width || y + 4 >= height;
+                    let d: u16 = if border {
+                        0
+                    } else {
+                        1000 + ((x + y) % 500) as u16
+                    };
+                    data[y * stride + x * 2..][..2].copy_from_slice(&d.to_le_bytes());
+                }
+            }
+        }
+        k4a_image_format_t_K4A_IMAGE_FORMAT_IR16 => {
+            for y in 0..height {
+                for x in 0..width {
+                    let ir = ((x * 7 + y * 3 + frame as usize) % 1024) as u16;
+                    data[y * stride + x * 2..][..2].copy_from_slice(&ir.to_le_bytes());
+                }
+            }
+        }
+        k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_BGRA32 => {
+            for y in 0..height {
+                for x in 0..width {
+                    data[y * stride + x * 4..][..4].copy_from_slice(&[
+                        x as u8,
+                        y as u8,
+                        frame as u8,
+                        255,
+                    ]);
+                }
+            }
+        }
+        k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_MJPG => {
+            //  Only the JPEG markers, the payload is not decodable.
+            let len = data.len();
+            data[..2].copy_from_slice(&[0xFF, 0xD8]);
+            data[len - 2..].copy_from_slice(&[0xFF, 0xD9]);
+        }
+        _ => {
+            for (i, b) in data.iter_mut().enumerate() {
+                *b = (i as u64 + frame) as u8;
+            }
+        }
+    }
+}
+
+fn create_frame(
+    format: k4a_image_format_t,
+    (width, height): (i32, i32),
+    frame: u64,
+    device_timestamp_usec: u64,
+    exposure_usec: u64,
+    white_balance: u32,
+) -> k4a_image_t {
+    let image = if format == k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_MJPG {
+        //  Compressed frames are much smaller than the raw image.
+        super::image::create_image_from_vec(
+            format,
+            width,
+            height,
+            0,
+            vec![0u8; (width * height / 8) as usize],
+        )
+    } else {
+        create_image(format, width, height, 0)
+    };
+    fill_image(image, format, frame);
+    unsafe { super::image::image(image) }.unwrap().set_metadata(
+        device_timestamp_usec,
+        system_timestamp_nsec(),
+        exposure_usec,
+        white_balance,
+        if format == k4a_image_format_t_K4A_IMAGE_FORMAT_DEPTH16
+            || format == k4a_image_format_t_K4A_IMAGE_FORMAT_IR16
+        {
+            0
+        } else {
+            100
+        },
+    );
+    image
+}
+
+pub(super) unsafe extern "C" fn k4a_device_get_capture(
+    device_handle: k4a_device_t,
+    capture_handle: *mut k4a_capture_t,
+    timeout_in_ms: i32,
+) -> k4a_wait_result_t {
+    let device = match device(device_handle) {
+        Some(device) => device,
+        None => return k4a_wait_result_t_K4A_WAIT_RESULT_FAILED,
+    };
+    if capture_handle.is_null() {
+        return k4a_wait_result_t_K4A_WAIT_RESULT_FAILED;
+    }
+    let (started, period_usec, next_frame) = match device.state.lock().unwrap().cameras.as_ref() {
+        Some(cameras) => (cameras.started, cameras.period_usec, cameras.next_frame),
+        None => {
+            mock_error!("cameras are not started");
+            return k4a_wait_result_t_K4A_WAIT_RESULT_FAILED;
+        }
+    };
+    //  Wait without holding the lock, the IMU and color controls stay usable meanwhile.
+    let frame = match wait_for_item(
+        started,
+        period_usec,
+        next_frame,
+        CAPTURE_QUEUE_LENGTH,
+        timeout_in_ms,
+    ) {
+        Some(frame) => frame,
+        None => return k4a_wait_result_t_K4A_WAIT_RESULT_TIMEOUT,
+    };
+    let (config, color_control) = {
+        let mut state = device.state.lock().unwrap();
+        let color_controls = state.color_controls;
+        match state.cameras.as_mut() {
+            Some(cameras) if cameras.started == started => {
+                cameras.next_frame = cameras.next_frame.max(frame + 1);
+                (cameras.config, color_controls)
+            }
+            _ => {
+                mock_error!("cameras were stopped while waiting for a capture");
+                return k4a_wait_result_t_K4A_WAIT_RESULT_FAILED;
+            }
+        }
+    };
+
+    let timestamp = DEVICE_TIMESTAMP_BASE_USEC
+        + frame * period_usec
+        + config.subordinate_delay_off_master_usec as u64;
+    let (exposure_mode, exposure) = color_control[0];
+    let (white_balance_mode, white_balance) = color_control[6];
+    let mut images = [std::ptr::null_mut(); 3];
+    if let Some(resolution) = super::color_resolution(config.color_resolution) {
+        images[0] = create_frame(
+            config.color_format,
+            resolution,
+            frame,
+            timestamp,
+            if exposure_mode == AUTO {
+                period_usec / 2
+            } else {
+                exposure as u64
+            },
+            if white_balance_mode == AUTO {
+                4500
+            } else {
+                white_balance as u32
+            },
+        );
+    }
+    if let Some(resolution) = super::depth_mode_resolution(config.depth_mode) {
+        let timestamp = (timestamp as i64 + config.depth_delay_off_color_usec as i64) as u64;
+        if config.depth_mode != k4a_depth_mode_t_K4A_DEPTH_MODE_PASSIVE_IR {
+            images[1] = create_frame(
+                k4a_image_format_t_K4A_IMAGE_FORMAT_DEPTH16,
+                resolution,
+                frame,
+                timestamp,
+                0,
+                0,
+            );
+        }
+        images[2] = create_frame(
+            k4a_image_format_t_K4A_IMAGE_FORMAT_IR16,
+            resolution,
+            frame,
+            timestamp,
+            0,
+            0,
+        );
+    }
+    *capture_handle = create_capture(
+        images[0],
+        images[1],
+        images[2],
+        30.0 + (frame % 10) as f32 * 0.1,
+    );
+    k4a_wait_result_t_K4A_WAIT_RESULT_SUCCEEDED
+}
+
+/// Synthesizes IMU sample `n`: the device at rest, slowly turning around its vertical axis.
+pub(super) fn imu_sample(n: u64) -> k4a_imu_sample_t {
+    let timestamp = DEVICE_TIMESTAMP_BASE_USEC + n * IMU_PERIOD_USEC;
+    let phase = (n % 1600) as f32 / 1600.0 * std::f32::consts::TAU;
+    k4a_imu_sample_t {
+        temperature: 30.5,
+        acc_sample: k4a_float3_t {
+            v: [-9.81, 0.01 * phase.sin(), 0.01 * phase.cos()],
+        },
+        acc_timestamp_usec: timestamp,
+        gyro_sample: k4a_float3_t {
+            v: [0.1, 0.001 * phase.sin(), 0.0],
+        },
+        gyro_timestamp_usec: timestamp - 50,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_device_get_imu_sample(
+    device_handle: k4a_device_t,
+    imu_sample: *mut k4a_imu_sample_t,
+    timeout_in_ms: i32,
+) -> k4a_wait_result_t {
+    let device = match device(device_handle) {
+        Some(device) => device,
+        None => return k4a_wait_result_t_K4A_WAIT_RESULT_FAILED,
+    };
+    if imu_sample.is_null() {
+        return k4a_wait_result_t_K4A_WAIT_RESULT_FAILED;
+    }
+    let (started, next_sample) = match device.state.lock().unwrap().imu.as_ref() {
+        Some(imu) => (imu.started, imu.next_sample),
+        None => {
+            mock_error!("the IMU is not started");
+            return k4a_wait_result_t_K4A_WAIT_RESULT_FAILED;
+        }
+    };
+    let n = match wait_for_item(
+        started,
+        IMU_PERIOD_USEC,
+        next_sample,
+        IMU_QUEUE_LENGTH,
+        timeout_in_ms,
+    ) {
+        Some(n) => n,
+        None => return k4a_wait_result_t_K4A_WAIT_RESULT_TIMEOUT,
+    };
+    match device.state.lock().unwrap().imu.as_mut() {
+        Some(imu) if imu.started == started => {
+            imu.next_sample = imu.next_sample.max(n + 1);
+            *imu_sample = self::imu_sample(n);
+            k4a_wait_result_t_K4A_WAIT_RESULT_SUCCEEDED
+        }
+        _ => {
+            mock_error!("the IMU was stopped while waiting for a sample");
+            k4a_wait_result_t_K4A_WAIT_RESULT_FAILED
+        }
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_device_get_serialnum(
+    device_handle: k4a_device_t,
+    serial_number: *mut c_char,
+    serial_number_size: *mut usize,
+) -> k4a_buffer_result_t {
+    match device(device_handle) {
+        Some(device) => {
+            super::write_string(&device.serial_number, serial_number, serial_number_size)
+        }
+        None => k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED,
+    }
+}
+
+/// Firmware version reported by every fake device.
+pub(super) const VERSION: k4a_hardware_version_t = k4a_hardware_version_t {
+    rgb: k4a_version_t {
+        major: 1,
+        minor: 6,
+        iteration: 110,
+    },
+    depth: k4a_version_t {
+        major: 1,
+        minor: 6,
+        iteration: 79,
+    },
+    audio: k4a_version_t {
+        major: 1,
+        minor: 6,
+        iteration: 14,
+    },
+    depth_sensor: k4a_version_t {
+        major: 6109,
+        minor: 7,
+        iteration: 0,
+    },
+    firmware_build: k4a_firmware_build_t_K4A_FIRMWARE_BUILD_RELEASE,
+    firmware_signature: k4a_firmware_signature_t_K4A_FIRMWARE_SIGNATURE_MSFT,
+};
+
+pub(super) unsafe extern "C" fn k4a_device_get_version(
+    device_handle: k4a_device_t,
+    version: *mut k4a_hardware_version_t,
+) -> k4a_result_t {
+    if device(device_handle).is_none() || version.is_null() {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    *version = VERSION;
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) unsafe extern "C" fn k4a_device_get_color_control_capabilities(
+    device_handle: k4a_device_t,
+    command: k4a_color_control_command_t,
+    supports_auto: *mut bool,
+    min_value: *mut i32,
+    max_value: *mut i32,
+    step_value: *mut i32,
+    default_value: *mut i32,
+    default_mode: *mut k4a_color_control_mode_t,
+) -> k4a_result_t {
+    if device(device_handle).is_none()
+        || supports_auto.is_null()
+        || min_value.is_null()
+        || max_value.is_null()
+        || step_value.is_null()
+        || default_value.is_null()
+        || default_mode.is_null()
+    {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    match color_control(command) {
+        Some((_, control)) => {
+            *supports_auto = control.supports_auto;
+            *min_value = control.min;
+            *max_value = control.max;
+            *step_value = control.step;
+            *default_value = control.default;
+            *default_mode = control.default_mode;
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        None => {
+            mock_error!("invalid color control command {}", command);
+            k4a_result_t_K4A_RESULT_FAILED
+        }
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_device_get_color_control(
+    device_handle: k4a_device_t,
+    command: k4a_color_control_command_t,
+    mode: *mut k4a_color_control_mode_t,
+    value: *mut i32,
+) -> k4a_result_t {
+    let device = match device(device_handle) {
+        Some(device) => device,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    if mode.is_null() || value.is_null() {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    match color_control(command) {
+        Some((index, _)) => {
+            let (m, v) = device.state.lock().unwrap().color_controls[index];
+            *mode = m;
+            *value = v;
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        None => {
+            mock_error!("invalid color control command {}", command);
+            k4a_result_t_K4A_RESULT_FAILED
+        }
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_device_set_color_control(
+    device_handle: k4a_device_t,
+    command: k4a_color_control_command_t,
+    mode: k4a_color_control_mode_t,
+    value: i32,
+) -> k4a_result_t {
+    let device = match device(device_handle) {
+        Some(device) => device,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let (index, control) = match color_control(command) {
+        Some(control) => control,
+        None => {
+            mock_error!("invalid color control command {}", command);
+            return k4a_result_t_K4A_RESULT_FAILED;
+        }
+    };
+    let valid = match mode {
+        AUTO => control.supports_auto,
+        MANUAL => value >= control.min && value <= control.max,
+        _ => false,
+    };
+    if !valid {
+        mock_error!(
+            "invalid mode {} or value {} for color control command {}",
+            mode,
+            value,
+            command
+        );
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let mut state = device.state.lock().unwrap();
+    state.color_controls[index] = (
+        mode,
+        if mode == AUTO {
+            state.color_controls[index].1
+        } else {
+            value
+        },
+    );
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_device_get_raw_calibration(
+    device_handle: k4a_device_t,
+    data: *mut u8,
+    data_size: *mut usize,
+) -> k4a_buffer_result_t {
+    match device(device_handle) {
+        Some(device) => super::calibration::write_raw(
+            &device.factory_calibration,
+            &device.serial_number,
+            data,
+            data_size,
+        ),
+        None => k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_device_get_calibration(
+    device_handle: k4a_device_t,
+    depth_mode: k4a_depth_mode_t,
+    color_resolution: k4a_color_resolution_t,
+    calibration: *mut k4a_calibration_t,
+) -> k4a_result_t {
+    let device = match device(device_handle) {
+        Some(device) => device,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    if calibration.is_null() {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    match device
+        .factory_calibration
+        .calibration(depth_mode, color_resolution)
+    {
+        Some(c) => {
+            *calibration = c;
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        None => {
+            mock_error!(
+                "invalid depth_mode {} or color_resolution {}",
+                depth_mode,
+                color_resolution
+            );
+            k4a_result_t_K4A_RESULT_FAILED
+        }
+    }
+}
+
+/// Sync jack state of the device at `index`: (sync in, sync out).
+/// Device 0 is cabled as the master of the others.
+fn sync_jack(index: u32) -> (bool, bool) {
+    (index != 0, index == 0)
+}
+
+pub(super) unsafe extern "C" fn k4a_device_get_sync_jack(
+    device_handle: k4a_device_t,
+    sync_in_jack_connected: *mut bool,
+    sync_out_jack_connected: *mut bool,
+) -> k4a_result_t {
+    let device = match device(device_handle) {
+        Some(device) => device,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    if sync_in_jack_connected.is_null() || sync_out_jack_connected.is_null() {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let (sync_in, sync_out) = sync_jack(device.index);
+    *sync_in_jack_connected = sync_in;
+    *sync_out_jack_connected = sync_out;
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
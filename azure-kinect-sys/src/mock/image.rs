@@ -0,0 +1,496 @@
+//  Reference counted images and captures.
+
+use super::mock_error;
+use crate::k4a::*;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+enum Storage {
+    Owned {
+        _buffer: Vec<u8>,
+    },
+    External {
+        release_cb: k4a_memory_destroy_cb_t,
+        context: usize,
+    },
+}
+
+pub(super) struct MockImage {
+    refcount: AtomicUsize,
+    pub(super) format: k4a_image_format_t,
+    pub(super) width: i32,
+    pub(super) height: i32,
+    pub(super) stride: i32,
+    buffer: *mut u8,
+    size: usize,
+    storage: Storage,
+    device_timestamp_usec: AtomicU64,
+    system_timestamp_nsec: AtomicU64,
+    exposure_usec: AtomicU64,
+    white_balance: AtomicU32,
+    iso_speed: AtomicU32,
+}
+
+//  The buffer is only accessed through the handle like in the SDK.
+unsafe impl Send for MockImage {}
+unsafe impl Sync for MockImage {}
+
+impl Drop for MockImage {
+    fn drop(&mut self) {
+        if let Storage::External {
+            release_cb: Some(release_cb),
+            context,
+        } = self.storage
+        {
+            unsafe { release_cb(self.buffer as *mut c_void, context as *mut c_void) };
+        }
+    }
+}
+
+impl MockImage {
+    pub(super) fn data(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.buffer, self.size) }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub(super) fn data_mut(&self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.buffer, self.size) }
+    }
+
+    pub(super) fn device_timestamp_usec(&self) -> u64 {
+        self.device_timestamp_usec.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn system_timestamp_nsec(&self) -> u64 {
+        self.system_timestamp_nsec.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn exposure_usec(&self) -> u64 {
+        self.exposure_usec.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn white_balance(&self) -> u32 {
+        self.white_balance.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn iso_speed(&self) -> u32 {
+        self.iso_speed.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn set_metadata(
+        &self,
+        device_timestamp_usec: u64,
+        system_timestamp_nsec: u64,
+        exposure_usec: u64,
+        white_balance: u32,
+        iso_speed: u32,
+    ) {
+        self.device_timestamp_usec
+            .store(device_timestamp_usec, Ordering::Relaxed);
+        self.system_timestamp_nsec
+            .store(system_timestamp_nsec, Ordering::Relaxed);
+        self.exposure_usec.store(exposure_usec, Ordering::Relaxed);
+        self.white_balance.store(white_balance, Ordering::Relaxed);
+        self.iso_speed.store(iso_speed, Ordering::Relaxed);
+    }
+
+    fn into_handle(self) -> k4a_image_t {
+        Box::into_raw(Box::new(self)) as k4a_image_t
+    }
+}
+
+/// Returns the image behind a handle, or None for a null handle.
+pub(super) unsafe fn image<'a>(image_handle: k4a_image_t) -> Option<&'a MockImage> {
+    (image_handle as *const MockImage).as_ref()
+}
+
+/// Returns the stride the SDK derives for a format when 0 is passed to `k4a_image_create`.
+fn default_stride(format: k4a_image_format_t, width: i32) -> Option<i32> {
+    match format {
+        k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_NV12
+        | k4a_image_format_t_K4A_IMAGE_FORMAT_CUSTOM8 => Some(width),
+        k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_YUY2
+        | k4a_image_format_t_K4A_IMAGE_FORMAT_DEPTH16
+        | k4a_image_format_t_K4A_IMAGE_FORMAT_IR16
+        | k4a_image_format_t_K4A_IMAGE_FORMAT_CUSTOM16 => Some(width * 2),
+        k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_BGRA32 => Some(width * 4),
+        _ => None,
+    }
+}
+
+/// Allocates an image owned by the fake SDK. Returns null if the parameters are invalid.
+pub(super) fn create_image(
+    format: k4a_image_format_t,
+    width: i32,
+    height: i32,
+    stride: i32,
+) -> k4a_image_t {
+    let stride = if stride == 0 {
+        match default_stride(format, width) {
+            Some(stride) => stride,
+            None => {
+                mock_error!("stride_bytes must be specified for image format {}", format);
+                return ptr::null_mut();
+            }
+        }
+    } else {
+        stride
+    };
+    if width <= 0
+        || height <= 0
+        || stride <= 0
+        || format > k4a_image_format_t_K4A_IMAGE_FORMAT_CUSTOM
+    {
+        mock_error!(
+            "invalid image parameters: format {}, {}x{}, stride {}",
+            format,
+            width,
+            height,
+            stride
+        );
+        return ptr::null_mut();
+    }
+    let size = if format == k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_NV12 {
+        height as usize * 3 / 2 * stride as usize
+    } else {
+        height as usize * stride as usize
+    };
+    create_image_from_vec(format, width, height, stride, vec![0u8; size])
+}
+
+/// Wraps `data` into an image handle. The buffer size is the length of `data`.
+pub(super) fn create_image_from_vec(
+    format: k4a_image_format_t,
+    width: i32,
+    height: i32,
+    stride: i32,
+    mut data: Vec<u8>,
+) -> k4a_image_t {
+    let buffer = data.as_mut_ptr();
+    let size = data.len();
+    MockImage {
+        refcount: AtomicUsize::new(1),
+        format,
+        width,
+        height,
+        stride,
+        buffer,
+        size,
+        storage: Storage::Owned { _buffer: data },
+        device_timestamp_usec: AtomicU64::new(0),
+        system_timestamp_nsec: AtomicU64::new(0),
+        exposure_usec: AtomicU64::new(0),
+        white_balance: AtomicU32::new(0),
+        iso_speed: AtomicU32::new(0),
+    }
+    .into_handle()
+}
+
+pub(super) unsafe extern "C" fn k4a_image_create(
+    format: k4a_image_format_t,
+    width_pixels: c_int,
+    height_pixels: c_int,
+    stride_bytes: c_int,
+    image_handle: *mut k4a_image_t,
+) -> k4a_result_t {
+    if image_handle.is_null() {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    *image_handle = create_image(format, width_pixels, height_pixels, stride_bytes);
+    super::to_result(!(*image_handle).is_null())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) unsafe extern "C" fn k4a_image_create_from_buffer(
+    format: k4a_image_format_t,
+    width_pixels: c_int,
+    height_pixels: c_int,
+    stride_bytes: c_int,
+    buffer: *mut u8,
+    buffer_size: usize,
+    buffer_release_cb: k4a_memory_destroy_cb_t,
+    buffer_release_cb_context: *mut c_void,
+    image_handle: *mut k4a_image_t,
+) -> k4a_result_t {
+    if image_handle.is_null() || buffer.is_null() || width_pixels <= 0 || height_pixels <= 0 {
+        mock_error!("invalid arguments to k4a_image_create_from_buffer");
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    *image_handle = MockImage {
+        refcount: AtomicUsize::new(1),
+        format,
+        width: width_pixels,
+        height: height_pixels,
+        stride: stride_bytes,
+        buffer,
+        size: buffer_size,
+        storage: Storage::External {
+            release_cb: buffer_release_cb,
+            context: buffer_release_cb_context as usize,
+        },
+        device_timestamp_usec: AtomicU64::new(0),
+        system_timestamp_nsec: AtomicU64::new(0),
+        exposure_usec: AtomicU64::new(0),
+        white_balance: AtomicU32::new(0),
+        iso_speed: AtomicU32::new(0),
+    }
+    .into_handle();
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_image_reference(image_handle: k4a_image_t) {
+    if let Some(image) = image(image_handle) {
+        image.refcount.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_image_release(image_handle: k4a_image_t) {
+    if let Some(image) = image(image_handle) {
+        if image.refcount.fetch_sub(1, Ordering::AcqRel) == 1 {
+            drop(Box::from_raw(image_handle as *mut MockImage));
+        }
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_buffer(image_handle: k4a_image_t) -> *mut u8 {
+    image(image_handle).map_or(ptr::null_mut(), |image| image.buffer)
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_size(image_handle: k4a_image_t) -> usize {
+    image(image_handle).map_or(0, |image| image.size)
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_format(
+    image_handle: k4a_image_t,
+) -> k4a_image_format_t {
+    image(image_handle).map_or(k4a_image_format_t_K4A_IMAGE_FORMAT_CUSTOM, |image| {
+        image.format
+    })
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_width_pixels(image_handle: k4a_image_t) -> c_int {
+    image(image_handle).map_or(0, |image| image.width)
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_height_pixels(image_handle: k4a_image_t) -> c_int {
+    image(image_handle).map_or(0, |image| image.height)
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_stride_bytes(image_handle: k4a_image_t) -> c_int {
+    image(image_handle).map_or(0, |image| image.stride)
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_device_timestamp_usec(
+    image_handle: k4a_image_t,
+) -> u64 {
+    image(image_handle).map_or(0, |image| image.device_timestamp_usec())
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_system_timestamp_nsec(
+    image_handle: k4a_image_t,
+) -> u64 {
+    image(image_handle).map_or(0, |image| image.system_timestamp_nsec())
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_exposure_usec(image_handle: k4a_image_t) -> u64 {
+    image(image_handle).map_or(0, |image| image.exposure_usec())
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_white_balance(image_handle: k4a_image_t) -> u32 {
+    image(image_handle).map_or(0, |image| image.white_balance())
+}
+
+pub(super) unsafe extern "C" fn k4a_image_get_iso_speed(image_handle: k4a_image_t) -> u32 {
+    image(image_handle).map_or(0, |image| image.iso_speed())
+}
+
+pub(super) unsafe extern "C" fn k4a_image_set_device_timestamp_usec(
+    image_handle: k4a_image_t,
+    timestamp_usec: u64,
+) {
+    if let Some(image) = image(image_handle) {
+        image
+            .device_timestamp_usec
+            .store(timestamp_usec, Ordering::Relaxed);
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_image_set_system_timestamp_nsec(
+    image_handle: k4a_image_t,
+    timestamp_nsec: u64,
+) {
+    if let Some(image) = image(image_handle) {
+        image
+            .system_timestamp_nsec
+            .store(timestamp_nsec, Ordering::Relaxed);
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_image_set_exposure_usec(
+    image_handle: k4a_image_t,
+    exposure_usec: u64,
+) {
+    if let Some(image) = image(image_handle) {
+        image.exposure_usec.store(exposure_usec, Ordering::Relaxed);
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_image_set_white_balance(
+    image_handle: k4a_image_t,
+    white_balance: u32,
+) {
+    if let Some(image) = image(image_handle) {
+        image.white_balance.store(white_balance, Ordering::Relaxed);
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_image_set_iso_speed(image_handle: k4a_image_t, iso_speed: u32) {
+    if let Some(image) = image(image_handle) {
+        image.iso_speed.store(iso_speed, Ordering::Relaxed);
+    }
+}
+
+const COLOR: usize = 0;
+const DEPTH: usize = 1;
+const IR: usize = 2;
+
+pub(super) struct MockCapture {
+    refcount: AtomicUsize,
+    images: Mutex<[usize; 3]>,
+    temperature_c: AtomicU32,
+}
+
+impl Drop for MockCapture {
+    fn drop(&mut self) {
+        for image in self.images.get_mut().unwrap().iter() {
+            unsafe { k4a_image_release(*image as k4a_image_t) };
+        }
+    }
+}
+
+impl MockCapture {
+    fn get(&self, index: usize) -> k4a_image_t {
+        let image = self.images.lock().unwrap()[index] as k4a_image_t;
+        unsafe { k4a_image_reference(image) };
+        image
+    }
+
+    fn set(&self, index: usize, image: k4a_image_t) {
+        unsafe { k4a_image_reference(image) };
+        let old = std::mem::replace(&mut self.images.lock().unwrap()[index], image as usize);
+        unsafe { k4a_image_release(old as k4a_image_t) };
+    }
+}
+
+unsafe fn capture<'a>(capture_handle: k4a_capture_t) -> Option<&'a MockCapture> {
+    (capture_handle as *const MockCapture).as_ref()
+}
+
+/// Creates a capture which takes over the references of the given images.
+pub(super) fn create_capture(
+    color: k4a_image_t,
+    depth: k4a_image_t,
+    ir: k4a_image_t,
+    temperature_c: f32,
+) -> k4a_capture_t {
+    Box::into_raw(Box::new(MockCapture {
+        refcount: AtomicUsize::new(1),
+        images: Mutex::new([color as usize, depth as usize, ir as usize]),
+        temperature_c: AtomicU32::new(temperature_c.to_bits()),
+    })) as k4a_capture_t
+}
+
+/// Returns new references to the color, depth and IR images of a capture.
+pub(super) unsafe fn capture_images(capture_handle: k4a_capture_t) -> Option<[k4a_image_t; 3]> {
+    capture(capture_handle).map(|capture| [capture.get(COLOR), capture.get(DEPTH), capture.get(IR)])
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_create(
+    capture_handle: *mut k4a_capture_t,
+) -> k4a_result_t {
+    if capture_handle.is_null() {
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    *capture_handle = create_capture(ptr::null_mut(), ptr::null_mut(), ptr::null_mut(), f32::NAN);
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_reference(capture_handle: k4a_capture_t) {
+    if let Some(capture) = capture(capture_handle) {
+        capture.refcount.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_release(capture_handle: k4a_capture_t) {
+    if let Some(capture) = capture(capture_handle) {
+        if capture.refcount.fetch_sub(1, Ordering::AcqRel) == 1 {
+            drop(Box::from_raw(capture_handle as *mut MockCapture));
+        }
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_get_color_image(
+    capture_handle: k4a_capture_t,
+) -> k4a_image_t {
+    capture(capture_handle).map_or(ptr::null_mut(), |capture| capture.get(COLOR))
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_get_depth_image(
+    capture_handle: k4a_capture_t,
+) -> k4a_image_t {
+    capture(capture_handle).map_or(ptr::null_mut(), |capture| capture.get(DEPTH))
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_get_ir_image(
+    capture_handle: k4a_capture_t,
+) -> k4a_image_t {
+    capture(capture_handle).map_or(ptr::null_mut(), |capture| capture.get(IR))
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_set_color_image(
+    capture_handle: k4a_capture_t,
+    image_handle: k4a_image_t,
+) {
+    if let Some(capture) = capture(capture_handle) {
+        capture.set(COLOR, image_handle);
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_set_depth_image(
+    capture_handle: k4a_capture_t,
+    image_handle: k4a_image_t,
+) {
+    if let Some(capture) = capture(capture_handle) {
+        capture.set(DEPTH, image_handle);
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_set_ir_image(
+    capture_handle: k4a_capture_t,
+    image_handle: k4a_image_t,
+) {
+    if let Some(capture) = capture(capture_handle) {
+        capture.set(IR, image_handle);
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_set_temperature_c(
+    capture_handle: k4a_capture_t,
+    temperature_c: f32,
+) {
+    if let Some(capture) = capture(capture_handle) {
+        capture
+            .temperature_c
+            .store(temperature_c.to_bits(), Ordering::Relaxed);
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_capture_get_temperature_c(
+    capture_handle: k4a_capture_t,
+) -> f32 {
+    capture(capture_handle).map_or(f32::NAN, |capture| {
+        f32::from_bits(capture.temperature_c.load(Ordering::Relaxed))
+    })
+}
@@ -0,0 +1,315 @@
+//! In-process fake of the Azure Kinect Sensor SDK.
+//!
+//! [`api`] and [`api_record`] return function tables that behave like `k4a` and `k4arecord`
+//! without any native library or sensor attached, so that code built on the safe wrapper
+//! can be exercised on machines without an Azure Kinect (e.g. CI).
+//!
+//! The fake SDK provides:
+//! - [`DEVICE_COUNT`] devices with stable serial numbers, firmware versions and sync jack state
+//!   (device 0 has its sync out jack connected, the other devices their sync in jack).
+//! - Captures paced in real time by the configured frame rate, carrying synthesized depth, IR and
+//!   color images (all color formats), and IMU samples at 1.6 kHz.
+//! - A factory calibration blob in the same JSON layout as the device, and the mode specific
+//!   calibration derived from it (pinhole intrinsics without lens distortion).
+//! - Calibration and transformation functions consistent with that calibration.
+//! - Recordings kept in memory by path: everything written through `k4a_record_*` can be read back
+//!   with `k4a_playback_*` by the same process.
+
+#![allow(non_upper_case_globals)]
+
+use crate::api::{Api, ApiRecord};
+use crate::k4a::*;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
+use std::sync::Mutex;
+
+mod calibration;
+mod device;
+mod image;
+mod record;
+mod transformation;
+
+/// Number of devices reported by `k4a_device_get_installed_count`.
+pub const DEVICE_COUNT: u32 = 2;
+
+/// Returns an [`Api`] backed by the fake SDK.
+pub fn api() -> Api {
+    Api::with_funcs(Funcs {
+        k4a_device_get_installed_count: device::k4a_device_get_installed_count,
+        k4a_set_debug_message_handler,
+        k4a_set_allocator,
+        k4a_device_open: device::k4a_device_open,
+        k4a_device_close: device::k4a_device_close,
+        k4a_device_get_capture: device::k4a_device_get_capture,
+        k4a_device_get_imu_sample: device::k4a_device_get_imu_sample,
+        k4a_capture_create: image::k4a_capture_create,
+        k4a_capture_release: image::k4a_capture_release,
+        k4a_capture_reference: image::k4a_capture_reference,
+        k4a_capture_get_color_image: image::k4a_capture_get_color_image,
+        k4a_capture_get_depth_image: image::k4a_capture_get_depth_image,
+        k4a_capture_get_ir_image: image::k4a_capture_get_ir_image,
+        k4a_capture_set_color_image: image::k4a_capture_set_color_image,
+        k4a_capture_set_depth_image: image::k4a_capture_set_depth_image,
+        k4a_capture_set_ir_image: image::k4a_capture_set_ir_image,
+        k4a_capture_set_temperature_c: image::k4a_capture_set_temperature_c,
+        k4a_capture_get_temperature_c: image::k4a_capture_get_temperature_c,
+        k4a_image_create: image::k4a_image_create,
+        k4a_image_create_from_buffer: image::k4a_image_create_from_buffer,
+        k4a_image_get_buffer: image::k4a_image_get_buffer,
+        k4a_image_get_size: image::k4a_image_get_size,
+        k4a_image_get_format: image::k4a_image_get_format,
+        k4a_image_get_width_pixels: image::k4a_image_get_width_pixels,
+        k4a_image_get_height_pixels: image::k4a_image_get_height_pixels,
+        k4a_image_get_stride_bytes: image::k4a_image_get_stride_bytes,
+        k4a_image_get_timestamp_usec: image::k4a_image_get_device_timestamp_usec,
+        k4a_image_get_device_timestamp_usec: image::k4a_image_get_device_timestamp_usec,
+        k4a_image_get_system_timestamp_nsec: image::k4a_image_get_system_timestamp_nsec,
+        k4a_image_get_exposure_usec: image::k4a_image_get_exposure_usec,
+        k4a_image_get_white_balance: image::k4a_image_get_white_balance,
+        k4a_image_get_iso_speed: image::k4a_image_get_iso_speed,
+        k4a_image_set_device_timestamp_usec: image::k4a_image_set_device_timestamp_usec,
+        k4a_image_set_timestamp_usec: image::k4a_image_set_device_timestamp_usec,
+        k4a_image_set_system_timestamp_nsec: image::k4a_image_set_system_timestamp_nsec,
+        k4a_image_set_exposure_usec: image::k4a_image_set_exposure_usec,
+        k4a_image_set_exposure_time_usec: image::k4a_image_set_exposure_usec,
+        k4a_image_set_white_balance: image::k4a_image_set_white_balance,
+        k4a_image_set_iso_speed: image::k4a_image_set_iso_speed,
+        k4a_image_reference: image::k4a_image_reference,
+        k4a_image_release: image::k4a_image_release,
+        k4a_device_start_cameras: device::k4a_device_start_cameras,
+        k4a_device_stop_cameras: device::k4a_device_stop_cameras,
+        k4a_device_start_imu: device::k4a_device_start_imu,
+        k4a_device_stop_imu: device::k4a_device_stop_imu,
+        k4a_device_get_serialnum: device::k4a_device_get_serialnum,
+        k4a_device_get_version: device::k4a_device_get_version,
+        k4a_device_get_color_control_capabilities:
+            device::k4a_device_get_color_control_capabilities,
+        k4a_device_get_color_control: device::k4a_device_get_color_control,
+        k4a_device_set_color_control: device::k4a_device_set_color_control,
+        k4a_device_get_raw_calibration: device::k4a_device_get_raw_calibration,
+        k4a_device_get_calibration: device::k4a_device_get_calibration,
+        k4a_device_get_sync_jack: device::k4a_device_get_sync_jack,
+        k4a_calibration_get_from_raw: calibration::k4a_calibration_get_from_raw,
+        k4a_calibration_3d_to_3d: calibration::k4a_calibration_3d_to_3d,
+        k4a_calibration_2d_to_3d: calibration::k4a_calibration_2d_to_3d,
+        k4a_calibration_3d_to_2d: calibration::k4a_calibration_3d_to_2d,
+        k4a_calibration_2d_to_2d: calibration::k4a_calibration_2d_to_2d,
+        k4a_calibration_color_2d_to_depth_2d: calibration::k4a_calibration_color_2d_to_depth_2d,
+        k4a_transformation_create: transformation::k4a_transformation_create,
+        k4a_transformation_destroy: transformation::k4a_transformation_destroy,
+        k4a_transformation_depth_image_to_color_camera:
+            transformation::k4a_transformation_depth_image_to_color_camera,
+        k4a_transformation_depth_image_to_color_camera_custom:
+            transformation::k4a_transformation_depth_image_to_color_camera_custom,
+        k4a_transformation_color_image_to_depth_camera:
+            transformation::k4a_transformation_color_image_to_depth_camera,
+        k4a_transformation_depth_image_to_point_cloud:
+            transformation::k4a_transformation_depth_image_to_point_cloud,
+    })
+}
+
+/// Returns an [`ApiRecord`] backed by the fake SDK.
+///
+/// Handles are interchangeable with the ones of [`api`], e.g. captures read by a playback
+/// can be released through either table.
+pub fn api_record() -> ApiRecord {
+    ApiRecord::with_funcs(crate::k4arecord::Funcs {
+        k4a_record_create: record::k4a_record_create,
+        k4a_record_add_tag: record::k4a_record_add_tag,
+        k4a_record_add_imu_track: record::k4a_record_add_imu_track,
+        k4a_record_add_attachment: record::k4a_record_add_attachment,
+        k4a_record_add_custom_video_track: record::k4a_record_add_custom_video_track,
+        k4a_record_add_custom_subtitle_track: record::k4a_record_add_custom_subtitle_track,
+        k4a_record_write_header: record::k4a_record_write_header,
+        k4a_record_write_capture: record::k4a_record_write_capture,
+        k4a_record_write_imu_sample: record::k4a_record_write_imu_sample,
+        k4a_record_write_custom_track_data: record::k4a_record_write_custom_track_data,
+        k4a_record_flush: record::k4a_record_flush,
+        k4a_record_close: record::k4a_record_close,
+        k4a_playback_open: record::k4a_playback_open,
+        k4a_playback_get_raw_calibration: record::k4a_playback_get_raw_calibration,
+        k4a_playback_get_calibration: record::k4a_playback_get_calibration,
+        k4a_playback_get_record_configuration: record::k4a_playback_get_record_configuration,
+        k4a_playback_check_track_exists: record::k4a_playback_check_track_exists,
+        k4a_playback_get_track_count: record::k4a_playback_get_track_count,
+        k4a_playback_get_track_name: record::k4a_playback_get_track_name,
+        k4a_playback_track_is_builtin: record::k4a_playback_track_is_builtin,
+        k4a_playback_track_get_video_settings: record::k4a_playback_track_get_video_settings,
+        k4a_playback_track_get_codec_id: record::k4a_playback_track_get_codec_id,
+        k4a_playback_track_get_codec_context: record::k4a_playback_track_get_codec_context,
+        k4a_playback_get_tag: record::k4a_playback_get_tag,
+        k4a_playback_set_color_conversion: record::k4a_playback_set_color_conversion,
+        k4a_playback_get_attachment: record::k4a_playback_get_attachment,
+        k4a_playback_get_next_capture: record::k4a_playback_get_next_capture,
+        k4a_playback_get_previous_capture: record::k4a_playback_get_previous_capture,
+        k4a_playback_get_next_imu_sample: record::k4a_playback_get_next_imu_sample,
+        k4a_playback_get_previous_imu_sample: record::k4a_playback_get_previous_imu_sample,
+        k4a_playback_get_next_data_block: record::k4a_playback_get_next_data_block,
+        k4a_playback_get_previous_data_block: record::k4a_playback_get_previous_data_block,
+        k4a_playback_data_block_get_device_timestamp_usec:
+            record::k4a_playback_data_block_get_device_timestamp_usec,
+        k4a_playback_data_block_get_buffer_size: record::k4a_playback_data_block_get_buffer_size,
+        k4a_playback_data_block_get_buffer: record::k4a_playback_data_block_get_buffer,
+        k4a_playback_data_block_release: record::k4a_playback_data_block_release,
+        k4a_playback_seek_timestamp: record::k4a_playback_seek_timestamp,
+        k4a_playback_get_recording_length_usec: record::k4a_playback_get_recording_length_usec,
+        k4a_playback_get_last_timestamp_usec: record::k4a_playback_get_last_timestamp_usec,
+        k4a_playback_close: record::k4a_playback_close,
+    })
+}
+
+struct Logger {
+    message_cb: k4a_logging_message_cb_t,
+    context: usize,
+    min_level: k4a_log_level_t,
+}
+
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+
+unsafe extern "C" fn k4a_set_debug_message_handler(
+    message_cb: k4a_logging_message_cb_t,
+    message_cb_context: *mut c_void,
+    min_level: k4a_log_level_t,
+) -> k4a_result_t {
+    *LOGGER.lock().unwrap() = message_cb.map(|_| Logger {
+        message_cb,
+        context: message_cb_context as usize,
+        min_level,
+    });
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+unsafe extern "C" fn k4a_set_allocator(
+    _allocate: k4a_memory_allocate_cb_t,
+    _free: k4a_memory_destroy_cb_t,
+) -> k4a_result_t {
+    //  Buffers of the fake SDK are always owned by Rust.
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+/// Reports a message to the handler registered by `k4a_set_debug_message_handler`.
+fn log(level: k4a_log_level_t, file: &str, line: u32, message: &str) {
+    let logger = LOGGER.lock().unwrap();
+    if let Some(logger) = logger.as_ref() {
+        if level <= logger.min_level {
+            if let Some(cb) = logger.message_cb {
+                let file = std::ffi::CString::new(file).unwrap_or_default();
+                let message = std::ffi::CString::new(message).unwrap_or_default();
+                unsafe {
+                    cb(
+                        logger.context as *mut c_void,
+                        level,
+                        file.as_ptr(),
+                        line as c_int,
+                        message.as_ptr(),
+                    )
+                };
+            }
+        }
+    }
+}
+
+macro_rules! mock_error {
+    ($($arg:tt)*) => {
+        $crate::mock::log(
+            $crate::k4a::k4a_log_level_t_K4A_LOG_LEVEL_ERROR,
+            file!(),
+            line!(),
+            &format!($($arg)*),
+        )
+    };
+}
+use mock_error;
+
+macro_rules! mock_info {
+    ($($arg:tt)*) => {
+        $crate::mock::log(
+            $crate::k4a::k4a_log_level_t_K4A_LOG_LEVEL_INFO,
+            file!(),
+            line!(),
+            &format!($($arg)*),
+        )
+    };
+}
+use mock_info;
+
+/// Copies `data` to a caller supplied buffer following the `k4a_buffer_result_t` protocol.
+unsafe fn write_buffer(data: &[u8], out: *mut u8, out_size: *mut usize) -> k4a_buffer_result_t {
+    if out_size.is_null() {
+        return k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED;
+    }
+    if out.is_null() || *out_size < data.len() {
+        *out_size = data.len();
+        return k4a_buffer_result_t_K4A_BUFFER_RESULT_TOO_SMALL;
+    }
+    std::ptr::copy_nonoverlapping(data.as_ptr(), out, data.len());
+    *out_size = data.len();
+    k4a_buffer_result_t_K4A_BUFFER_RESULT_SUCCEEDED
+}
+
+/// Copies `s` with a terminating NUL to a caller supplied buffer.
+unsafe fn write_string(s: &str, out: *mut c_char, out_size: *mut usize) -> k4a_buffer_result_t {
+    let mut data = Vec::with_capacity(s.len() + 1);
+    data.extend_from_slice(s.as_bytes());
+    data.push(0);
+    write_buffer(&data, out as *mut u8, out_size)
+}
+
+unsafe fn read_string(s: *const c_char) -> Option<String> {
+    if s.is_null() {
+        None
+    } else {
+        CStr::from_ptr(s).to_str().ok().map(String::from)
+    }
+}
+
+fn to_result(ok: bool) -> k4a_result_t {
+    if ok {
+        k4a_result_t_K4A_RESULT_SUCCEEDED
+    } else {
+        k4a_result_t_K4A_RESULT_FAILED
+    }
+}
+
+/// Returns the nominal frame period of a frame rate in microseconds.
+fn frame_period_usec(camera_fps: k4a_fps_t) -> Option<u64> {
+    match camera_fps {
+        k4a_fps_t_K4A_FRAMES_PER_SECOND_5 => Some(200_000),
+        k4a_fps_t_K4A_FRAMES_PER_SECOND_15 => Some(66_666),
+        k4a_fps_t_K4A_FRAMES_PER_SECOND_30 => Some(33_333),
+        _ => None,
+    }
+}
+
+fn frame_rate(camera_fps: k4a_fps_t) -> u64 {
+    match camera_fps {
+        k4a_fps_t_K4A_FRAMES_PER_SECOND_5 => 5,
+        k4a_fps_t_K4A_FRAMES_PER_SECOND_15 => 15,
+        _ => 30,
+    }
+}
+
+/// Returns the image size of a depth mode, or None for `K4A_DEPTH_MODE_OFF`.
+fn depth_mode_resolution(depth_mode: k4a_depth_mode_t) -> Option<(i32, i32)> {
+    match depth_mode {
+        k4a_depth_mode_t_K4A_DEPTH_MODE_NFOV_2X2BINNED => Some((320, 288)),
+        k4a_depth_mode_t_K4A_DEPTH_MODE_NFOV_UNBINNED => Some((640, 576)),
+        k4a_depth_mode_t_K4A_DEPTH_MODE_WFOV_2X2BINNED => Some((512, 512)),
+        k4a_depth_mode_t_K4A_DEPTH_MODE_WFOV_UNBINNED => Some((1024, 1024)),
+        k4a_depth_mode_t_K4A_DEPTH_MODE_PASSIVE_IR => Some((1024, 1024)),
+        _ => None,
+    }
+}
+
+/// Returns the image size of a color resolution, or None for `K4A_COLOR_RESOLUTION_OFF`.
+fn color_resolution(color_resolution: k4a_color_resolution_t) -> Option<(i32, i32)> {
+    match color_resolution {
+        k4a_color_resolution_t_K4A_COLOR_RESOLUTION_720P => Some((1280, 720)),
+        k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1080P => Some((1920, 1080)),
+        k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1440P => Some((2560, 1440)),
+        k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1536P => Some((2048, 1536)),
+        k4a_color_resolution_t_K4A_COLOR_RESOLUTION_2160P => Some((3840, 2160)),
+        k4a_color_resolution_t_K4A_COLOR_RESOLUTION_3072P => Some((4096, 3072)),
+        _ => None,
+    }
+}
@@ -0,0 +1,1229 @@
+//  Recordings kept in memory by path, written by k4a_record_* and read back by k4a_playback_*.
+
+use super::calibration::FactoryCalibration;
+use super::device::{MockDevice, VERSION};
+use super::image::{capture_images, create_capture, create_image_from_vec, image};
+use super::{mock_error, read_string, to_result, write_buffer, write_string};
+use crate::k4a;
+use crate::k4arecord::*;
+use std::os::raw::c_char;
+use std::sync::{Arc, Mutex};
+
+const CALIBRATION_FILE: &str = "calibration.json";
+const BUILTIN_TRACKS: [&str; 4] = ["COLOR", "DEPTH", "IR", "IMU"];
+
+#[derive(Clone)]
+struct StoredImage {
+    format: k4a_image_format_t,
+    width: i32,
+    height: i32,
+    stride: i32,
+    data: Vec<u8>,
+    device_timestamp_usec: u64,
+    system_timestamp_nsec: u64,
+    exposure_usec: u64,
+    white_balance: u32,
+    iso_speed: u32,
+}
+
+impl StoredImage {
+    unsafe fn from_handle(handle: k4a::k4a_image_t) -> Option<StoredImage> {
+        let image = image(handle)?;
+        Some(StoredImage {
+            format: image.format,
+            width: image.width,
+            height: image.height,
+            stride: image.stride,
+            data: image.data().to_vec(),
+            device_timestamp_usec: image.device_timestamp_usec(),
+            system_timestamp_nsec: image.system_timestamp_nsec(),
+            exposure_usec: image.exposure_usec(),
+            white_balance: image.white_balance(),
+            iso_speed: image.iso_speed(),
+        })
+    }
+
+    fn to_handle(&self, format: k4a_image_format_t) -> k4a::k4a_image_t {
+        let handle = if format == self.format {
+            create_image_from_vec(
+                self.format,
+                self.width,
+                self.height,
+                self.stride,
+                self.data.clone(),
+            )
+        } else {
+            //  Color conversion: the fake SDK does not decode, the converted image is gray.
+            let mut data = vec![128u8; (self.width * self.height * 4) as usize];
+            for pixel in data.chunks_exact_mut(4) {
+                pixel[3] = 255;
+            }
+            create_image_from_vec(format, self.width, self.height, self.width * 4, data)
+        };
+        unsafe { image(handle) }.unwrap().set_metadata(
+            self.device_timestamp_usec,
+            self.system_timestamp_nsec,
+            self.exposure_usec,
+            self.white_balance,
+            self.iso_speed,
+        );
+        handle
+    }
+}
+
+#[derive(Clone)]
+struct StoredCapture {
+    //  color, depth, IR
+    images: [Option<StoredImage>; 3],
+    temperature_c: f32,
+    timestamp_usec: u64,
+}
+
+#[derive(Clone)]
+struct CustomTrack {
+    name: String,
+    codec_id: String,
+    codec_context: Vec<u8>,
+    video_settings: Option<k4a_record_video_settings_t>,
+    blocks: Vec<(u64, Vec<u8>)>,
+}
+
+#[derive(Clone)]
+struct Recording {
+    config: k4a_record_configuration_t,
+    serial_number: Option<String>,
+    raw_calibration: Option<Vec<u8>>,
+    tags: Vec<(String, String)>,
+    attachments: Vec<(String, Vec<u8>)>,
+    custom_tracks: Vec<CustomTrack>,
+    captures: Vec<StoredCapture>,
+    imu_samples: Vec<k4a_imu_sample_t>,
+}
+
+impl Recording {
+    fn start_timestamp_usec(&self) -> u64 {
+        self.captures
+            .iter()
+            .map(|c| c.timestamp_usec)
+            .chain(self.imu_samples.iter().map(|s| s.acc_timestamp_usec))
+            .chain(
+                self.custom_tracks
+                    .iter()
+                    .flat_map(|t| t.blocks.iter().map(|b| b.0)),
+            )
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn end_timestamp_usec(&self) -> u64 {
+        self.captures
+            .iter()
+            .flat_map(|c| c.images.iter().flatten().map(|i| i.device_timestamp_usec))
+            .chain(self.imu_samples.iter().map(|s| s.acc_timestamp_usec))
+            .chain(
+                self.custom_tracks
+                    .iter()
+                    .flat_map(|t| t.blocks.iter().map(|b| b.0)),
+            )
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn track_names(&self) -> Vec<String> {
+        let enabled = [
+            self.config.color_track_enabled,
+            self.config.depth_track_enabled,
+            self.config.ir_track_enabled,
+            self.config.imu_track_enabled,
+        ];
+        BUILTIN_TRACKS
+            .iter()
+            .zip(enabled.iter())
+            .filter(|(_, enabled)| **enabled)
+            .map(|(name, _)| String::from(*name))
+            .chain(self.custom_tracks.iter().map(|t| t.name.clone()))
+            .collect()
+    }
+
+    fn custom_track(&self, name: &str) -> Option<&CustomTrack> {
+        self.custom_tracks.iter().find(|t| t.name == name)
+    }
+
+    fn builtin_tags(&self) -> Vec<(String, String)> {
+        let config = &self.config;
+        let mut tags = Vec::new();
+        let mut tag = |name: &str, value: String| tags.push((String::from(name), value));
+        if config.color_track_enabled {
+            let format = match config.color_format {
+                k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_MJPG => "MJPG",
+                k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_NV12 => "NV12",
+                k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_YUY2 => "YUY2",
+                _ => "BGRA",
+            };
+            let resolution = match config.color_resolution {
+                k4a_color_resolution_t_K4A_COLOR_RESOLUTION_720P => "720P",
+                k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1080P => "1080P",
+                k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1440P => "1440P",
+                k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1536P => "1536P",
+                k4a_color_resolution_t_K4A_COLOR_RESOLUTION_2160P => "2160P",
+                _ => "3072P",
+            };
+            tag("K4A_COLOR_MODE", format!("{}_{}", format, resolution));
+        } else {
+            tag("K4A_COLOR_MODE", String::from("OFF"));
+        }
+        tag(
+            "K4A_DEPTH_MODE",
+            String::from(match config.depth_mode {
+                k4a_depth_mode_t_K4A_DEPTH_MODE_NFOV_2X2BINNED => "NFOV_2X2BINNED",
+                k4a_depth_mode_t_K4A_DEPTH_MODE_NFOV_UNBINNED => "NFOV_UNBINNED",
+                k4a_depth_mode_t_K4A_DEPTH_MODE_WFOV_2X2BINNED => "WFOV_2X2BINNED",
+                k4a_depth_mode_t_K4A_DEPTH_MODE_WFOV_UNBINNED => "WFOV_UNBINNED",
+                k4a_depth_mode_t_K4A_DEPTH_MODE_PASSIVE_IR => "PASSIVE_IR",
+                _ => "OFF",
+            }),
+        );
+        tag(
+            "K4A_IR_MODE",
+            String::from(if !config.ir_track_enabled {
+                "OFF"
+            } else if config.depth_mode == k4a_depth_mode_t_K4A_DEPTH_MODE_PASSIVE_IR {
+                "PASSIVE"
+            } else {
+                "ACTIVE"
+            }),
+        );
+        tag(
+            "K4A_IMU_MODE",
+            String::from(if config.imu_track_enabled {
+                "ON"
+            } else {
+                "OFF"
+            }),
+        );
+        if self.raw_calibration.is_some() {
+            tag("K4A_CALIBRATION_FILE", String::from(CALIBRATION_FILE));
+        }
+        tag(
+            "K4A_DEPTH_DELAY_NS",
+            format!("{}", config.depth_delay_off_color_usec as i64 * 1000),
+        );
+        tag(
+            "K4A_WIRED_SYNC_MODE",
+            String::from(match config.wired_sync_mode {
+                k4a_wired_sync_mode_t_K4A_WIRED_SYNC_MODE_MASTER => "MASTER",
+                k4a_wired_sync_mode_t_K4A_WIRED_SYNC_MODE_SUBORDINATE => "SUBORDINATE",
+                _ => "STANDALONE",
+            }),
+        );
+        tag(
+            "K4A_SUBORDINATE_DELAY_NS",
+            format!("{}", config.subordinate_delay_off_master_usec as u64 * 1000),
+        );
+        tag(
+            "K4A_START_OFFSET_NS",
+            format!("{}", config.start_timestamp_offset_usec as u64 * 1000),
+        );
+        if let Some(serial_number) = self.serial_number.as_ref() {
+            let version =
+                |v: &k4a::k4a_version_t| format!("{}.{}.{}", v.major, v.minor, v.iteration);
+            tag("K4A_COLOR_FIRMWARE_VERSION", version(&VERSION.rgb));
+            tag("K4A_DEPTH_FIRMWARE_VERSION", version(&VERSION.depth));
+            tag("K4A_DEVICE_SERIAL_NUMBER", serial_number.clone());
+        }
+        tags
+    }
+}
+
+static RECORDINGS: Mutex<Vec<(String, Arc<Recording>)>> = Mutex::new(Vec::new());
+
+fn publish(path: &str, mut recording: Recording) {
+    recording.config.start_timestamp_offset_usec = recording.start_timestamp_usec() as u32;
+    let mut recordings = RECORDINGS.lock().unwrap();
+    recordings.retain(|(p, _)| p != path);
+    recordings.push((String::from(path), Arc::new(recording)));
+}
+
+struct RecordState {
+    header_written: bool,
+    recording: Recording,
+}
+
+struct MockRecord {
+    path: String,
+    state: Mutex<RecordState>,
+}
+
+unsafe fn record<'a>(handle: k4a_record_t) -> Option<&'a MockRecord> {
+    let record = (handle as *const MockRecord).as_ref();
+    if record.is_none() {
+        mock_error!("invalid recording handle");
+    }
+    record
+}
+
+/// Runs `f` on the recording if the header has (`header_written`) or has not been written.
+unsafe fn with_record<F: FnOnce(&mut Recording) -> bool>(
+    handle: k4a_record_t,
+    header_written: bool,
+    f: F,
+) -> k4a_result_t {
+    match record(handle) {
+        Some(record) => {
+            let mut state = record.state.lock().unwrap();
+            if state.header_written != header_written {
+                mock_error!(
+                    "the operation is only allowed {} the header is written",
+                    if header_written { "after" } else { "before" }
+                );
+                return k4a_result_t_K4A_RESULT_FAILED;
+            }
+            to_result(f(&mut state.recording))
+        }
+        None => k4a_result_t_K4A_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_record_create(
+    path: *const c_char,
+    device: k4a_device_t,
+    device_config: k4a_device_configuration_t,
+    recording_handle: *mut k4a_record_t,
+) -> k4a_result_t {
+    let path = match read_string(path) {
+        Some(path) if !path.is_empty() && !recording_handle.is_null() => path,
+        _ => {
+            mock_error!("invalid recording path");
+            return k4a_result_t_K4A_RESULT_FAILED;
+        }
+    };
+    //  The device is optional, recordings without one have no calibration.
+    let device = (device as *const MockDevice).as_ref();
+    let depth_mode = device_config.depth_mode;
+    let recording = Recording {
+        config: k4a_record_configuration_t {
+            color_format: device_config.color_format,
+            color_resolution: device_config.color_resolution,
+            depth_mode,
+            camera_fps: device_config.camera_fps,
+            color_track_enabled: device_config.color_resolution
+                != k4a_color_resolution_t_K4A_COLOR_RESOLUTION_OFF,
+            depth_track_enabled: depth_mode != k4a_depth_mode_t_K4A_DEPTH_MODE_OFF
+                && depth_mode != k4a_depth_mode_t_K4A_DEPTH_MODE_PASSIVE_IR,
+            ir_track_enabled: depth_mode != k4a_depth_mode_t_K4A_DEPTH_MODE_OFF,
+            imu_track_enabled: false,
+            depth_delay_off_color_usec: device_config.depth_delay_off_color_usec,
+            wired_sync_mode: device_config.wired_sync_mode,
+            subordinate_delay_off_master_usec: device_config.subordinate_delay_off_master_usec,
+            start_timestamp_offset_usec: 0,
+        },
+        serial_number: device.map(|d| d.serial_number.clone()),
+        raw_calibration: device
+            .map(|d| d.factory_calibration.to_json(&d.serial_number).into_bytes()),
+        tags: Vec::new(),
+        attachments: Vec::new(),
+        custom_tracks: Vec::new(),
+        captures: Vec::new(),
+        imu_samples: Vec::new(),
+    };
+    *recording_handle = Box::into_raw(Box::new(MockRecord {
+        path,
+        state: Mutex::new(RecordState {
+            header_written: false,
+            recording,
+        }),
+    })) as k4a_record_t;
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_record_add_tag(
+    recording_handle: k4a_record_t,
+    name: *const c_char,
+    value: *const c_char,
+) -> k4a_result_t {
+    let (name, value) = match (read_string(name), read_string(value)) {
+        (Some(name), Some(value)) if !name.is_empty() => (name, value),
+        _ => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    with_record(recording_handle, false, |recording| {
+        recording.tags.push((name, value));
+        true
+    })
+}
+
+pub(super) unsafe extern "C" fn k4a_record_add_imu_track(
+    recording_handle: k4a_record_t,
+) -> k4a_result_t {
+    with_record(recording_handle, false, |recording| {
+        if recording.config.imu_track_enabled {
+            mock_error!("the IMU track is already added");
+            return false;
+        }
+        recording.config.imu_track_enabled = true;
+        true
+    })
+}
+
+pub(super) unsafe extern "C" fn k4a_record_add_attachment(
+    recording_handle: k4a_record_t,
+    attachment_name: *const c_char,
+    buffer: *const u8,
+    buffer_size: usize,
+) -> k4a_result_t {
+    let name = match read_string(attachment_name) {
+        Some(name) if !name.is_empty() && !buffer.is_null() => name,
+        _ => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let data = std::slice::from_raw_parts(buffer, buffer_size).to_vec();
+    with_record(recording_handle, false, |recording| {
+        if name == CALIBRATION_FILE {
+            //  Recordings without a device take the calibration of the recording they are copied from.
+            if recording.raw_calibration.is_some() {
+                mock_error!("attachment {} already exists", name);
+                return false;
+            }
+            recording.raw_calibration = Some(data);
+            return true;
+        }
+        if recording.attachments.iter().any(|(n, _)| *n == name) {
+            mock_error!("attachment {} already exists", name);
+            return false;
+        }
+        recording.attachments.push((name, data));
+        true
+    })
+}
+
+unsafe fn add_custom_track(
+    recording_handle: k4a_record_t,
+    track_name: *const c_char,
+    codec_id: *const c_char,
+    codec_context: *const u8,
+    codec_context_size: usize,
+    video_settings: Option<k4a_record_video_settings_t>,
+) -> k4a_result_t {
+    let (name, codec_id) = match (read_string(track_name), read_string(codec_id)) {
+        (Some(name), Some(codec_id)) if !name.is_empty() && !codec_id.is_empty() => {
+            (name, codec_id)
+        }
+        _ => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let codec_context = if codec_context.is_null() {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(codec_context, codec_context_size).to_vec()
+    };
+    with_record(recording_handle, false, |recording| {
+        if BUILTIN_TRACKS.contains(&name.as_str()) || recording.custom_track(&name).is_some() {
+            mock_error!("track {} already exists", name);
+            return false;
+        }
+        recording.custom_tracks.push(CustomTrack {
+            name,
+            codec_id,
+            codec_context,
+            video_settings,
+            blocks: Vec::new(),
+        });
+        true
+    })
+}
+
+pub(super) unsafe extern "C" fn k4a_record_add_custom_video_track(
+    recording_handle: k4a_record_t,
+    track_name: *const c_char,
+    codec_id: *const c_char,
+    codec_context: *const u8,
+    codec_context_size: usize,
+    track_settings: *const k4a_record_video_settings_t,
+) -> k4a_result_t {
+    match track_settings.as_ref() {
+        Some(settings) => add_custom_track(
+            recording_handle,
+            track_name,
+            codec_id,
+            codec_context,
+            codec_context_size,
+            Some(*settings),
+        ),
+        None => k4a_result_t_K4A_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_record_add_custom_subtitle_track(
+    recording_handle: k4a_record_t,
+    track_name: *const c_char,
+    codec_id: *const c_char,
+    codec_context: *const u8,
+    codec_context_size: usize,
+    _track_settings: *const k4a_record_subtitle_settings_t,
+) -> k4a_result_t {
+    add_custom_track(
+        recording_handle,
+        track_name,
+        codec_id,
+        codec_context,
+        codec_context_size,
+        None,
+    )
+}
+
+pub(super) unsafe extern "C" fn k4a_record_write_header(
+    recording_handle: k4a_record_t,
+) -> k4a_result_t {
+    match record(recording_handle) {
+        Some(record) => {
+            let mut state = record.state.lock().unwrap();
+            if state.header_written {
+                mock_error!("the header is already written");
+                return k4a_result_t_K4A_RESULT_FAILED;
+            }
+            state.header_written = true;
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        None => k4a_result_t_K4A_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_record_write_capture(
+    recording_handle: k4a_record_t,
+    capture_handle: k4a_capture_t,
+) -> k4a_result_t {
+    let images = match capture_images(capture_handle as k4a::k4a_capture_t) {
+        Some(images) => images,
+        None => {
+            mock_error!("invalid capture handle");
+            return k4a_result_t_K4A_RESULT_FAILED;
+        }
+    };
+    let stored = [
+        StoredImage::from_handle(images[0]),
+        StoredImage::from_handle(images[1]),
+        StoredImage::from_handle(images[2]),
+    ];
+    for image in images.iter() {
+        super::image::k4a_image_release(*image);
+    }
+    let temperature_c =
+        super::image::k4a_capture_get_temperature_c(capture_handle as k4a::k4a_capture_t);
+    with_record(recording_handle, true, |recording| {
+        let config = &recording.config;
+        let enabled = [
+            config.color_track_enabled,
+            config.depth_track_enabled,
+            config.ir_track_enabled,
+        ];
+        if stored
+            .iter()
+            .zip(enabled.iter())
+            .any(|(s, e)| s.is_some() && !e)
+        {
+            mock_error!("the capture contains an image of a track that is not recorded");
+            return false;
+        }
+        if let Some(color) = stored[0].as_ref() {
+            if color.format != config.color_format {
+                mock_error!("the color image format does not match the recording");
+                return false;
+            }
+        }
+        let timestamp_usec = match stored
+            .iter()
+            .flatten()
+            .map(|i| i.device_timestamp_usec)
+            .min()
+        {
+            Some(timestamp) => timestamp,
+            None => {
+                mock_error!("the capture is empty");
+                return false;
+            }
+        };
+        recording.captures.push(StoredCapture {
+            images: stored,
+            temperature_c,
+            timestamp_usec,
+        });
+        true
+    })
+}
+
+pub(super) unsafe extern "C" fn k4a_record_write_imu_sample(
+    recording_handle: k4a_record_t,
+    imu_sample: k4a_imu_sample_t,
+) -> k4a_result_t {
+    with_record(recording_handle, true, |recording| {
+        if !recording.config.imu_track_enabled {
+            mock_error!("the IMU track is not added");
+            return false;
+        }
+        recording.imu_samples.push(imu_sample);
+        true
+    })
+}
+
+pub(super) unsafe extern "C" fn k4a_record_write_custom_track_data(
+    recording_handle: k4a_record_t,
+    track_name: *const c_char,
+    device_timestamp_usec: u64,
+    custom_data: *mut u8,
+    custom_data_size: usize,
+) -> k4a_result_t {
+    let name = match read_string(track_name) {
+        Some(name) if !custom_data.is_null() => name,
+        _ => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let data = std::slice::from_raw_parts(custom_data, custom_data_size).to_vec();
+    with_record(recording_handle, true, |recording| {
+        match recording.custom_tracks.iter_mut().find(|t| t.name == name) {
+            Some(track) => {
+                track.blocks.push((device_timestamp_usec, data));
+                true
+            }
+            None => {
+                mock_error!("track {} does not exist", name);
+                false
+            }
+        }
+    })
+}
+
+pub(super) unsafe extern "C" fn k4a_record_flush(recording_handle: k4a_record_t) -> k4a_result_t {
+    match record(recording_handle) {
+        Some(record) => {
+            let state = record.state.lock().unwrap();
+            if !state.header_written {
+                mock_error!("the header is not written");
+                return k4a_result_t_K4A_RESULT_FAILED;
+            }
+            publish(&record.path, state.recording.clone());
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        None => k4a_result_t_K4A_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_record_close(recording_handle: k4a_record_t) {
+    if !recording_handle.is_null() {
+        let record = Box::from_raw(recording_handle as *mut MockRecord);
+        let state = record.state.into_inner().unwrap();
+        publish(&record.path, state.recording);
+    }
+}
+
+//  Playback positions are kept in half steps: 2 * i is "item i was returned last",
+//  2 * i + 1 is "between item i and i + 1" (after opening or seeking).
+#[derive(Clone, Copy)]
+struct Position(i64);
+
+impl Position {
+    const BEGIN: Position = Position(-1);
+
+    fn before(index: usize) -> Position {
+        Position(2 * index as i64 - 1)
+    }
+
+    fn next(&mut self, len: usize) -> Option<usize> {
+        let index = self.0.div_euclid(2) + 1;
+        if index >= len as i64 {
+            *self = Position::before(len);
+            None
+        } else {
+            *self = Position(2 * index);
+            Some(index as usize)
+        }
+    }
+
+    fn previous(&mut self) -> Option<usize> {
+        let index = (self.0 + 1).div_euclid(2) - 1;
+        if index < 0 {
+            *self = Position::BEGIN;
+            None
+        } else {
+            *self = Position(2 * index);
+            Some(index as usize)
+        }
+    }
+}
+
+struct PlaybackState {
+    color_format: k4a_image_format_t,
+    capture: Position,
+    imu: Position,
+    blocks: Vec<Position>,
+}
+
+struct MockPlayback {
+    recording: Arc<Recording>,
+    state: Mutex<PlaybackState>,
+}
+
+unsafe fn playback<'a>(handle: k4a_playback_t) -> Option<&'a MockPlayback> {
+    let playback = (handle as *const MockPlayback).as_ref();
+    if playback.is_none() {
+        mock_error!("invalid playback handle");
+    }
+    playback
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_open(
+    path: *const c_char,
+    playback_handle: *mut k4a_playback_t,
+) -> k4a_result_t {
+    let path = match read_string(path) {
+        Some(path) if !playback_handle.is_null() => path,
+        _ => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let recording = RECORDINGS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(p, _)| *p == path)
+        .map(|(_, r)| r.clone());
+    match recording {
+        Some(recording) => {
+            let state = PlaybackState {
+                color_format: recording.config.color_format,
+                capture: Position::BEGIN,
+                imu: Position::BEGIN,
+                blocks: vec![Position::BEGIN; recording.custom_tracks.len()],
+            };
+            *playback_handle = Box::into_raw(Box::new(MockPlayback {
+                recording,
+                state: Mutex::new(state),
+            })) as k4a_playback_t;
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        None => {
+            mock_error!("recording {} does not exist", path);
+            k4a_result_t_K4A_RESULT_FAILED
+        }
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_close(playback_handle: k4a_playback_t) {
+    if !playback_handle.is_null() {
+        drop(Box::from_raw(playback_handle as *mut MockPlayback));
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_raw_calibration(
+    playback_handle: k4a_playback_t,
+    data: *mut u8,
+    data_size: *mut usize,
+) -> k4a_buffer_result_t {
+    match playback(playback_handle).and_then(|p| p.recording.raw_calibration.as_ref()) {
+        Some(raw) => {
+            let mut raw = raw.clone();
+            raw.push(0);
+            write_buffer(&raw, data, data_size)
+        }
+        None => k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_calibration(
+    playback_handle: k4a_playback_t,
+    calibration: *mut k4a_calibration_t,
+) -> k4a_result_t {
+    let recording = match playback(playback_handle) {
+        Some(playback) => &playback.recording,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let c = recording
+        .raw_calibration
+        .as_ref()
+        .and_then(|raw| std::str::from_utf8(raw).ok())
+        .and_then(FactoryCalibration::from_json)
+        .and_then(|f| {
+            f.calibration(
+                recording.config.depth_mode,
+                recording.config.color_resolution,
+            )
+        });
+    match (c, calibration.is_null()) {
+        (Some(c), false) => {
+            //  k4arecord declares its own copy of k4a_calibration_t with the same layout.
+            *(calibration as *mut k4a::k4a_calibration_t) = c;
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        _ => {
+            mock_error!("the recording has no calibration");
+            k4a_result_t_K4A_RESULT_FAILED
+        }
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_record_configuration(
+    playback_handle: k4a_playback_t,
+    config: *mut k4a_record_configuration_t,
+) -> k4a_result_t {
+    match (playback(playback_handle), config.is_null()) {
+        (Some(playback), false) => {
+            *config = playback.recording.config;
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        _ => k4a_result_t_K4A_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_check_track_exists(
+    playback_handle: k4a_playback_t,
+    track_name: *const c_char,
+) -> bool {
+    match (playback(playback_handle), read_string(track_name)) {
+        (Some(playback), Some(name)) => playback.recording.track_names().contains(&name),
+        _ => false,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_track_count(
+    playback_handle: k4a_playback_t,
+) -> usize {
+    playback(playback_handle).map_or(0, |p| p.recording.track_names().len())
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_track_name(
+    playback_handle: k4a_playback_t,
+    track_index: usize,
+    track_name: *mut c_char,
+    track_name_size: *mut usize,
+) -> k4a_buffer_result_t {
+    match playback(playback_handle)
+        .and_then(|p| p.recording.track_names().get(track_index).cloned())
+    {
+        Some(name) => write_string(&name, track_name, track_name_size),
+        None => k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_track_is_builtin(
+    playback_handle: k4a_playback_t,
+    track_name: *const c_char,
+) -> bool {
+    match (playback(playback_handle), read_string(track_name)) {
+        (Some(playback), Some(name)) => {
+            BUILTIN_TRACKS.contains(&name.as_str())
+                && playback.recording.track_names().contains(&name)
+        }
+        _ => false,
+    }
+}
+
+/// Looks up a track and returns (codec id, codec context, video settings).
+unsafe fn track_info(
+    playback_handle: k4a_playback_t,
+    track_name: *const c_char,
+) -> Option<(String, Vec<u8>, Option<k4a_record_video_settings_t>)> {
+    let recording = &playback(playback_handle)?.recording;
+    let name = read_string(track_name)?;
+    if !recording.track_names().contains(&name) {
+        mock_error!("track {} does not exist", name);
+        return None;
+    }
+    if let Some(track) = recording.custom_track(&name) {
+        return Some((
+            track.codec_id.clone(),
+            track.codec_context.clone(),
+            track.video_settings,
+        ));
+    }
+    let config = &recording.config;
+    let frame_rate = super::frame_rate(config.camera_fps);
+    let (resolution, fourcc, bit_count) = match name.as_str() {
+        "COLOR" => (
+            super::color_resolution(config.color_resolution)?,
+            match config.color_format {
+                k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_MJPG => *b"MJPG",
+                k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_NV12 => *b"NV12",
+                k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_YUY2 => *b"YUY2",
+                _ => [0; 4],
+            },
+            match config.color_format {
+                k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_NV12 => 12u16,
+                k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_BGRA32 => 32,
+                _ => 16,
+            },
+        ),
+        "DEPTH" | "IR" => (
+            super::depth_mode_resolution(config.depth_mode)?,
+            *b"b16g",
+            16,
+        ),
+        _ => return Some((String::from("S_K4A/IMU"), Vec::new(), None)),
+    };
+    let codec_id = if name == "COLOR"
+        && config.color_format == k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_MJPG
+    {
+        "V_MJPEG"
+    } else {
+        "V_MS/VFW/FOURCC"
+    };
+    //  BITMAPINFOHEADER
+    let (width, height) = resolution;
+    let mut header = Vec::with_capacity(40);
+    header.extend_from_slice(&40u32.to_le_bytes());
+    header.extend_from_slice(&width.to_le_bytes());
+    header.extend_from_slice(&height.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&bit_count.to_le_bytes());
+    header.extend_from_slice(&fourcc);
+    header.extend_from_slice(&((width * height) as u32 * bit_count as u32 / 8).to_le_bytes());
+    header.resize(40, 0);
+    Some((
+        String::from(codec_id),
+        header,
+        Some(k4a_record_video_settings_t {
+            width: width as u64,
+            height: height as u64,
+            frame_rate,
+        }),
+    ))
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_track_get_video_settings(
+    playback_handle: k4a_playback_t,
+    track_name: *const c_char,
+    video_settings: *mut k4a_record_video_settings_t,
+) -> k4a_result_t {
+    match (
+        track_info(playback_handle, track_name),
+        video_settings.is_null(),
+    ) {
+        (Some((_, _, Some(settings))), false) => {
+            *video_settings = settings;
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        _ => k4a_result_t_K4A_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_track_get_codec_id(
+    playback_handle: k4a_playback_t,
+    track_name: *const c_char,
+    codec_id: *mut c_char,
+    codec_id_size: *mut usize,
+) -> k4a_buffer_result_t {
+    match track_info(playback_handle, track_name) {
+        Some((id, _, _)) => write_string(&id, codec_id, codec_id_size),
+        None => k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_track_get_codec_context(
+    playback_handle: k4a_playback_t,
+    track_name: *const c_char,
+    codec_context: *mut u8,
+    codec_context_size: *mut usize,
+) -> k4a_buffer_result_t {
+    match track_info(playback_handle, track_name) {
+        Some((_, context, _)) => write_buffer(&context, codec_context, codec_context_size),
+        None => k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_tag(
+    playback_handle: k4a_playback_t,
+    name: *const c_char,
+    value: *mut c_char,
+    value_size: *mut usize,
+) -> k4a_buffer_result_t {
+    let (recording, name) = match (playback(playback_handle), read_string(name)) {
+        (Some(playback), Some(name)) => (&playback.recording, name),
+        _ => return k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED,
+    };
+    match recording
+        .builtin_tags()
+        .into_iter()
+        .chain(recording.tags.iter().cloned())
+        .find(|(n, _)| *n == name)
+    {
+        Some((_, v)) => write_string(&v, value, value_size),
+        None => k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_set_color_conversion(
+    playback_handle: k4a_playback_t,
+    target_format: k4a_image_format_t,
+) -> k4a_result_t {
+    let playback = match playback(playback_handle) {
+        Some(playback) => playback,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    if !playback.recording.config.color_track_enabled {
+        mock_error!("the recording has no color track");
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    if target_format != k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_BGRA32
+        && target_format != playback.recording.config.color_format
+    {
+        mock_error!("unsupported color conversion to format {}", target_format);
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    playback.state.lock().unwrap().color_format = target_format;
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_attachment(
+    playback_handle: k4a_playback_t,
+    file_name: *const c_char,
+    data: *mut u8,
+    data_size: *mut usize,
+) -> k4a_buffer_result_t {
+    let (recording, name) = match (playback(playback_handle), read_string(file_name)) {
+        (Some(playback), Some(name)) => (&playback.recording, name),
+        _ => return k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED,
+    };
+    let attachment = if name == CALIBRATION_FILE {
+        recording.raw_calibration.as_ref()
+    } else {
+        recording
+            .attachments
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, a)| a)
+    };
+    match attachment {
+        Some(attachment) => write_buffer(attachment, data, data_size),
+        None => k4a_buffer_result_t_K4A_BUFFER_RESULT_FAILED,
+    }
+}
+
+fn to_stream_result(index: Option<usize>) -> k4a_stream_result_t {
+    match index {
+        Some(_) => k4a_stream_result_t_K4A_STREAM_RESULT_SUCCEEDED,
+        None => k4a_stream_result_t_K4A_STREAM_RESULT_EOF,
+    }
+}
+
+unsafe fn get_capture(
+    playback_handle: k4a_playback_t,
+    capture_handle: *mut k4a_capture_t,
+    forward: bool,
+) -> k4a_stream_result_t {
+    let playback = match (playback(playback_handle), capture_handle.is_null()) {
+        (Some(playback), false) => playback,
+        _ => return k4a_stream_result_t_K4A_STREAM_RESULT_FAILED,
+    };
+    let captures = &playback.recording.captures;
+    let (index, color_format) = {
+        let mut state = playback.state.lock().unwrap();
+        let index = if forward {
+            state.capture.next(captures.len())
+        } else {
+            state.capture.previous()
+        };
+        (index, state.color_format)
+    };
+    if let Some(index) = index {
+        let capture = &captures[index];
+        let handle = |i: usize| {
+            capture.images[i]
+                .as_ref()
+                .map_or(std::ptr::null_mut(), |image| {
+                    image.to_handle(if i == 0 { color_format } else { image.format })
+                })
+        };
+        let (color, depth, ir) = (handle(0), handle(1), handle(2));
+        *capture_handle = create_capture(color, depth, ir, capture.temperature_c) as k4a_capture_t;
+    }
+    to_stream_result(index)
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_next_capture(
+    playback_handle: k4a_playback_t,
+    capture_handle: *mut k4a_capture_t,
+) -> k4a_stream_result_t {
+    get_capture(playback_handle, capture_handle, true)
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_previous_capture(
+    playback_handle: k4a_playback_t,
+    capture_handle: *mut k4a_capture_t,
+) -> k4a_stream_result_t {
+    get_capture(playback_handle, capture_handle, false)
+}
+
+unsafe fn get_imu_sample(
+    playback_handle: k4a_playback_t,
+    imu_sample: *mut k4a_imu_sample_t,
+    forward: bool,
+) -> k4a_stream_result_t {
+    let playback = match (playback(playback_handle), imu_sample.is_null()) {
+        (Some(playback), false) => playback,
+        _ => return k4a_stream_result_t_K4A_STREAM_RESULT_FAILED,
+    };
+    if !playback.recording.config.imu_track_enabled {
+        mock_error!("the recording has no IMU track");
+        return k4a_stream_result_t_K4A_STREAM_RESULT_FAILED;
+    }
+    let samples = &playback.recording.imu_samples;
+    let mut state = playback.state.lock().unwrap();
+    let index = if forward {
+        state.imu.next(samples.len())
+    } else {
+        state.imu.previous()
+    };
+    if let Some(index) = index {
+        *imu_sample = samples[index];
+    }
+    to_stream_result(index)
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_next_imu_sample(
+    playback_handle: k4a_playback_t,
+    imu_sample: *mut k4a_imu_sample_t,
+) -> k4a_stream_result_t {
+    get_imu_sample(playback_handle, imu_sample, true)
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_previous_imu_sample(
+    playback_handle: k4a_playback_t,
+    imu_sample: *mut k4a_imu_sample_t,
+) -> k4a_stream_result_t {
+    get_imu_sample(playback_handle, imu_sample, false)
+}
+
+struct MockDataBlock {
+    device_timestamp_usec: u64,
+    data: Vec<u8>,
+}
+
+unsafe fn get_data_block(
+    playback_handle: k4a_playback_t,
+    track_name: *const c_char,
+    data_block_handle: *mut k4a_playback_data_block_t,
+    forward: bool,
+) -> k4a_stream_result_t {
+    let (playback, name) = match (
+        playback(playback_handle),
+        read_string(track_name),
+        data_block_handle.is_null(),
+    ) {
+        (Some(playback), Some(name), false) => (playback, name),
+        _ => return k4a_stream_result_t_K4A_STREAM_RESULT_FAILED,
+    };
+    let tracks = &playback.recording.custom_tracks;
+    let track_index = match tracks.iter().position(|t| t.name == name) {
+        Some(track_index) => track_index,
+        None => {
+            mock_error!("{} is not a custom track", name);
+            return k4a_stream_result_t_K4A_STREAM_RESULT_FAILED;
+        }
+    };
+    let blocks = &tracks[track_index].blocks;
+    let mut state = playback.state.lock().unwrap();
+    let position = &mut state.blocks[track_index];
+    let index = if forward {
+        position.next(blocks.len())
+    } else {
+        position.previous()
+    };
+    if let Some(index) = index {
+        let (device_timestamp_usec, data) = &blocks[index];
+        *data_block_handle = Box::into_raw(Box::new(MockDataBlock {
+            device_timestamp_usec: *device_timestamp_usec,
+            data: data.clone(),
+        })) as k4a_playback_data_block_t;
+    }
+    to_stream_result(index)
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_next_data_block(
+    playback_handle: k4a_playback_t,
+    track_name: *const c_char,
+    data_block_handle: *mut k4a_playback_data_block_t,
+) -> k4a_stream_result_t {
+    get_data_block(playback_handle, track_name, data_block_handle, true)
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_previous_data_block(
+    playback_handle: k4a_playback_t,
+    track_name: *const c_char,
+    data_block_handle: *mut k4a_playback_data_block_t,
+) -> k4a_stream_result_t {
+    get_data_block(playback_handle, track_name, data_block_handle, false)
+}
+
+unsafe fn data_block<'a>(handle: k4a_playback_data_block_t) -> Option<&'a mut MockDataBlock> {
+    (handle as *mut MockDataBlock).as_mut()
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_data_block_get_device_timestamp_usec(
+    data_block_handle: k4a_playback_data_block_t,
+) -> u64 {
+    data_block(data_block_handle).map_or(0, |b| b.device_timestamp_usec)
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_data_block_get_buffer_size(
+    data_block_handle: k4a_playback_data_block_t,
+) -> usize {
+    data_block(data_block_handle).map_or(0, |b| b.data.len())
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_data_block_get_buffer(
+    data_block_handle: k4a_playback_data_block_t,
+) -> *mut u8 {
+    data_block(data_block_handle).map_or(std::ptr::null_mut(), |b| b.data.as_mut_ptr())
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_data_block_release(
+    data_block_handle: k4a_playback_data_block_t,
+) {
+    if !data_block_handle.is_null() {
+        drop(Box::from_raw(data_block_handle as *mut MockDataBlock));
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_seek_timestamp(
+    playback_handle: k4a_playback_t,
+    offset_usec: i64,
+    origin: k4a_playback_seek_origin_t,
+) -> k4a_result_t {
+    let playback = match playback(playback_handle) {
+        Some(playback) => playback,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let recording = &playback.recording;
+    let start = recording.config.start_timestamp_offset_usec as i64;
+    let end = recording.end_timestamp_usec() as i64;
+    let target = match origin {
+        k4a_playback_seek_origin_t_K4A_PLAYBACK_SEEK_BEGIN => start + offset_usec,
+        k4a_playback_seek_origin_t_K4A_PLAYBACK_SEEK_END => end + 1 + offset_usec,
+        k4a_playback_seek_origin_t_K4A_PLAYBACK_SEEK_DEVICE_TIME => offset_usec,
+        _ => {
+            mock_error!("invalid seek origin {}", origin);
+            return k4a_result_t_K4A_RESULT_FAILED;
+        }
+    };
+    if target < start || target > end + 1 {
+        mock_error!("seek target {} is out of the recording", target);
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    let target = target as u64;
+    let first_at = |timestamps: &mut dyn Iterator<Item = u64>| {
+        Position::before(timestamps.take_while(|t| *t < target).count())
+    };
+    let mut state = playback.state.lock().unwrap();
+    state.capture = first_at(&mut recording.captures.iter().map(|c| c.timestamp_usec));
+    state.imu = first_at(&mut recording.imu_samples.iter().map(|s| s.acc_timestamp_usec));
+    for (position, track) in state.blocks.iter_mut().zip(recording.custom_tracks.iter()) {
+        *position = first_at(&mut track.blocks.iter().map(|b| b.0));
+    }
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_recording_length_usec(
+    playback_handle: k4a_playback_t,
+) -> u64 {
+    playback(playback_handle).map_or(0, |p| {
+        p.recording.end_timestamp_usec() - p.recording.config.start_timestamp_offset_usec as u64
+    })
+}
+
+pub(super) unsafe extern "C" fn k4a_playback_get_last_timestamp_usec(
+    playback_handle: k4a_playback_t,
+) -> u64 {
+    k4a_playback_get_recording_length_usec(playback_handle)
+}
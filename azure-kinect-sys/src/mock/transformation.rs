@@ -0,0 +1,327 @@
+//  Image transformations based on the calibration functions of the fake SDK.
+
+use super::calibration::{project, transform_3d, unproject};
+use super::image::{image, MockImage};
+use super::mock_error;
+use crate::k4a::*;
+
+const DEPTH: k4a_calibration_type_t = k4a_calibration_type_t_K4A_CALIBRATION_TYPE_DEPTH;
+const COLOR: k4a_calibration_type_t = k4a_calibration_type_t_K4A_CALIBRATION_TYPE_COLOR;
+
+struct MockTransformation {
+    calibration: k4a_calibration_t,
+}
+
+unsafe fn transformation<'a>(handle: k4a_transformation_t) -> Option<&'a MockTransformation> {
+    (handle as *const MockTransformation).as_ref()
+}
+
+pub(super) unsafe extern "C" fn k4a_transformation_create(
+    calibration: *const k4a_calibration_t,
+) -> k4a_transformation_t {
+    match calibration.as_ref() {
+        Some(calibration) => Box::into_raw(Box::new(MockTransformation {
+            calibration: *calibration,
+        })) as k4a_transformation_t,
+        None => std::ptr::null_mut(),
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_transformation_destroy(
+    transformation_handle: k4a_transformation_t,
+) {
+    if !transformation_handle.is_null() {
+        drop(Box::from_raw(
+            transformation_handle as *mut MockTransformation,
+        ));
+    }
+}
+
+fn get_u16(image: &MockImage, x: i32, y: i32) -> u16 {
+    let offset = (y * image.stride + x * 2) as usize;
+    let data = image.data();
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn set_u16(image: &MockImage, x: i32, y: i32, value: u16) {
+    let offset = (y * image.stride + x * 2) as usize;
+    image.data_mut()[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Checks that `image` exists, has `format` (if any) and the size of `camera`.
+unsafe fn checked_image<'a>(
+    handle: k4a_image_t,
+    format: Option<k4a_image_format_t>,
+    camera: &k4a_calibration_camera_t,
+    name: &str,
+) -> Option<&'a MockImage> {
+    match image(handle) {
+        Some(image)
+            if format.is_none_or(|f| f == image.format)
+                && image.width == camera.resolution_width
+                && image.height == camera.resolution_height =>
+        {
+            Some(image)
+        }
+        _ => {
+            mock_error!(
+                "{} does not match the expected format or resolution {}x{}",
+                name,
+                camera.resolution_width,
+                camera.resolution_height
+            );
+            None
+        }
+    }
+}
+
+/// Splats every depth pixel into the color camera, keeping the nearest surface.
+/// Calls `write(depth pixel, color pixel, depth in color camera)` for every kept pixel.
+fn depth_to_color<F: FnMut((i32, i32), (i32, i32))>(
+    calibration: &k4a_calibration_t,
+    depth: &MockImage,
+    transformed_depth: &MockImage,
+    mut write: F,
+) {
+    for b in transformed_depth.data_mut().iter_mut() {
+        *b = 0;
+    }
+    let color = &calibration.color_camera_calibration;
+    for y in 0..depth.height {
+        for x in 0..depth.width {
+            let d = get_u16(depth, x, y);
+            if d == 0 {
+                continue;
+            }
+            let (p, _) = unproject(
+                &calibration.depth_camera_calibration,
+                [x as f32, y as f32],
+                d as f32,
+            );
+            let p = transform_3d(calibration, p, DEPTH, COLOR);
+            let (q, valid) = project(color, p);
+            if !valid {
+                continue;
+            }
+            let (u, v) = (q[0].round() as i32, q[1].round() as i32);
+            if u < 0 || v < 0 || u >= color.resolution_width || v >= color.resolution_height {
+                continue;
+            }
+            let z = p[2].round().clamp(1.0, u16::MAX as f32) as u16;
+            let current = get_u16(transformed_depth, u, v);
+            if current == 0 || z < current {
+                set_u16(transformed_depth, u, v, z);
+                write((x, y), (u, v));
+            }
+        }
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_transformation_depth_image_to_color_camera(
+    transformation_handle: k4a_transformation_t,
+    depth_image: k4a_image_t,
+    transformed_depth_image: k4a_image_t,
+) -> k4a_result_t {
+    let calibration = match transformation(transformation_handle) {
+        Some(t) => &t.calibration,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let depth16 = Some(k4a_image_format_t_K4A_IMAGE_FORMAT_DEPTH16);
+    match (
+        checked_image(
+            depth_image,
+            depth16,
+            &calibration.depth_camera_calibration,
+            "depth_image",
+        ),
+        checked_image(
+            transformed_depth_image,
+            depth16,
+            &calibration.color_camera_calibration,
+            "transformed_depth_image",
+        ),
+    ) {
+        (Some(depth), Some(transformed_depth)) => {
+            depth_to_color(calibration, depth, transformed_depth, |_, _| {});
+            k4a_result_t_K4A_RESULT_SUCCEEDED
+        }
+        _ => k4a_result_t_K4A_RESULT_FAILED,
+    }
+}
+
+pub(super) unsafe extern "C" fn k4a_transformation_depth_image_to_color_camera_custom(
+    transformation_handle: k4a_transformation_t,
+    depth_image: k4a_image_t,
+    custom_image: k4a_image_t,
+    transformed_depth_image: k4a_image_t,
+    transformed_custom_image: k4a_image_t,
+    _interpolation_type: k4a_transformation_interpolation_type_t,
+    invalid_custom_value: u32,
+) -> k4a_result_t {
+    //  The custom image is always resampled with nearest neighbor interpolation.
+    let calibration = match transformation(transformation_handle) {
+        Some(t) => &t.calibration,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let depth16 = Some(k4a_image_format_t_K4A_IMAGE_FORMAT_DEPTH16);
+    let depth_camera = &calibration.depth_camera_calibration;
+    let color_camera = &calibration.color_camera_calibration;
+    let (depth, custom, transformed_depth, transformed_custom) = match (
+        checked_image(depth_image, depth16, depth_camera, "depth_image"),
+        checked_image(custom_image, None, depth_camera, "custom_image"),
+        checked_image(
+            transformed_depth_image,
+            depth16,
+            color_camera,
+            "transformed_depth_image",
+        ),
+        checked_image(
+            transformed_custom_image,
+            None,
+            color_camera,
+            "transformed_custom_image",
+        ),
+    ) {
+        (Some(a), Some(b), Some(c), Some(d)) => (a, b, c, d),
+        _ => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let bytes_per_pixel = match custom.format {
+        k4a_image_format_t_K4A_IMAGE_FORMAT_CUSTOM8 => 1,
+        k4a_image_format_t_K4A_IMAGE_FORMAT_CUSTOM16 => 2,
+        _ => {
+            mock_error!("custom_image must be CUSTOM8 or CUSTOM16");
+            return k4a_result_t_K4A_RESULT_FAILED;
+        }
+    };
+    if transformed_custom.format != custom.format {
+        mock_error!("transformed_custom_image must have the format of custom_image");
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+
+    let invalid = (invalid_custom_value as u16).to_le_bytes();
+    let (width, height) = (transformed_custom.width, transformed_custom.height);
+    for y in 0..height {
+        for x in 0..width {
+            let offset = (y * transformed_custom.stride + x * bytes_per_pixel) as usize;
+            transformed_custom.data_mut()[offset..offset + bytes_per_pixel as usize]
+                .copy_from_slice(&invalid[..bytes_per_pixel as usize]);
+        }
+    }
+    depth_to_color(calibration, depth, transformed_depth, |(x, y), (u, v)| {
+        let source = (y * custom.stride + x * bytes_per_pixel) as usize;
+        let target = (v * transformed_custom.stride + u * bytes_per_pixel) as usize;
+        let n = bytes_per_pixel as usize;
+        transformed_custom.data_mut()[target..target + n]
+            .copy_from_slice(&custom.data()[source..source + n]);
+    });
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_transformation_color_image_to_depth_camera(
+    transformation_handle: k4a_transformation_t,
+    depth_image: k4a_image_t,
+    color_image: k4a_image_t,
+    transformed_color_image: k4a_image_t,
+) -> k4a_result_t {
+    let calibration = match transformation(transformation_handle) {
+        Some(t) => &t.calibration,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let bgra32 = Some(k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_BGRA32);
+    let depth_camera = &calibration.depth_camera_calibration;
+    let color_camera = &calibration.color_camera_calibration;
+    let (depth, color, transformed_color) = match (
+        checked_image(
+            depth_image,
+            Some(k4a_image_format_t_K4A_IMAGE_FORMAT_DEPTH16),
+            depth_camera,
+            "depth_image",
+        ),
+        checked_image(color_image, bgra32, color_camera, "color_image"),
+        checked_image(
+            transformed_color_image,
+            bgra32,
+            depth_camera,
+            "transformed_color_image",
+        ),
+    ) {
+        (Some(a), Some(b), Some(c)) => (a, b, c),
+        _ => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    for y in 0..depth.height {
+        for x in 0..depth.width {
+            let target = (y * transformed_color.stride + x * 4) as usize;
+            let d = get_u16(depth, x, y);
+            let mut pixel = [0u8; 4];
+            if d != 0 {
+                let (p, _) = unproject(depth_camera, [x as f32, y as f32], d as f32);
+                let (q, valid) = project(color_camera, transform_3d(calibration, p, DEPTH, COLOR));
+                let (u, v) = (q[0].round() as i32, q[1].round() as i32);
+                if valid && u >= 0 && v >= 0 && u < color.width && v < color.height {
+                    let source = (v * color.stride + u * 4) as usize;
+                    pixel.copy_from_slice(&color.data()[source..source + 4]);
+                }
+            }
+            transformed_color.data_mut()[target..target + 4].copy_from_slice(&pixel);
+        }
+    }
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
+
+pub(super) unsafe extern "C" fn k4a_transformation_depth_image_to_point_cloud(
+    transformation_handle: k4a_transformation_t,
+    depth_image: k4a_image_t,
+    camera: k4a_calibration_type_t,
+    xyz_image: k4a_image_t,
+) -> k4a_result_t {
+    let calibration = match transformation(transformation_handle) {
+        Some(t) => &t.calibration,
+        None => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    let camera_calibration = match camera {
+        DEPTH => &calibration.depth_camera_calibration,
+        COLOR => &calibration.color_camera_calibration,
+        _ => {
+            mock_error!("camera must be DEPTH or COLOR");
+            return k4a_result_t_K4A_RESULT_FAILED;
+        }
+    };
+    let (depth, xyz) = match (
+        checked_image(
+            depth_image,
+            Some(k4a_image_format_t_K4A_IMAGE_FORMAT_DEPTH16),
+            camera_calibration,
+            "depth_image",
+        ),
+        checked_image(
+            xyz_image,
+            Some(k4a_image_format_t_K4A_IMAGE_FORMAT_CUSTOM),
+            camera_calibration,
+            "xyz_image",
+        ),
+    ) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return k4a_result_t_K4A_RESULT_FAILED,
+    };
+    if xyz.stride < xyz.width * 6 {
+        mock_error!("xyz_image must have a stride of at least 6 bytes per pixel");
+        return k4a_result_t_K4A_RESULT_FAILED;
+    }
+    for y in 0..depth.height {
+        for x in 0..depth.width {
+            let d = get_u16(depth, x, y);
+            let p = if d != 0 {
+                unproject(camera_calibration, [x as f32, y as f32], d as f32).0
+            } else {
+                [0.0; 3]
+            };
+            let offset = (y * xyz.stride + x * 6) as usize;
+            for (i, v) in p.iter().enumerate() {
+                let v = v.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+                xyz.data_mut()[offset + i * 2..offset + i * 2 + 2]
+                    .copy_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+    k4a_result_t_K4A_RESULT_SUCCEEDED
+}
@@ -11,14 +11,14 @@ impl<'a> Calibration<'a> {
     pub fn from_handle(
         api: &azure_kinect_sys::api::Api,
         calibration: k4a_calibration_t,
-    ) -> Calibration {
+    ) -> Calibration<'_> {
         Calibration { api, calibration }
     }
 
-    #[deprecated(since = "0.2", note = "Factory::calibration_get_from_raw")]
+    #[deprecated(since = "0.2.0", note = "Factory::calibration_get_from_raw")]
     pub fn from_raw(
         factory: &'a Factory,
-        raw_calibration: &Vec<u8>,
+        raw_calibration: &[u8],
         target_depth_mode: DepthMode,
         target_color_resolution: ColorResolution,
     ) -> Result<Calibration<'a>, Error> {
@@ -33,6 +33,49 @@ impl<'a> Calibration<'a> {
         &self.calibration
     }
 
+    /// Depth mode the calibration was derived for.
+    pub fn depth_mode(&self) -> DepthMode {
+        DepthMode::from_primitive(self.calibration.depth_mode)
+    }
+
+    /// Color resolution the calibration was derived for.
+    pub fn color_resolution(&self) -> ColorResolution {
+        ColorResolution::from_primitive(self.calibration.color_resolution)
+    }
+
+    /// Intrinsic calibration of the depth or color camera.
+    pub fn intrinsics(&self, camera: CalibrationType) -> Result<Intrinsics, Error> {
+        match camera {
+            CalibrationType::Depth => Ok(Intrinsics {
+                value: self.calibration.depth_camera_calibration,
+            }),
+            CalibrationType::Color => Ok(Intrinsics {
+                value: self.calibration.color_camera_calibration,
+            }),
+            _ => Err(Error::Failed),
+        }
+    }
+
+    /// Transformation from the source to the target coordinate system.
+    pub fn extrinsics(
+        &self,
+        source: CalibrationType,
+        target: CalibrationType,
+    ) -> Result<Extrinsics, Error> {
+        let index = |t: CalibrationType| match t {
+            CalibrationType::Unknown => Err(Error::Failed),
+            _ => Ok(t as usize),
+        };
+        Ok(Extrinsics {
+            value: self.calibration.extrinsics[index(source)?][index(target)?],
+        })
+    }
+
+    /// Copy the calibration into a model which converts points without the native library.
+    pub fn model(&self) -> Result<CalibrationModel, Error> {
+        CalibrationModel::from_native(&self.calibration)
+    }
+
     /// Transform a 3d point of a source coordinate system into a 3d point of the target coordinate system.
     pub fn convert_3d_to_3d(
         &self,
@@ -4,6 +4,7 @@ use std::ptr;
 
 pub struct Camera<'a> {
     pub(crate) device: &'a Device<'a>,
+    configuration: DeviceConfiguration,
 }
 
 impl<'a> Camera<'a> {
@@ -11,39 +12,172 @@ impl<'a> Camera<'a> {
         device: &'a Device<'a>,
         configuration: &DeviceConfiguration,
     ) -> Result<Camera<'a>, Error> {
-        Error::from_k4a_result_t(unsafe {
-            (device.api.funcs.k4a_device_start_cameras)(device.handle, &configuration.value)
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!(
+            target: logging::K4A_TARGET,
+            "device_start_cameras",
+            color_format = ?configuration.color_format(),
+            color_resolution = ?configuration.color_resolution(),
+            depth_mode = ?configuration.depth_mode(),
+            camera_fps = configuration.camera_fps().get_u32(),
+            wired_sync_mode = ?configuration.wired_sync_mode(),
+        )
+        .entered();
+        ErrorContext::new("k4a_device_start_cameras")
+            .arg(
+                "color_format",
+                format!("{:?}", configuration.color_format()),
+            )
+            .arg(
+                "color_resolution",
+                format!("{:?}", configuration.color_resolution()),
+            )
+            .arg("depth_mode", format!("{:?}", configuration.depth_mode()))
+            .arg("camera_fps", configuration.camera_fps().get_u32())
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (device.api.funcs.k4a_device_start_cameras)(device.handle, &configuration.value)
+                })
+                .to_result(())
+            })?;
+        Ok(Camera::<'a> {
+            device,
+            configuration: *configuration,
         })
-        .to_result(())?;
-        Ok(Camera::<'a> { device })
+    }
+
+    /// Get the frame rate the cameras were started with
+    pub fn camera_fps(&self) -> Fps {
+        self.configuration.camera_fps()
+    }
+
+    /// Get the configuration the cameras were started with
+    pub fn configuration(&self) -> &DeviceConfiguration {
+        &self.configuration
     }
 
     /// Reads a sensor capture into cap.  Returns true if a capture was read, false if the read timed out.
-    pub fn get_capture(&self, timeout_in_ms: i32) -> Result<Capture, Error> {
+    pub fn get_capture(&self, timeout_in_ms: i32) -> Result<Capture<'_>, Error> {
         let mut handle: k4a_capture_t = ptr::null_mut();
-        Error::from_k4a_wait_result_t(unsafe {
-            (self.device.api.funcs.k4a_device_get_capture)(
-                self.device.handle,
-                &mut handle,
-                timeout_in_ms,
-            )
+        ErrorContext::new("k4a_device_get_capture").call(|| {
+            Error::from_k4a_wait_result_t(unsafe {
+                (self.device.api.funcs.k4a_device_get_capture)(
+                    self.device.handle,
+                    &mut handle,
+                    timeout_in_ms,
+                )
+            })
+            .to_result_fn(|| Capture::from_handle(self.device.api, handle))
         })
-        .to_result_fn(|| Capture::from_handle(&self.device.api, handle))
     }
 
     /// Reads a sensor capture into cap.  Returns true if a capture was read, false if the read timed out.
-    pub fn get_capture_wait_infinite(&self) -> Result<Capture, Error> {
+    pub fn get_capture_wait_infinite(&self) -> Result<Capture<'_>, Error> {
         self.get_capture(K4A_WAIT_INFINITE)
     }
 
     /// Starts the K4A IMU
-    pub fn start_imu(&self) -> Result<Imu, Error> {
+    pub fn start_imu(&self) -> Result<Imu<'_>, Error> {
         Imu::new(self.device)
     }
+
+    /// Pulls captures on a dedicated thread into a queue of `capacity` captures while `f` runs.
+    /// The thread is stopped before this function returns.
+    ///
+    /// The worker borrows the camera, so it only lives during `f` and cannot be moved to a thread or
+    /// task that outlives it. It is `Sync`: `f` can share it with threads spawned by
+    /// `std::thread::scope`, or forward the captures to a `std::sync::mpsc` channel read within the
+    /// scope. To hand captures to a thread that is not scoped, read a [`SharedCamera`] and send the
+    /// [`SharedCapture`]s instead.
+    pub fn with_capture_worker<R, F: FnOnce(&CaptureWorker<'_>) -> R>(
+        &self,
+        capacity: usize,
+        policy: QueuePolicy,
+        f: F,
+    ) -> R {
+        CaptureWorker::run(self, capacity, policy, f)
+    }
+
+    /// Iterates over sensor captures, waiting infinitely for each one.
+    /// The iteration ends after the first error other than a timeout.
+    pub fn captures(&self) -> Captures<'_> {
+        Captures {
+            camera: self,
+            timeout_in_ms: K4A_WAIT_INFINITE,
+            on_timeout: TimeoutBehavior::Drop,
+            finished: false,
+        }
+    }
+}
+
+/// What an iterator over captures or IMU samples does when a read times out.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TimeoutBehavior {
+    /// Ignore the timeout and read again.
+    Drop,
+    /// Yield `Err(Error::Timeout)` and read again on the next call, e.g. to check for an abort request.
+    Yield,
+}
+
+/// Iterator over sensor captures, created by [`Camera::captures`].
+pub struct Captures<'a> {
+    camera: &'a Camera<'a>,
+    timeout_in_ms: i32,
+    on_timeout: TimeoutBehavior,
+    finished: bool,
+}
+
+impl Captures<'_> {
+    /// Sets the timeout of each read.
+    pub fn timeout(mut self, timeout_in_ms: i32) -> Self {
+        self.timeout_in_ms = timeout_in_ms;
+        self
+    }
+
+    /// Sets what happens when a read times out.
+    pub fn on_timeout(mut self, on_timeout: TimeoutBehavior) -> Self {
+        self.on_timeout = on_timeout;
+        self
+    }
+}
+
+impl<'a> Iterator for Captures<'a> {
+    type Item = Result<Capture<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let camera = self.camera;
+        let timeout_in_ms = self.timeout_in_ms;
+        next_until_error(&mut self.finished, self.on_timeout, || {
+            camera.get_capture(timeout_in_ms)
+        })
+    }
+}
+
+/// Reads until an item, a timeout to yield, or an error that finishes the iteration.
+pub(crate) fn next_until_error<T, F: FnMut() -> Result<T, Error>>(
+    finished: &mut bool,
+    on_timeout: TimeoutBehavior,
+    mut read: F,
+) -> Option<Result<T, Error>> {
+    while !*finished {
+        match read() {
+            Err(Error::Timeout) if on_timeout == TimeoutBehavior::Drop => continue,
+            Err(Error::Timeout) => return Some(Err(Error::Timeout)),
+            Err(e) => {
+                *finished = true;
+                return Some(Err(e));
+            }
+            Ok(item) => return Some(Ok(item)),
+        }
+    }
+    None
 }
 
 impl Drop for Camera<'_> {
     fn drop(&mut self) {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::info_span!(target: logging::K4A_TARGET, "device_stop_cameras").entered();
         unsafe {
             (self.device.api.funcs.k4a_device_stop_cameras)(self.device.handle);
         }
@@ -0,0 +1,350 @@
+//  Camera model of the SDK (intrinsic_transformations.c) evaluated in Rust, so that calibrations
+//  can be used without the native library. Single precision as in the SDK, so results match it.
+
+use crate::*;
+use azure_kinect_sys::k4a::*;
+
+/// Intrinsics of a camera with the Brown-Conrady or Rational 6KT lens model.
+#[derive(Copy, Clone, Debug)]
+pub struct CameraModel {
+    model_type: CalibrationModelType,
+    parameters: k4a_calibration_intrinsic_parameters_t__param,
+    resolution_width: i32,
+    resolution_height: i32,
+    metric_radius: f32,
+}
+
+impl CameraModel {
+    /// Fails if the lens model is neither Brown-Conrady nor Rational 6KT.
+    pub fn from_native(camera: &k4a_calibration_camera_t) -> Result<CameraModel, Error> {
+        let model_type = CalibrationModelType::from_primitive(camera.intrinsics.type_);
+        if model_type != CalibrationModelType::BrownConrady
+            && model_type != CalibrationModelType::Rational6KT
+        {
+            return Err(Error::Failed);
+        }
+        Ok(CameraModel {
+            model_type,
+            parameters: unsafe { camera.intrinsics.parameters.param },
+            resolution_width: camera.resolution_width,
+            resolution_height: camera.resolution_height,
+            metric_radius: camera.metric_radius,
+        })
+    }
+
+    pub fn model_type(&self) -> CalibrationModelType {
+        self.model_type
+    }
+
+    pub fn resolution_width(&self) -> i32 {
+        self.resolution_width
+    }
+
+    pub fn resolution_height(&self) -> i32 {
+        self.resolution_height
+    }
+
+    /// Projects a 3d point in millimeters to a pixel.
+    /// Returns false if the point is behind the camera, outside of the metric radius or outside of the image.
+    pub fn project(&self, point3d: &Float3) -> (Float2, bool) {
+        if point3d.z() <= 0.0 {
+            return (Float2::new(0.0, 0.0), false);
+        }
+        let xy = [point3d.x() / point3d.z(), point3d.y() / point3d.z()];
+        match self.project_normalized(xy, false) {
+            Some((uv, _)) => (Float2::new(uv[0], uv[1]), self.is_inside(uv)),
+            None => (Float2::new(0.0, 0.0), false),
+        }
+    }
+
+    /// Unprojects a pixel with a depth in millimeters to a 3d point.
+    /// Returns false if the depth is 0, the pixel is outside of the image or the lens model can not be inverted.
+    pub fn unproject(&self, point2d: &Float2, depth: f32) -> (Float3, bool) {
+        if depth == 0.0 {
+            return (Float3::new(0.0, 0.0, 0.0), false);
+        }
+        let uv = [point2d.x(), point2d.y()];
+        let (xy, valid) = self.unproject_normalized(uv);
+        (
+            Float3::new(xy[0] * depth, xy[1] * depth, depth),
+            valid && self.is_inside(uv),
+        )
+    }
+
+    /// Unprojects a pixel to the point with a depth of 1, used to build per-pixel lookup tables.
+    pub fn unproject_ray(&self, point2d: &Float2) -> Option<(f32, f32)> {
+        match self.unproject_normalized([point2d.x(), point2d.y()]) {
+            (xy, true) => Some((xy[0], xy[1])),
+            _ => None,
+        }
+    }
+
+    fn is_inside(&self, uv: [f32; 2]) -> bool {
+        uv[0] >= -0.5
+            && uv[1] >= -0.5
+            && uv[0] <= self.resolution_width as f32 - 0.5
+            && uv[1] <= self.resolution_height as f32 - 0.5
+    }
+
+    /// Distorts and projects normalized coordinates, optionally with the Jacobian d(uv)/d(xy).
+    fn project_normalized(&self, xy: [f32; 2], jacobian: bool) -> Option<([f32; 2], [f32; 4])> {
+        let p = &self.parameters;
+        let xp = xy[0] - p.codx;
+        let yp = xy[1] - p.cody;
+
+        let xp2 = xp * xp;
+        let yp2 = yp * yp;
+        let xyp = xp * yp;
+        let rs = xp2 + yp2;
+        if self.metric_radius > 0.0 && rs > self.metric_radius * self.metric_radius {
+            return None;
+        }
+        let rss = rs * rs;
+        let rsc = rss * rs;
+        let a = 1.0 + p.k1 * rs + p.k2 * rss + p.k3 * rsc;
+        let b = 1.0 + p.k4 * rs + p.k5 * rss + p.k6 * rsc;
+        let bi = if b != 0.0 { 1.0 / b } else { 1.0 };
+        let d = a * bi;
+
+        //  Brown-Conrady differs from Rational 6KT only by a factor 2 on the xyp tangential terms.
+        let t = if self.model_type == CalibrationModelType::Rational6KT {
+            1.0
+        } else {
+            2.0
+        };
+        let xp_d = xp * d + (rs + 2.0 * xp2) * p.p2 + t * xyp * p.p1;
+        let yp_d = yp * d + (rs + 2.0 * yp2) * p.p1 + t * xyp * p.p2;
+
+        let uv = [(xp_d + p.codx) * p.fx + p.cx, (yp_d + p.cody) * p.fy + p.cy];
+        if !jacobian {
+            return Some((uv, [0.0; 4]));
+        }
+
+        let dudrs = p.k1 + 2.0 * p.k2 * rs + 3.0 * p.k3 * rss;
+        let dvdrs = p.k4 + 2.0 * p.k5 * rs + 3.0 * p.k6 * rss;
+        let dddrs_2 = (dudrs * b - a * dvdrs) * bi * bi * 2.0;
+        let yp_xp_dddrs_2 = yp * xp * dddrs_2;
+        let j = [
+            p.fx * (d + xp * xp * dddrs_2 + 6.0 * xp * p.p2 + t * yp * p.p1),
+            p.fx * (yp_xp_dddrs_2 + 2.0 * yp * p.p2 + t * xp * p.p1),
+            p.fy * (yp_xp_dddrs_2 + 2.0 * xp * p.p1 + t * yp * p.p2),
+            p.fy * (d + yp * yp * dddrs_2 + 6.0 * yp * p.p1 + t * xp * p.p2),
+        ];
+        Some((uv, j))
+    }
+
+    /// Closed form approximation of the inverse, refined by Gauss-Newton iterations.
+    fn unproject_normalized(&self, uv: [f32; 2]) -> ([f32; 2], bool) {
+        let p = &self.parameters;
+        let xp_d = (uv[0] - p.cx) / p.fx - p.codx;
+        let yp_d = (uv[1] - p.cy) / p.fy - p.cody;
+
+        let rs = xp_d * xp_d + yp_d * yp_d;
+        let rss = rs * rs;
+        let rsc = rss * rs;
+        let a = 1.0 + p.k1 * rs + p.k2 * rss + p.k3 * rsc;
+        let b = 1.0 + p.k4 * rs + p.k5 * rss + p.k6 * rsc;
+        let ai = if a != 0.0 { 1.0 / a } else { 1.0 };
+        let di = ai * b;
+
+        let mut xy = [xp_d * di, yp_d * di];
+        let two_xy = 2.0 * xy[0] * xy[1];
+        let xx = xy[0] * xy[0];
+        let yy = xy[1] * xy[1];
+        xy[0] -= (yy + 3.0 * xx) * p.p2 + two_xy * p.p1;
+        xy[1] -= (xx + 3.0 * yy) * p.p1 + two_xy * p.p2;
+        xy[0] += p.codx;
+        xy[1] += p.cody;
+
+        const MAX_PASSES: usize = 20;
+        let mut best_xy = [0.0f32; 2];
+        let mut best_err = f32::MAX;
+        for pass in 0..MAX_PASSES {
+            let (projected, j) = match self.project_normalized(xy, true) {
+                Some(result) => result,
+                None => return (xy, false),
+            };
+            let err_x = uv[0] - projected[0];
+            let err_y = uv[1] - projected[1];
+            let err = err_x * err_x + err_y * err_y;
+            if err >= best_err {
+                xy = best_xy;
+                break;
+            }
+            best_err = err;
+            best_xy = xy;
+            if pass + 1 == MAX_PASSES || best_err < 1e-22 {
+                break;
+            }
+            let inv_det = 1.0 / (j[0] * j[3] - j[1] * j[2]);
+            xy[0] += inv_det * (j[3] * err_x - j[1] * err_y);
+            xy[1] += inv_det * (-j[2] * err_x + j[0] * err_y);
+        }
+        (xy, best_err <= 1e-6)
+    }
+}
+
+/// The calibration of the depth and color cameras, with the conversions of [`Calibration`]
+/// implemented in Rust. Usable without a [`Factory`].
+#[derive(Copy, Clone)]
+pub struct CalibrationModel {
+    depth: CameraModel,
+    color: CameraModel,
+    extrinsics: [[k4a_calibration_extrinsics_t; 4]; 4],
+}
+
+impl CalibrationModel {
+    pub fn from_native(calibration: &k4a_calibration_t) -> Result<CalibrationModel, Error> {
+        Ok(CalibrationModel {
+            depth: CameraModel::from_native(&calibration.depth_camera_calibration)?,
+            color: CameraModel::from_native(&calibration.color_camera_calibration)?,
+            extrinsics: calibration.extrinsics,
+        })
+    }
+
+    /// Gets the model of the depth or color camera.
+    pub fn camera(&self, camera: CalibrationType) -> Result<&CameraModel, Error> {
+        match camera {
+            CalibrationType::Depth => Ok(&self.depth),
+            CalibrationType::Color => Ok(&self.color),
+            _ => Err(Error::Failed),
+        }
+    }
+
+    /// Transform a 3d point of a source coordinate system into a 3d point of the target coordinate system.
+    pub fn convert_3d_to_3d(
+        &self,
+        source_point3d: &Float3,
+        source_camera: CalibrationType,
+        target_camera: CalibrationType,
+    ) -> Result<Float3, Error> {
+        if source_camera == CalibrationType::Unknown || target_camera == CalibrationType::Unknown {
+            return Err(Error::Failed);
+        }
+        if source_camera == target_camera {
+            return Ok(*source_point3d);
+        }
+        let e = &self.extrinsics[source_camera as usize][target_camera as usize];
+        let (r, t) = (&e.rotation, &e.translation);
+        let (x, y, z) = (source_point3d.x(), source_point3d.y(), source_point3d.z());
+        Ok(Float3::new(
+            r[0] * x + r[1] * y + r[2] * z + t[0],
+            r[3] * x + r[4] * y + r[5] * z + t[1],
+            r[6] * x + r[7] * y + r[8] * z + t[2],
+        ))
+    }
+
+    /// Transform a 2d pixel coordinate with an associated depth value of the source camera into a 3d point of the target coordinate system.
+    /// Returns false if the point is invalid in the target coordinate system (and therefore target_point3d should not be used)
+    pub fn convert_2d_to_3d(
+        &self,
+        source_point2d: &Float2,
+        source_depth: f32,
+        source_camera: CalibrationType,
+        target_camera: CalibrationType,
+    ) -> Result<(Float3, bool), Error> {
+        let (point3d, valid) = self
+            .camera(source_camera)?
+            .unproject(source_point2d, source_depth);
+        let point3d = self.convert_3d_to_3d(&point3d, source_camera, target_camera)?;
+        Ok((point3d, valid))
+    }
+
+    /// Transform a 3d point of a source coordinate system into a 2d pixel coordinate of the target camera.
+    /// Returns false if the point is invalid in the target coordinate system (and therefore target_point2d should not be used)
+    pub fn convert_3d_to_2d(
+        &self,
+        source_point3d: &Float3,
+        source_camera: CalibrationType,
+        target_camera: CalibrationType,
+    ) -> Result<(Float2, bool), Error> {
+        let target = self.camera(target_camera)?;
+        let point3d = self.convert_3d_to_3d(source_point3d, source_camera, target_camera)?;
+        Ok(target.project(&point3d))
+    }
+
+    /// Transform a 2d pixel coordinate with an associated depth value of the source camera into a 2d pixel coordinate of the target camera
+    /// Returns false if the point is invalid in the target coordinate system (and therefore target_point2d should not be used)
+    pub fn convert_2d_to_2d(
+        &self,
+        source_point2d: &Float2,
+        source_depth: f32,
+        source_camera: CalibrationType,
+        target_camera: CalibrationType,
+    ) -> Result<(Float2, bool), Error> {
+        let (point3d, valid) =
+            self.convert_2d_to_3d(source_point2d, source_depth, source_camera, target_camera)?;
+        let (point2d, projected) = self.convert_3d_to_2d(&point3d, target_camera, target_camera)?;
+        Ok((point2d, valid && projected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //  Typical parameters of the depth camera in NFOV unbinned mode.
+    fn depth_camera(model_type: CalibrationModelType) -> CameraModel {
+        CameraModel {
+            model_type,
+            parameters: k4a_calibration_intrinsic_parameters_t__param {
+                cx: 319.3,
+                cy: 339.6,
+                fx: 504.4,
+                fy: 504.5,
+                k1: 3.23,
+                k2: 2.07,
+                k3: 0.10,
+                k4: 3.56,
+                k5: 3.12,
+                k6: 0.54,
+                codx: 0.0,
+                cody: 0.0,
+                p2: -6.5e-5,
+                p1: 5.4e-5,
+                metric_radius: 1.74,
+            },
+            resolution_width: 640,
+            resolution_height: 576,
+            metric_radius: 1.74,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for model_type in &[
+            CalibrationModelType::BrownConrady,
+            CalibrationModelType::Rational6KT,
+        ] {
+            let camera = depth_camera(*model_type);
+            for &(u, v) in &[(0.0, 0.0), (320.0, 288.0), (639.0, 575.0), (100.5, 500.25)] {
+                let (p, valid) = camera.unproject(&Float2::new(u, v), 1000.0);
+                assert!(valid);
+                assert_eq!(p.z(), 1000.0);
+                let (q, valid) = camera.project(&p);
+                assert!(valid);
+                assert!((q.x() - u).abs() < 1e-2 && (q.y() - v).abs() < 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_points() {
+        let camera = depth_camera(CalibrationModelType::BrownConrady);
+        assert!(!camera.project(&Float3::new(0.0, 0.0, -1.0)).1);
+        //  Outside of the metric radius
+        assert!(!camera.project(&Float3::new(2000.0, 0.0, 1000.0)).1);
+        //  Outside of the image
+        assert!(!camera.project(&Float3::new(1000.0, 0.0, 1000.0)).1);
+        assert!(!camera.unproject(&Float2::new(320.0, 288.0), 0.0).1);
+        assert!(!camera.unproject(&Float2::new(-10.0, 288.0), 1000.0).1);
+    }
+
+    #[test]
+    fn test_unsupported_model() {
+        let mut native = k4a_calibration_camera_t::default();
+        native.intrinsics.type_ =
+            k4a_calibration_model_type_t_K4A_CALIBRATION_LENS_DISTORTION_MODEL_THETA;
+        assert!(CameraModel::from_native(&native).is_err());
+    }
+}
@@ -3,64 +3,96 @@ use azure_kinect_sys::k4a::*;
 use std::ptr;
 
 pub struct Capture<'a> {
-    api: &'a azure_kinect_sys::api::Api,
+    pub(crate) api: &'a azure_kinect_sys::api::Api,
     pub(crate) handle: k4a_capture_t,
 }
 
+//  Captures are reference counted by the SDK and can be released on any thread. Clones share the
+//  images and the temperature, which are written by the unsafe setters only.
+unsafe impl Send for Capture<'_> {}
+unsafe impl Sync for Capture<'_> {}
+
 impl<'a> Capture<'a> {
-    #[deprecated(since = "0.2", note = "Factory::capture_create")]
+    #[deprecated(since = "0.2.0", note = "Factory::capture_create")]
     pub fn new(factory: &'a Factory) -> Result<Capture<'a>, Error> {
         let mut handle: k4a_capture_t = ptr::null_mut();
-        Error::from_k4a_result_t(unsafe { (factory.api().funcs.k4a_capture_create)(&mut handle) })
+        ErrorContext::new("k4a_capture_create").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (factory.api().funcs.k4a_capture_create)(&mut handle)
+            })
             .to_result_fn(|| Capture::from_handle(factory.api(), handle))
+        })
     }
 
-    pub fn from_handle(
-        api: &'a azure_kinect_sys::api::Api,
-        handle: k4a_capture_t,
-    ) -> Capture<'a> {
+    pub fn from_handle(api: &'a azure_kinect_sys::api::Api, handle: k4a_capture_t) -> Capture<'a> {
         Capture { api, handle }
     }
 
     /// Get the color image associated with the capture
-    pub fn get_color_image(&self) -> Image {
+    pub fn get_color_image(&self) -> Image<'_> {
         Image::from_handle(self.api, unsafe {
             (self.api.funcs.k4a_capture_get_color_image)(self.handle)
         })
     }
 
     /// Get the depth image associated with the capture
-    pub fn get_depth_image(&self) -> Image {
+    pub fn get_depth_image(&self) -> Image<'_> {
         Image::from_handle(self.api, unsafe {
             (self.api.funcs.k4a_capture_get_depth_image)(self.handle)
         })
     }
 
     /// Get the IR image associated with the capture
-    pub fn get_ir_image(&self) -> Image {
+    pub fn get_ir_image(&self) -> Image<'_> {
         Image::from_handle(self.api, unsafe {
             (self.api.funcs.k4a_capture_get_ir_image)(self.handle)
         })
     }
 
+    /// Get the device timestamp of the first image of depth, color and IR in the capture,
+    /// None if the capture has no image.
+    pub fn get_device_timestamp_usec(&self) -> Option<u64> {
+        [
+            self.get_depth_image(),
+            self.get_color_image(),
+            self.get_ir_image(),
+        ]
+        .iter()
+        .find(|image| !image.handle.is_null())
+        .map(|image| image.get_device_timestamp_usec())
+    }
+
     /// Set / add a color image to the capture
-    pub fn set_color_image(&mut self, color_image: &Image) {
-        unsafe { (self.api.funcs.k4a_capture_set_color_image)(self.handle, color_image.handle) }
+    ///
+    /// # Safety
+    /// Clones of a Capture share the same images and temperature. No other handle to this capture may
+    /// be used while they are set.
+    pub unsafe fn set_color_image(&mut self, color_image: &Image) {
+        (self.api.funcs.k4a_capture_set_color_image)(self.handle, color_image.handle)
     }
 
     /// Set / add a depth image to the capture
-    pub fn set_depth_image(&mut self, depth_image: &Image) {
-        unsafe { (self.api.funcs.k4a_capture_set_depth_image)(self.handle, depth_image.handle) }
+    ///
+    /// # Safety
+    /// See [`Capture::set_color_image`].
+    pub unsafe fn set_depth_image(&mut self, depth_image: &Image) {
+        (self.api.funcs.k4a_capture_set_depth_image)(self.handle, depth_image.handle)
     }
 
     /// Set / add an IR image to the capture
-    pub fn set_ir_image(&mut self, ir_image: &Image) {
-        unsafe { (self.api.funcs.k4a_capture_set_ir_image)(self.handle, ir_image.handle) }
+    ///
+    /// # Safety
+    /// See [`Capture::set_color_image`].
+    pub unsafe fn set_ir_image(&mut self, ir_image: &Image) {
+        (self.api.funcs.k4a_capture_set_ir_image)(self.handle, ir_image.handle)
     }
 
     /// Set the temperature associated with the capture in Celsius.
-    pub fn set_temperature_c(&mut self, temperature_c: f32) {
-        unsafe { (self.api.funcs.k4a_capture_set_temperature_c)(self.handle, temperature_c) }
+    ///
+    /// # Safety
+    /// See [`Capture::set_color_image`].
+    pub unsafe fn set_temperature_c(&mut self, temperature_c: f32) {
+        (self.api.funcs.k4a_capture_set_temperature_c)(self.handle, temperature_c)
     }
 
     /// Get temperature (in Celsius) associated with the capture.
@@ -0,0 +1,135 @@
+//! Traits over the sources of captures and IMU samples, so that the same code can read a live device
+//! or a recording replayed by [`PlaybackCamera`](crate::playback_camera::PlaybackCamera).
+//! [`SensorSource`] adds the calibration, configuration and identity, so that tools can take either a
+//! device or a recording.
+
+use crate::playback::Playback;
+use crate::*;
+
+/// Returns sensor captures like [`Camera::get_capture`].
+pub trait CaptureSource {
+    /// Reads a capture, waiting at most `timeout_in_ms` milliseconds, or infinitely if it is negative.
+    /// Returns `Error::Timeout` if no capture is available in time and `Error::Eof` if the source ended.
+    fn get_capture(&self, timeout_in_ms: i32) -> Result<Capture<'_>, Error>;
+}
+
+/// Returns IMU samples like [`Imu::get_imu_sample`].
+pub trait ImuSource {
+    /// Reads an IMU sample, with the same timeout and errors as [`CaptureSource::get_capture`].
+    fn get_imu_sample(&self, timeout_in_ms: i32) -> Result<ImuSample, Error>;
+}
+
+/// A live device or a recording with its calibration, configuration and identity, see [`DeviceSource`]
+/// and [`Playback`].
+pub trait SensorSource: CaptureSource + ImuSource {
+    /// Gets the calibration for the depth mode and color resolution of the configuration.
+    fn get_calibration(&self) -> Result<Calibration<'_>, Error>;
+
+    /// Gets the configuration the cameras were started or recorded with.
+    fn get_device_configuration(&self) -> Result<DeviceConfiguration, Error>;
+
+    /// Gets the serial number of the device. Fails for a recording made without a device.
+    fn get_serial_number(&self) -> Result<String, Error>;
+}
+
+/// A started [`Camera`] and optionally its [`Imu`], read as one [`SensorSource`].
+pub struct DeviceSource<'a> {
+    camera: &'a Camera<'a>,
+    imu: Option<&'a Imu<'a>>,
+}
+
+impl<'a> DeviceSource<'a> {
+    /// Reading IMU samples fails if `imu` is None.
+    pub fn new(camera: &'a Camera<'a>, imu: Option<&'a Imu<'a>>) -> DeviceSource<'a> {
+        DeviceSource { camera, imu }
+    }
+
+    pub fn device(&self) -> &Device<'a> {
+        self.camera.device
+    }
+
+    pub fn camera(&self) -> &Camera<'a> {
+        self.camera
+    }
+
+    pub fn imu(&self) -> Option<&Imu<'a>> {
+        self.imu
+    }
+}
+
+impl CaptureSource for DeviceSource<'_> {
+    fn get_capture(&self, timeout_in_ms: i32) -> Result<Capture<'_>, Error> {
+        self.camera.get_capture(timeout_in_ms)
+    }
+}
+
+impl ImuSource for DeviceSource<'_> {
+    fn get_imu_sample(&self, timeout_in_ms: i32) -> Result<ImuSample, Error> {
+        match self.imu {
+            Some(imu) => imu.get_imu_sample(timeout_in_ms),
+            None => Err(ErrorContext::new("k4a_device_get_imu_sample").into()),
+        }
+    }
+}
+
+impl SensorSource for DeviceSource<'_> {
+    fn get_calibration(&self) -> Result<Calibration<'_>, Error> {
+        let configuration = self.camera.configuration();
+        self.camera
+            .device
+            .get_calibration(configuration.depth_mode(), configuration.color_resolution())
+    }
+
+    fn get_device_configuration(&self) -> Result<DeviceConfiguration, Error> {
+        Ok(*self.camera.configuration())
+    }
+
+    fn get_serial_number(&self) -> Result<String, Error> {
+        self.camera.device.get_serialnum()
+    }
+}
+
+//  A recording is read as fast as it is called, the timeouts are ignored.
+impl CaptureSource for Playback<'_> {
+    fn get_capture(&self, _timeout_in_ms: i32) -> Result<Capture<'_>, Error> {
+        self.get_next_capture()
+    }
+}
+
+impl ImuSource for Playback<'_> {
+    fn get_imu_sample(&self, _timeout_in_ms: i32) -> Result<ImuSample, Error> {
+        self.get_next_imu_sample()
+    }
+}
+
+impl SensorSource for Playback<'_> {
+    fn get_calibration(&self) -> Result<Calibration<'_>, Error> {
+        Playback::get_calibration(self)
+    }
+
+    fn get_device_configuration(&self) -> Result<DeviceConfiguration, Error> {
+        Ok(self.get_record_configuration()?.device_configuration())
+    }
+
+    fn get_serial_number(&self) -> Result<String, Error> {
+        self.get_tag("K4A_DEVICE_SERIAL_NUMBER")
+    }
+}
+
+impl CaptureSource for Camera<'_> {
+    fn get_capture(&self, timeout_in_ms: i32) -> Result<Capture<'_>, Error> {
+        Camera::get_capture(self, timeout_in_ms)
+    }
+}
+
+impl CaptureSource for CaptureWorker<'_> {
+    fn get_capture(&self, timeout_in_ms: i32) -> Result<Capture<'_>, Error> {
+        CaptureWorker::get_capture(self, timeout_in_ms)
+    }
+}
+
+impl ImuSource for Imu<'_> {
+    fn get_imu_sample(&self, timeout_in_ms: i32) -> Result<ImuSample, Error> {
+        Imu::get_imu_sample(self, timeout_in_ms)
+    }
+}
@@ -0,0 +1,269 @@
+use crate::*;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// What the capture thread does when the queue is full.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum QueuePolicy {
+    /// Wait until the consumer takes a capture. The SDK drops frames meanwhile.
+    Block,
+    /// Drop the oldest queued capture to make room for the new one.
+    DropOldest,
+    /// Drop the new capture.
+    DropNewest,
+}
+
+/// Counters of a [`CaptureWorker`].
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct CaptureStatistics {
+    /// Captures taken by the consumer.
+    pub delivered: u64,
+    /// Captures dropped by the queue policy.
+    pub dropped: u64,
+    /// Frames missing between captures, based on the device timestamps and the frame rate.
+    pub gap_frames: u64,
+}
+
+/// Result of [`Queue::push`].
+#[derive(PartialEq, Debug)]
+enum Pushed<T> {
+    Queued,
+    /// A capture was dropped by the policy, the new one or the oldest one.
+    Dropped,
+    /// The queue is full and the policy is `Block`, the item is given back.
+    Full(T),
+}
+
+/// Bounded queue applying a [`QueuePolicy`], generic so that the policies are tested without a camera.
+struct Queue<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    policy: QueuePolicy,
+}
+
+impl<T> Queue<T> {
+    fn new(capacity: usize, policy: QueuePolicy) -> Queue<T> {
+        let capacity = capacity.max(1);
+        Queue {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+            policy,
+        }
+    }
+
+    fn push(&mut self, item: T) -> Pushed<T> {
+        if self.items.len() < self.capacity {
+            self.items.push_back(item);
+            return Pushed::Queued;
+        }
+        match self.policy {
+            QueuePolicy::Block => Pushed::Full(item),
+            QueuePolicy::DropOldest => {
+                self.items.pop_front();
+                self.items.push_back(item);
+                Pushed::Dropped
+            }
+            QueuePolicy::DropNewest => Pushed::Dropped,
+        }
+    }
+}
+
+struct State<'a> {
+    captures: Queue<Capture<'a>>,
+    statistics: CaptureStatistics,
+    error: Option<Error>,
+    stopped: bool,
+}
+
+/// Queue of captures filled by a dedicated thread, created by [`Camera::with_capture_worker`].
+pub struct CaptureWorker<'a> {
+    state: Mutex<State<'a>>,
+    changed: Condvar,
+}
+
+impl<'a> CaptureWorker<'a> {
+    pub(crate) fn run<R, F: FnOnce(&CaptureWorker<'_>) -> R>(
+        camera: &'a Camera<'a>,
+        capacity: usize,
+        policy: QueuePolicy,
+        f: F,
+    ) -> R {
+        let worker = CaptureWorker {
+            state: Mutex::new(State {
+                captures: Queue::new(capacity, policy),
+                statistics: CaptureStatistics::default(),
+                error: None,
+                stopped: false,
+            }),
+            changed: Condvar::new(),
+        };
+        std::thread::scope(|scope| {
+            let worker = &worker;
+            scope.spawn(move || worker.pull(camera));
+            //  Stops the thread even if `f` panics, otherwise the scope would never end.
+            let _stop = StopOnDrop(worker);
+            f(worker)
+        })
+    }
+
+    /// Takes the oldest queued capture, waiting up to `timeout_in_ms` (`K4A_WAIT_INFINITE` for no limit).
+    /// Fails with the error of the capture thread once the queue is empty.
+    pub fn get_capture(&self, timeout_in_ms: i32) -> Result<Capture<'a>, Error> {
+        let deadline = if timeout_in_ms >= 0 {
+            Some(Instant::now() + Duration::from_millis(timeout_in_ms as u64))
+        } else {
+            None
+        };
+        let mut state = self.lock();
+        loop {
+            if let Some(capture) = state.captures.items.pop_front() {
+                state.statistics.delivered += 1;
+                self.changed.notify_all();
+                return Ok(capture);
+            }
+            if let Some(e) = state.error.as_ref() {
+                return Err(e.clone());
+            }
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(Error::Timeout);
+                    }
+                    self.changed.wait_timeout(state, deadline - now).unwrap().0
+                }
+                None => self.changed.wait(state).unwrap(),
+            };
+        }
+    }
+
+    /// Takes the oldest queued capture, waiting infinitely.
+    pub fn get_capture_wait_infinite(&self) -> Result<Capture<'a>, Error> {
+        self.get_capture(azure_kinect_sys::k4a::K4A_WAIT_INFINITE)
+    }
+
+    /// Get the number of queued captures
+    pub fn len(&self) -> usize {
+        self.lock().captures.items.len()
+    }
+
+    /// Returns true if no capture is queued
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the counters so far
+    pub fn statistics(&self) -> CaptureStatistics {
+        self.lock().statistics
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<'a>> {
+        self.state.lock().unwrap()
+    }
+
+    fn pull(&self, camera: &'a Camera<'a>) {
+        let period_usec = 1_000_000 / camera.camera_fps().get_u32() as u64;
+        //  Poll so that a stop request is noticed within a few frames.
+        let timeout_in_ms = (period_usec * 2 / 1000) as i32;
+        let mut last_timestamp_usec = None;
+        while !self.lock().stopped {
+            let capture = match camera.get_capture(timeout_in_ms) {
+                Ok(capture) => capture,
+                Err(Error::Timeout) => continue,
+                Err(e) => {
+                    self.lock().error = Some(e);
+                    self.changed.notify_all();
+                    return;
+                }
+            };
+
+            let mut state = self.lock();
+            if let Some(timestamp_usec) = capture.get_device_timestamp_usec() {
+                if let Some(last) = last_timestamp_usec {
+                    state.statistics.gap_frames +=
+                        missing_frames(last, timestamp_usec, period_usec);
+                }
+                last_timestamp_usec = Some(timestamp_usec);
+            }
+            let mut capture = capture;
+            loop {
+                match state.captures.push(capture) {
+                    Pushed::Queued => break,
+                    Pushed::Dropped => {
+                        state.statistics.dropped += 1;
+                        break;
+                    }
+                    Pushed::Full(c) => {
+                        if state.stopped {
+                            return;
+                        }
+                        capture = c;
+                        state = self.changed.wait(state).unwrap();
+                    }
+                }
+            }
+            self.changed.notify_all();
+        }
+    }
+}
+
+struct StopOnDrop<'w, 'a>(&'w CaptureWorker<'a>);
+
+impl Drop for StopOnDrop<'_, '_> {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.0.state.lock() {
+            state.stopped = true;
+        }
+        self.0.changed.notify_all();
+    }
+}
+
+/// Number of frames between two timestamps that did not arrive, rounded to whole frames.
+fn missing_frames(last_usec: u64, timestamp_usec: u64, period_usec: u64) -> u64 {
+    let frames = (timestamp_usec.saturating_sub(last_usec) + period_usec / 2) / period_usec;
+    frames.saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{missing_frames, Pushed, Queue, QueuePolicy};
+
+    fn queued<T: Copy>(queue: &Queue<T>) -> Vec<T> {
+        queue.items.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_queue_policies() {
+        let mut queue = Queue::new(2, QueuePolicy::DropNewest);
+        assert_eq!(queue.push(1), Pushed::Queued);
+        assert_eq!(queue.push(2), Pushed::Queued);
+        assert_eq!(queue.push(3), Pushed::Dropped);
+        assert_eq!(queued(&queue), [1, 2]);
+
+        let mut queue = Queue::new(2, QueuePolicy::DropOldest);
+        for i in 1..=4 {
+            queue.push(i);
+        }
+        assert_eq!(queue.push(5), Pushed::Dropped);
+        assert_eq!(queued(&queue), [4, 5]);
+
+        let mut queue = Queue::new(1, QueuePolicy::Block);
+        assert_eq!(queue.push(1), Pushed::Queued);
+        assert_eq!(queue.push(2), Pushed::Full(2));
+        assert_eq!(queued(&queue), [1]);
+
+        //  A capacity of 0 still queues one capture
+        let mut queue = Queue::new(0, QueuePolicy::DropNewest);
+        assert_eq!(queue.push(1), Pushed::Queued);
+    }
+
+    #[test]
+    fn test_missing_frames() {
+        assert_eq!(missing_frames(0, 33_333, 33_333), 0);
+        assert_eq!(missing_frames(0, 36_000, 33_333), 0);
+        assert_eq!(missing_frames(0, 66_666, 33_333), 1);
+        assert_eq!(missing_frames(0, 200_000, 33_333), 5);
+        assert_eq!(missing_frames(100, 0, 33_333), 0);
+    }
+}
@@ -8,6 +8,11 @@ pub struct Device<'a> {
     pub(crate) handle: k4a_device_t,
 }
 
+//  The functions of a k4a_device_t are thread safe, e.g. reading captures on one thread while
+//  changing color controls on another. It is closed on drop, when no other reference exists.
+unsafe impl Send for Device<'_> {}
+unsafe impl Sync for Device<'_> {}
+
 #[derive(Copy, Clone)]
 pub struct ColorControlCapabilities {
     pub supports_auto: bool,
@@ -19,19 +24,21 @@ pub struct ColorControlCapabilities {
 }
 
 impl Device<'_> {
-    pub fn from_handle(api: &azure_kinect_sys::api::Api, handle: k4a_device_t) -> Device {
+    pub fn from_handle(api: &azure_kinect_sys::api::Api, handle: k4a_device_t) -> Device<'_> {
         Device { api, handle }
     }
 
     /// Starts the K4A device's cameras
-    pub fn start_cameras(&self, configuration: &DeviceConfiguration) -> Result<Camera, Error> {
-        Camera::new(&self, configuration)
+    pub fn start_cameras(&self, configuration: &DeviceConfiguration) -> Result<Camera<'_>, Error> {
+        Camera::new(self, configuration)
     }
 
     /// Get the K4A device serial number
     pub fn get_serialnum(&self) -> Result<String, Error> {
-        get_k4a_string(&|serialnum, buffer| unsafe {
-            (self.api.funcs.k4a_device_get_serialnum)(self.handle, serialnum, buffer)
+        ErrorContext::new("k4a_device_get_serialnum").call(|| {
+            get_k4a_string(&|serialnum, buffer| unsafe {
+                (self.api.funcs.k4a_device_get_serialnum)(self.handle, serialnum, buffer)
+            })
         })
     }
 
@@ -43,33 +50,42 @@ impl Device<'_> {
         let mut mode: k4a_color_control_mode_t =
             k4a_color_control_mode_t_K4A_COLOR_CONTROL_MODE_AUTO;
         let mut value: i32 = 0;
-        Error::from_k4a_result_t(unsafe {
-            (self.api.funcs.k4a_device_get_color_control)(
-                self.handle,
-                command.into(),
-                &mut mode,
-                &mut value,
-            )
-        })
-        .to_result((ColorControlMode::from_primitive(mode), value))
+        ErrorContext::new("k4a_device_get_color_control")
+            .arg("command", format!("{:?}", command))
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api.funcs.k4a_device_get_color_control)(
+                        self.handle,
+                        command.into(),
+                        &mut mode,
+                        &mut value,
+                    )
+                })
+                .to_result((ColorControlMode::from_primitive(mode), value))
+            })
     }
 
     /// Set the K4A color sensor control value
     pub fn set_color_control(
-        &mut self,
+        &self,
         command: ColorControlCommand,
         mode: ColorControlMode,
         value: i32,
     ) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
-            (self.api.funcs.k4a_device_set_color_control)(
-                self.handle,
-                command.into(),
-                mode.into(),
-                value,
-            )
-        })
-        .to_result(())
+        ErrorContext::new("k4a_device_set_color_control")
+            .arg("command", format!("{:?}", command))
+            .arg("value", value)
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api.funcs.k4a_device_set_color_control)(
+                        self.handle,
+                        command.into(),
+                        mode.into(),
+                        value,
+                    )
+                })
+                .to_result(())
+            })
     }
 
     pub fn get_color_control_capabilities(
@@ -78,28 +94,34 @@ impl Device<'_> {
     ) -> Result<ColorControlCapabilities, Error> {
         let mut capabilties = unsafe { std::mem::zeroed::<ColorControlCapabilities>() };
         let mut mode: k4a_color_control_mode_t = k4a_color_control_mode_t::default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api.funcs.k4a_device_get_color_control_capabilities)(
-                self.handle,
-                command.into(),
-                &mut capabilties.supports_auto,
-                &mut capabilties.min_value,
-                &mut capabilties.max_value,
-                &mut capabilties.step_value,
-                &mut capabilties.default_value,
-                &mut mode,
-            )
-        })
-        .to_result({
-            capabilties.default_mode = ColorControlMode::from_primitive(mode);
-            capabilties
-        })
+        ErrorContext::new("k4a_device_get_color_control_capabilities")
+            .arg("command", format!("{:?}", command))
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api.funcs.k4a_device_get_color_control_capabilities)(
+                        self.handle,
+                        command.into(),
+                        &mut capabilties.supports_auto,
+                        &mut capabilties.min_value,
+                        &mut capabilties.max_value,
+                        &mut capabilties.step_value,
+                        &mut capabilties.default_value,
+                        &mut mode,
+                    )
+                })
+                .to_result({
+                    capabilties.default_mode = ColorControlMode::from_primitive(mode);
+                    capabilties
+                })
+            })
     }
 
     /// Get the raw calibration blob for the entire K4A device.
     pub fn get_raw_calibration(&self) -> Result<Vec<u8>, Error> {
-        get_k4a_binary_data(&|calibration, buffer| unsafe {
-            (self.api.funcs.k4a_device_get_raw_calibration)(self.handle, calibration, buffer)
+        ErrorContext::new("k4a_device_get_raw_calibration").call(|| {
+            get_k4a_binary_data(&|calibration, buffer| unsafe {
+                (self.api.funcs.k4a_device_get_raw_calibration)(self.handle, calibration, buffer)
+            })
         })
     }
 
@@ -108,31 +130,38 @@ impl Device<'_> {
         &self,
         depth_mode: DepthMode,
         color_resolution: ColorResolution,
-    ) -> Result<Calibration, Error> {
+    ) -> Result<Calibration<'_>, Error> {
         let mut calibaraion = k4a_calibration_t::default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api.funcs.k4a_device_get_calibration)(
-                self.handle,
-                depth_mode.into(),
-                color_resolution.into(),
-                &mut calibaraion,
-            )
-        })
-        .to_result_fn(|| Calibration::from_handle(self.api, calibaraion))
+        ErrorContext::new("k4a_device_get_calibration")
+            .arg("depth_mode", format!("{:?}", depth_mode))
+            .arg("color_resolution", format!("{:?}", color_resolution))
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api.funcs.k4a_device_get_calibration)(
+                        self.handle,
+                        depth_mode.into(),
+                        color_resolution.into(),
+                        &mut calibaraion,
+                    )
+                })
+                .to_result_fn(|| Calibration::from_handle(self.api, calibaraion))
+            })
     }
 
     /// Get the device jack status for the synchronization connectors
     pub fn is_sync_connected(&self) -> Result<(bool, bool), Error> {
         let mut sync_in_jack_connected = false;
         let mut sync_out_jack_connected = false;
-        Error::from_k4a_result_t(unsafe {
-            (self.api.funcs.k4a_device_get_sync_jack)(
-                self.handle,
-                &mut sync_in_jack_connected,
-                &mut sync_out_jack_connected,
-            )
+        ErrorContext::new("k4a_device_get_sync_jack").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (self.api.funcs.k4a_device_get_sync_jack)(
+                    self.handle,
+                    &mut sync_in_jack_connected,
+                    &mut sync_out_jack_connected,
+                )
+            })
+            .to_result((sync_in_jack_connected, sync_out_jack_connected))
         })
-        .to_result((sync_in_jack_connected, sync_out_jack_connected))
     }
 
     /// Get the device jack status for the synchronization in connector
@@ -148,10 +177,12 @@ impl Device<'_> {
     /// Get the version numbers of the K4A subsystems' firmware
     pub fn get_version(&self) -> Result<HardwareVersion, Error> {
         let mut version = k4a_hardware_version_t::default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api.funcs.k4a_device_get_version)(self.handle, &mut version)
+        ErrorContext::new("k4a_device_get_version").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (self.api.funcs.k4a_device_get_version)(self.handle, &mut version)
+            })
+            .to_result(HardwareVersion { value: version })
         })
-        .to_result(HardwareVersion { value: version })
     }
 }
 
@@ -201,7 +232,7 @@ impl DeviceConfiguration {
 
     #[doc = " Desired frame rate for the color and depth camera."]
     pub fn camera_fps(&self) -> Fps {
-        Fps::from_primitive(self.value.camera_fps.into())
+        Fps::from_primitive(self.value.camera_fps)
     }
 
     #[doc = " Only produce k4a_capture_t objects if they contain synchronized color and depth images."]
@@ -216,7 +247,7 @@ impl DeviceConfiguration {
 
     #[doc = " The external synchronization mode."]
     pub fn wired_sync_mode(&self) -> WiredSyncMode {
-        WiredSyncMode::from_primitive(self.value.wired_sync_mode.into())
+        WiredSyncMode::from_primitive(self.value.wired_sync_mode)
     }
 
     #[doc = " The external synchronization timing."]
@@ -1,5 +1,10 @@
 use crate::structs::*;
 use azure_kinect_sys::k4a::*;
+use azure_kinect_sys::k4arecord::{
+    k4a_playback_seek_origin_t, k4a_playback_seek_origin_t_K4A_PLAYBACK_SEEK_BEGIN,
+    k4a_playback_seek_origin_t_K4A_PLAYBACK_SEEK_DEVICE_TIME,
+    k4a_playback_seek_origin_t_K4A_PLAYBACK_SEEK_END,
+};
 
 macro_rules! impl_conv_primitive_to_enum {
     ($enum_type:ident, $primitive_type:ident) => {
@@ -348,3 +353,17 @@ pub enum FirmwareSignatureType {
 }
 
 impl_conv_primitive_to_enum!(FirmwareSignatureType, k4a_firmware_signature_t);
+
+#[repr(u32)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[doc = " Playback seeking positions."]
+pub enum SeekOrigin {
+    #[doc = "< Seek relative to the beginning of a recording."]
+    Begin = k4a_playback_seek_origin_t_K4A_PLAYBACK_SEEK_BEGIN,
+    #[doc = "< Seek relative to the end of a recording."]
+    End = k4a_playback_seek_origin_t_K4A_PLAYBACK_SEEK_END,
+    #[doc = "< Seek to an absolute device timestamp."]
+    DeviceTime = k4a_playback_seek_origin_t_K4A_PLAYBACK_SEEK_DEVICE_TIME,
+}
+
+impl_conv_primitive_to_enum!(SeekOrigin, k4a_playback_seek_origin_t);
@@ -1,16 +1,179 @@
 #![allow(non_upper_case_globals)]
 
+use crate::ImageFormat;
 use azure_kinect_sys::k4a::*;
 use azure_kinect_sys::k4arecord::k4a_stream_result_t;
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 
-#[derive(Clone, Copy, Debug)]
+/// `Timeout` and `Eof` are never wrapped in a context, they can be matched directly when reading in a loop.
+/// Use `kind()` to match other errors regardless of their context.
+#[derive(Clone, Debug)]
 pub enum Error {
     Succeeded,
     Failed,
     TooSmall,
     Timeout,
-    Win32Error(u32),
+    LoadLibrary(String),
     Eof,
+    UnexpectedImageFormat(ImageFormat),
+    /// An error with the operation that failed, its arguments and the SDK messages reported during the call.
+    WithContext(Box<ErrorContext>),
+}
+
+/// Kind of an error, like `std::io::ErrorKind`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorKind {
+    Failed,
+    TooSmall,
+    Timeout,
+    LoadLibrary,
+    Eof,
+    UnexpectedImageFormat,
+    /// Reading or writing a file failed.
+    Io,
+    /// A file or a calibration blob could not be parsed.
+    InvalidData,
+}
+
+impl ErrorKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Failed => "failed",
+            ErrorKind::TooSmall => "buffer too small",
+            ErrorKind::Timeout => "timed out",
+            ErrorKind::LoadLibrary => "failed to load the library",
+            ErrorKind::Eof => "end of file",
+            ErrorKind::UnexpectedImageFormat => "unexpected image format",
+            ErrorKind::Io => "I/O error",
+            ErrorKind::InvalidData => "invalid data",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ErrorContext {
+    kind: ErrorKind,
+    operation: Option<&'static str>,
+    arguments: Vec<(&'static str, String)>,
+    messages: Vec<String>,
+    source: Option<Arc<dyn std::error::Error + Send + Sync>>,
+}
+
+thread_local! {
+    //  Messages reported by the SDK on this thread while an ErrorContext::call runs.
+    static MESSAGES: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Keeps a message reported by the SDK for the error of the running call, if any.
+pub(crate) fn capture_message(message: &str) {
+    MESSAGES.with(|messages| {
+        if let Some(messages) = messages.borrow_mut().as_mut() {
+            messages.push(message.to_string());
+        }
+    });
+}
+
+impl ErrorContext {
+    pub(crate) fn new(operation: &'static str) -> ErrorContext {
+        ErrorContext {
+            kind: ErrorKind::Failed,
+            operation: Some(operation),
+            arguments: Vec::new(),
+            messages: Vec::new(),
+            source: None,
+        }
+    }
+
+    pub(crate) fn arg<T: Display>(mut self, name: &'static str, value: T) -> ErrorContext {
+        self.arguments.push((name, value.to_string()));
+        self
+    }
+
+    pub(crate) fn with_kind(mut self, kind: ErrorKind) -> ErrorContext {
+        self.kind = kind;
+        self
+    }
+
+    pub(crate) fn with_source<E: std::error::Error + Send + Sync + 'static>(
+        mut self,
+        source: E,
+    ) -> ErrorContext {
+        self.source = Some(Arc::new(source));
+        self
+    }
+
+    /// Runs an SDK call, a failure is returned with this context and the messages the SDK reported meanwhile.
+    pub(crate) fn call<T, F: FnOnce() -> Result<T, Error>>(self, f: F) -> Result<T, Error> {
+        let previous = MESSAGES.with(|messages| messages.replace(Some(Vec::new())));
+        let result = f();
+        let messages = MESSAGES
+            .with(|messages| messages.replace(previous))
+            .unwrap_or_default();
+        result.map_err(|e| match e {
+            Error::Timeout | Error::Eof | Error::WithContext(_) => e,
+            e => Error::WithContext(Box::new(ErrorContext {
+                kind: e.kind(),
+                messages,
+                ..self
+            })),
+        })
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Gets the SDK function or the operation that failed.
+    pub fn operation(&self) -> Option<&'static str> {
+        self.operation
+    }
+
+    /// Gets the arguments of the operation that identify what failed, e.g. a device index or a file path.
+    pub fn arguments(&self) -> &[(&'static str, String)] {
+        &self.arguments
+    }
+
+    pub fn argument(&self, name: &str) -> Option<&str> {
+        self.arguments
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Gets the warnings and errors the SDK reported during the call.
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+}
+
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.operation {
+            Some(operation) => {
+                write!(f, "{}(", operation)?;
+                for (i, (name, value)) in self.arguments.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} = {}", name, value)?;
+                }
+                write!(f, ") failed")?;
+                if self.kind != ErrorKind::Failed {
+                    write!(f, " ({})", self.kind.as_str())?;
+                }
+            }
+            None => write!(f, "{}", self.kind.as_str())?,
+        }
+        if !self.messages.is_empty() {
+            write!(f, ": {}", self.messages.join("; "))?;
+        }
+        if let Some(source) = self.source.as_ref() {
+            write!(f, ": {}", source)?;
+        }
+        Ok(())
+    }
 }
 
 impl Error {
@@ -66,25 +229,128 @@ impl Error {
             _ => Err(self),
         }
     }
+
+    /// Gets the kind of the error. `Succeeded` is never returned as an error and reported as `Failed`.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Succeeded | Error::Failed => ErrorKind::Failed,
+            Error::TooSmall => ErrorKind::TooSmall,
+            Error::Timeout => ErrorKind::Timeout,
+            Error::LoadLibrary(_) => ErrorKind::LoadLibrary,
+            Error::Eof => ErrorKind::Eof,
+            Error::UnexpectedImageFormat(_) => ErrorKind::UnexpectedImageFormat,
+            Error::WithContext(context) => context.kind,
+        }
+    }
+
+    /// Gets the operation, arguments and SDK messages of the error, if it has them.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::WithContext(context) => Some(context),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorContext> for Error {
+    fn from(context: ErrorContext) -> Self {
+        Error::WithContext(Box::new(context))
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        ErrorContext {
+            kind: ErrorKind::Io,
+            operation: None,
+            arguments: Vec::new(),
+            messages: Vec::new(),
+            source: Some(Arc::new(e)),
+        }
+        .into()
+    }
 }
 
 impl From<azure_kinect_sys::Error> for Error {
     fn from(e: azure_kinect_sys::Error) -> Self {
         match e {
             azure_kinect_sys::Error::Failed => Self::Failed,
-            azure_kinect_sys::Error::Win32Error(code) => Self::Win32Error(code),
+            azure_kinect_sys::Error::LoadLibrary(message) => Self::LoadLibrary(message),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::WithContext(context) => context
+                .source
+                .as_ref()
+                .map(|source| &**source as &(dyn std::error::Error + 'static)),
+            _ => None,
+        }
+    }
+}
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Succeeded => write!(f, "succeeded"),
+            Error::LoadLibrary(message) => write!(f, "{}: {}", self.kind().as_str(), message),
+            Error::UnexpectedImageFormat(format) => {
+                write!(f, "{} {:?}", self.kind().as_str(), format)
+            }
+            Error::WithContext(context) => write!(f, "{}", context),
+            _ => write!(f, "{}", self.kind().as_str()),
+        }
     }
 }
 
-pub(crate) trait ToResult: Sized {
-    fn to_result(&self) -> Result<Self, Error>;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_context_test() {
+        let e = ErrorContext::new("k4a_device_open")
+            .arg("index", 3)
+            .call(|| {
+                capture_message("device index 3 is out of range");
+                Error::Failed.to_result(())
+            })
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Failed);
+        let context = e.context().unwrap();
+        assert_eq!(context.operation(), Some("k4a_device_open"));
+        assert_eq!(context.argument("index"), Some("3"));
+        assert_eq!(
+            e.to_string(),
+            "k4a_device_open(index = 3) failed: device index 3 is out of range"
+        );
+
+        //  Timeout and Eof stay matchable, messages outside of a call are not kept
+        capture_message("not kept");
+        let e = ErrorContext::new("k4a_device_get_capture")
+            .call(|| Error::Timeout.to_result(()))
+            .unwrap_err();
+        assert!(matches!(e, Error::Timeout));
+        assert!(ErrorContext::new("k4a_device_get_capture")
+            .call(|| Error::Succeeded.to_result(()))
+            .is_ok());
+
+        let e = ErrorContext::new("k4a_playback_get_tag")
+            .arg("name", "K4A_DEPTH_MODE")
+            .call(|| Error::TooSmall.to_result(()))
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::TooSmall);
+        assert_eq!(
+            e.to_string(),
+            "k4a_playback_get_tag(name = K4A_DEPTH_MODE) failed (buffer too small)"
+        );
+
+        let e = Error::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(e.kind(), ErrorKind::Io);
+        assert!(std::error::Error::source(&e).is_some());
+        assert!(matches!(Error::Eof.kind(), ErrorKind::Eof));
+    }
 }
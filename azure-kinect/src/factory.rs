@@ -2,12 +2,16 @@ use crate::playback::Playback;
 use crate::record::Record;
 use crate::*;
 use azure_kinect_sys::api::ApiRecord;
-use azure_kinect_sys::k4a::{k4a_calibration_t, k4a_capture_t, k4a_image_t};
+use azure_kinect_sys::k4a::{
+    k4a_calibration_t, k4a_capture_t, k4a_image_t, k4a_memory_destroy_cb_t,
+};
 use std::ffi::CString;
 use std::os::raw;
 use std::ptr;
+use std::sync::{Arc, Mutex};
 
-pub type DebugMessageHandler = dyn FnMut(LogLevel, &str, raw::c_int, &str);
+/// Called by the SDK on the thread that reports the message, hence `Send`.
+pub type DebugMessageHandler = dyn FnMut(LogLevel, &str, raw::c_int, &str) + Send;
 pub type MemoryDestroyCallback = extern "C" fn(buffer: *mut (), context: *mut ());
 
 pub trait PreAllocatedBufferInfo {
@@ -21,59 +25,113 @@ pub trait PreAllocatedBufferInfo {
 
 pub struct Factory {
     api: azure_kinect_sys::api::Api,
-    debug_message_handler: Option<Box<DebugMessageHandler>>,
+    //  Registered in DEBUG_MESSAGE_HANDLERS until the Factory is dropped.
+    debug_message: Arc<Mutex<DebugMessage>>,
 }
 
+struct DebugMessage {
+    handler: Option<Box<DebugMessageHandler>>,
+    min_level: LogLevel,
+}
+
+//  The SDK has a single debug message handler per process. It is always debug_message_handler_func,
+//  which forwards the messages to the handlers of all the Factories alive.
+static DEBUG_MESSAGE_HANDLERS: Mutex<Vec<Arc<Mutex<DebugMessage>>>> = Mutex::new(Vec::new());
+
+//  Serializes the calls to k4a_set_debug_message_handler. They are made without holding
+//  DEBUG_MESSAGE_HANDLERS because the SDK may hold its own lock while calling the handler.
+static DEBUG_MESSAGE_INSTALL: Mutex<()> = Mutex::new(());
+
+//  Least severe level reported to the SDK call that failed, see ErrorContext::call.
+const CAPTURE_LEVEL: LogLevel = LogLevel::Warning;
+
 impl Factory {
     pub fn new() -> Result<Factory, Error> {
-        Ok(Factory {
-            api: azure_kinect_sys::api::Api::new()?,
-            debug_message_handler: None,
-        })
+        Ok(Factory::with_api(azure_kinect_sys::api::Api::new()?))
     }
 
     pub fn with_library_directory(lib_dir: &str) -> Result<Factory, Error> {
-        Ok(Factory {
-            api: azure_kinect_sys::api::Api::with_library_directory(lib_dir)?,
-            debug_message_handler: None,
-        })
+        Ok(Factory::with_api(
+            azure_kinect_sys::api::Api::with_library_directory(lib_dir)?,
+        ))
     }
 
     pub fn with_get_module() -> Result<Factory, Error> {
-        Ok(Factory {
-            api: azure_kinect_sys::api::Api::with_get_module()?,
-            debug_message_handler: None,
-        })
+        Ok(Factory::with_api(
+            azure_kinect_sys::api::Api::with_get_module()?,
+        ))
+    }
+
+    /// Creates a Factory from an already created Api, e.g. `azure_kinect_sys::mock::api()`
+    /// to run without the SDK and a device.
+    pub fn with_api(api: azure_kinect_sys::api::Api) -> Factory {
+        let factory = Factory {
+            api,
+            debug_message: Arc::new(Mutex::new(DebugMessage {
+                handler: None,
+                min_level: LogLevel::Off,
+            })),
+        };
+        DEBUG_MESSAGE_HANDLERS
+            .lock()
+            .unwrap()
+            .push(factory.debug_message.clone());
+        factory.install_debug_message_handler();
+        factory
     }
 
     /// Sets and clears the callback function to receive debug messages from the Azure Kinect device.
     pub fn set_debug_message_handler(
-        mut self,
+        self,
         debug_message_handler: Box<DebugMessageHandler>,
         min_level: LogLevel,
     ) -> Self {
-        self.debug_message_handler = Some(debug_message_handler);
-        unsafe {
-            (self.api().funcs.k4a_set_debug_message_handler)(
-                Some(Self::debug_message_handler_func),
-                std::mem::transmute(self.debug_message_handler.as_mut()),
-                min_level.into(),
-            );
-        }
+        *self.debug_message.lock().unwrap() = DebugMessage {
+            handler: Some(debug_message_handler),
+            min_level,
+        };
+        self.install_debug_message_handler();
         self
     }
 
     /// Clears the callback function to receive debug messages from the Azure Kinect device.
-    pub fn reset_debug_message_handler(mut self) -> Self {
-        self.debug_message_handler = None;
+    pub fn reset_debug_message_handler(self) -> Self {
+        *self.debug_message.lock().unwrap() = DebugMessage {
+            handler: None,
+            min_level: LogLevel::Off,
+        };
+        self.install_debug_message_handler();
+        self
+    }
+
+    //  Requests the most verbose level of all the registered handlers. Warnings and errors are
+    //  always requested, they are kept for the error of the failed call.
+    fn install_debug_message_handler(&self) {
+        let _install = DEBUG_MESSAGE_INSTALL.lock().unwrap();
+        let handlers = DEBUG_MESSAGE_HANDLERS.lock().unwrap().clone();
+        if handlers.is_empty() {
+            unsafe {
+                (self.api().funcs.k4a_set_debug_message_handler)(
+                    None,
+                    ptr::null_mut(),
+                    azure_kinect_sys::k4a::k4a_log_level_t_K4A_LOG_LEVEL_OFF,
+                );
+            }
+            return;
+        }
+        let min_level = handlers
+            .iter()
+            .map(|debug_message| debug_message.lock().unwrap().min_level)
+            .filter(|level| *level != LogLevel::Off)
+            .map(azure_kinect_sys::k4a::k4a_log_level_t::from)
+            .fold(CAPTURE_LEVEL.into(), std::cmp::max);
         unsafe {
             (self.api().funcs.k4a_set_debug_message_handler)(
-                None,
+                Some(Self::debug_message_handler_func),
                 ptr::null_mut(),
-                azure_kinect_sys::k4a::k4a_log_level_t_K4A_LOG_LEVEL_OFF,
+                min_level,
             );
         }
-        self
     }
 
     pub fn api(&self) -> &azure_kinect_sys::api::Api {
@@ -86,36 +144,52 @@ impl Factory {
     }
 
     /// Open a k4a device.
-    pub fn device_open(&self, index: u32) -> Result<Device, Error> {
+    pub fn device_open(&self, index: u32) -> Result<Device<'_>, Error> {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::info_span!(target: logging::K4A_TARGET, "device_open", index).entered();
         let mut handle: azure_kinect_sys::k4a::k4a_device_t = ptr::null_mut();
-        Error::from_k4a_result_t(unsafe { (self.api().funcs.k4a_device_open)(index, &mut handle) })
-            .to_result_fn(|| Device::from_handle(&self.api, handle))
+        ErrorContext::new("k4a_device_open")
+            .arg("index", index)
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api().funcs.k4a_device_open)(index, &mut handle)
+                })
+                .to_result_fn(|| Device::from_handle(&self.api, handle))
+            })
     }
 
     /// Get the camera calibration for a device from a raw calibration blob.
     pub fn calibration_get_from_raw(
         &self,
-        raw_calibration: &Vec<u8>,
+        raw_calibration: &[u8],
         target_depth_mode: DepthMode,
         target_color_resolution: ColorResolution,
-    ) -> Result<Calibration, Error> {
+    ) -> Result<Calibration<'_>, Error> {
         let mut calibration = k4a_calibration_t::default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api().funcs.k4a_calibration_get_from_raw)(
-                raw_calibration.as_ptr() as *mut i8,
-                raw_calibration.len(),
-                target_depth_mode.into(),
-                target_color_resolution.into(),
-                &mut calibration,
-            )
-        })
-        .to_result_fn(|| Calibration::from_handle(&self.api, calibration))
-    }
-
-    pub fn capture_create(&self) -> Result<Capture, Error> {
+        ErrorContext::new("k4a_calibration_get_from_raw")
+            .arg("depth_mode", format!("{:?}", target_depth_mode))
+            .arg("color_resolution", format!("{:?}", target_color_resolution))
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api().funcs.k4a_calibration_get_from_raw)(
+                        raw_calibration.as_ptr() as *mut i8,
+                        raw_calibration.len(),
+                        target_depth_mode.into(),
+                        target_color_resolution.into(),
+                        &mut calibration,
+                    )
+                })
+                .to_result_fn(|| Calibration::from_handle(&self.api, calibration))
+            })
+    }
+
+    pub fn capture_create(&self) -> Result<Capture<'_>, Error> {
         let mut handle: k4a_capture_t = ptr::null_mut();
-        Error::from_k4a_result_t(unsafe { (self.api().funcs.k4a_capture_create)(&mut handle) })
-            .to_result_fn(|| Capture::from_handle(&self.api, handle))
+        ErrorContext::new("k4a_capture_create").call(|| {
+            Error::from_k4a_result_t(unsafe { (self.api().funcs.k4a_capture_create)(&mut handle) })
+                .to_result_fn(|| Capture::from_handle(&self.api, handle))
+        })
     }
 
     /// Create a blank image
@@ -125,21 +199,28 @@ impl Factory {
         width_pixels: i32,
         height_pixels: i32,
         stride_bytes: i32,
-    ) -> Result<Image, Error> {
+    ) -> Result<Image<'_>, Error> {
         let mut handle: k4a_image_t = ptr::null_mut();
-        Error::from_k4a_result_t(unsafe {
-            (self.api().funcs.k4a_image_create)(
-                format.into(),
-                width_pixels,
-                height_pixels,
-                stride_bytes,
-                &mut handle,
-            )
-        })
-        .to_result_fn(|| Image::from_handle(&self.api, handle))
+        ErrorContext::new("k4a_image_create")
+            .arg("format", format!("{:?}", format))
+            .arg("width_pixels", width_pixels)
+            .arg("height_pixels", height_pixels)
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api().funcs.k4a_image_create)(
+                        format.into(),
+                        width_pixels,
+                        height_pixels,
+                        stride_bytes,
+                        &mut handle,
+                    )
+                })
+                .to_result_fn(|| Image::from_handle(&self.api, handle))
+            })
     }
 
     /// Create an image from a pre-allocated buffer
+    #[allow(clippy::too_many_arguments, clippy::not_unsafe_ptr_arg_deref)]
     pub fn image_create_from_buffer_native(
         &self,
         format: ImageFormat,
@@ -150,25 +231,34 @@ impl Factory {
         buffer_size: usize,
         buffer_release_cb: Option<MemoryDestroyCallback>,
         buffer_release_cb_context: *mut (),
-    ) -> Result<Image, Error> {
+    ) -> Result<Image<'_>, Error> {
         let mut handle: k4a_image_t = ptr::null_mut();
-        Error::from_k4a_result_t(unsafe {
-            (self.api().funcs.k4a_image_create_from_buffer)(
-                format.into(),
-                width_pixels,
-                height_pixels,
-                stride_bytes,
-                buffer,
-                buffer_size,
-                std::mem::transmute(buffer_release_cb),
-                buffer_release_cb_context as _,
-                &mut handle,
-            )
-        })
-        .to_result_fn(|| Image::from_handle(&self.api, handle))
+        ErrorContext::new("k4a_image_create_from_buffer")
+            .arg("format", format!("{:?}", format))
+            .arg("width_pixels", width_pixels)
+            .arg("height_pixels", height_pixels)
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api().funcs.k4a_image_create_from_buffer)(
+                        format.into(),
+                        width_pixels,
+                        height_pixels,
+                        stride_bytes,
+                        buffer,
+                        buffer_size,
+                        std::mem::transmute::<Option<MemoryDestroyCallback>, k4a_memory_destroy_cb_t>(
+                            buffer_release_cb,
+                        ),
+                        buffer_release_cb_context as _,
+                        &mut handle,
+                    )
+                })
+                .to_result_fn(|| Image::from_handle(&self.api, handle))
+            })
     }
 
     /// Create an image from a pre-allocated buffer
+    #[allow(clippy::too_many_arguments)]
     pub fn image_create_from_buffer<T: FnOnce(*mut ())>(
         &self,
         format: ImageFormat,
@@ -178,9 +268,9 @@ impl Factory {
         buffer: *mut u8,
         buffer_size: usize,
         buffer_release_cb: Box<T>,
-    ) -> Result<Image, Error> {
+    ) -> Result<Image<'_>, Error> {
         self.image_create_from_buffer_native(
-            format.into(),
+            format,
             width_pixels,
             height_pixels,
             stride_bytes,
@@ -195,9 +285,9 @@ impl Factory {
     pub fn image_create_from_buffer_with_info<T: PreAllocatedBufferInfo + Drop>(
         &self,
         mut buffer_info: Box<T>,
-    ) -> Result<Image, Error> {
+    ) -> Result<Image<'_>, Error> {
         self.image_create_from_buffer(
-            buffer_info.format().into(),
+            buffer_info.format(),
             buffer_info.width_pixels(),
             buffer_info.height_pixels(),
             buffer_info.stride_bytes(),
@@ -213,26 +303,42 @@ impl Factory {
     pub fn transformation_create<'a>(&'a self, calibration: &'a Calibration) -> Transformation<'a> {
         let handle =
             unsafe { (self.api().funcs.k4a_transformation_create)(&calibration.calibration) };
-        Transformation::from_handle(&self, handle, calibration)
+        Transformation::from_handle(self, handle, calibration)
     }
 
     extern "C" fn debug_message_handler_func(
-        context: *mut ::std::os::raw::c_void,
+        _context: *mut ::std::os::raw::c_void,
         level: azure_kinect_sys::k4a::k4a_log_level_t,
         file: *const ::std::os::raw::c_char,
         line: ::std::os::raw::c_int,
         message: *const ::std::os::raw::c_char,
     ) {
         unsafe {
-            let f = std::mem::transmute::<_, &mut Box<DebugMessageHandler>>(context);
-            f(
-                LogLevel::from_primitive(level),
-                std::ffi::CStr::from_ptr(file).to_str().unwrap_or_default(),
-                line,
-                std::ffi::CStr::from_ptr(message)
-                    .to_str()
-                    .unwrap_or_default(),
-            );
+            let message = std::ffi::CStr::from_ptr(message)
+                .to_str()
+                .unwrap_or_default();
+            let file = std::ffi::CStr::from_ptr(file).to_str().unwrap_or_default();
+            if level <= CAPTURE_LEVEL.into() {
+                error::capture_message(message);
+            }
+            //  The handlers are called without holding the registry, they may create or drop Factories.
+            let handlers = match DEBUG_MESSAGE_HANDLERS.lock() {
+                Ok(handlers) => handlers.clone(),
+                Err(_) => return,
+            };
+            for debug_message in handlers {
+                let mut debug_message = match debug_message.lock() {
+                    Ok(debug_message) => debug_message,
+                    Err(_) => continue,
+                };
+                if debug_message.min_level != LogLevel::Off
+                    && level <= debug_message.min_level.into()
+                {
+                    if let Some(f) = debug_message.handler.as_mut() {
+                        f(LogLevel::from_primitive(level), file, line, message);
+                    }
+                }
+            }
         }
     }
 
@@ -244,6 +350,17 @@ impl Factory {
     }
 }
 
+impl Drop for Factory {
+    fn drop(&mut self) {
+        //  The handlers of the other Factories keep receiving the messages, at their own level.
+        DEBUG_MESSAGE_HANDLERS
+            .lock()
+            .unwrap()
+            .retain(|debug_message| !Arc::ptr_eq(debug_message, &self.debug_message));
+        self.install_debug_message_handler();
+    }
+}
+
 pub struct FactoryRecord {
     core: Factory,
     api_record: azure_kinect_sys::api::ApiRecord,
@@ -269,6 +386,18 @@ impl FactoryRecord {
         })
     }
 
+    /// Creates a FactoryRecord from already created Api and ApiRecord,
+    /// e.g. `azure_kinect_sys::mock::api()` and `azure_kinect_sys::mock::api_record()`.
+    pub fn with_api(
+        api: azure_kinect_sys::api::Api,
+        api_record: azure_kinect_sys::api::ApiRecord,
+    ) -> FactoryRecord {
+        FactoryRecord {
+            core: Factory::with_api(api),
+            api_record,
+        }
+    }
+
     /// Sets and clears the callback function to receive debug messages from the Azure Kinect device.
     pub fn set_debug_message_handler(
         mut self,
@@ -296,13 +425,19 @@ impl FactoryRecord {
     }
 
     /// Opens a K4A recording for playback.
-    pub fn playback_open(&self, path: &str) -> Result<Playback, Error> {
+    pub fn playback_open(&self, path: &str) -> Result<Playback<'_>, Error> {
         let mut handle: azure_kinect_sys::k4arecord::k4a_playback_t = ptr::null_mut();
         let path = CString::new(path).unwrap_or_default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record().funcs.k4a_playback_open)(path.as_ptr(), &mut handle)
-        })
-        .to_result_fn(|| Playback::from_handle(&self, handle))
+        ErrorContext::new("k4a_playback_open")
+            .arg("path", path.to_string_lossy())
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api_record().funcs.k4a_playback_open)(path.as_ptr(), &mut handle)
+                })
+                .to_result_fn(|| {
+                    Playback::from_handle(self, handle).with_path(&path.to_string_lossy())
+                })
+            })
     }
 
     /// Opens a new recording file for writing
@@ -311,18 +446,47 @@ impl FactoryRecord {
         path: &str,
         device: &Device,
         device_configuration: &DeviceConfiguration,
-    ) -> Result<Record, Error> {
+    ) -> Result<Record<'_>, Error> {
         let mut handle: azure_kinect_sys::k4arecord::k4a_record_t = ptr::null_mut();
         let path = CString::new(path).unwrap_or_default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record().funcs.k4a_record_create)(
-                path.as_ptr(),
-                device.handle as _,
-                *device_configuration.for_k4arecord(),
-                &mut handle,
-            )
-        })
-        .to_result_fn(|| Record::from_handle(&self.api_record, handle))
+        ErrorContext::new("k4a_record_create")
+            .arg("path", path.to_string_lossy())
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api_record().funcs.k4a_record_create)(
+                        path.as_ptr(),
+                        device.handle as _,
+                        *device_configuration.for_k4arecord(),
+                        &mut handle,
+                    )
+                })
+                .to_result_fn(|| Record::from_handle(&self.api_record, handle))
+            })
+    }
+
+    /// Opens a new recording file for writing without a device, e.g. to rewrite an existing recording.
+    /// The calibration, serial number and firmware versions are not written, they can be added as
+    /// tags and attachments before the header is written.
+    pub fn record_create_without_device(
+        &self,
+        path: &str,
+        device_configuration: &DeviceConfiguration,
+    ) -> Result<Record<'_>, Error> {
+        let mut handle: azure_kinect_sys::k4arecord::k4a_record_t = ptr::null_mut();
+        let path = CString::new(path).unwrap_or_default();
+        ErrorContext::new("k4a_record_create")
+            .arg("path", path.to_string_lossy())
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api_record().funcs.k4a_record_create)(
+                        path.as_ptr(),
+                        ptr::null_mut(),
+                        *device_configuration.for_k4arecord(),
+                        &mut handle,
+                    )
+                })
+                .to_result_fn(|| Record::from_handle(&self.api_record, handle))
+            })
     }
 }
 
@@ -333,29 +497,19 @@ mod tests {
 
     #[test]
     fn test() -> std::result::Result<(), Box<dyn std::error::Error>> {
-        let manager = Factory::with_library_directory(
-            std::env::current_dir()?.to_str().ok_or(Error::Failed)?,
-        );
-        assert!(manager.is_ok());
-        let manager2 = manager.unwrap();
-        let c = unsafe { (manager2.api().funcs.k4a_device_get_installed_count)() };
+        let manager = Factory::with_api(azure_kinect_sys::mock::api());
+        let c = unsafe { (manager.api().funcs.k4a_device_get_installed_count)() };
         println!("device count = {}", c);
+        assert_eq!(c, azure_kinect_sys::mock::DEVICE_COUNT);
         Ok(())
     }
 
     #[test]
     fn test_image_create_from_buffer() -> std::result::Result<(), Box<dyn std::error::Error>> {
-        let factory = Factory::with_library_directory(
-            std::env::current_dir()?.to_str().ok_or(Error::Failed)?,
-        );
-        assert!(factory.is_ok());
+        let factory = Factory::with_api(azure_kinect_sys::mock::api());
 
-        let mut mem = Vec::<u8>::with_capacity(256 * 4 * 256);
-        unsafe {
-            mem.set_len(mem.capacity());
-        }
+        let mut mem = vec![0u8; 256 * 4 * 256];
 
-        let factory = factory.unwrap();
         let _ = factory.image_create_from_buffer(
             ImageFormat::BGRA32,
             255,
@@ -377,10 +531,7 @@ mod tests {
 
     impl BufferInfo {
         pub fn new() -> BufferInfo {
-            let mut mem = Vec::<u8>::with_capacity(256 * 4 * 256);
-            unsafe {
-                mem.set_len(mem.capacity());
-            }
+            let mem = vec![0u8; 256 * 4 * 256];
 
             BufferInfo { mem }
         }
@@ -421,12 +572,7 @@ mod tests {
     #[test]
     fn test_image_create_from_buffer_with_info(
     ) -> std::result::Result<(), Box<dyn std::error::Error>> {
-        let factory = Factory::with_library_directory(
-            std::env::current_dir()?.to_str().ok_or(Error::Failed)?,
-        );
-        assert!(factory.is_ok());
-
-        let factory = factory.unwrap();
+        let factory = Factory::with_api(azure_kinect_sys::mock::api());
         let buffer_info = Box::new(BufferInfo::new());
         let _ = factory.image_create_from_buffer_with_info(buffer_info)?;
 
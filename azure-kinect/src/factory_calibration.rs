@@ -0,0 +1,325 @@
+//  Factory calibration as stored on the device (`Device::get_raw_calibration`) and in recordings
+//  (the "calibration.json" attachment). Mode specific calibration is derived as in the SDK.
+
+use crate::*;
+use azure_kinect_sys::k4a::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The factory calibration blob of a device. Every struct keeps the fields it does not know in
+/// `extra`, so that serializing a parsed calibration gives back all of the fields of the device.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FactoryCalibration {
+    #[serde(rename = "CalibrationInformation")]
+    pub calibration_information: CalibrationInformation,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalibrationInformation {
+    pub cameras: Vec<CameraCalibration>,
+    pub inertial_sensors: Vec<InertialSensorCalibration>,
+    pub metadata: CalibrationMetadata,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CameraCalibration {
+    pub intrinsics: CameraIntrinsics,
+    /// "CALIBRATION_CameraLocationD0" for the depth camera, "CALIBRATION_CameraLocationPV0" for the color camera
+    pub location: String,
+    pub purpose: String,
+    pub metric_radius: f32,
+    /// Relative to the depth camera
+    pub rt: Rt,
+    pub sensor_height: i32,
+    pub sensor_width: i32,
+    pub shutter: String,
+    pub thermal_adjustment_params: ThermalAdjustmentParams,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CameraIntrinsics {
+    pub model_parameter_count: u32,
+    /// cx, cy, fx, fy normalized by the sensor size, k1..k6, codx, cody, p2, p1
+    pub model_parameters: Vec<f32>,
+    pub model_type: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ThermalAdjustmentParams {
+    pub params: Vec<f32>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Rotation (row major) and translation in meters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Rt {
+    pub rotation: [f32; 9],
+    pub translation: [f32; 3],
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct InertialSensorCalibration {
+    pub bias_temperature_model: Vec<f32>,
+    pub bias_uncertainty: Vec<f32>,
+    pub id: String,
+    pub mixing_matrix_temperature_model: Vec<f32>,
+    pub model_type_mask: u32,
+    pub noise: Vec<f32>,
+    /// Relative to the depth camera
+    pub rt: Rt,
+    pub second_order_scaling: Vec<f32>,
+    /// "CALIBRATION_InertialSensorType_Gyro" or "CALIBRATION_InertialSensorType_Accelerometer"
+    pub sensor_type: String,
+    pub temperature_bounds: Vec<f32>,
+    #[serde(rename = "TemperatureC")]
+    pub temperature_c: f32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalibrationMetadata {
+    /// Serial number of the device
+    pub serial_id: String,
+    pub factory_cal_date: String,
+    pub version: CalibrationVersion,
+    pub device_name: String,
+    pub notes: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalibrationVersion {
+    pub major: u32,
+    pub minor: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+const DEPTH_LOCATION: &str = "CALIBRATION_CameraLocationD0";
+const COLOR_LOCATION: &str = "CALIBRATION_CameraLocationPV0";
+const GYRO_TYPE: &str = "CALIBRATION_InertialSensorType_Gyro";
+const ACCEL_TYPE: &str = "CALIBRATION_InertialSensorType_Accelerometer";
+
+impl FactoryCalibration {
+    /// Parses a raw calibration blob, which may be NUL-terminated.
+    pub fn from_raw(raw_calibration: &[u8]) -> Result<FactoryCalibration, Error> {
+        let end = raw_calibration
+            .iter()
+            .position(|c| *c == 0)
+            .unwrap_or(raw_calibration.len());
+        serde_json::from_slice(&raw_calibration[..end]).map_err(|e| {
+            ErrorContext::new("FactoryCalibration::from_raw")
+                .with_kind(ErrorKind::InvalidData)
+                .with_source(e)
+                .into()
+        })
+    }
+
+    /// Serializes to a NUL-terminated raw calibration blob, as returned by the device.
+    pub fn to_raw(&self) -> Result<Vec<u8>, Error> {
+        let mut raw = serde_json::to_vec(self).map_err(|e| {
+            Error::from(
+                ErrorContext::new("FactoryCalibration::to_raw")
+                    .with_kind(ErrorKind::InvalidData)
+                    .with_source(e),
+            )
+        })?;
+        raw.push(0);
+        Ok(raw)
+    }
+
+    /// Gets the serial number of the device that was calibrated.
+    pub fn serial_number(&self) -> &str {
+        &self.calibration_information.metadata.serial_id
+    }
+
+    fn camera(&self, location: &str) -> Result<&CameraCalibration, Error> {
+        self.calibration_information
+            .cameras
+            .iter()
+            .find(|c| c.location == location)
+            .ok_or(Error::Failed)
+    }
+
+    fn inertial_sensor(&self, sensor_type: &str) -> Result<&InertialSensorCalibration, Error> {
+        self.calibration_information
+            .inertial_sensors
+            .iter()
+            .find(|s| s.sensor_type == sensor_type)
+            .ok_or(Error::Failed)
+    }
+
+    /// Derives the calibration of a depth mode and color resolution, as `k4a_calibration_get_from_raw` does.
+    pub fn to_native(
+        &self,
+        depth_mode: DepthMode,
+        color_resolution: ColorResolution,
+    ) -> Result<k4a_calibration_t, Error> {
+        let depth = self.camera(DEPTH_LOCATION)?;
+        let color = self.camera(COLOR_LOCATION)?;
+        let sensors = [
+            &depth.rt,
+            &color.rt,
+            &self.inertial_sensor(GYRO_TYPE)?.rt,
+            &self.inertial_sensor(ACCEL_TYPE)?.rt,
+        ];
+        let mut extrinsics = [[k4a_calibration_extrinsics_t::default(); 4]; 4];
+        for (source, row) in extrinsics.iter_mut().enumerate() {
+            for (target, e) in row.iter_mut().enumerate() {
+                *e = relative_extrinsics(sensors[source], sensors[target]);
+            }
+        }
+        Ok(k4a_calibration_t {
+            depth_camera_calibration: mode_specific(depth, depth_mode_info(depth_mode))?,
+            color_camera_calibration: mode_specific(
+                color,
+                color_resolution_info(color_resolution),
+            )?,
+            extrinsics,
+            depth_mode: depth_mode.into(),
+            color_resolution: color_resolution.into(),
+        })
+    }
+
+    /// Derives the calibration of a depth mode and color resolution, for use without the native library.
+    pub fn to_model(
+        &self,
+        depth_mode: DepthMode,
+        color_resolution: ColorResolution,
+    ) -> Result<CalibrationModel, Error> {
+        CalibrationModel::from_native(&self.to_native(depth_mode, color_resolution)?)
+    }
+
+    /// Derives the calibration of a depth mode and color resolution without calling into the SDK.
+    pub fn to_calibration<'a>(
+        &self,
+        factory: &'a Factory,
+        depth_mode: DepthMode,
+        color_resolution: ColorResolution,
+    ) -> Result<Calibration<'a>, Error> {
+        Ok(Calibration::from_handle(
+            factory.api(),
+            self.to_native(depth_mode, color_resolution)?,
+        ))
+    }
+}
+
+impl CameraIntrinsics {
+    pub fn model_type(&self) -> CalibrationModelType {
+        match self.model_type.as_str() {
+            "CALIBRATION_LensDistortionModelTheta" => CalibrationModelType::Theta,
+            "CALIBRATION_LensDistortionModelPolynomial3K" => CalibrationModelType::Polynomial3K,
+            "CALIBRATION_LensDistortionModelRational6KT" => CalibrationModelType::Rational6KT,
+            "CALIBRATION_LensDistortionModelBrownConrady" => CalibrationModelType::BrownConrady,
+            _ => CalibrationModelType::Unknown,
+        }
+    }
+}
+
+//  (binned sensor resolution, crop offset, output resolution)
+type ModeInfo = ([i32; 2], [i32; 2], [i32; 2]);
+
+fn depth_mode_info(depth_mode: DepthMode) -> ModeInfo {
+    match depth_mode {
+        DepthMode::Off => ([1024, 1024], [0, 0], [0, 0]),
+        DepthMode::NFov2x2Binned => ([512, 512], [96, 90], [320, 288]),
+        DepthMode::NFovUnbinned => ([1024, 1024], [192, 180], [640, 576]),
+        DepthMode::WFov2x2Binned => ([512, 512], [0, 0], [512, 512]),
+        DepthMode::WFovUnbinned | DepthMode::PassiveIr => ([1024, 1024], [0, 0], [1024, 1024]),
+    }
+}
+
+fn color_resolution_info(color_resolution: ColorResolution) -> ModeInfo {
+    match color_resolution {
+        ColorResolution::Off => ([4096, 3072], [0, 0], [0, 0]),
+        ColorResolution::_720p => ([1280, 960], [0, 120], [1280, 720]),
+        ColorResolution::_1080p => ([1920, 1440], [0, 180], [1920, 1080]),
+        ColorResolution::_1440p => ([2560, 1920], [0, 240], [2560, 1440]),
+        ColorResolution::_1536p => ([2048, 1536], [0, 0], [2048, 1536]),
+        ColorResolution::_2160p => ([3840, 2880], [0, 360], [3840, 2160]),
+        ColorResolution::_3072p => ([4096, 3072], [0, 0], [4096, 3072]),
+    }
+}
+
+/// Scales the normalized intrinsics to the binned sensor resolution and shifts by the crop offset.
+fn mode_specific(
+    camera: &CameraCalibration,
+    (binned, crop, output): ModeInfo,
+) -> Result<k4a_calibration_camera_t, Error> {
+    let p = &camera.intrinsics.model_parameters;
+    if p.len() < 14 {
+        return Err(Error::Failed);
+    }
+    let mut v = [0.0f32; 15];
+    v[..14].copy_from_slice(&p[..14]);
+    //  The SDK puts the center of the first pixel at (0, 0)
+    v[0] = p[0] * binned[0] as f32 - crop[0] as f32 - 0.5;
+    v[1] = p[1] * binned[1] as f32 - crop[1] as f32 - 0.5;
+    v[2] = p[2] * binned[0] as f32;
+    v[3] = p[3] * binned[1] as f32;
+    v[14] = camera.metric_radius;
+    Ok(k4a_calibration_camera_t {
+        extrinsics: to_extrinsics(camera.rt.rotation, camera.rt.translation),
+        intrinsics: k4a_calibration_intrinsics_t {
+            type_: camera.intrinsics.model_type().into(),
+            parameter_count: 14,
+            parameters: k4a_calibration_intrinsic_parameters_t { v },
+        },
+        resolution_width: output[0],
+        resolution_height: output[1],
+        metric_radius: camera.metric_radius,
+    })
+}
+
+/// The translation is in meters, as in the factory calibration.
+fn to_extrinsics(rotation: [f32; 9], translation: [f32; 3]) -> k4a_calibration_extrinsics_t {
+    k4a_calibration_extrinsics_t {
+        rotation,
+        translation: [
+            translation[0] * 1000.0,
+            translation[1] * 1000.0,
+            translation[2] * 1000.0,
+        ],
+    }
+}
+
+/// Transformation from `source` to `target`, both given relative to the depth camera.
+fn relative_extrinsics(source: &Rt, target: &Rt) -> k4a_calibration_extrinsics_t {
+    let (rs, rt) = (&source.rotation, &target.rotation);
+    let mut r = [0.0f32; 9];
+    for i in 0..3 {
+        for j in 0..3 {
+            //  target.R * transpose(source.R)
+            r[i * 3 + j] = (0..3).map(|k| rt[i * 3 + k] * rs[j * 3 + k]).sum();
+        }
+    }
+    let mut t = [0.0f32; 3];
+    for (i, t) in t.iter_mut().enumerate() {
+        *t = target.translation[i]
+            - (0..3)
+                .map(|k| r[i * 3 + k] * source.translation[k])
+                .sum::<f32>();
+    }
+    to_extrinsics(r, t)
+}
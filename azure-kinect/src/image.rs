@@ -1,5 +1,6 @@
 use crate::*;
 use azure_kinect_sys::k4a::*;
+use std::marker::PhantomData;
 use std::ptr;
 
 pub struct Image<'a> {
@@ -7,13 +8,17 @@ pub struct Image<'a> {
     pub(crate) handle: k4a_image_t,
 }
 
+//  Images are reference counted by the SDK and can be released on any thread. Clones share the
+//  buffer and the metadata, which are written by the unsafe `as_bytes_mut` and setters only.
+unsafe impl Send for Image<'_> {}
+
 impl<'a> Image<'a> {
-    pub fn from_handle(api: &azure_kinect_sys::api::Api, handle: k4a_image_t) -> Image {
+    pub fn from_handle(api: &azure_kinect_sys::api::Api, handle: k4a_image_t) -> Image<'_> {
         Image { api, handle }
     }
 
     /// Create a blank image
-    #[deprecated(since = "0.2", note = "Factory::image_create")]
+    #[deprecated(since = "0.2.0", note = "Factory::image_create")]
     pub fn with_format(
         factory: &'a Factory,
         format: ImageFormat,
@@ -25,7 +30,8 @@ impl<'a> Image<'a> {
     }
 
     /// Create an image from a pre-allocated buffer
-    #[deprecated(since = "0.2", note = "Factory::image_create_from_buffer")]
+    #[deprecated(since = "0.2.0", note = "Factory::image_create_from_buffer")]
+    #[allow(clippy::too_many_arguments)]
     pub fn with_buffer(
         factory: &'a Factory,
         format: ImageFormat,
@@ -64,6 +70,78 @@ impl<'a> Image<'a> {
         unsafe { (self.api.funcs.k4a_image_get_size)(self.handle) }
     }
 
+    /// Get the image buffer as a byte slice
+    pub fn as_bytes(&self) -> &[u8] {
+        let buffer = self.get_buffer();
+        if buffer.is_null() {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(buffer, self.get_size()) }
+    }
+
+    /// Get the image buffer as a mutable byte slice, [`OwnedImage::as_bytes_mut`] is the safe
+    /// alternative.
+    ///
+    /// # Safety
+    /// Clones of an Image, and the images returned again by a capture, share the same buffer. No other
+    /// handle to this image may access its buffer while the returned slice is alive.
+    pub unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
+        let buffer = self.get_mut_buffer();
+        if buffer.is_null() {
+            return &mut [];
+        }
+        std::slice::from_raw_parts_mut(buffer, self.get_size())
+    }
+
+    /// Iterate over the rows of the image in bytes, without the padding at the end of each row
+    ///
+    /// NV12 images yield the rows of the luminance plane, MJPG images have no rows.
+    pub fn rows(&self) -> Result<Rows<'_, u8>, Error> {
+        let format = self.get_format();
+        let row_bytes = row_bytes(format, self.get_width_pixels(), self.get_stride_bytes())?;
+        self.typed_rows(&[format], row_bytes)
+    }
+
+    /// Iterate over the rows of a DEPTH16 image, in millimeters
+    pub fn depth16(&self) -> Result<Rows<'_, u16>, Error> {
+        self.typed_rows(&[ImageFormat::Depth16], self.get_width_pixels() as usize)
+    }
+
+    /// Iterate over the rows of an IR16 image
+    pub fn ir16(&self) -> Result<Rows<'_, u16>, Error> {
+        self.typed_rows(&[ImageFormat::IR16], self.get_width_pixels() as usize)
+    }
+
+    /// Iterate over the rows of a BGRA32 image, each pixel as `[b, g, r, a]`
+    pub fn bgra32(&self) -> Result<Rows<'_, [u8; 4]>, Error> {
+        self.typed_rows(&[ImageFormat::BGRA32], self.get_width_pixels() as usize)
+    }
+
+    /// Iterate over the rows of a CUSTOM8 image
+    pub fn custom8(&self) -> Result<Rows<'_, u8>, Error> {
+        self.typed_rows(&[ImageFormat::Custom8], self.get_width_pixels() as usize)
+    }
+
+    /// Iterate over the rows of a CUSTOM16 image
+    pub fn custom16(&self) -> Result<Rows<'_, u16>, Error> {
+        self.typed_rows(&[ImageFormat::Custom16], self.get_width_pixels() as usize)
+    }
+
+    fn typed_rows<T: Pixel>(
+        &self,
+        formats: &[ImageFormat],
+        width: usize,
+    ) -> Result<Rows<'_, T>, Error> {
+        Rows::new(
+            self.as_bytes(),
+            self.get_format(),
+            formats,
+            width,
+            self.get_height_pixels(),
+            self.get_stride_bytes(),
+        )
+    }
+
     /// Get the image format of the image
     pub fn get_format(&self) -> ImageFormat {
         ImageFormat::from_primitive(unsafe { (self.api.funcs.k4a_image_get_format)(self.handle) })
@@ -110,28 +188,44 @@ impl<'a> Image<'a> {
     }
 
     /// Set the image's device timestamp in microseconds
-    pub fn set_device_timestamp_usec(&mut self, timestamp: u64) {
-        unsafe { (self.api.funcs.k4a_image_set_device_timestamp_usec)(self.handle, timestamp) }
+    ///
+    /// # Safety
+    /// Clones of an Image, and the images returned again by a capture, share the same metadata. No other
+    /// handle to this image may be used while it is set.
+    pub unsafe fn set_device_timestamp_usec(&mut self, timestamp: u64) {
+        (self.api.funcs.k4a_image_set_device_timestamp_usec)(self.handle, timestamp)
     }
 
     /// Set the image's system timestamp in nanoseconds
-    pub fn set_system_timestamp_nsec(&mut self, timestamp: u64) {
-        unsafe { (self.api.funcs.k4a_image_set_system_timestamp_nsec)(self.handle, timestamp) }
+    ///
+    /// # Safety
+    /// See [`Image::set_device_timestamp_usec`].
+    pub unsafe fn set_system_timestamp_nsec(&mut self, timestamp: u64) {
+        (self.api.funcs.k4a_image_set_system_timestamp_nsec)(self.handle, timestamp)
     }
 
     /// Set the image exposure time in microseconds
-    pub fn set_exposure_usec(&mut self, exposure: u64) {
-        unsafe { (self.api.funcs.k4a_image_set_exposure_usec)(self.handle, exposure) }
+    ///
+    /// # Safety
+    /// See [`Image::set_device_timestamp_usec`].
+    pub unsafe fn set_exposure_usec(&mut self, exposure: u64) {
+        (self.api.funcs.k4a_image_set_exposure_usec)(self.handle, exposure)
     }
 
     /// Set the image white balance in Kelvin (color images only)
-    pub fn set_white_balance(&mut self, white_balance: u32) {
-        unsafe { (self.api.funcs.k4a_image_set_white_balance)(self.handle, white_balance) }
+    ///
+    /// # Safety
+    /// See [`Image::set_device_timestamp_usec`].
+    pub unsafe fn set_white_balance(&mut self, white_balance: u32) {
+        (self.api.funcs.k4a_image_set_white_balance)(self.handle, white_balance)
     }
 
     /// Set the image's ISO speed (color images only)
-    pub fn set_iso_speed(&mut self, iso_speed: u32) {
-        unsafe { (self.api.funcs.k4a_image_set_iso_speed)(self.handle, iso_speed) }
+    ///
+    /// # Safety
+    /// See [`Image::set_device_timestamp_usec`].
+    pub unsafe fn set_iso_speed(&mut self, iso_speed: u32) {
+        (self.api.funcs.k4a_image_set_iso_speed)(self.handle, iso_speed)
     }
 }
 
@@ -155,6 +249,144 @@ impl Clone for Image<'_> {
         unsafe {
             (self.api.funcs.k4a_image_reference)(self.handle);
         }
-        Image::from_handle(&self.api, self.handle)
+        Image::from_handle(self.api, self.handle)
+    }
+}
+
+/// Pixel types that can be read from an image buffer with any bit pattern.
+pub trait Pixel: Copy + 'static + private::Sealed {}
+
+impl Pixel for u8 {}
+impl Pixel for u16 {}
+impl Pixel for [u8; 4] {}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for [u8; 4] {}
+}
+
+/// Iterator over the rows of an image, created by [`Image::rows`] and the typed views.
+pub struct Rows<'b, T: Pixel> {
+    buffer: &'b [u8],
+    stride: usize,
+    width: usize,
+    height: usize,
+    _pixel: PhantomData<T>,
+}
+
+impl<'b, T: Pixel> Rows<'b, T> {
+    /// Checks `format` against `formats` and that `buffer` holds `height` rows of `width` pixels.
+    pub(crate) fn new(
+        buffer: &'b [u8],
+        format: ImageFormat,
+        formats: &[ImageFormat],
+        width: usize,
+        height: i32,
+        stride: i32,
+    ) -> Result<Rows<'b, T>, Error> {
+        if !formats.contains(&format) {
+            return Err(Error::UnexpectedImageFormat(format));
+        }
+        let height = height.max(0) as usize;
+        let stride = stride.max(0) as usize;
+        let row_bytes = width * std::mem::size_of::<T>();
+        if height > 0 && (stride < row_bytes || buffer.len() < stride * (height - 1) + row_bytes) {
+            return Err(Error::TooSmall);
+        }
+        let align = std::mem::align_of::<T>();
+        if !(buffer.as_ptr() as usize).is_multiple_of(align) || !stride.is_multiple_of(align) {
+            return Err(Error::Failed);
+        }
+        Ok(Rows {
+            buffer,
+            stride,
+            width,
+            height,
+            _pixel: PhantomData,
+        })
+    }
+}
+
+/// Number of bytes of pixel data in a row, without padding.
+pub(crate) fn row_bytes(format: ImageFormat, width: i32, stride: i32) -> Result<usize, Error> {
+    let width = width.max(0) as usize;
+    match format {
+        ImageFormat::MJPG => Err(Error::UnexpectedImageFormat(format)),
+        ImageFormat::NV12 | ImageFormat::Custom8 => Ok(width),
+        ImageFormat::YUY2 | ImageFormat::Depth16 | ImageFormat::IR16 | ImageFormat::Custom16 => {
+            Ok(width * 2)
+        }
+        ImageFormat::BGRA32 => Ok(width * 4),
+        ImageFormat::Custom => Ok(stride.max(0) as usize),
+    }
+}
+
+impl<'b, T: Pixel> Iterator for Rows<'b, T> {
+    type Item = &'b [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.height == 0 {
+            return None;
+        }
+        //  Size and alignment are checked by Rows::new.
+        let row =
+            unsafe { std::slice::from_raw_parts(self.buffer.as_ptr() as *const T, self.width) };
+        self.height -= 1;
+        if self.height > 0 {
+            self.buffer = &self.buffer[self.stride..];
+        }
+        Some(row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.height, Some(self.height))
+    }
+}
+
+impl<T: Pixel> ExactSizeIterator for Rows<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_typed_rows() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let factory = Factory::with_api(azure_kinect_sys::mock::api());
+        let mut image = factory.image_create(ImageFormat::Depth16, 3, 2, 8)?;
+        //  The image was just created, there is no other handle to it.
+        unsafe { image.as_bytes_mut() }
+            .copy_from_slice(&[1, 0, 2, 0, 3, 0, 0xff, 0xff, 4, 0, 5, 0, 6, 0, 0xff, 0xff]);
+
+        let rows = image.depth16()?.collect::<Vec<_>>();
+        assert_eq!(rows, vec![&[1u16, 2, 3][..], &[4, 5, 6][..]]);
+        assert_eq!(
+            image.rows()?.map(|row| row.len()).collect::<Vec<_>>(),
+            vec![6, 6]
+        );
+        assert!(matches!(
+            image.bgra32(),
+            Err(Error::UnexpectedImageFormat(ImageFormat::Depth16))
+        ));
+        assert!(image.ir16().is_err());
+
+        let image = factory.image_create(ImageFormat::BGRA32, 2, 1, 8)?;
+        assert_eq!(image.bgra32()?.next(), Some(&[[0u8; 4]; 2][..]));
+
+        let mut mem = vec![0u8; 16];
+        let image = factory.image_create_from_buffer(
+            ImageFormat::MJPG,
+            2,
+            2,
+            0,
+            mem.as_mut_ptr(),
+            mem.len(),
+            Box::new(|_| {}),
+        )?;
+        assert!(image.rows().is_err());
+        assert_eq!(image.as_bytes().len(), 16);
+        Ok(())
     }
 }
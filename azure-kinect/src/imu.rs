@@ -31,6 +31,16 @@ impl ImuSample {
     pub fn gyro_timestamp_usec(&self) -> u64 {
         self.value.gyro_timestamp_usec
     }
+
+    /// Set the timestamp of the accelerometer in microseconds
+    pub fn set_acc_timestamp_usec(&mut self, timestamp: u64) {
+        self.value.acc_timestamp_usec = timestamp;
+    }
+
+    /// Set the timestamp of the gyroscope in microseconds
+    pub fn set_gyro_timestamp_usec(&mut self, timestamp: u64) {
+        self.value.gyro_timestamp_usec = timestamp;
+    }
 }
 
 impl Display for ImuSample {
@@ -51,31 +61,86 @@ pub struct Imu<'a> {
 
 impl<'a> Imu<'a> {
     pub(crate) fn new(device: &'a Device<'a>) -> Result<Imu<'a>, Error> {
-        Error::from_k4a_result_t(unsafe { (device.api.funcs.k4a_device_start_imu)(device.handle) })
-            .to_result(())?;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!(target: logging::K4A_TARGET, "device_start_imu").entered();
+        ErrorContext::new("k4a_device_start_imu").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (device.api.funcs.k4a_device_start_imu)(device.handle)
+            })
+            .to_result(())
+        })?;
         Ok(Imu { device })
     }
 
     /// Reads an IMU sample.  Returns true if a sample was read, false if the read timed out.
     pub fn get_imu_sample(&self, timeout_in_ms: i32) -> Result<ImuSample, Error> {
         let mut imu_sample = k4a_imu_sample_t::default();
-        Error::from_k4a_wait_result_t(unsafe {
-            (self.device.api.funcs.k4a_device_get_imu_sample)(
-                self.device.handle,
-                &mut imu_sample,
-                timeout_in_ms,
-            )
+        ErrorContext::new("k4a_device_get_imu_sample").call(|| {
+            Error::from_k4a_wait_result_t(unsafe {
+                (self.device.api.funcs.k4a_device_get_imu_sample)(
+                    self.device.handle,
+                    &mut imu_sample,
+                    timeout_in_ms,
+                )
+            })
+            .to_result(ImuSample::from_native(imu_sample))
         })
-        .to_result(ImuSample::from_native(imu_sample))
     }
 
     pub fn get_imu_sample_wait_infinite(&self) -> Result<ImuSample, Error> {
         self.get_imu_sample(azure_kinect_sys::k4a::K4A_WAIT_INFINITE)
     }
+
+    /// Iterates over IMU samples, waiting infinitely for each one.
+    /// The iteration ends after the first error other than a timeout.
+    pub fn samples(&self) -> ImuSamples<'_> {
+        ImuSamples {
+            imu: self,
+            timeout_in_ms: K4A_WAIT_INFINITE,
+            on_timeout: TimeoutBehavior::Drop,
+            finished: false,
+        }
+    }
+}
+
+/// Iterator over IMU samples, created by [`Imu::samples`].
+pub struct ImuSamples<'a> {
+    imu: &'a Imu<'a>,
+    timeout_in_ms: i32,
+    on_timeout: TimeoutBehavior,
+    finished: bool,
+}
+
+impl ImuSamples<'_> {
+    /// Sets the timeout of each read.
+    pub fn timeout(mut self, timeout_in_ms: i32) -> Self {
+        self.timeout_in_ms = timeout_in_ms;
+        self
+    }
+
+    /// Sets what happens when a read times out.
+    pub fn on_timeout(mut self, on_timeout: TimeoutBehavior) -> Self {
+        self.on_timeout = on_timeout;
+        self
+    }
+}
+
+impl Iterator for ImuSamples<'_> {
+    type Item = Result<ImuSample, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let imu = self.imu;
+        let timeout_in_ms = self.timeout_in_ms;
+        camera::next_until_error(&mut self.finished, self.on_timeout, || {
+            imu.get_imu_sample(timeout_in_ms)
+        })
+    }
 }
 
 impl Drop for Imu<'_> {
     fn drop(&mut self) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!(target: logging::K4A_TARGET, "device_stop_imu").entered();
         unsafe { (self.device.api.funcs.k4a_device_stop_imu)(self.device.handle) }
     }
 }
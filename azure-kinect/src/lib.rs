@@ -1,34 +1,55 @@
 pub use calibration::Calibration;
-pub use camera::Camera;
+pub use camera::{Camera, Captures, TimeoutBehavior};
+pub use camera_model::{CalibrationModel, CameraModel};
 pub use capture::Capture;
+pub use capture_source::{CaptureSource, DeviceSource, ImuSource, SensorSource};
+pub use capture_worker::{CaptureStatistics, CaptureWorker, QueuePolicy};
 pub use device::{Device, DeviceConfiguration, DeviceConfigurationBuilder};
 pub use enums::*;
-pub use error::Error;
+pub use error::{Error, ErrorContext, ErrorKind};
 pub use factory::{DebugMessageHandler, Factory, FactoryRecord, MemoryDestroyCallback};
-pub use image::Image;
-pub use imu::{Imu, ImuSample};
+pub use factory_calibration::FactoryCalibration;
+pub use image::{Image, Pixel, Rows};
+pub use imu::{Imu, ImuSample, ImuSamples};
+pub use owned::{OwnedCapture, OwnedImage};
+pub use point_cloud::{PointCloud, PointCloudFormat};
+pub use shared::{SharedCamera, SharedCapture, SharedDevice};
 pub use structs::*;
 pub use transformation::Transformation;
+pub use transformation_model::TransformationModel;
 pub use vectors::*;
 
 pub mod calibration;
 pub mod camera;
+pub mod camera_model;
 pub mod capture;
+pub mod capture_source;
+pub mod capture_worker;
 pub mod device;
 pub mod enums;
 pub mod error;
 pub mod factory;
+pub mod factory_calibration;
 pub mod image;
 pub mod imu;
+pub mod logging;
+pub mod mkv;
+pub mod owned;
 pub mod playback;
+pub mod playback_camera;
 pub mod playback_data_block;
 pub mod playback_track;
+pub mod point_cloud;
 pub mod record;
+pub mod shared;
 pub mod structs;
 pub mod transformation;
+pub mod transformation_model;
 pub mod utility;
 pub mod vectors;
 
 pub trait NativeHandle {
+    /// # Safety
+    /// The returned handle is owned by `self` and must not be released or used after `self` is dropped.
     unsafe fn get_native_handle(&self) -> *mut ();
-}
\ No newline at end of file
+}
@@ -0,0 +1,181 @@
+//! Ready-made debug message handlers forwarding SDK messages to the `log` crate (feature `log`)
+//! or to `tracing` events (feature `tracing`).
+//!
+//! ```ignore
+//! let factory = Factory::new()?
+//!     .set_debug_message_handler(logging::log_message_handler(), logging::log_max_level());
+//! ```
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+use crate::*;
+
+/// Target of messages from the k4a library.
+pub const K4A_TARGET: &str = "k4a";
+/// Target of messages from the k4arecord library.
+pub const K4ARECORD_TARGET: &str = "k4arecord";
+
+/// Gets the target of a message from the source file it was reported from.
+/// The SDK has a single debug message handler, the sources of k4arecord are under `src/record`.
+pub fn library_target(file: &str) -> &'static str {
+    let mut components = file.rsplit(['/', '\\']);
+    let stem = components
+        .next()
+        .and_then(|name| name.split('.').next())
+        .unwrap_or_default();
+    if stem == "record" || stem == "playback" || components.any(|c| c == "record") {
+        K4ARECORD_TARGET
+    } else {
+        K4A_TARGET
+    }
+}
+
+/// Creates a handler that forwards SDK messages to the `log` crate.
+/// The original file and line are set on the record, the target is `k4a` or `k4arecord`.
+#[cfg(feature = "log")]
+pub fn log_message_handler() -> Box<DebugMessageHandler> {
+    Box::new(|level, file, line, message| {
+        let level = match level {
+            LogLevel::Critical | LogLevel::Error => log::Level::Error,
+            LogLevel::Warning => log::Level::Warn,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Trace => log::Level::Trace,
+            LogLevel::Off => return,
+        };
+        if level > log::max_level() {
+            return;
+        }
+        log::logger().log(
+            &log::Record::builder()
+                .level(level)
+                .target(library_target(file))
+                .file(Some(file))
+                .line(Some(line as u32))
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    })
+}
+
+/// Gets the least severe level the SDK needs to report for the current `log::max_level()`.
+#[cfg(feature = "log")]
+pub fn log_max_level() -> LogLevel {
+    match log::max_level() {
+        log::LevelFilter::Off => LogLevel::Off,
+        log::LevelFilter::Error => LogLevel::Error,
+        log::LevelFilter::Warn => LogLevel::Warning,
+        log::LevelFilter::Info => LogLevel::Info,
+        log::LevelFilter::Debug | log::LevelFilter::Trace => LogLevel::Trace,
+    }
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! sdk_event {
+    ($level:expr, $file:expr, $line:expr, $message:expr) => {
+        if library_target($file) == K4ARECORD_TARGET {
+            tracing::event!(target: K4ARECORD_TARGET, $level, file = $file, line = $line, "{}", $message)
+        } else {
+            tracing::event!(target: K4A_TARGET, $level, file = $file, line = $line, "{}", $message)
+        }
+    };
+}
+
+/// Creates a handler that emits SDK messages as `tracing` events.
+/// The original file and line are recorded as the `file` and `line` fields, the target is `k4a` or `k4arecord`.
+/// Messages are reported inside the spans of the device open/start/stop that caused them.
+#[cfg(feature = "tracing")]
+pub fn tracing_message_handler() -> Box<DebugMessageHandler> {
+    Box::new(|level, file, line, message| match level {
+        LogLevel::Critical | LogLevel::Error => {
+            sdk_event!(tracing::Level::ERROR, file, line, message)
+        }
+        LogLevel::Warning => sdk_event!(tracing::Level::WARN, file, line, message),
+        LogLevel::Info => sdk_event!(tracing::Level::INFO, file, line, message),
+        LogLevel::Trace => sdk_event!(tracing::Level::TRACE, file, line, message),
+        LogLevel::Off => (),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::*;
+
+    #[test]
+    fn library_target_test() {
+        assert_eq!(
+            library_target("src/record/sdk/record.cpp"),
+            K4ARECORD_TARGET
+        );
+        assert_eq!(
+            library_target("C:\\sdk\\src\\record\\internal\\matroska_read.cpp"),
+            K4ARECORD_TARGET
+        );
+        assert_eq!(
+            library_target("azure-kinect-sys/src/mock/playback.rs"),
+            K4ARECORD_TARGET
+        );
+        assert_eq!(library_target("src/sdk/k4a.c"), K4A_TARGET);
+        assert_eq!(
+            library_target("azure-kinect-sys/src/mock/device.rs"),
+            K4A_TARGET
+        );
+        assert_eq!(library_target(""), K4A_TARGET);
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn log_message_handler_test() -> Result<(), Box<dyn std::error::Error>> {
+        use std::sync::Mutex;
+
+        //  Level, target, file and message
+        type Entry = (log::Level, String, String, String);
+        struct Logger(Mutex<Vec<Entry>>);
+
+        impl log::Log for Logger {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+            fn log(&self, record: &log::Record) {
+                self.0.lock().unwrap().push((
+                    record.level(),
+                    record.target().to_string(),
+                    record.file().unwrap_or_default().to_string(),
+                    record.args().to_string(),
+                ));
+            }
+            fn flush(&self) {}
+        }
+
+        static LOGGER: Logger = Logger(Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Info);
+        assert_eq!(log_max_level(), LogLevel::Info);
+
+        let factory = FactoryRecord::with_api(
+            azure_kinect_sys::mock::api(),
+            azure_kinect_sys::mock::api_record(),
+        )
+        .set_debug_message_handler(log_message_handler(), log_max_level());
+        assert!(factory.core().device_open(100).is_err());
+        assert!(factory
+            .record_create_without_device("", &DeviceConfiguration::default())
+            .is_err());
+        std::mem::drop(factory);
+
+        let records = LOGGER.0.lock().unwrap();
+        let (level, target, file, _) = records
+            .iter()
+            .find(|(_, _, _, message)| message.starts_with("device index 100"))
+            .unwrap();
+        assert_eq!(*level, log::Level::Error);
+        assert_eq!(target, K4A_TARGET);
+        assert!(file.ends_with("device.rs"));
+        let (_, target, _, _) = records
+            .iter()
+            .find(|(_, _, _, message)| message == "invalid recording path")
+            .unwrap();
+        assert_eq!(target, K4ARECORD_TARGET);
+        Ok(())
+    }
+}
@@ -0,0 +1,322 @@
+//  Minimal EBML (Matroska container) element reading.
+
+use crate::Error;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+pub(crate) const EBML: u32 = 0x1A45_DFA3;
+pub(crate) const EBML_VERSION: u32 = 0x4286;
+pub(crate) const EBML_READ_VERSION: u32 = 0x42F7;
+pub(crate) const EBML_MAX_ID_LENGTH: u32 = 0x42F2;
+pub(crate) const EBML_MAX_SIZE_LENGTH: u32 = 0x42F3;
+pub(crate) const DOC_TYPE: u32 = 0x4282;
+pub(crate) const DOC_TYPE_VERSION: u32 = 0x4287;
+pub(crate) const DOC_TYPE_READ_VERSION: u32 = 0x4285;
+pub(crate) const VOID: u32 = 0xEC;
+pub(crate) const SEGMENT: u32 = 0x1853_8067;
+pub(crate) const SEEK_HEAD: u32 = 0x114D_9B74;
+pub(crate) const SEEK: u32 = 0x4DBB;
+pub(crate) const SEEK_ID: u32 = 0x53AB;
+pub(crate) const SEEK_POSITION: u32 = 0x53AC;
+pub(crate) const INFO: u32 = 0x1549_A966;
+pub(crate) const TIMECODE_SCALE: u32 = 0x2A_D7B1;
+pub(crate) const DURATION: u32 = 0x4489;
+pub(crate) const MUXING_APP: u32 = 0x4D80;
+pub(crate) const WRITING_APP: u32 = 0x5741;
+pub(crate) const TRACKS: u32 = 0x1654_AE6B;
+pub(crate) const TRACK_ENTRY: u32 = 0xAE;
+pub(crate) const TRACK_NUMBER: u32 = 0xD7;
+pub(crate) const TRACK_UID: u32 = 0x73C5;
+pub(crate) const TRACK_TYPE: u32 = 0x83;
+pub(crate) const DEFAULT_DURATION: u32 = 0x23_E383;
+pub(crate) const NAME: u32 = 0x536E;
+pub(crate) const CODEC_ID: u32 = 0x86;
+pub(crate) const CODEC_PRIVATE: u32 = 0x63A2;
+pub(crate) const VIDEO: u32 = 0xE0;
+pub(crate) const PIXEL_WIDTH: u32 = 0xB0;
+pub(crate) const PIXEL_HEIGHT: u32 = 0xBA;
+pub(crate) const CLUSTER: u32 = 0x1F43_B675;
+pub(crate) const TIMECODE: u32 = 0xE7;
+pub(crate) const SIMPLE_BLOCK: u32 = 0xA3;
+pub(crate) const BLOCK_GROUP: u32 = 0xA0;
+pub(crate) const BLOCK: u32 = 0xA1;
+pub(crate) const CUES: u32 = 0x1C53_BB6B;
+pub(crate) const CUE_POINT: u32 = 0xBB;
+pub(crate) const CUE_TIME: u32 = 0xB3;
+pub(crate) const CUE_TRACK_POSITIONS: u32 = 0xB7;
+pub(crate) const CUE_TRACK: u32 = 0xF7;
+pub(crate) const CUE_CLUSTER_POSITION: u32 = 0xF1;
+pub(crate) const TAGS: u32 = 0x1254_C367;
+pub(crate) const TAG: u32 = 0x7373;
+pub(crate) const TARGETS: u32 = 0x63C0;
+pub(crate) const SIMPLE_TAG: u32 = 0x67C8;
+pub(crate) const TAG_NAME: u32 = 0x45A3;
+pub(crate) const TAG_STRING: u32 = 0x4487;
+pub(crate) const ATTACHMENTS: u32 = 0x1941_A469;
+pub(crate) const ATTACHED_FILE: u32 = 0x61A7;
+pub(crate) const FILE_NAME: u32 = 0x466E;
+pub(crate) const FILE_MIME_TYPE: u32 = 0x4660;
+pub(crate) const FILE_DATA: u32 = 0x465C;
+pub(crate) const FILE_UID: u32 = 0x46AE;
+
+/// Top level elements of a segment, which end a cluster of unknown size.
+pub(crate) const SEGMENT_CHILDREN: [u32; 8] = [
+    SEEK_HEAD,
+    INFO,
+    TRACKS,
+    CLUSTER,
+    CUES,
+    TAGS,
+    ATTACHMENTS,
+    0x1043_A770, //  Chapters
+];
+
+/// Header of an element: its id, the size of its data (None if unknown) and where the data starts.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Header {
+    pub id: u32,
+    pub size: Option<u64>,
+    pub data_offset: u64,
+}
+
+impl Header {
+    /// Offset of the next element, if the size is known.
+    pub fn end(&self) -> Option<u64> {
+        self.size.map(|size| self.data_offset + size)
+    }
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> Result<u8, Error> {
+    let mut b = [0u8; 1];
+    reader.read_exact(&mut b).map_err(Error::from)?;
+    Ok(b[0])
+}
+
+/// Reads a variable size integer, returns (value without the length marker, length in bytes).
+pub(crate) fn read_vint<R: Read>(reader: &mut R) -> Result<(u64, usize), Error> {
+    let first = read_u8(reader)?;
+    let length = first.leading_zeros() as usize + 1;
+    if length > 8 {
+        return Err(Error::Failed);
+    }
+    let mut value = (first as u64) & (0xFF >> length);
+    for _ in 1..length {
+        value = (value << 8) | read_u8(reader)? as u64;
+    }
+    Ok((value, length))
+}
+
+/// Parses a variable size integer from the start of `data`, returns (value, length in bytes).
+pub(crate) fn parse_vint(data: &[u8]) -> Result<(u64, usize), Error> {
+    let mut reader = data;
+    read_vint(&mut reader)
+}
+
+/// Reads the header of the element at the current position, None at the end of the stream.
+pub(crate) fn read_header<R: Read + Seek>(reader: &mut R) -> Result<Option<Header>, Error> {
+    let first = match read_u8(reader) {
+        Ok(first) => first,
+        Err(_) => return Ok(None),
+    };
+    let length = first.leading_zeros() as usize + 1;
+    if length > 4 {
+        return Err(Error::Failed);
+    }
+    let mut id = first as u32;
+    for _ in 1..length {
+        id = (id << 8) | read_u8(reader)? as u32;
+    }
+    let (size, size_length) = read_vint(reader)?;
+    let unknown = size == (1u64 << (7 * size_length)) - 1;
+    Ok(Some(Header {
+        id,
+        size: if unknown { None } else { Some(size) },
+        data_offset: reader.stream_position().map_err(Error::from)?,
+    }))
+}
+
+/// Reads the data of an element of known size.
+pub(crate) fn read_data<R: Read + Seek>(reader: &mut R, header: &Header) -> Result<Vec<u8>, Error> {
+    let size = header.size.ok_or(Error::Failed)?;
+    reader
+        .seek(SeekFrom::Start(header.data_offset))
+        .map_err(Error::from)?;
+    let mut data = Vec::new();
+    reader
+        .take(size)
+        .read_to_end(&mut data)
+        .map_err(Error::from)?;
+    if data.len() as u64 != size {
+        return Err(Error::Failed);
+    }
+    Ok(data)
+}
+
+/// A master element read into memory.
+pub(crate) struct Element {
+    pub id: u32,
+    pub data: Vec<u8>,
+}
+
+impl Element {
+    /// Child elements of a master element.
+    pub fn children(&self) -> Result<Vec<Element>, Error> {
+        let mut children = Vec::new();
+        let mut reader = std::io::Cursor::new(&self.data);
+        while let Some(header) = read_header(&mut reader)? {
+            let data = read_data(&mut reader, &header)?;
+            reader
+                .seek(SeekFrom::Start(header.end().ok_or(Error::Failed)?))
+                .map_err(Error::from)?;
+            children.push(Element {
+                id: header.id,
+                data,
+            });
+        }
+        Ok(children)
+    }
+
+    pub fn as_uint(&self) -> u64 {
+        self.data
+            .iter()
+            .fold(0, |value, b| (value << 8) | *b as u64)
+    }
+
+    pub fn as_string(&self) -> String {
+        let end = self
+            .data
+            .iter()
+            .position(|b| *b == 0)
+            .unwrap_or(self.data.len());
+        String::from_utf8_lossy(&self.data[..end]).into_owned()
+    }
+}
+
+/// Splits the data of a Block or SimpleBlock into (track number, relative timecode, frames).
+pub(crate) fn parse_block(data: &[u8]) -> Result<(u64, i16, Vec<&[u8]>), Error> {
+    let (track, length) = parse_vint(data)?;
+    if data.len() < length + 3 {
+        return Err(Error::Failed);
+    }
+    let timecode = i16::from_be_bytes([data[length], data[length + 1]]);
+    let flags = data[length + 2];
+    let payload = &data[length + 3..];
+    let frames = match (flags >> 1) & 0x03 {
+        0 => vec![payload],
+        lacing => split_laced(lacing, payload)?,
+    };
+    Ok((track, timecode, frames))
+}
+
+fn split_laced(lacing: u8, payload: &[u8]) -> Result<Vec<&[u8]>, Error> {
+    let (&count, mut rest) = payload.split_first().ok_or(Error::Failed)?;
+    let count = count as usize + 1;
+    let mut sizes = Vec::with_capacity(count);
+    match lacing {
+        //  Xiph lacing
+        1 => {
+            for _ in 1..count {
+                let mut size = 0usize;
+                loop {
+                    let (&b, r) = rest.split_first().ok_or(Error::Failed)?;
+                    rest = r;
+                    size += b as usize;
+                    if b != 0xFF {
+                        break;
+                    }
+                }
+                sizes.push(size);
+            }
+        }
+        //  Fixed-size lacing
+        2 => {
+            if rest.len() % count != 0 {
+                return Err(Error::Failed);
+            }
+            sizes.resize(count - 1, rest.len() / count);
+        }
+        //  EBML lacing
+        _ => {
+            if count > 1 {
+                let (first, length) = parse_vint(rest)?;
+                rest = &rest[length..];
+                sizes.push(first as usize);
+                for _ in 2..count {
+                    let (raw, length) = parse_vint(rest)?;
+                    rest = &rest[length..];
+                    let bias = (1i64 << (7 * length - 1)) - 1;
+                    let size = *sizes.last().unwrap() as i64 + (raw as i64 - bias);
+                    if size < 0 {
+                        return Err(Error::Failed);
+                    }
+                    sizes.push(size as usize);
+                }
+            }
+        }
+    }
+    let mut frames = Vec::with_capacity(count);
+    for size in sizes {
+        if rest.len() < size {
+            return Err(Error::Failed);
+        }
+        let (frame, r) = rest.split_at(size);
+        frames.push(frame);
+        rest = r;
+    }
+    frames.push(rest);
+    Ok(frames)
+}
+
+/// Encodes a variable size integer with the fewest bytes, e.g. the track number of a block. The value
+/// with all bits set is reserved at each length.
+pub(crate) fn vint(value: u64) -> Vec<u8> {
+    let length = (1..=8).find(|l| value < (1 << (7 * l)) - 1).unwrap_or(8);
+    let mut data = value.to_be_bytes()[8 - length..].to_vec();
+    data[0] |= 0x80 >> (length - 1);
+    data
+}
+
+/// Encodes an element id followed by an 8 byte data size, which can be patched in place later.
+pub(crate) fn header(id: u32, size: u64) -> Vec<u8> {
+    let mut data: Vec<u8> = id
+        .to_be_bytes()
+        .iter()
+        .copied()
+        .skip_while(|b| *b == 0)
+        .collect();
+    data.push(0x01);
+    data.extend_from_slice(&size.to_be_bytes()[1..]);
+    data
+}
+
+/// Encodes an element.
+pub(crate) fn element(id: u32, body: &[u8]) -> Vec<u8> {
+    let mut data = header(id, body.len() as u64);
+    data.extend_from_slice(body);
+    data
+}
+
+/// Encodes an unsigned integer element with the fewest bytes.
+pub(crate) fn uint_element(id: u32, value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = (value.leading_zeros() as usize / 8).min(7);
+    element(id, &bytes[skip..])
+}
+
+pub(crate) fn float_element(id: u32, value: f64) -> Vec<u8> {
+    element(id, &value.to_be_bytes())
+}
+
+pub(crate) fn string_element(id: u32, value: &str) -> Vec<u8> {
+    element(id, value.as_bytes())
+}
+
+/// Encodes a Void element of exactly `size` bytes, at least 9.
+pub(crate) fn void_element(size: usize) -> Vec<u8> {
+    let mut data = header(VOID, (size - 9) as u64);
+    data.resize(size, 0);
+    data
+}
+
+pub(crate) fn write_all<W: Write>(writer: &mut W, data: &[u8]) -> Result<(), Error> {
+    writer.write_all(data).map_err(Error::from)
+}
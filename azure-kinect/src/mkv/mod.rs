@@ -0,0 +1,26 @@
+//! Reading and writing Azure Kinect recordings in pure Rust, without the record SDK.
+//!
+//! The layout follows the recordings of the SDK: video tracks named "COLOR", "DEPTH" and "IR",
+//! an "IMU" track, the device settings as tags and the calibration as an attachment.
+
+mod ebml;
+pub mod reader;
+pub mod writer;
+
+pub use reader::{MkvPlayback, MkvTrack};
+pub use writer::MkvRecord;
+
+pub(crate) const COLOR_TRACK: &str = "COLOR";
+pub(crate) const DEPTH_TRACK: &str = "DEPTH";
+pub(crate) const IR_TRACK: &str = "IR";
+pub(crate) const IMU_TRACK: &str = "IMU";
+pub(crate) const BUILTIN_TRACKS: [&str; 4] = [COLOR_TRACK, DEPTH_TRACK, IR_TRACK, IMU_TRACK];
+
+pub(crate) const CODEC_MJPEG: &str = "V_MJPEG";
+pub(crate) const CODEC_FOURCC: &str = "V_MS/VFW/FOURCC";
+pub(crate) const CODEC_IMU: &str = "S_K4A/IMU";
+
+pub(crate) const CALIBRATION_FILE: &str = "calibration.json";
+
+/// Size of an IMU sample in a block: accelerometer timestamp and x, y, z, then the same for the gyroscope.
+pub(crate) const IMU_SAMPLE_SIZE: usize = 40;
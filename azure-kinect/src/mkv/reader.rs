@@ -0,0 +1,1263 @@
+#![allow(non_upper_case_globals)]
+
+use super::ebml::*;
+use super::*;
+use crate::playback::{RecordConfiguration, RecordingMetadata};
+use crate::record::RecordVideoSettings;
+use crate::*;
+use azure_kinect_sys::k4a::{k4a_float3_t, k4a_imu_sample_t};
+use azure_kinect_sys::k4arecord::*;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+/// A track of a recording, read by [`MkvPlayback`].
+#[derive(Clone, Debug)]
+pub struct MkvTrack {
+    name: String,
+    number: u64,
+    uid: u64,
+    codec_id: String,
+    codec_context: Vec<u8>,
+    width: u64,
+    height: u64,
+    default_duration_ns: u64,
+}
+
+impl MkvTrack {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Checks whether the track is one of the built-in tracks: "COLOR", "DEPTH", etc...
+    pub fn is_builtin(&self) -> bool {
+        BUILTIN_TRACKS.contains(&self.name.as_str())
+    }
+
+    /// Gets the video-specific track information for a video track.
+    pub fn get_video_settings(&self) -> Result<RecordVideoSettings, Error> {
+        if self.width == 0 || self.height == 0 {
+            return Err(Error::Failed);
+        }
+        let frame_rate = (1_000_000_000 + self.default_duration_ns / 2)
+            .checked_div(self.default_duration_ns)
+            .unwrap_or(0);
+        Ok(RecordVideoSettings::new(
+            self.width,
+            self.height,
+            frame_rate,
+        ))
+    }
+
+    /// Gets the codec id string of the track.
+    pub fn get_codec_id(&self) -> &str {
+        &self.codec_id
+    }
+
+    /// Gets the codec context of the track.
+    pub fn get_codec_context(&self) -> &[u8] {
+        &self.codec_context
+    }
+}
+
+/// Where a built-in video track is decoded to.
+#[derive(Clone, Copy, Debug)]
+struct VideoTrack {
+    number: u64,
+    format: ImageFormat,
+    width: i32,
+    height: i32,
+    stride: i32,
+    big_endian: bool,
+    //  Subtracted from the block timestamps so that the images of a capture line up.
+    delay_ns: i64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ClusterInfo {
+    data_offset: u64,
+    end: u64,
+    timecode: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct BlockRef {
+    track: u64,
+    timestamp_ns: i64,
+    data_offset: u64,
+    size: u64,
+}
+
+/// The gap before a block: the index of a cluster and of a block in that cluster.
+#[derive(Clone, Copy, Default, PartialEq, PartialOrd, Debug)]
+struct BlockPos {
+    cluster: usize,
+    block: usize,
+}
+
+struct AttachmentRef {
+    name: String,
+    data_offset: u64,
+    size: u64,
+}
+
+const CACHED_CLUSTERS: usize = 4;
+
+/// Reads Azure Kinect recordings (Matroska files) without the SDK.
+///
+/// Captures and IMU samples are returned as owned data. Timestamps are device timestamps,
+/// i.e. the start offset of the recording is added back.
+pub struct MkvPlayback<R = BufReader<File>> {
+    reader: R,
+    timecode_scale: u64,
+    tracks: Vec<MkvTrack>,
+    tags: Vec<(String, String)>,
+    attachments: Vec<AttachmentRef>,
+    clusters: Vec<ClusterInfo>,
+    cache: Vec<(usize, Arc<Vec<BlockRef>>)>,
+    videos: Vec<(VideoTrack, BlockPos)>,
+    imu: Option<(u64, BlockPos, usize)>,
+    configuration: k4a_record_configuration_t,
+    start_offset_ns: i64,
+    period_ns: i64,
+    last_timestamp_ns: i64,
+}
+
+impl MkvPlayback {
+    /// Opens a recording file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<MkvPlayback, Error> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| {
+            ErrorContext::new("File::open")
+                .arg("path", path.display())
+                .with_kind(ErrorKind::Io)
+                .with_source(e)
+        })?;
+        MkvPlayback::from_reader(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> MkvPlayback<R> {
+    /// Reads a recording from a stream.
+    pub fn from_reader(mut reader: R) -> Result<MkvPlayback<R>, Error> {
+        reader.seek(SeekFrom::Start(0)).map_err(Error::from)?;
+        let ebml = read_header(&mut reader)?.ok_or(Error::Failed)?;
+        if ebml.id != EBML {
+            return Err(Error::Failed);
+        }
+        let doc_type = Element {
+            id: EBML,
+            data: read_data(&mut reader, &ebml)?,
+        }
+        .children()?
+        .into_iter()
+        .find(|e| e.id == DOC_TYPE)
+        .map(|e| e.as_string());
+        if doc_type.as_deref() != Some("matroska") {
+            return Err(Error::Failed);
+        }
+        reader
+            .seek(SeekFrom::Start(ebml.end().ok_or(Error::Failed)?))
+            .map_err(Error::from)?;
+        let segment = read_header(&mut reader)?.ok_or(Error::Failed)?;
+        if segment.id != SEGMENT {
+            return Err(Error::Failed);
+        }
+
+        let mut playback = MkvPlayback {
+            reader,
+            timecode_scale: 1_000_000,
+            tracks: Vec::new(),
+            tags: Vec::new(),
+            attachments: Vec::new(),
+            clusters: Vec::new(),
+            cache: Vec::new(),
+            videos: Vec::new(),
+            imu: None,
+            configuration: k4a_record_configuration_t::default(),
+            start_offset_ns: 0,
+            period_ns: 0,
+            last_timestamp_ns: 0,
+        };
+        playback.read_segment(&segment)?;
+        playback.read_configuration()?;
+        playback.last_timestamp_ns = playback.find_last_timestamp_ns()?;
+        playback.rewind()?;
+        Ok(playback)
+    }
+
+    /// Scans the top level elements of the segment, indexing clusters without reading their blocks.
+    fn read_segment(&mut self, segment: &Header) -> Result<(), Error> {
+        let end = segment.end().unwrap_or(u64::MAX);
+        let mut offset = segment.data_offset;
+        while offset < end {
+            self.seek(offset)?;
+            let header = match read_header(&mut self.reader)? {
+                Some(header) => header,
+                None => break,
+            };
+            offset = match header.id {
+                INFO => {
+                    for e in self.read_element(&header)?.children()? {
+                        if e.id == TIMECODE_SCALE {
+                            self.timecode_scale = e.as_uint().max(1);
+                        }
+                    }
+                    header.end().ok_or(Error::Failed)?
+                }
+                TRACKS => {
+                    self.read_tracks(&header)?;
+                    header.end().ok_or(Error::Failed)?
+                }
+                TAGS => {
+                    self.read_tags(&header)?;
+                    header.end().ok_or(Error::Failed)?
+                }
+                ATTACHMENTS => {
+                    self.read_attachments(&header)?;
+                    header.end().ok_or(Error::Failed)?
+                }
+                CLUSTER => self.index_cluster(&header)?,
+                _ => match header.end() {
+                    Some(end) => end,
+                    None => return Err(Error::Failed),
+                },
+            };
+        }
+        Ok(())
+    }
+
+    fn read_element(&mut self, header: &Header) -> Result<Element, Error> {
+        Ok(Element {
+            id: header.id,
+            data: read_data(&mut self.reader, header)?,
+        })
+    }
+
+    fn read_tracks(&mut self, header: &Header) -> Result<(), Error> {
+        for entry in self.read_element(header)?.children()? {
+            if entry.id != TRACK_ENTRY {
+                continue;
+            }
+            let mut track = MkvTrack {
+                name: String::new(),
+                number: 0,
+                uid: 0,
+                codec_id: String::new(),
+                codec_context: Vec::new(),
+                width: 0,
+                height: 0,
+                default_duration_ns: 0,
+            };
+            for e in entry.children()? {
+                match e.id {
+                    NAME => track.name = e.as_string(),
+                    TRACK_NUMBER => track.number = e.as_uint(),
+                    TRACK_UID => track.uid = e.as_uint(),
+                    CODEC_ID => track.codec_id = e.as_string(),
+                    CODEC_PRIVATE => track.codec_context = e.data,
+                    DEFAULT_DURATION => track.default_duration_ns = e.as_uint(),
+                    VIDEO => {
+                        for v in e.children()? {
+                            match v.id {
+                                PIXEL_WIDTH => track.width = v.as_uint(),
+                                PIXEL_HEIGHT => track.height = v.as_uint(),
+                                _ => (),
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            self.tracks.push(track);
+        }
+        Ok(())
+    }
+
+    fn read_tags(&mut self, header: &Header) -> Result<(), Error> {
+        for tag in self.read_element(header)?.children()? {
+            if tag.id != TAG {
+                continue;
+            }
+            for simple_tag in tag.children()?.into_iter().filter(|e| e.id == SIMPLE_TAG) {
+                let children = simple_tag.children()?;
+                let find = |id| children.iter().find(|e| e.id == id).map(|e| e.as_string());
+                if let (Some(name), Some(value)) = (find(TAG_NAME), find(TAG_STRING)) {
+                    self.tags.push((name, value));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_attachments(&mut self, header: &Header) -> Result<(), Error> {
+        let end = header.end().ok_or(Error::Failed)?;
+        let mut offset = header.data_offset;
+        while offset < end {
+            self.seek(offset)?;
+            let file = read_header(&mut self.reader)?.ok_or(Error::Failed)?;
+            let file_end = file.end().ok_or(Error::Failed)?;
+            if file.id == ATTACHED_FILE {
+                //  Only the headers are read, the data is read on demand.
+                let mut attachment = AttachmentRef {
+                    name: String::new(),
+                    data_offset: 0,
+                    size: 0,
+                };
+                let mut child_offset = file.data_offset;
+                while child_offset < file_end {
+                    self.seek(child_offset)?;
+                    let child = read_header(&mut self.reader)?.ok_or(Error::Failed)?;
+                    match child.id {
+                        FILE_NAME => attachment.name = self.read_element(&child)?.as_string(),
+                        FILE_DATA => {
+                            attachment.data_offset = child.data_offset;
+                            attachment.size = child.size.ok_or(Error::Failed)?;
+                        }
+                        _ => (),
+                    }
+                    child_offset = child.end().ok_or(Error::Failed)?;
+                }
+                self.attachments.push(attachment);
+            }
+            offset = file_end;
+        }
+        Ok(())
+    }
+
+    /// Adds a cluster to the index and returns the offset of the next element.
+    fn index_cluster(&mut self, header: &Header) -> Result<u64, Error> {
+        let mut timecode = None;
+        let mut offset = header.data_offset;
+        let end = loop {
+            if header.end() == Some(offset) {
+                break offset;
+            }
+            self.seek(offset)?;
+            let child = match read_header(&mut self.reader)? {
+                Some(child) => child,
+                None => break offset,
+            };
+            if header.size.is_none() && SEGMENT_CHILDREN.contains(&child.id) {
+                break offset;
+            }
+            if child.id == TIMECODE {
+                timecode = Some(self.read_element(&child)?.as_uint());
+                if let Some(end) = header.end() {
+                    break end;
+                }
+            }
+            offset = child.end().ok_or(Error::Failed)?;
+        };
+        self.clusters.push(ClusterInfo {
+            data_offset: header.data_offset,
+            end,
+            timecode: timecode.ok_or(Error::Failed)?,
+        });
+        Ok(end)
+    }
+
+    fn read_configuration(&mut self) -> Result<(), Error> {
+        let track_by_tag = |playback: &Self, tag: &str, name: &str| -> Option<MkvTrack> {
+            let uid = playback
+                .get_tag(tag)
+                .ok()
+                .and_then(|uid| uid.parse::<u64>().ok());
+            playback
+                .tracks
+                .iter()
+                .find(|t| match uid {
+                    Some(uid) => t.uid == uid,
+                    None => t.name == name,
+                })
+                .cloned()
+        };
+        let color = track_by_tag(self, "K4A_COLOR_TRACK", COLOR_TRACK);
+        let depth = track_by_tag(self, "K4A_DEPTH_TRACK", DEPTH_TRACK);
+        let ir = track_by_tag(self, "K4A_IR_TRACK", IR_TRACK);
+        let imu = track_by_tag(self, "K4A_IMU_TRACK", IMU_TRACK);
+
+        let tag_i64 = |name| {
+            self.get_tag(name)
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or(0)
+        };
+        let depth_delay_ns = tag_i64("K4A_DEPTH_DELAY_NS");
+        let start_offset_ns = tag_i64("K4A_START_OFFSET_NS");
+        let subordinate_delay_ns = tag_i64("K4A_SUBORDINATE_DELAY_NS");
+        self.start_offset_ns = start_offset_ns;
+
+        let mut config = k4a_record_configuration_t {
+            color_format: k4a_image_format_t_K4A_IMAGE_FORMAT_COLOR_MJPG,
+            color_resolution: k4a_color_resolution_t_K4A_COLOR_RESOLUTION_OFF,
+            depth_mode: k4a_depth_mode_t_K4A_DEPTH_MODE_OFF,
+            camera_fps: k4a_fps_t_K4A_FRAMES_PER_SECOND_30,
+            color_track_enabled: color.is_some(),
+            depth_track_enabled: depth.is_some(),
+            ir_track_enabled: ir.is_some(),
+            imu_track_enabled: imu.is_some(),
+            depth_delay_off_color_usec: (depth_delay_ns / 1000) as i32,
+            wired_sync_mode: match self.get_tag("K4A_WIRED_SYNC_MODE").as_deref() {
+                Ok("MASTER") => k4a_wired_sync_mode_t_K4A_WIRED_SYNC_MODE_MASTER,
+                Ok("SUBORDINATE") => k4a_wired_sync_mode_t_K4A_WIRED_SYNC_MODE_SUBORDINATE,
+                _ => k4a_wired_sync_mode_t_K4A_WIRED_SYNC_MODE_STANDALONE,
+            },
+            subordinate_delay_off_master_usec: (subordinate_delay_ns / 1000) as u32,
+            start_timestamp_offset_usec: (self.start_offset_ns / 1000) as u32,
+        };
+
+        if let Some(track) = color.as_ref() {
+            let video = video_track(track, None, 0)?;
+            config.color_format = video.format.into();
+            config.color_resolution = match video.height {
+                720 => k4a_color_resolution_t_K4A_COLOR_RESOLUTION_720P,
+                1080 => k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1080P,
+                1440 => k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1440P,
+                1536 => k4a_color_resolution_t_K4A_COLOR_RESOLUTION_1536P,
+                2160 => k4a_color_resolution_t_K4A_COLOR_RESOLUTION_2160P,
+                3072 => k4a_color_resolution_t_K4A_COLOR_RESOLUTION_3072P,
+                _ => return Err(Error::Failed),
+            };
+            self.videos.push((video, BlockPos::default()));
+        }
+        config.depth_mode = match self.get_tag("K4A_DEPTH_MODE").as_deref() {
+            Ok("NFOV_2X2BINNED") => k4a_depth_mode_t_K4A_DEPTH_MODE_NFOV_2X2BINNED,
+            Ok("NFOV_UNBINNED") => k4a_depth_mode_t_K4A_DEPTH_MODE_NFOV_UNBINNED,
+            Ok("WFOV_2X2BINNED") => k4a_depth_mode_t_K4A_DEPTH_MODE_WFOV_2X2BINNED,
+            Ok("WFOV_UNBINNED") => k4a_depth_mode_t_K4A_DEPTH_MODE_WFOV_UNBINNED,
+            Ok("PASSIVE_IR") => k4a_depth_mode_t_K4A_DEPTH_MODE_PASSIVE_IR,
+            _ => k4a_depth_mode_t_K4A_DEPTH_MODE_OFF,
+        };
+        if let Some(track) = depth.as_ref() {
+            let video = video_track(track, Some(ImageFormat::Depth16), depth_delay_ns)?;
+            self.videos.push((video, BlockPos::default()));
+        }
+        if let Some(track) = ir.as_ref() {
+            let video = video_track(track, Some(ImageFormat::IR16), depth_delay_ns)?;
+            self.videos.push((video, BlockPos::default()));
+        }
+        if let Some(track) = imu.as_ref() {
+            self.imu = Some((track.number, BlockPos::default(), 0));
+        }
+
+        let default_duration_ns = [color, depth, ir]
+            .iter()
+            .flatten()
+            .map(|t| t.default_duration_ns)
+            .find(|d| *d > 0);
+        if let Some(duration) = default_duration_ns {
+            config.camera_fps = match (1_000_000_000 + duration / 2) / duration {
+                5 => k4a_fps_t_K4A_FRAMES_PER_SECOND_5,
+                15 => k4a_fps_t_K4A_FRAMES_PER_SECOND_15,
+                _ => k4a_fps_t_K4A_FRAMES_PER_SECOND_30,
+            };
+        }
+        self.period_ns = match config.camera_fps {
+            k4a_fps_t_K4A_FRAMES_PER_SECOND_5 => 200_000_000,
+            k4a_fps_t_K4A_FRAMES_PER_SECOND_15 => 66_666_667,
+            _ => 33_333_333,
+        };
+        self.configuration = config;
+        Ok(())
+    }
+
+    fn find_last_timestamp_ns(&mut self) -> Result<i64, Error> {
+        //  Blocks of delayed tracks may end up in the cluster before the last one.
+        let mut last = 0;
+        for cluster in self.clusters.len().saturating_sub(2)..self.clusters.len() {
+            for block in self.blocks(cluster)?.iter() {
+                last = last.max(block.timestamp_ns);
+            }
+        }
+        Ok(last)
+    }
+
+    fn seek(&mut self, offset: u64) -> Result<(), Error> {
+        self.reader
+            .seek(SeekFrom::Start(offset))
+            .map(|_| ())
+            .map_err(Error::from)
+    }
+
+    /// Blocks of a cluster, read on demand and cached.
+    fn blocks(&mut self, cluster: usize) -> Result<Arc<Vec<BlockRef>>, Error> {
+        if let Some(i) = self.cache.iter().position(|(c, _)| *c == cluster) {
+            let entry = self.cache.remove(i);
+            let blocks = entry.1.clone();
+            self.cache.push(entry);
+            return Ok(blocks);
+        }
+        let blocks = Arc::new(self.read_blocks(cluster)?);
+        if self.cache.len() >= CACHED_CLUSTERS {
+            self.cache.remove(0);
+        }
+        self.cache.push((cluster, blocks.clone()));
+        Ok(blocks)
+    }
+
+    /// Reads the track, timestamp and position of the blocks of a cluster.
+    fn read_blocks(&mut self, cluster: usize) -> Result<Vec<BlockRef>, Error> {
+        let info = self.clusters[cluster];
+        let scale = self.timecode_scale as i64;
+        let mut blocks = Vec::new();
+        let mut offset = info.data_offset;
+        while offset < info.end {
+            self.seek(offset)?;
+            let header = read_header(&mut self.reader)?.ok_or(Error::Failed)?;
+            let block = match header.id {
+                SIMPLE_BLOCK => Some(header),
+                BLOCK_GROUP => {
+                    let mut block = None;
+                    let mut child_offset = header.data_offset;
+                    while child_offset < header.end().ok_or(Error::Failed)? {
+                        self.seek(child_offset)?;
+                        let child = read_header(&mut self.reader)?.ok_or(Error::Failed)?;
+                        if child.id == BLOCK {
+                            block = Some(child);
+                        }
+                        child_offset = child.end().ok_or(Error::Failed)?;
+                    }
+                    block
+                }
+                _ => None,
+            };
+            if let Some(block) = block {
+                //  Track number and timecode are at the start of the block.
+                self.seek(block.data_offset)?;
+                let mut start = [0u8; 11];
+                let size = block.size.ok_or(Error::Failed)?;
+                let n = (size as usize).min(start.len());
+                self.reader
+                    .read_exact(&mut start[..n])
+                    .map_err(Error::from)?;
+                let (track, length) = parse_vint(&start[..n])?;
+                if n < length + 2 {
+                    return Err(Error::Failed);
+                }
+                let timecode = i16::from_be_bytes([start[length], start[length + 1]]);
+                blocks.push(BlockRef {
+                    track,
+                    timestamp_ns: (info.timecode as i64 + timecode as i64) * scale,
+                    data_offset: block.data_offset,
+                    size,
+                });
+            }
+            offset = header.end().ok_or(Error::Failed)?;
+        }
+        Ok(blocks)
+    }
+
+    /// The first block of `track` at or after `from`.
+    fn find_next(
+        &mut self,
+        track: u64,
+        from: BlockPos,
+    ) -> Result<Option<(BlockPos, BlockRef)>, Error> {
+        let mut pos = from;
+        while pos.cluster < self.clusters.len() {
+            let blocks = self.blocks(pos.cluster)?;
+            if let Some(i) = blocks[pos.block.min(blocks.len())..]
+                .iter()
+                .position(|b| b.track == track)
+            {
+                let pos = BlockPos {
+                    cluster: pos.cluster,
+                    block: pos.block + i,
+                };
+                return Ok(Some((pos, blocks[pos.block])));
+            }
+            pos = BlockPos {
+                cluster: pos.cluster + 1,
+                block: 0,
+            };
+        }
+        Ok(None)
+    }
+
+    /// The last block of `track` before `before`.
+    fn find_previous(
+        &mut self,
+        track: u64,
+        before: BlockPos,
+    ) -> Result<Option<(BlockPos, BlockRef)>, Error> {
+        let mut cluster = before.cluster.min(self.clusters.len());
+        let mut end = if cluster < self.clusters.len() {
+            before.block
+        } else {
+            usize::MAX
+        };
+        loop {
+            if cluster < self.clusters.len() {
+                let blocks = self.blocks(cluster)?;
+                if let Some(i) = blocks[..end.min(blocks.len())]
+                    .iter()
+                    .rposition(|b| b.track == track)
+                {
+                    return Ok(Some((BlockPos { cluster, block: i }, blocks[i])));
+                }
+            }
+            if cluster == 0 {
+                return Ok(None);
+            }
+            cluster -= 1;
+            end = usize::MAX;
+        }
+    }
+
+    /// The first block of `track` whose timestamp minus `delay_ns` is at or after `target_ns`.
+    fn find_timestamp(
+        &mut self,
+        track: u64,
+        target_ns: i64,
+        delay_ns: i64,
+    ) -> Result<BlockPos, Error> {
+        let scale = self.timecode_scale as i64;
+        //  Start a cluster early, block timecodes may be before the cluster timecode.
+        let cluster = self
+            .clusters
+            .iter()
+            .position(|c| c.timecode as i64 * scale - delay_ns > target_ns)
+            .unwrap_or(self.clusters.len())
+            .saturating_sub(2);
+        let mut pos = BlockPos { cluster, block: 0 };
+        while let Some((found, block)) = self.find_next(track, pos)? {
+            if block.timestamp_ns - delay_ns >= target_ns {
+                return Ok(found);
+            }
+            pos = found.next();
+        }
+        Ok(self.end())
+    }
+
+    fn end(&self) -> BlockPos {
+        BlockPos {
+            cluster: self.clusters.len(),
+            block: 0,
+        }
+    }
+
+    fn read_block(&mut self, block: &BlockRef) -> Result<Vec<u8>, Error> {
+        read_data(
+            &mut self.reader,
+            &Header {
+                id: BLOCK,
+                size: Some(block.size),
+                data_offset: block.data_offset,
+            },
+        )
+    }
+
+    fn rewind(&mut self) -> Result<(), Error> {
+        self.seek_to_ns(i64::MIN)
+    }
+
+    fn seek_to_ns(&mut self, target_ns: i64) -> Result<(), Error> {
+        for i in 0..self.videos.len() {
+            let (video, _) = self.videos[i];
+            let pos = self.find_timestamp(video.number, target_ns, video.delay_ns)?;
+            self.videos[i].1 = pos;
+        }
+        if let Some((track, _, _)) = self.imu {
+            let pos = self.find_timestamp(track, target_ns, 0)?;
+            //  The samples of the block before may reach past the target.
+            let (pos, sample) = match self.find_previous(track, pos)? {
+                Some((previous, block)) => {
+                    let samples = self.imu_samples(&block)?;
+                    match samples.iter().position(|s| {
+                        (s.acc_timestamp_usec as i64) * 1000
+                            >= target_ns.saturating_add(self.start_offset_ns)
+                    }) {
+                        Some(i) => (previous, i),
+                        None => (pos, 0),
+                    }
+                }
+                None => (pos, 0),
+            };
+            self.imu = Some((track, pos, sample));
+        }
+        Ok(())
+    }
+
+    fn decode_image(&mut self, video: &VideoTrack, block: &BlockRef) -> Result<OwnedImage, Error> {
+        let data = self.read_block(block)?;
+        let (_, _, frames) = parse_block(&data)?;
+        let mut buffer = frames.first().ok_or(Error::Failed)?.to_vec();
+        if video.big_endian {
+            for pixel in buffer.chunks_exact_mut(2) {
+                pixel.swap(0, 1);
+            }
+        }
+        let mut image = OwnedImage::new(
+            video.format,
+            video.width,
+            video.height,
+            video.stride,
+            buffer,
+        )?;
+        image.set_device_timestamp_usec(self.device_timestamp_usec(block.timestamp_ns));
+        Ok(image)
+    }
+
+    fn device_timestamp_usec(&self, timestamp_ns: i64) -> u64 {
+        ((timestamp_ns + self.start_offset_ns).max(0) / 1000) as u64
+    }
+
+    fn imu_samples(&mut self, block: &BlockRef) -> Result<Vec<k4a_imu_sample_t>, Error> {
+        let data = self.read_block(block)?;
+        let (_, _, frames) = parse_block(&data)?;
+        let mut samples = Vec::new();
+        for frame in frames {
+            for sample in frame.chunks_exact(IMU_SAMPLE_SIZE) {
+                samples.push(self.imu_sample(sample));
+            }
+        }
+        Ok(samples)
+    }
+
+    fn imu_sample(&self, data: &[u8]) -> k4a_imu_sample_t {
+        let u64_at = |i: usize| {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(&data[i..i + 8]);
+            u64::from_le_bytes(b)
+        };
+        let f32_at =
+            |i: usize| f32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        //  Unlike the block timestamps, the timestamps in the payload are device timestamps.
+        let timestamp_usec = |ns: u64| ns / 1000;
+        k4a_imu_sample_t {
+            temperature: f32::NAN,
+            acc_sample: k4a_float3_t {
+                v: [f32_at(8), f32_at(12), f32_at(16)],
+            },
+            acc_timestamp_usec: timestamp_usec(u64_at(0)),
+            gyro_sample: k4a_float3_t {
+                v: [f32_at(28), f32_at(32), f32_at(36)],
+            },
+            gyro_timestamp_usec: timestamp_usec(u64_at(20)),
+        }
+    }
+
+    /// Get the raw calibration blob for the K4A device that made the recording.
+    pub fn get_raw_calibration(&mut self) -> Result<Vec<u8>, Error> {
+        let name = self
+            .get_tag("K4A_CALIBRATION_FILE")
+            .unwrap_or_else(|_| String::from(CALIBRATION_FILE));
+        let mut calibration = self.get_attachment(&name)?;
+        //  The SDK expects a NUL-terminated string.
+        if calibration.last() != Some(&0) {
+            calibration.push(0);
+        }
+        Ok(calibration)
+    }
+
+    /// Gets the configuration of the recording
+    pub fn get_record_configuration(&self) -> Result<RecordConfiguration, Error> {
+        Ok(RecordConfiguration {
+            value: self.configuration,
+        })
+    }
+
+    /// Get the next capture in the recording.
+    pub fn get_next_capture(&mut self) -> Result<OwnedCapture, Error> {
+        let mut heads = Vec::new();
+        for (i, (video, pos)) in self.videos.clone().iter().enumerate() {
+            if let Some((found, block)) = self.find_next(video.number, *pos)? {
+                heads.push((i, found, block, block.timestamp_ns - video.delay_ns));
+            }
+        }
+        let first = heads.iter().map(|h| h.3).min().ok_or(Error::Eof)?;
+        let mut capture = OwnedCapture::new();
+        for (i, found, block, timestamp_ns) in heads {
+            if timestamp_ns - first < self.period_ns / 2 {
+                self.videos[i].1 = found.next();
+                let video = self.videos[i].0;
+                self.set_image(&mut capture, &video, &block)?;
+            }
+        }
+        Ok(capture)
+    }
+
+    /// Get the previous capture in the recording.
+    pub fn get_previous_capture(&mut self) -> Result<OwnedCapture, Error> {
+        let mut tails = Vec::new();
+        for (i, (video, pos)) in self.videos.clone().iter().enumerate() {
+            if let Some((found, block)) = self.find_previous(video.number, *pos)? {
+                tails.push((i, found, block, block.timestamp_ns - video.delay_ns));
+            }
+        }
+        let last = tails.iter().map(|t| t.3).max().ok_or(Error::Eof)?;
+        let mut capture = OwnedCapture::new();
+        for (i, found, block, timestamp_ns) in tails {
+            if last - timestamp_ns < self.period_ns / 2 {
+                self.videos[i].1 = found;
+                let video = self.videos[i].0;
+                self.set_image(&mut capture, &video, &block)?;
+            }
+        }
+        Ok(capture)
+    }
+
+    fn set_image(
+        &mut self,
+        capture: &mut OwnedCapture,
+        video: &VideoTrack,
+        block: &BlockRef,
+    ) -> Result<(), Error> {
+        let image = Some(self.decode_image(video, block)?);
+        match video.format {
+            ImageFormat::Depth16 => capture.set_depth_image(image),
+            ImageFormat::IR16 => capture.set_ir_image(image),
+            _ => capture.set_color_image(image),
+        }
+        Ok(())
+    }
+
+    /// Get the next IMU sample in the recording.
+    pub fn get_next_imu_sample(&mut self) -> Result<ImuSample, Error> {
+        let (track, mut pos, mut sample) = self.imu.ok_or(Error::Eof)?;
+        loop {
+            let (found, block) = self.find_next(track, pos)?.ok_or(Error::Eof)?;
+            if found != pos {
+                sample = 0;
+            }
+            let samples = self.imu_samples(&block)?;
+            if sample < samples.len() {
+                self.imu = Some((track, found, sample + 1));
+                return Ok(ImuSample::from_native(samples[sample]));
+            }
+            pos = found.next();
+            sample = 0;
+        }
+    }
+
+    /// Get the previous IMU sample in the recording.
+    pub fn get_previous_imu_sample(&mut self) -> Result<ImuSample, Error> {
+        let (track, mut pos, mut sample) = self.imu.ok_or(Error::Eof)?;
+        loop {
+            if sample > 0 {
+                if let Some((found, block)) = self.find_next(track, pos)? {
+                    if found == pos {
+                        let samples = self.imu_samples(&block)?;
+                        let i = sample.min(samples.len());
+                        if i > 0 {
+                            self.imu = Some((track, pos, i - 1));
+                            return Ok(ImuSample::from_native(samples[i - 1]));
+                        }
+                    }
+                }
+            }
+            let (found, block) = self.find_previous(track, pos)?.ok_or(Error::Eof)?;
+            pos = found;
+            sample = self.imu_samples(&block)?.len();
+        }
+    }
+
+    /// Seeks to a specific time point in the recording
+    pub fn seek_timestamp(&mut self, offset_usec: i64, origin: SeekOrigin) -> Result<(), Error> {
+        let target_usec = match origin {
+            SeekOrigin::Begin => offset_usec,
+            SeekOrigin::End => self.get_recording_length_usec() as i64 + 1 + offset_usec,
+            SeekOrigin::DeviceTime => offset_usec - self.start_offset_ns / 1000,
+        };
+        if target_usec < 0 || target_usec > self.get_recording_length_usec() as i64 + 1 {
+            return Err(Error::Failed);
+        }
+        self.seek_to_ns(target_usec * 1000)
+    }
+
+    /// Get the last valid timestamp in the recording
+    pub fn get_recording_length_usec(&self) -> u64 {
+        (self.last_timestamp_ns.max(0) / 1000) as u64
+    }
+
+    /// Reads the value of a tag from the recording
+    pub fn get_tag(&self, name: &str) -> Result<String, Error> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.clone())
+            .ok_or(Error::Failed)
+    }
+
+    /// Gets the names and values of all tags in the recording.
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    /// Gets the names and sizes in bytes of all attachments in the recording.
+    pub fn attachments(&self) -> Vec<(String, u64)> {
+        self.attachments
+            .iter()
+            .map(|a| (a.name.clone(), a.size))
+            .collect()
+    }
+
+    /// Gets the configuration, tags and attachments of the recording.
+    pub fn get_metadata(&self) -> Result<RecordingMetadata, Error> {
+        Ok(RecordingMetadata::new(
+            self.get_record_configuration()?,
+            self.tags.clone(),
+            self.attachments(),
+        ))
+    }
+
+    /// Get the attachment block from the recording.
+    pub fn get_attachment(&mut self, attachment: &str) -> Result<Vec<u8>, Error> {
+        let (data_offset, size) = self
+            .attachments
+            .iter()
+            .find(|a| a.name == attachment)
+            .map(|a| (a.data_offset, a.size))
+            .ok_or(Error::Failed)?;
+        read_data(
+            &mut self.reader,
+            &Header {
+                id: FILE_DATA,
+                size: Some(size),
+                data_offset,
+            },
+        )
+    }
+
+    /// Counts the blocks of every track, from the headers of the blocks without reading their data.
+    /// A block holds one image of a video track or one data block of a custom track, blocks of the IMU
+    /// track may hold several samples.
+    pub fn block_counts(&mut self) -> Result<Vec<(String, u64)>, Error> {
+        let mut counts = vec![0u64; self.tracks.len()];
+        for cluster in 0..self.clusters.len() {
+            for block in self.read_blocks(cluster)? {
+                if let Some(i) = self.tracks.iter().position(|t| t.number == block.track) {
+                    counts[i] += 1;
+                }
+            }
+        }
+        Ok(self
+            .tracks
+            .iter()
+            .zip(counts)
+            .map(|(track, count)| (track.name.clone(), count))
+            .collect())
+    }
+
+    /// Get the number of tracks in a playback file.
+    pub fn get_track_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Gets the track at a specific index.
+    pub fn get_track(&self, track_index: usize) -> Result<&MkvTrack, Error> {
+        self.tracks.get(track_index).ok_or(Error::Failed)
+    }
+}
+
+impl BlockPos {
+    fn next(self) -> BlockPos {
+        BlockPos {
+            cluster: self.cluster,
+            block: self.block + 1,
+        }
+    }
+}
+
+/// Decodes the codec of a built-in video track.
+fn video_track(
+    track: &MkvTrack,
+    format: Option<ImageFormat>,
+    delay_ns: i64,
+) -> Result<VideoTrack, Error> {
+    let context = &track.codec_context;
+    let (bit_count, fourcc) = if context.len() >= 20 {
+        (
+            u16::from_le_bytes([context[14], context[15]]),
+            [context[16], context[17], context[18], context[19]],
+        )
+    } else {
+        (0, [0; 4])
+    };
+    let (width, height) = if track.width > 0 && track.height > 0 {
+        (track.width as i32, track.height as i32)
+    } else if context.len() >= 12 {
+        (
+            i32::from_le_bytes([context[4], context[5], context[6], context[7]]),
+            i32::from_le_bytes([context[8], context[9], context[10], context[11]]).abs(),
+        )
+    } else {
+        return Err(Error::Failed);
+    };
+    let (format, stride, big_endian) = match (track.codec_id.as_str(), format) {
+        (CODEC_MJPEG, None) => (ImageFormat::MJPG, 0, false),
+        (CODEC_FOURCC, None) => match &fourcc {
+            b"MJPG" => (ImageFormat::MJPG, 0, false),
+            b"NV12" => (ImageFormat::NV12, width, false),
+            b"YUY2" => (ImageFormat::YUY2, width * 2, false),
+            [0, 0, 0, 0] if bit_count == 32 => (ImageFormat::BGRA32, width * 4, false),
+            _ => return Err(Error::Failed),
+        },
+        (CODEC_FOURCC, Some(format)) => (format, width * 2, &fourcc == b"b16g"),
+        _ => return Err(Error::Failed),
+    };
+    Ok(VideoTrack {
+        number: track.number,
+        format,
+        width,
+        height,
+        stride,
+        big_endian,
+        delay_ns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn simple_block(track: u8, timecode: i16, payload: &[u8]) -> Vec<u8> {
+        let mut body = vec![0x80 | track];
+        body.extend_from_slice(&timecode.to_be_bytes());
+        body.push(0x80);
+        body.extend_from_slice(payload);
+        element(SIMPLE_BLOCK, &body)
+    }
+
+    fn depth_track() -> Vec<u8> {
+        let mut bitmap = vec![0u8; 40];
+        bitmap[0..4].copy_from_slice(&40u32.to_le_bytes());
+        bitmap[4..8].copy_from_slice(&2i32.to_le_bytes());
+        bitmap[8..12].copy_from_slice(&1i32.to_le_bytes());
+        bitmap[14..16].copy_from_slice(&16u16.to_le_bytes());
+        bitmap[16..20].copy_from_slice(b"b16g");
+        let video = [uint_element(PIXEL_WIDTH, 2), uint_element(PIXEL_HEIGHT, 1)].concat();
+        element(
+            TRACK_ENTRY,
+            &[
+                uint_element(TRACK_NUMBER, 1),
+                uint_element(TRACK_UID, 11),
+                string_element(NAME, "DEPTH"),
+                string_element(CODEC_ID, CODEC_FOURCC),
+                element(CODEC_PRIVATE, &bitmap),
+                uint_element(DEFAULT_DURATION, 33_333_333),
+                element(VIDEO, &video),
+            ]
+            .concat(),
+        )
+    }
+
+    //  Samples with device timestamps in the payload, laid out like the SDK writes them. The gyro is
+    //  sampled 500 us before the accelerometer.
+    fn imu_block(timecode: i16, timestamps_ns: &[u64]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        for (i, ts) in timestamps_ns.iter().enumerate() {
+            payload.extend_from_slice(&ts.to_le_bytes());
+            for v in &[i as f32, 0.0, 9.8] {
+                payload.extend_from_slice(&v.to_le_bytes());
+            }
+            payload.extend_from_slice(&(ts - 500_000).to_le_bytes());
+            for v in &[0.0f32, 0.0, 1.0] {
+                payload.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        simple_block(2, timecode, &payload)
+    }
+
+    fn simple_tag(name: &str, value: &str) -> Vec<u8> {
+        element(
+            SIMPLE_TAG,
+            &[
+                string_element(TAG_NAME, name),
+                string_element(TAG_STRING, value),
+            ]
+            .concat(),
+        )
+    }
+
+    //  A 2x1 depth recording at 30 fps with three frames, two IMU blocks and a calibration.
+    fn recording() -> Vec<u8> {
+        let header = element(EBML, &string_element(DOC_TYPE, "matroska"));
+        let info = element(INFO, &uint_element(TIMECODE_SCALE, 1_000_000));
+        let imu_track = element(
+            TRACK_ENTRY,
+            &[
+                uint_element(TRACK_NUMBER, 2),
+                uint_element(TRACK_UID, 12),
+                string_element(NAME, "IMU"),
+                string_element(CODEC_ID, "S_K4A/IMU"),
+            ]
+            .concat(),
+        );
+        let tracks = element(TRACKS, &[depth_track(), imu_track].concat());
+        let tags = element(
+            TAGS,
+            &element(
+                TAG,
+                &[
+                    simple_tag("K4A_DEPTH_MODE", "NFOV_UNBINNED"),
+                    simple_tag("K4A_DEPTH_TRACK", "11"),
+                    simple_tag("K4A_IMU_TRACK", "12"),
+                    simple_tag("K4A_START_OFFSET_NS", "1000000"),
+                ]
+                .concat(),
+            ),
+        );
+        let attachments = element(
+            ATTACHMENTS,
+            &element(
+                ATTACHED_FILE,
+                &[
+                    string_element(FILE_NAME, "calibration.json"),
+                    element(FILE_DATA, b"{}"),
+                ]
+                .concat(),
+            ),
+        );
+        let first = element(
+            CLUSTER,
+            &[
+                uint_element(TIMECODE, 0),
+                simple_block(1, 0, &[0x00, 0x01, 0x02, 0x00]),
+                imu_block(0, &[1_000_000, 2_000_000]),
+                simple_block(1, 33, &[0x00, 0x03, 0x04, 0x00]),
+                imu_block(34, &[35_000_000]),
+            ]
+            .concat(),
+        );
+        let second = element(
+            CLUSTER,
+            &[
+                uint_element(TIMECODE, 66),
+                simple_block(1, 0, &[0x00, 0x05, 0x06, 0x00]),
+            ]
+            .concat(),
+        );
+        let segment = element(
+            SEGMENT,
+            &[info, tracks, tags, attachments, first, second].concat(),
+        );
+        [header, segment].concat()
+    }
+
+    fn open() -> MkvPlayback<Cursor<Vec<u8>>> {
+        MkvPlayback::from_reader(Cursor::new(recording())).unwrap()
+    }
+
+    #[test]
+    fn test_configuration_and_tracks() {
+        let mut playback = open();
+        let config = playback.get_record_configuration().unwrap();
+        assert_eq!(config.depth_mode(), DepthMode::NFovUnbinned);
+        assert_eq!(config.camera_fps(), Fps::_30fps);
+        assert!(config.depth_track_enabled());
+        assert!(!config.color_track_enabled());
+        assert!(config.imu_track_enabled());
+        assert_eq!(config.start_timestamp_offset_usec(), 1000);
+        assert_eq!(playback.get_recording_length_usec(), 66_000);
+
+        assert_eq!(playback.get_track_count(), 2);
+        let track = playback.get_track(0).unwrap();
+        assert_eq!(track.get_name(), "DEPTH");
+        assert!(track.is_builtin());
+        assert_eq!(track.get_video_settings().unwrap().frame_rate(), 30);
+        assert!(playback.get_track(2).is_err());
+
+        assert_eq!(playback.get_tag("K4A_DEPTH_MODE").unwrap(), "NFOV_UNBINNED");
+        assert!(playback.get_tag("MISSING").is_err());
+        assert_eq!(playback.get_attachment("calibration.json").unwrap(), b"{}");
+        assert_eq!(playback.get_raw_calibration().unwrap(), b"{}\0");
+    }
+
+    #[test]
+    fn test_captures() {
+        let mut playback = open();
+        let mut timestamps = Vec::new();
+        while let Ok(capture) = playback.get_next_capture() {
+            let depth = capture.get_depth_image().unwrap();
+            assert!(capture.get_color_image().is_none());
+            assert_eq!(depth.get_format(), ImageFormat::Depth16);
+            timestamps.push(depth.get_device_timestamp_usec());
+        }
+        assert_eq!(timestamps, vec![1000, 34_000, 67_000]);
+        assert!(matches!(playback.get_next_capture(), Err(Error::Eof)));
+
+        let capture = playback.get_previous_capture().unwrap();
+        let depth = capture.get_depth_image().unwrap();
+        //  b16g is big endian
+        assert_eq!(
+            depth.depth16().unwrap().next().unwrap(),
+            &[0x0005, 0x0600][..]
+        );
+        playback.seek_timestamp(33_000, SeekOrigin::Begin).unwrap();
+        let capture = playback.get_next_capture().unwrap();
+        assert_eq!(
+            capture
+                .get_depth_image()
+                .unwrap()
+                .get_device_timestamp_usec(),
+            34_000
+        );
+        assert!(playback.seek_timestamp(1, SeekOrigin::End).is_err());
+    }
+
+    #[test]
+    fn test_block_counts() {
+        let mut playback = open();
+        playback.get_next_capture().unwrap();
+        assert_eq!(
+            playback.block_counts().unwrap(),
+            vec![("DEPTH".to_string(), 3), ("IMU".to_string(), 2)]
+        );
+        //  Counting does not move the playback
+        let capture = playback.get_next_capture().unwrap();
+        assert_eq!(
+            capture
+                .get_depth_image()
+                .unwrap()
+                .get_device_timestamp_usec(),
+            34_000
+        );
+    }
+
+    #[test]
+    fn test_imu_samples() {
+        let mut playback = open();
+        let mut timestamps = Vec::new();
+        while let Ok(sample) = playback.get_next_imu_sample() {
+            assert_eq!(
+                sample.gyro_timestamp_usec(),
+                sample.acc_timestamp_usec() - 500
+            );
+            timestamps.push(sample.acc_timestamp_usec());
+        }
+        assert_eq!(timestamps, vec![1000, 2000, 35_000]);
+        assert_eq!(
+            playback
+                .get_previous_imu_sample()
+                .unwrap()
+                .acc_timestamp_usec(),
+            35_000
+        );
+        assert_eq!(
+            playback
+                .get_previous_imu_sample()
+                .unwrap()
+                .acc_timestamp_usec(),
+            2000
+        );
+        playback
+            .seek_timestamp(1_500, SeekOrigin::DeviceTime)
+            .unwrap();
+        assert_eq!(
+            playback.get_next_imu_sample().unwrap().acc_timestamp_usec(),
+            2000
+        );
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        assert!(MkvPlayback::from_reader(Cursor::new(vec![0u8; 16])).is_err());
+        let mut truncated = recording();
+        truncated.truncate(40);
+        assert!(MkvPlayback::from_reader(Cursor::new(truncated)).is_err());
+    }
+}
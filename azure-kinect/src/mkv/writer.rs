@@ -0,0 +1,1050 @@
+use super::ebml::*;
+use super::*;
+use crate::record::{RecordSubtitleSettings, RecordVideoSettings};
+use crate::*;
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+//  Same as the SDK: timestamps in microseconds and clusters short enough for 16 bit block timecodes.
+const TIMECODE_SCALE_NS: u64 = 1000;
+const CLUSTER_LENGTH_NS: u64 = 32_000_000;
+//  Blocks are kept this long before their cluster is written, so that streams can arrive out of order.
+const PENDING_LENGTH_NS: u64 = 1_000_000_000;
+const SEEK_HEAD_SIZE: usize = 256;
+
+const TRACK_TYPE_VIDEO: u64 = 1;
+const TRACK_TYPE_SUBTITLE: u64 = 0x11;
+
+struct TrackHeader {
+    number: u64,
+    uid: u64,
+    name: String,
+    track_type: u64,
+    codec_id: String,
+    codec_context: Vec<u8>,
+    video: Option<(u64, u64, u64)>,
+}
+
+/// Format of the images of a built-in video track.
+#[derive(Clone, Copy)]
+struct ImageTrack {
+    number: u64,
+    format: ImageFormat,
+    width: i32,
+    height: i32,
+}
+
+struct PendingBlock {
+    track: u64,
+    timestamp_ns: u64,
+    data: Vec<u8>,
+}
+
+/// Writes Azure Kinect recordings (Matroska files) without the SDK.
+///
+/// As with `Record`, tracks, tags and attachments are added before `write_header`.
+/// The file is complete once `close` is called or the writer is dropped.
+pub struct MkvRecord<W: Write + Seek = BufWriter<File>> {
+    writer: Option<W>,
+    configuration: DeviceConfiguration,
+    tracks: Vec<TrackHeader>,
+    tags: Vec<(String, String)>,
+    attachments: Vec<(String, Vec<u8>)>,
+    color: Option<ImageTrack>,
+    depth: Option<ImageTrack>,
+    ir: Option<ImageTrack>,
+    imu: Option<u64>,
+    header_written: bool,
+    segment_offset: u64,
+    duration_offset: u64,
+    //  Top level elements and their positions, for the SeekHead.
+    seek_positions: Vec<(u32, u64)>,
+    start_offset_ns: Option<u64>,
+    pending: BTreeMap<u64, Vec<PendingBlock>>,
+    //  Timestamps before this are already written out.
+    written_until_ns: u64,
+    last_timestamp_ns: u64,
+    cues: Vec<(u64, u64, u64)>,
+    uids: RandomState,
+}
+
+impl MkvRecord {
+    /// Creates a recording file with the built-in tracks of a device configuration.
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        configuration: &DeviceConfiguration,
+    ) -> Result<MkvRecord, Error> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| {
+            ErrorContext::new("File::create")
+                .arg("path", path.display())
+                .with_kind(ErrorKind::Io)
+                .with_source(e)
+        })?;
+        MkvRecord::from_writer(BufWriter::new(file), configuration)
+    }
+}
+
+impl<W: Write + Seek> MkvRecord<W> {
+    /// Writes a recording to a stream.
+    pub fn from_writer(
+        writer: W,
+        configuration: &DeviceConfiguration,
+    ) -> Result<MkvRecord<W>, Error> {
+        let mut record = MkvRecord {
+            writer: Some(writer),
+            configuration: *configuration,
+            tracks: Vec::new(),
+            tags: Vec::new(),
+            attachments: Vec::new(),
+            color: None,
+            depth: None,
+            ir: None,
+            imu: None,
+            header_written: false,
+            segment_offset: 0,
+            duration_offset: 0,
+            seek_positions: Vec::new(),
+            start_offset_ns: None,
+            pending: BTreeMap::new(),
+            written_until_ns: 0,
+            last_timestamp_ns: 0,
+            cues: Vec::new(),
+            uids: RandomState::new(),
+        };
+        let fps = configuration.camera_fps().get_u32() as u64;
+
+        let format = configuration.color_format();
+        let dimension = configuration.color_resolution().get_dimension();
+        if configuration.color_resolution() != ColorResolution::Off {
+            let (codec_id, bit_count, fourcc) = match format {
+                ImageFormat::MJPG => (CODEC_MJPEG, 24, *b"MJPG"),
+                ImageFormat::NV12 => (CODEC_FOURCC, 12, *b"NV12"),
+                ImageFormat::YUY2 => (CODEC_FOURCC, 16, *b"YUY2"),
+                ImageFormat::BGRA32 => (CODEC_FOURCC, 32, [0; 4]),
+                _ => return Err(Error::Failed),
+            };
+            let number = record.add_track(
+                COLOR_TRACK,
+                TRACK_TYPE_VIDEO,
+                codec_id,
+                bitmap_info_header(dimension.width, dimension.height, bit_count, fourcc),
+                Some((dimension.width as u64, dimension.height as u64, fps)),
+            )?;
+            record.color = Some(ImageTrack {
+                number,
+                format,
+                width: dimension.width,
+                height: dimension.height,
+            });
+        }
+
+        let depth_mode = configuration.depth_mode();
+        let dimension = depth_mode.get_dimension();
+        let depth_track = |record: &mut MkvRecord<W>, name, format| {
+            record
+                .add_track(
+                    name,
+                    TRACK_TYPE_VIDEO,
+                    CODEC_FOURCC,
+                    bitmap_info_header(dimension.width, dimension.height, 16, *b"b16g"),
+                    Some((dimension.width as u64, dimension.height as u64, fps)),
+                )
+                .map(|number| ImageTrack {
+                    number,
+                    format,
+                    width: dimension.width,
+                    height: dimension.height,
+                })
+        };
+        if depth_mode != DepthMode::Off && depth_mode != DepthMode::PassiveIr {
+            record.depth = Some(depth_track(&mut record, DEPTH_TRACK, ImageFormat::Depth16)?);
+        }
+        if depth_mode != DepthMode::Off {
+            record.ir = Some(depth_track(&mut record, IR_TRACK, ImageFormat::IR16)?);
+        }
+        Ok(record)
+    }
+
+    fn writer(&mut self) -> &mut W {
+        self.writer.as_mut().unwrap()
+    }
+
+    fn position(&mut self) -> Result<u64, Error> {
+        self.writer().stream_position().map_err(Error::from)
+    }
+
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> Result<(), Error> {
+        let writer = self.writer();
+        let end = writer.stream_position().map_err(Error::from)?;
+        writer.seek(SeekFrom::Start(offset)).map_err(Error::from)?;
+        write_all(writer, data)?;
+        writer
+            .seek(SeekFrom::Start(end))
+            .map(|_| ())
+            .map_err(Error::from)
+    }
+
+    fn new_uid(&self) -> u64 {
+        let mut hasher = self.uids.build_hasher();
+        hasher.write_usize(self.tracks.len() + self.attachments.len());
+        //  UIDs must not be 0
+        hasher.finish().max(1)
+    }
+
+    fn add_track(
+        &mut self,
+        name: &str,
+        track_type: u64,
+        codec_id: &str,
+        codec_context: Vec<u8>,
+        video: Option<(u64, u64, u64)>,
+    ) -> Result<u64, Error> {
+        if self.header_written || name.is_empty() || self.tracks.iter().any(|t| t.name == name) {
+            return Err(Error::Failed);
+        }
+        let number = self.tracks.len() as u64 + 1;
+        let uid = self.new_uid();
+        self.tracks.push(TrackHeader {
+            number,
+            uid,
+            name: name.to_string(),
+            track_type,
+            codec_id: codec_id.to_string(),
+            codec_context,
+            video,
+        });
+        Ok(number)
+    }
+
+    fn track_number(&self, name: &str) -> Result<u64, Error> {
+        self.tracks
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.number)
+            .ok_or(Error::Failed)
+    }
+
+    /// Adds a tag to the recording
+    pub fn add_tag(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        if name.is_empty() {
+            return Err(Error::Failed);
+        }
+        self.tags.push((name.to_string(), value.to_string()));
+        Ok(())
+    }
+
+    /// Adds the track header for recording IMU
+    pub fn add_imu_track(&mut self) -> Result<(), Error> {
+        if self.imu.is_some() {
+            return Err(Error::Failed);
+        }
+        let number = self.add_track(IMU_TRACK, TRACK_TYPE_SUBTITLE, CODEC_IMU, Vec::new(), None)?;
+        self.imu = Some(number);
+        Ok(())
+    }
+
+    /// Adds an attachment to the recording
+    pub fn add_attachment(&mut self, attachment_name: &str, buffer: &[u8]) -> Result<(), Error> {
+        if self.header_written
+            || attachment_name.is_empty()
+            || self.attachments.iter().any(|(n, _)| n == attachment_name)
+        {
+            return Err(Error::Failed);
+        }
+        self.attachments
+            .push((attachment_name.to_string(), buffer.to_vec()));
+        Ok(())
+    }
+
+    /// Adds custom video tracks to the recording
+    pub fn add_custom_video_track(
+        &mut self,
+        track_name: &str,
+        codec_id: &str,
+        codec_context: &[u8],
+        track_settings: &RecordVideoSettings,
+    ) -> Result<(), Error> {
+        if BUILTIN_TRACKS.contains(&track_name) {
+            return Err(Error::Failed);
+        }
+        self.add_track(
+            track_name,
+            TRACK_TYPE_VIDEO,
+            codec_id,
+            codec_context.to_vec(),
+            Some((
+                track_settings.width(),
+                track_settings.height(),
+                track_settings.frame_rate(),
+            )),
+        )
+        .map(|_| ())
+    }
+
+    /// Adds custom subtitle tracks to the recording
+    ///
+    /// Each call to `write_custom_track_data` is written as its own block, also for high frequency data.
+    pub fn add_custom_subtitle_track(
+        &mut self,
+        track_name: &str,
+        codec_id: &str,
+        codec_context: &[u8],
+        _track_settings: &RecordSubtitleSettings,
+    ) -> Result<(), Error> {
+        if BUILTIN_TRACKS.contains(&track_name) {
+            return Err(Error::Failed);
+        }
+        self.add_track(
+            track_name,
+            TRACK_TYPE_SUBTITLE,
+            codec_id,
+            codec_context.to_vec(),
+            None,
+        )
+        .map(|_| ())
+    }
+
+    /// Writes the recording header and metadata to file
+    pub fn write_header(&mut self) -> Result<(), Error> {
+        if self.header_written {
+            return Err(Error::Failed);
+        }
+        let ebml = element(
+            EBML,
+            &[
+                uint_element(EBML_VERSION, 1),
+                uint_element(EBML_READ_VERSION, 1),
+                uint_element(EBML_MAX_ID_LENGTH, 4),
+                uint_element(EBML_MAX_SIZE_LENGTH, 8),
+                string_element(DOC_TYPE, "matroska"),
+                uint_element(DOC_TYPE_VERSION, 4),
+                uint_element(DOC_TYPE_READ_VERSION, 2),
+            ]
+            .concat(),
+        );
+        write_all(self.writer(), &ebml)?;
+        //  The size of the segment is patched when closing.
+        write_all(self.writer(), &header(SEGMENT, 0))?;
+        self.segment_offset = self.position()?;
+        write_all(self.writer(), &void_element(SEEK_HEAD_SIZE))?;
+
+        let app = concat!("azure-kinect-rs ", env!("CARGO_PKG_VERSION"));
+        let info_start = self.position()?;
+        self.seek_positions
+            .push((INFO, info_start - self.segment_offset));
+        let info = element(
+            INFO,
+            &[
+                uint_element(TIMECODE_SCALE, TIMECODE_SCALE_NS),
+                string_element(MUXING_APP, app),
+                string_element(WRITING_APP, app),
+                float_element(DURATION, 0.0),
+            ]
+            .concat(),
+        );
+        self.duration_offset = info_start + info.len() as u64 - 8;
+        write_all(self.writer(), &info)?;
+
+        let tracks: Vec<u8> = self
+            .tracks
+            .iter()
+            .map(|track| {
+                let mut entry = vec![
+                    uint_element(TRACK_NUMBER, track.number),
+                    uint_element(TRACK_UID, track.uid),
+                    uint_element(TRACK_TYPE, track.track_type),
+                    string_element(NAME, &track.name),
+                    string_element(CODEC_ID, &track.codec_id),
+                ];
+                if !track.codec_context.is_empty() {
+                    entry.push(element(CODEC_PRIVATE, &track.codec_context));
+                }
+                if let Some((width, height, frame_rate)) = track.video {
+                    if let Some(duration) = 1_000_000_000u64.checked_div(frame_rate) {
+                        entry.push(uint_element(DEFAULT_DURATION, duration));
+                    }
+                    entry.push(element(
+                        VIDEO,
+                        &[
+                            uint_element(PIXEL_WIDTH, width),
+                            uint_element(PIXEL_HEIGHT, height),
+                        ]
+                        .concat(),
+                    ));
+                }
+                element(TRACK_ENTRY, &entry.concat())
+            })
+            .collect::<Vec<_>>()
+            .concat();
+        let tracks = element(TRACKS, &tracks);
+        let position = self.position()? - self.segment_offset;
+        self.seek_positions.push((TRACKS, position));
+        write_all(self.writer(), &tracks)?;
+
+        if !self.attachments.is_empty() {
+            let mut files = Vec::new();
+            for (i, (name, data)) in self.attachments.iter().enumerate() {
+                let mut hasher = self.uids.build_hasher();
+                hasher.write_usize(usize::MAX - i);
+                files.push(element(
+                    ATTACHED_FILE,
+                    &[
+                        string_element(FILE_NAME, name),
+                        string_element(FILE_MIME_TYPE, "application/octet-stream"),
+                        element(FILE_DATA, data),
+                        uint_element(FILE_UID, hasher.finish().max(1)),
+                    ]
+                    .concat(),
+                ));
+            }
+            let attachments = element(ATTACHMENTS, &files.concat());
+            let position = self.position()? - self.segment_offset;
+            self.seek_positions.push((ATTACHMENTS, position));
+            write_all(self.writer(), &attachments)?;
+        }
+        self.header_written = true;
+        Ok(())
+    }
+
+    /// Relative timestamp of a device timestamp, the first one written is the start of the recording.
+    /// A failure is reported as an error of `operation`.
+    fn relative_ns(
+        &mut self,
+        operation: &'static str,
+        device_timestamp_usec: u64,
+    ) -> Result<u64, Error> {
+        let context =
+            ErrorContext::new(operation).arg("device_timestamp_usec", device_timestamp_usec);
+        if !self.header_written {
+            return Err(context.arg("header_written", false).into());
+        }
+        let timestamp_ns = device_timestamp_usec * 1000;
+        let start = *self.start_offset_ns.get_or_insert(timestamp_ns);
+        match timestamp_ns.checked_sub(start) {
+            Some(relative) if relative >= self.written_until_ns => Ok(relative),
+            Some(_) => Err(context
+                .arg("written_until_usec", (start + self.written_until_ns) / 1000)
+                .into()),
+            None => Err(context.arg("start_offset_usec", start / 1000).into()),
+        }
+    }
+
+    fn push_block(&mut self, track: u64, timestamp_ns: u64, data: Vec<u8>) -> Result<(), Error> {
+        self.pending
+            .entry(timestamp_ns / CLUSTER_LENGTH_NS)
+            .or_default()
+            .push(PendingBlock {
+                track,
+                timestamp_ns,
+                data,
+            });
+        self.last_timestamp_ns = self.last_timestamp_ns.max(timestamp_ns);
+        self.write_clusters(self.last_timestamp_ns.saturating_sub(PENDING_LENGTH_NS))
+    }
+
+    /// Writes the pending clusters which end before `until_ns`.
+    fn write_clusters(&mut self, until_ns: u64) -> Result<(), Error> {
+        while let Some((&index, _)) = self.pending.iter().next() {
+            let cluster_ns = index * CLUSTER_LENGTH_NS;
+            if cluster_ns + CLUSTER_LENGTH_NS > until_ns {
+                break;
+            }
+            let mut blocks = self.pending.remove(&index).unwrap_or_default();
+            blocks.sort_by_key(|b| b.timestamp_ns);
+            let timecode = cluster_ns / TIMECODE_SCALE_NS;
+            let mut body = uint_element(TIMECODE, timecode);
+            for block in &blocks {
+                let relative = (block.timestamp_ns / TIMECODE_SCALE_NS - timecode) as i16;
+                let mut data = vint(block.track);
+                data.reserve(block.data.len() + 3);
+                data.extend_from_slice(&relative.to_be_bytes());
+                //  Every frame is a key frame
+                data.push(0x80);
+                data.extend_from_slice(&block.data);
+                body.extend_from_slice(&element(SIMPLE_BLOCK, &data));
+            }
+            let position = self.position()? - self.segment_offset;
+            if let Some(first) = blocks.first() {
+                self.cues.push((timecode, first.track, position));
+            }
+            let cluster = element(CLUSTER, &body);
+            write_all(self.writer(), &cluster)?;
+            self.written_until_ns = cluster_ns + CLUSTER_LENGTH_NS;
+        }
+        Ok(())
+    }
+
+    /// Writes a camera capture to file
+    ///
+    /// The images must match the format and resolution of the device configuration.
+    pub fn write_capture(&mut self, capture: &OwnedCapture) -> Result<(), Error> {
+        let images = [
+            (self.color, capture.get_color_image()),
+            (self.depth, capture.get_depth_image()),
+            (self.ir, capture.get_ir_image()),
+        ];
+        let mut blocks = Vec::new();
+        for (track, image) in images.iter() {
+            let image = match image {
+                Some(image) => image,
+                None => continue,
+            };
+            let track = track.ok_or(Error::Failed)?;
+            if image.get_format() != track.format
+                || image.get_width_pixels() != track.width
+                || image.get_height_pixels() != track.height
+            {
+                return Err(Error::Failed);
+            }
+            let data = match track.format {
+                ImageFormat::MJPG => image.as_bytes().to_vec(),
+                ImageFormat::Depth16 | ImageFormat::IR16 => {
+                    //  b16g is big endian and without padding
+                    let mut data =
+                        Vec::with_capacity(track.width as usize * track.height as usize * 2);
+                    for row in image.rows()? {
+                        for pixel in row.chunks_exact(2) {
+                            data.extend_from_slice(&[pixel[1], pixel[0]]);
+                        }
+                    }
+                    data
+                }
+                _ => image.rows()?.flatten().copied().collect(),
+            };
+            blocks.push((track.number, image.get_device_timestamp_usec(), data));
+        }
+        if blocks.is_empty() {
+            return Err(Error::Failed);
+        }
+        //  The start of the recording is the earliest image of the first capture.
+        if self.start_offset_ns.is_none() && self.header_written {
+            let first = blocks.iter().map(|b| b.1).min().unwrap_or(0);
+            self.start_offset_ns = Some(first * 1000);
+        }
+        let mut relative = Vec::with_capacity(blocks.len());
+        for (_, timestamp, _) in &blocks {
+            relative.push(self.relative_ns("MkvRecord::write_capture", *timestamp)?);
+        }
+        for ((track, _, data), timestamp_ns) in blocks.into_iter().zip(relative) {
+            self.push_block(track, timestamp_ns, data)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an imu sample to file
+    pub fn write_imu_sample(&mut self, imu_sample: &ImuSample) -> Result<(), Error> {
+        let track = self.imu.ok_or_else(|| {
+            ErrorContext::new("MkvRecord::write_imu_sample").arg("imu_track", "not added")
+        })?;
+        //  Like the SDK, the block is placed at the relative time of the accelerometer and the payload
+        //  keeps the device timestamps.
+        let block_ns = self.relative_ns(
+            "MkvRecord::write_imu_sample",
+            imu_sample.acc_timestamp_usec(),
+        )?;
+        let mut sample = Vec::with_capacity(IMU_SAMPLE_SIZE);
+        sample.extend_from_slice(&(imu_sample.acc_timestamp_usec() * 1000).to_le_bytes());
+        let (acc, gyro) = unsafe {
+            (
+                imu_sample.value.acc_sample.v,
+                imu_sample.value.gyro_sample.v,
+            )
+        };
+        for v in &acc {
+            sample.extend_from_slice(&v.to_le_bytes());
+        }
+        sample.extend_from_slice(&(imu_sample.gyro_timestamp_usec() * 1000).to_le_bytes());
+        for v in &gyro {
+            sample.extend_from_slice(&v.to_le_bytes());
+        }
+        //  Samples of a cluster are appended to a single block.
+        if let Some(block) = self
+            .pending
+            .get_mut(&(block_ns / CLUSTER_LENGTH_NS))
+            .and_then(|blocks| blocks.iter_mut().rev().find(|b| b.track == track))
+        {
+            if block.timestamp_ns <= block_ns {
+                block.data.extend_from_slice(&sample);
+                return Ok(());
+            }
+        }
+        self.push_block(track, block_ns, sample)
+    }
+
+    /// Writes data for a custom track to file
+    pub fn write_custom_track_data(
+        &mut self,
+        track_name: &str,
+        device_timestamp_usec: u64,
+        custom_data: &[u8],
+    ) -> Result<(), Error> {
+        if BUILTIN_TRACKS.contains(&track_name) {
+            return Err(Error::Failed);
+        }
+        let track = self.track_number(track_name)?;
+        let timestamp_ns =
+            self.relative_ns("MkvRecord::write_custom_track_data", device_timestamp_usec)?;
+        self.push_block(track, timestamp_ns, custom_data.to_vec())
+    }
+
+    /// Flushes all pending recording data to disk
+    ///
+    /// Data older than the flushed data can not be written afterwards.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.header_written {
+            self.write_clusters(u64::MAX)?;
+        }
+        self.writer().flush().map_err(Error::from)
+    }
+
+    /// Finishes the file: writes the pending data, the cues and the tags, then updates the header.
+    pub fn close(mut self) -> Result<W, Error> {
+        self.finish()?;
+        Ok(self.writer.take().unwrap())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        if !self.header_written {
+            self.write_header()?;
+        }
+        self.write_clusters(u64::MAX)?;
+
+        let position = self.position()? - self.segment_offset;
+        self.seek_positions.push((CUES, position));
+        let cue_points: Vec<u8> = self
+            .cues
+            .iter()
+            .map(|(time, track, position)| {
+                element(
+                    CUE_POINT,
+                    &[
+                        uint_element(CUE_TIME, *time),
+                        element(
+                            CUE_TRACK_POSITIONS,
+                            &[
+                                uint_element(CUE_TRACK, *track),
+                                uint_element(CUE_CLUSTER_POSITION, *position),
+                            ]
+                            .concat(),
+                        ),
+                    ]
+                    .concat(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .concat();
+        let cues = element(CUES, &cue_points);
+        write_all(self.writer(), &cues)?;
+
+        let position = self.position()? - self.segment_offset;
+        self.seek_positions.push((TAGS, position));
+        let tags: Vec<u8> = self
+            .builtin_tags()
+            .iter()
+            .chain(self.tags.iter())
+            .map(|(name, value)| {
+                element(
+                    TAG,
+                    &[
+                        element(TARGETS, &[]),
+                        element(
+                            SIMPLE_TAG,
+                            &[
+                                string_element(TAG_NAME, name),
+                                string_element(TAG_STRING, value),
+                            ]
+                            .concat(),
+                        ),
+                    ]
+                    .concat(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .concat();
+        let tags = element(TAGS, &tags);
+        write_all(self.writer(), &tags)?;
+
+        let end = self.position()?;
+        let seeks: Vec<u8> = self
+            .seek_positions
+            .iter()
+            .map(|(id, position)| {
+                element(
+                    SEEK,
+                    &[
+                        element(SEEK_ID, &id.to_be_bytes()),
+                        uint_element(SEEK_POSITION, *position),
+                    ]
+                    .concat(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .concat();
+        let seek_head = element(SEEK_HEAD, &seeks);
+        let mut reserved = seek_head.clone();
+        reserved.extend_from_slice(&void_element(SEEK_HEAD_SIZE - seek_head.len()));
+        self.write_at(self.segment_offset, &reserved)?;
+
+        let duration = self.last_timestamp_ns as f64 / TIMECODE_SCALE_NS as f64;
+        self.write_at(self.duration_offset, &duration.to_be_bytes())?;
+        let segment_size = end - self.segment_offset;
+        self.write_at(self.segment_offset - 8, &header(SEGMENT, segment_size)[4..])?;
+        self.writer().flush().map_err(Error::from)
+    }
+
+    fn builtin_tags(&self) -> Vec<(String, String)> {
+        let config = &self.configuration;
+        let mut tags = Vec::new();
+        let mut tag = |name: &str, value: String| tags.push((name.to_string(), value));
+
+        tag(
+            "K4A_COLOR_MODE",
+            match self.color {
+                Some(color) => format!(
+                    "{}_{}P",
+                    match color.format {
+                        ImageFormat::MJPG => "MJPG",
+                        ImageFormat::NV12 => "NV12",
+                        ImageFormat::YUY2 => "YUY2",
+                        _ => "BGRA",
+                    },
+                    color.height
+                ),
+                None => String::from("OFF"),
+            },
+        );
+        let depth_mode = config.depth_mode();
+        tag(
+            "K4A_DEPTH_MODE",
+            String::from(match depth_mode {
+                DepthMode::NFov2x2Binned => "NFOV_2X2BINNED",
+                DepthMode::NFovUnbinned => "NFOV_UNBINNED",
+                DepthMode::WFov2x2Binned => "WFOV_2X2BINNED",
+                DepthMode::WFovUnbinned => "WFOV_UNBINNED",
+                DepthMode::PassiveIr => "PASSIVE_IR",
+                DepthMode::Off => "OFF",
+            }),
+        );
+        tag(
+            "K4A_IR_MODE",
+            String::from(match depth_mode {
+                DepthMode::Off => "OFF",
+                DepthMode::PassiveIr => "PASSIVE",
+                _ => "ACTIVE",
+            }),
+        );
+        tag(
+            "K4A_IMU_MODE",
+            String::from(if self.imu.is_some() { "ON" } else { "OFF" }),
+        );
+        if self.attachments.iter().any(|(n, _)| n == CALIBRATION_FILE) {
+            tag("K4A_CALIBRATION_FILE", String::from(CALIBRATION_FILE));
+        }
+        if self.color.is_some() && self.depth.or(self.ir).is_some() {
+            tag(
+                "K4A_DEPTH_DELAY_NS",
+                (config.depth_delay_off_color_usec() as i64 * 1000).to_string(),
+            );
+        }
+        let sync_mode = config.wired_sync_mode();
+        tag(
+            "K4A_WIRED_SYNC_MODE",
+            String::from(match sync_mode {
+                WiredSyncMode::Master => "MASTER",
+                WiredSyncMode::Subordinate => "SUBORDINATE",
+                WiredSyncMode::Standalone => "STANDALONE",
+            }),
+        );
+        if sync_mode == WiredSyncMode::Subordinate {
+            tag(
+                "K4A_SUBORDINATE_DELAY_NS",
+                (config.subordinate_delay_off_master_usec() as u64 * 1000).to_string(),
+            );
+        }
+        tag(
+            "K4A_START_OFFSET_NS",
+            self.start_offset_ns.unwrap_or(0).to_string(),
+        );
+        for (name, builtin) in &[
+            ("K4A_COLOR_TRACK", COLOR_TRACK),
+            ("K4A_DEPTH_TRACK", DEPTH_TRACK),
+            ("K4A_IR_TRACK", IR_TRACK),
+            ("K4A_IMU_TRACK", IMU_TRACK),
+        ] {
+            if let Some(track) = self.tracks.iter().find(|t| t.name == *builtin) {
+                tag(name, track.uid.to_string());
+            }
+        }
+        tags
+    }
+}
+
+impl<W: Write + Seek> Drop for MkvRecord<W> {
+    fn drop(&mut self) {
+        if self.writer.is_some() {
+            let _ = self.finish();
+        }
+    }
+}
+
+/// BITMAPINFOHEADER, the codec context of "V_MS/VFW/FOURCC" tracks.
+fn bitmap_info_header(width: i32, height: i32, bit_count: u16, fourcc: [u8; 4]) -> Vec<u8> {
+    let mut header = Vec::with_capacity(40);
+    header.extend_from_slice(&40u32.to_le_bytes());
+    header.extend_from_slice(&width.to_le_bytes());
+    header.extend_from_slice(&height.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&bit_count.to_le_bytes());
+    header.extend_from_slice(&fourcc);
+    let size_image = width as u32 * height as u32 * bit_count as u32 / 8;
+    header.extend_from_slice(&size_image.to_le_bytes());
+    header.resize(40, 0);
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::playback::RecordConfiguration;
+    use azure_kinect_sys::k4a::{k4a_float3_t, k4a_imu_sample_t};
+    use std::io::Cursor;
+
+    fn depth_capture(timestamp_usec: u64, value: u16) -> OwnedCapture {
+        let dimension = DepthMode::NFov2x2Binned.get_dimension();
+        let pixels = (dimension.width * dimension.height) as usize;
+        let buffer = std::iter::repeat_n(value.to_ne_bytes(), pixels)
+            .flatten()
+            .collect();
+        let mut depth = OwnedImage::new(
+            ImageFormat::Depth16,
+            dimension.width,
+            dimension.height,
+            dimension.width * 2,
+            buffer,
+        )
+        .unwrap();
+        depth.set_device_timestamp_usec(timestamp_usec);
+        let mut capture = OwnedCapture::new();
+        capture.set_depth_image(Some(depth));
+        capture
+    }
+
+    fn imu_sample(timestamp_usec: u64) -> ImuSample {
+        ImuSample::from_native(k4a_imu_sample_t {
+            temperature: 30.0,
+            acc_sample: k4a_float3_t { v: [0.0, 0.0, 9.8] },
+            acc_timestamp_usec: timestamp_usec,
+            gyro_sample: k4a_float3_t { v: [0.5, 0.0, 0.0] },
+            gyro_timestamp_usec: timestamp_usec,
+        })
+    }
+
+    fn configuration() -> DeviceConfiguration {
+        DeviceConfiguration::builder()
+            .depth_mode(DepthMode::NFov2x2Binned)
+            .camera_fps(Fps::_15fps)
+            .build()
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<(), Error> {
+        let mut record = MkvRecord::from_writer(Cursor::new(Vec::new()), &configuration())?;
+        record.add_imu_track()?;
+        record.add_tag("CUSTOM_TAG", "value")?;
+        record.add_attachment(CALIBRATION_FILE, b"{\"calibration\":0}")?;
+        record.add_custom_subtitle_track(
+            "NOTES",
+            "S_TEXT/UTF8",
+            &[],
+            &RecordSubtitleSettings::new(false),
+        )?;
+        record.write_header()?;
+        assert!(record.add_imu_track().is_err());
+
+        let start = 5_000_000;
+        for i in 0..40u64 {
+            let timestamp = start + i * 66_667;
+            record.write_capture(&depth_capture(timestamp, i as u16))?;
+            for j in 0..4 {
+                record.write_imu_sample(&imu_sample(timestamp + j * 16_000))?;
+            }
+            if i == 2 {
+                record.write_custom_track_data("NOTES", start + 100_000, b"hello")?;
+            }
+        }
+        let data = record.close()?.into_inner();
+
+        let mut playback = MkvPlayback::from_reader(Cursor::new(data))?;
+        let config: RecordConfiguration = playback.get_record_configuration()?;
+        assert_eq!(config.depth_mode(), DepthMode::NFov2x2Binned);
+        assert_eq!(config.camera_fps(), Fps::_15fps);
+        assert!(config.depth_track_enabled() && config.ir_track_enabled());
+        assert!(!config.color_track_enabled());
+        assert!(config.imu_track_enabled());
+        assert_eq!(config.start_timestamp_offset_usec(), start as u32);
+        assert!(playback.get_recording_length_usec() >= 39 * 66_667);
+        assert_eq!(playback.get_tag("CUSTOM_TAG")?, "value");
+        assert_eq!(playback.get_tag("K4A_IMU_MODE")?, "ON");
+        assert_eq!(playback.get_raw_calibration()?, b"{\"calibration\":0}\0");
+        let metadata = playback.get_metadata()?;
+        assert_eq!(
+            metadata.configuration().depth_mode(),
+            DepthMode::NFov2x2Binned
+        );
+        assert_eq!(metadata.tag("K4A_IMU_MODE"), Some("ON"));
+        assert_eq!(
+            metadata.custom_tags().collect::<Vec<_>>(),
+            [&("CUSTOM_TAG".to_string(), "value".to_string())]
+        );
+        assert_eq!(metadata.calibration_file(), Some(CALIBRATION_FILE));
+        assert_eq!(metadata.attachments(), [(CALIBRATION_FILE.to_string(), 17)]);
+        assert_eq!(playback.tags().len(), metadata.tags().len());
+        assert_eq!(playback.get_track_count(), 4);
+        assert_eq!(playback.get_track(3)?.get_name(), "NOTES");
+        assert!(!playback.get_track(3)?.is_builtin());
+
+        let mut count = 0;
+        while let Ok(capture) = playback.get_next_capture() {
+            let depth = capture.get_depth_image().unwrap();
+            assert_eq!(depth.get_device_timestamp_usec(), start + count * 66_667);
+            assert!(depth.depth16()?.flatten().all(|d| *d == count as u16));
+            count += 1;
+        }
+        assert_eq!(count, 40);
+
+        playback.seek_timestamp(-100_000, SeekOrigin::End)?;
+        let sample = playback.get_next_imu_sample()?;
+        assert_eq!(sample.acc_timestamp_usec(), start + 38 * 66_667);
+        assert_eq!(sample.acc_sample().z(), 9.8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_errors() -> Result<(), Error> {
+        let mut record = MkvRecord::from_writer(Cursor::new(Vec::new()), &configuration())?;
+        //  Nothing can be written before the header
+        assert!(record.write_capture(&depth_capture(1000, 0)).is_err());
+        assert!(record
+            .add_custom_video_track(
+                "DEPTH",
+                "V_MS/VFW/FOURCC",
+                &[],
+                &RecordVideoSettings::new(1, 1, 30)
+            )
+            .is_err());
+        record.write_header()?;
+        assert_eq!(
+            record
+                .write_imu_sample(&imu_sample(1000))
+                .unwrap_err()
+                .context()
+                .unwrap()
+                .argument("imu_track"),
+            Some("not added")
+        );
+
+        record.write_capture(&depth_capture(2_000_000, 0))?;
+        //  Before the start of the recording
+        let e = record
+            .write_capture(&depth_capture(1_000_000, 0))
+            .unwrap_err();
+        assert_eq!(
+            e.context().unwrap().operation(),
+            Some("MkvRecord::write_capture")
+        );
+        assert_eq!(
+            e.context().unwrap().argument("start_offset_usec"),
+            Some("2000000")
+        );
+        record.flush()?;
+        //  Before data which is already flushed
+        let e = record
+            .write_capture(&depth_capture(2_000_000, 0))
+            .unwrap_err();
+        assert!(e
+            .context()
+            .unwrap()
+            .argument("written_until_usec")
+            .is_some());
+
+        let mut capture = depth_capture(3_000_000, 0);
+        capture.set_color_image(capture.get_depth_image().cloned());
+        assert!(record.write_capture(&capture).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_high_track_number() -> Result<(), Error> {
+        let mut record = MkvRecord::from_writer(Cursor::new(Vec::new()), &configuration())?;
+        //  Track numbers above 127 take two bytes in the block header
+        for i in 0..200 {
+            record.add_custom_subtitle_track(
+                &format!("TRACK_{}", i),
+                "S_TEXT/UTF8",
+                &[],
+                &RecordSubtitleSettings::new(false),
+            )?;
+        }
+        record.write_header()?;
+        record.write_capture(&depth_capture(1_000_000, 0))?;
+        record.write_custom_track_data("TRACK_199", 1_000_000, b"last")?;
+        record.write_custom_track_data("TRACK_126", 1_000_000, b"one byte")?;
+        let data = record.close()?.into_inner();
+
+        let mut playback = MkvPlayback::from_reader(Cursor::new(data))?;
+        let counts = playback.block_counts()?;
+        let count = |name: &str| counts.iter().find(|(n, _)| n == name).map(|(_, c)| *c);
+        assert_eq!(count("TRACK_199"), Some(1));
+        assert_eq!(count("TRACK_126"), Some(1));
+        assert_eq!(count("DEPTH"), Some(1));
+        assert_eq!(counts.iter().map(|(_, c)| c).sum::<u64>(), 3);
+        assert_eq!(
+            playback
+                .get_next_capture()?
+                .get_depth_image()
+                .map(|d| d.get_device_timestamp_usec()),
+            Some(1_000_000)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_vint() {
+        assert_eq!(vint(1), [0x81]);
+        assert_eq!(vint(126), [0xFE]);
+        //  0xFF is reserved
+        assert_eq!(vint(127), [0x40, 0x7F]);
+        assert_eq!(vint(202), [0x40, 0xCA]);
+        assert_eq!(vint(16_383), [0x20, 0x3F, 0xFF]);
+        for value in [1, 127, 202, 16_383, 1 << 40] {
+            assert_eq!(
+                parse_vint(&vint(value)).unwrap(),
+                (value, vint(value).len())
+            );
+        }
+    }
+
+    #[test]
+    fn test_gyro_before_start() -> Result<(), Error> {
+        let mut record = MkvRecord::from_writer(Cursor::new(Vec::new()), &configuration())?;
+        record.add_imu_track()?;
+        record.write_header()?;
+        //  The gyro of the first sample can be sampled before its accelerometer
+        let mut sample = imu_sample(1_000_000);
+        sample.set_gyro_timestamp_usec(999_000);
+        record.write_imu_sample(&sample)?;
+        record.write_capture(&depth_capture(1_000_000, 0))?;
+        let data = record.close()?.into_inner();
+
+        let mut playback = MkvPlayback::from_reader(Cursor::new(data))?;
+        let sample = playback.get_next_imu_sample()?;
+        assert_eq!(sample.acc_timestamp_usec(), 1_000_000);
+        assert_eq!(sample.gyro_timestamp_usec(), 999_000);
+        Ok(())
+    }
+}
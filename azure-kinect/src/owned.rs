@@ -0,0 +1,290 @@
+use crate::image::row_bytes;
+use crate::*;
+
+/// An image whose buffer is owned by Rust instead of the SDK, e.g. read by [`mkv::MkvPlayback`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OwnedImage {
+    format: ImageFormat,
+    width_pixels: i32,
+    height_pixels: i32,
+    stride_bytes: i32,
+    device_timestamp_usec: u64,
+    system_timestamp_nsec: u64,
+    exposure_usec: u64,
+    white_balance: u32,
+    iso_speed: u32,
+    buffer: Vec<u8>,
+}
+
+impl OwnedImage {
+    /// Create an image from a buffer. Fails if the buffer is too small for the format.
+    pub fn new(
+        format: ImageFormat,
+        width_pixels: i32,
+        height_pixels: i32,
+        stride_bytes: i32,
+        buffer: Vec<u8>,
+    ) -> Result<OwnedImage, Error> {
+        if width_pixels < 0 || height_pixels < 0 || stride_bytes < 0 {
+            return Err(Error::Failed);
+        }
+        if format != ImageFormat::MJPG {
+            let planes = if format == ImageFormat::NV12 { 3 } else { 2 };
+            let row_bytes = row_bytes(format, width_pixels, stride_bytes)?;
+            let size = stride_bytes as usize * height_pixels as usize * planes / 2;
+            if (stride_bytes as usize) < row_bytes || buffer.len() < size {
+                return Err(Error::TooSmall);
+            }
+        }
+        Ok(OwnedImage {
+            format,
+            width_pixels,
+            height_pixels,
+            stride_bytes,
+            device_timestamp_usec: 0,
+            system_timestamp_nsec: 0,
+            exposure_usec: 0,
+            white_balance: 0,
+            iso_speed: 0,
+            buffer,
+        })
+    }
+
+    /// Copy an image of the SDK, including its metadata
+    pub fn from_image(image: &Image) -> OwnedImage {
+        OwnedImage {
+            format: image.get_format(),
+            width_pixels: image.get_width_pixels(),
+            height_pixels: image.get_height_pixels(),
+            stride_bytes: image.get_stride_bytes(),
+            device_timestamp_usec: image.get_device_timestamp_usec(),
+            system_timestamp_nsec: image.get_system_timestamp_nsec(),
+            exposure_usec: image.get_exposure_usec(),
+            white_balance: image.get_white_balance(),
+            iso_speed: image.get_iso_speed(),
+            buffer: image.as_bytes().to_vec(),
+        }
+    }
+
+    /// Get the image buffer as a byte slice
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Get the image buffer as a mutable byte slice
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+
+    /// Take the image buffer
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    /// Get the image buffer size in bytes
+    pub fn get_size(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Get the image format of the image
+    pub fn get_format(&self) -> ImageFormat {
+        self.format
+    }
+
+    /// Get the image width in pixels
+    pub fn get_width_pixels(&self) -> i32 {
+        self.width_pixels
+    }
+
+    /// Get the image height in pixels
+    pub fn get_height_pixels(&self) -> i32 {
+        self.height_pixels
+    }
+
+    /// Get the image stride in bytes
+    pub fn get_stride_bytes(&self) -> i32 {
+        self.stride_bytes
+    }
+
+    /// Get the image's device timestamp in microseconds
+    pub fn get_device_timestamp_usec(&self) -> u64 {
+        self.device_timestamp_usec
+    }
+
+    /// Get the image's system timestamp in nanoseconds
+    pub fn get_system_timestamp_nsec(&self) -> u64 {
+        self.system_timestamp_nsec
+    }
+
+    /// Get the image exposure time in microseconds
+    pub fn get_exposure_usec(&self) -> u64 {
+        self.exposure_usec
+    }
+
+    /// Get the image white balance in Kelvin (color images only)
+    pub fn get_white_balance(&self) -> u32 {
+        self.white_balance
+    }
+
+    /// Get the image's ISO speed (color images only)
+    pub fn get_iso_speed(&self) -> u32 {
+        self.iso_speed
+    }
+
+    /// Set the image's device timestamp in microseconds
+    pub fn set_device_timestamp_usec(&mut self, timestamp: u64) {
+        self.device_timestamp_usec = timestamp;
+    }
+
+    /// Set the image's system timestamp in nanoseconds
+    pub fn set_system_timestamp_nsec(&mut self, timestamp: u64) {
+        self.system_timestamp_nsec = timestamp;
+    }
+
+    /// Set the image exposure time in microseconds
+    pub fn set_exposure_usec(&mut self, exposure: u64) {
+        self.exposure_usec = exposure;
+    }
+
+    /// Set the image white balance in Kelvin (color images only)
+    pub fn set_white_balance(&mut self, white_balance: u32) {
+        self.white_balance = white_balance;
+    }
+
+    /// Set the image's ISO speed (color images only)
+    pub fn set_iso_speed(&mut self, iso_speed: u32) {
+        self.iso_speed = iso_speed;
+    }
+
+    /// Iterate over the rows of the image in bytes, without the padding at the end of each row
+    ///
+    /// NV12 images yield the rows of the luminance plane, MJPG images have no rows.
+    pub fn rows(&self) -> Result<Rows<'_, u8>, Error> {
+        let row_bytes = row_bytes(self.format, self.width_pixels, self.stride_bytes)?;
+        self.typed_rows(&[self.format], row_bytes)
+    }
+
+    /// Iterate over the rows of a DEPTH16 image, in millimeters
+    pub fn depth16(&self) -> Result<Rows<'_, u16>, Error> {
+        self.typed_rows(&[ImageFormat::Depth16], self.width_pixels as usize)
+    }
+
+    /// Iterate over the rows of an IR16 image
+    pub fn ir16(&self) -> Result<Rows<'_, u16>, Error> {
+        self.typed_rows(&[ImageFormat::IR16], self.width_pixels as usize)
+    }
+
+    /// Iterate over the rows of a BGRA32 image, each pixel as `[b, g, r, a]`
+    pub fn bgra32(&self) -> Result<Rows<'_, [u8; 4]>, Error> {
+        self.typed_rows(&[ImageFormat::BGRA32], self.width_pixels as usize)
+    }
+
+    /// Iterate over the rows of a CUSTOM8 image
+    pub fn custom8(&self) -> Result<Rows<'_, u8>, Error> {
+        self.typed_rows(&[ImageFormat::Custom8], self.width_pixels as usize)
+    }
+
+    /// Iterate over the rows of a CUSTOM16 image
+    pub fn custom16(&self) -> Result<Rows<'_, u16>, Error> {
+        self.typed_rows(&[ImageFormat::Custom16], self.width_pixels as usize)
+    }
+
+    fn typed_rows<T: Pixel>(
+        &self,
+        formats: &[ImageFormat],
+        width: usize,
+    ) -> Result<Rows<'_, T>, Error> {
+        Rows::new(
+            &self.buffer,
+            self.format,
+            formats,
+            width,
+            self.height_pixels,
+            self.stride_bytes,
+        )
+    }
+}
+
+/// A capture of owned images, independent of the SDK.
+#[derive(Clone, Debug)]
+pub struct OwnedCapture {
+    color: Option<OwnedImage>,
+    depth: Option<OwnedImage>,
+    ir: Option<OwnedImage>,
+    temperature_c: f32,
+}
+
+impl OwnedCapture {
+    /// Create an empty capture, the temperature is NaN
+    pub fn new() -> OwnedCapture {
+        OwnedCapture {
+            color: None,
+            depth: None,
+            ir: None,
+            temperature_c: f32::NAN,
+        }
+    }
+
+    /// Copy a capture of the SDK
+    pub fn from_capture(capture: &Capture) -> OwnedCapture {
+        let image = |image: Image| {
+            if unsafe { image.get_native_handle() }.is_null() {
+                None
+            } else {
+                Some(OwnedImage::from_image(&image))
+            }
+        };
+        OwnedCapture {
+            color: image(capture.get_color_image()),
+            depth: image(capture.get_depth_image()),
+            ir: image(capture.get_ir_image()),
+            temperature_c: capture.get_temperature_c(),
+        }
+    }
+
+    /// Get the color image associated with the capture
+    pub fn get_color_image(&self) -> Option<&OwnedImage> {
+        self.color.as_ref()
+    }
+
+    /// Get the depth image associated with the capture
+    pub fn get_depth_image(&self) -> Option<&OwnedImage> {
+        self.depth.as_ref()
+    }
+
+    /// Get the IR image associated with the capture
+    pub fn get_ir_image(&self) -> Option<&OwnedImage> {
+        self.ir.as_ref()
+    }
+
+    /// Set / add a color image to the capture
+    pub fn set_color_image(&mut self, color_image: Option<OwnedImage>) {
+        self.color = color_image;
+    }
+
+    /// Set / add a depth image to the capture
+    pub fn set_depth_image(&mut self, depth_image: Option<OwnedImage>) {
+        self.depth = depth_image;
+    }
+
+    /// Set / add an IR image to the capture
+    pub fn set_ir_image(&mut self, ir_image: Option<OwnedImage>) {
+        self.ir = ir_image;
+    }
+
+    /// Get the temperature associated with the capture, in Celsius.
+    pub fn get_temperature_c(&self) -> f32 {
+        self.temperature_c
+    }
+
+    /// Set the temperature associated with the capture in Celsius.
+    pub fn set_temperature_c(&mut self, temperature_c: f32) {
+        self.temperature_c = temperature_c;
+    }
+}
+
+impl Default for OwnedCapture {
+    fn default() -> Self {
+        OwnedCapture::new()
+    }
+}
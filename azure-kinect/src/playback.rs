@@ -1,13 +1,14 @@
 use crate::imu::ImuSample;
+use crate::mkv::MkvPlayback;
 use crate::playback_data_block::PlaybackDataBlock;
 use crate::playback_track::PlaybackTrack;
 use crate::utility::*;
 use crate::*;
 use azure_kinect_sys::k4a::*;
 use azure_kinect_sys::k4arecord::{
-    k4a_playback_data_block_t, k4a_playback_seek_origin_t, k4a_playback_t,
-    k4a_record_configuration_t,
+    k4a_playback_data_block_t, k4a_playback_t, k4a_record_configuration_t,
 };
+use std::cell::RefCell;
 use std::ptr;
 
 pub struct RecordConfiguration {
@@ -66,145 +67,339 @@ impl RecordConfiguration {
     pub fn start_timestamp_offset_usec(&self) -> u32 {
         self.value.start_timestamp_offset_usec
     }
+
+    /// Device configuration that records the same tracks, e.g. for
+    /// [`FactoryRecord::record_create_without_device`]. The IMU track is added separately.
+    pub fn device_configuration(&self) -> DeviceConfiguration {
+        DeviceConfiguration::builder()
+            .color_format(self.color_format())
+            .color_resolution(if self.color_track_enabled() {
+                self.color_resolution()
+            } else {
+                ColorResolution::Off
+            })
+            .depth_mode(if self.depth_track_enabled() || self.ir_track_enabled() {
+                self.depth_mode()
+            } else {
+                DepthMode::Off
+            })
+            .camera_fps(self.camera_fps())
+            .depth_delay_off_color_usec(self.depth_delay_off_color_usec())
+            .wired_sync_mode(self.wired_sync_mode())
+            .subordinate_delay_off_master_usec(self.subordinate_delay_off_master_usec())
+            .build()
+    }
+}
+
+/// Configuration, tags and attachments of a recording, see [`Playback::get_metadata`].
+pub struct RecordingMetadata {
+    configuration: RecordConfiguration,
+    tags: Vec<(String, String)>,
+    attachments: Vec<(String, u64)>,
+}
+
+impl RecordingMetadata {
+    pub(crate) fn new(
+        configuration: RecordConfiguration,
+        tags: Vec<(String, String)>,
+        attachments: Vec<(String, u64)>,
+    ) -> RecordingMetadata {
+        RecordingMetadata {
+            configuration,
+            tags,
+            attachments,
+        }
+    }
+
+    pub fn configuration(&self) -> &RecordConfiguration {
+        &self.configuration
+    }
+
+    /// Gets the names and values of all tags, in the order of the file.
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Gets the tags that were not written by the SDK recorder, e.g. added with `Record::add_tag`.
+    pub fn custom_tags(&self) -> impl Iterator<Item = &(String, String)> {
+        self.tags
+            .iter()
+            .filter(|(name, _)| !name.starts_with("K4A_"))
+    }
+
+    /// Gets the names and sizes in bytes of all attachments.
+    pub fn attachments(&self) -> &[(String, u64)] {
+        &self.attachments
+    }
+
+    pub fn device_serial_number(&self) -> Option<&str> {
+        self.tag("K4A_DEVICE_SERIAL_NUMBER")
+    }
+
+    pub fn color_firmware_version(&self) -> Option<&str> {
+        self.tag("K4A_COLOR_FIRMWARE_VERSION")
+    }
+
+    pub fn depth_firmware_version(&self) -> Option<&str> {
+        self.tag("K4A_DEPTH_FIRMWARE_VERSION")
+    }
+
+    /// Gets the name of the attachment holding the calibration of the device.
+    pub fn calibration_file(&self) -> Option<&str> {
+        self.tag("K4A_CALIBRATION_FILE")
+    }
 }
 
 pub struct Playback<'a> {
     pub(crate) factory: &'a FactoryRecord,
     pub(crate) handle: k4a_playback_t,
+    //  The SDK can only read tags and attachments by name, they are listed from the file, which is
+    //  parsed on first use.
+    path: Option<String>,
+    mkv: RefCell<Option<MkvPlayback>>,
 }
 
+//  k4arecord handles are not thread safe, a playback can be moved to another thread but not shared.
+unsafe impl Send for Playback<'_> {}
+
 impl<'a> Playback<'a> {
     pub fn from_handle(
         factory: &'a FactoryRecord,
         handle: azure_kinect_sys::k4arecord::k4a_playback_t,
     ) -> Playback<'a> {
-        Playback { factory, handle }
+        Playback {
+            factory,
+            handle,
+            path: None,
+            mkv: RefCell::new(None),
+        }
+    }
+
+    pub(crate) fn with_path(mut self, path: &str) -> Playback<'a> {
+        self.path = Some(path.to_string());
+        self
     }
 
     /// Get the raw calibration blob for the K4A device that made the recording.
     pub fn get_raw_calibration(&self) -> Result<Vec<u8>, Error> {
-        get_k4a_binary_data(&|calibration, buffer| unsafe {
-            (self
-                .factory
-                .api_record()
-                .funcs
-                .k4a_playback_get_raw_calibration)(self.handle, calibration, buffer)
+        ErrorContext::new("k4a_playback_get_raw_calibration").call(|| {
+            get_k4a_binary_data(&|calibration, buffer| unsafe {
+                (self
+                    .factory
+                    .api_record()
+                    .funcs
+                    .k4a_playback_get_raw_calibration)(
+                    self.handle, calibration, buffer
+                )
+            })
         })
     }
 
     /// Get the camera calibration for the K4A device that made the recording, which is used for all transformation
-    pub fn get_calibration(&self) -> Result<Calibration, Error> {
+    pub fn get_calibration(&self) -> Result<Calibration<'_>, Error> {
         let mut calibaraion = k4a_calibration_t::default();
-        Error::from_k4a_result_t(unsafe {
-            (self.factory.api_record().funcs.k4a_playback_get_calibration)(
-                self.handle,
-                std::mem::transmute(&mut calibaraion),
-            )
+        ErrorContext::new("k4a_playback_get_calibration").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (self.factory.api_record().funcs.k4a_playback_get_calibration)(
+                    self.handle,
+                    &mut calibaraion as *mut _ as *mut _,
+                )
+            })
+            .to_result_fn(|| Calibration::from_handle(self.factory.core().api(), calibaraion))
         })
-        .to_result_fn(|| Calibration::from_handle(self.factory.core().api(), calibaraion))
     }
 
     /// Gets the configuration of the recording
     pub fn get_record_configuration(&self) -> Result<RecordConfiguration, Error> {
         let mut configuration = k4a_record_configuration_t::default();
-        Error::from_k4a_result_t(unsafe {
-            (self
-                .factory
-                .api_record()
-                .funcs
-                .k4a_playback_get_record_configuration)(self.handle, &mut configuration)
-        })
-        .to_result(RecordConfiguration {
-            value: configuration,
+        ErrorContext::new("k4a_playback_get_record_configuration").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (self
+                    .factory
+                    .api_record()
+                    .funcs
+                    .k4a_playback_get_record_configuration)(
+                    self.handle, &mut configuration
+                )
+            })
+            .to_result(RecordConfiguration {
+                value: configuration,
+            })
         })
     }
 
     /// Get the next capture in the recording.
-    pub fn get_next_capture(&self) -> Result<Capture, Error> {
+    pub fn get_next_capture(&self) -> Result<Capture<'_>, Error> {
         let mut handle: k4a_capture_t = ptr::null_mut();
-        Error::from_k4a_stream_result_t(unsafe {
-            (self.factory.api_record().funcs.k4a_playback_get_next_capture)(
-                self.handle,
-                std::mem::transmute(&mut handle),
-            )
+        ErrorContext::new("k4a_playback_get_next_capture").call(|| {
+            Error::from_k4a_stream_result_t(unsafe {
+                (self
+                    .factory
+                    .api_record()
+                    .funcs
+                    .k4a_playback_get_next_capture)(
+                    self.handle, &mut handle as *mut _ as *mut _
+                )
+            })
+            .to_result_fn(|| Capture::from_handle(self.factory.core().api(), handle))
         })
-        .to_result_fn(|| Capture::from_handle(self.factory.core().api(), handle))
     }
 
     /// Get the previous capture in the recording.
-    pub fn get_previous_capture(&self) -> Result<Capture, Error> {
+    pub fn get_previous_capture(&self) -> Result<Capture<'_>, Error> {
         let mut handle: k4a_capture_t = ptr::null_mut();
-        Error::from_k4a_stream_result_t(unsafe {
-            (self
-                .factory
-                .api_record()
-                .funcs
-                .k4a_playback_get_previous_capture)(
-                self.handle, std::mem::transmute(&mut handle)
-            )
+        ErrorContext::new("k4a_playback_get_previous_capture").call(|| {
+            Error::from_k4a_stream_result_t(unsafe {
+                (self
+                    .factory
+                    .api_record()
+                    .funcs
+                    .k4a_playback_get_previous_capture)(
+                    self.handle,
+                    &mut handle as *mut _ as *mut _,
+                )
+            })
+            .to_result_fn(|| Capture::from_handle(self.factory.core().api(), handle))
         })
-        .to_result_fn(|| Capture::from_handle(self.factory.core().api(), handle))
     }
 
     /// Reads the value of a tag from the recording
     pub fn get_tag(&self, name: &str) -> Result<String, Error> {
         let name = std::ffi::CString::new(name).unwrap_or_default();
-        get_k4a_string(&|tag, buffer| unsafe {
-            (self.factory.api_record().funcs.k4a_playback_get_tag)(
-                self.handle,
-                name.as_ptr(),
-                tag,
-                buffer,
-            )
-        })
+        ErrorContext::new("k4a_playback_get_tag")
+            .arg("name", name.to_string_lossy())
+            .call(|| {
+                get_k4a_string(&|tag, buffer| unsafe {
+                    (self.factory.api_record().funcs.k4a_playback_get_tag)(
+                        self.handle,
+                        name.as_ptr(),
+                        tag,
+                        buffer,
+                    )
+                })
+            })
     }
 
     /// Get the next IMU sample in the recording.
     pub fn get_next_imu_sample(&self) -> Result<ImuSample, Error> {
         let mut imu_sample = k4a_imu_sample_t::default();
-        Error::from_k4a_stream_result_t(unsafe {
-            (self
-                .factory
-                .api_record()
-                .funcs
-                .k4a_playback_get_next_imu_sample)(
-                self.handle,
-                std::mem::transmute(&mut imu_sample),
-            )
+        ErrorContext::new("k4a_playback_get_next_imu_sample").call(|| {
+            Error::from_k4a_stream_result_t(unsafe {
+                (self
+                    .factory
+                    .api_record()
+                    .funcs
+                    .k4a_playback_get_next_imu_sample)(
+                    self.handle,
+                    &mut imu_sample as *mut _ as *mut _,
+                )
+            })
+            .to_result(ImuSample::from_native(imu_sample))
         })
-        .to_result(ImuSample::from_native(imu_sample))
     }
 
     /// Get the previous IMU sample in the recording.
     pub fn get_previous_imu_sample(&self) -> Result<ImuSample, Error> {
         let mut imu_sample = k4a_imu_sample_t::default();
-        Error::from_k4a_stream_result_t(unsafe {
-            (self
-                .factory
-                .api_record()
-                .funcs
-                .k4a_playback_get_previous_imu_sample)(
-                self.handle,
-                std::mem::transmute(&mut imu_sample),
-            )
+        ErrorContext::new("k4a_playback_get_previous_imu_sample").call(|| {
+            Error::from_k4a_stream_result_t(unsafe {
+                (self
+                    .factory
+                    .api_record()
+                    .funcs
+                    .k4a_playback_get_previous_imu_sample)(
+                    self.handle,
+                    &mut imu_sample as *mut _ as *mut _,
+                )
+            })
+            .to_result(ImuSample::from_native(imu_sample))
         })
-        .to_result(ImuSample::from_native(imu_sample))
     }
 
     /// Seeks to a specific time point in the recording
-    pub fn seek_timestamp(
-        &self,
-        offset_usec: i64,
-        origin: k4a_playback_seek_origin_t,
-    ) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
-            (self.factory.api_record().funcs.k4a_playback_seek_timestamp)(
-                self.handle,
-                offset_usec,
-                origin,
-            )
-        })
-        .to_result(())
+    pub fn seek_timestamp(&self, offset_usec: i64, origin: SeekOrigin) -> Result<(), Error> {
+        ErrorContext::new("k4a_playback_seek_timestamp")
+            .arg("offset_usec", offset_usec)
+            .arg("origin", format!("{:?}", origin))
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.factory.api_record().funcs.k4a_playback_seek_timestamp)(
+                        self.handle,
+                        offset_usec,
+                        origin.into(),
+                    )
+                })
+                .to_result(())
+            })
+    }
+
+    /// Seeks so that the next call to `get_next_capture` returns the capture at `index`, counted from 0
+    /// at the beginning of the recording. `Error::Eof` is returned if the recording has fewer than `index` captures.
+    pub fn seek_capture(&self, index: usize) -> Result<(), Error> {
+        self.seek_timestamp(0, SeekOrigin::Begin)?;
+        self.step_captures(index as i64)
+    }
+
+    /// Seeks so that the next call to `get_next_capture` returns the capture whose device timestamp is the
+    /// nearest to `device_timestamp_usec`. Timestamps outside of the recording select the first or the last capture.
+    pub fn seek_nearest_capture(&self, device_timestamp_usec: u64) -> Result<(), Error> {
+        let start_usec = self
+            .get_record_configuration()?
+            .start_timestamp_offset_usec() as u64;
+        let target_usec =
+            device_timestamp_usec.clamp(start_usec, start_usec + self.get_last_timestamp_usec());
+        self.seek_timestamp(target_usec as i64, SeekOrigin::DeviceTime)?;
+
+        //  The seek stops before the first capture at or after the target, the nearest one is either that
+        //  capture or the one before.
+        let distance = |capture: &Capture| {
+            capture
+                .get_device_timestamp_usec()
+                .map_or(u64::MAX, |t| t.abs_diff(device_timestamp_usec))
+        };
+        let after = match self.get_next_capture() {
+            Ok(capture) => Some(distance(&capture)),
+            Err(Error::Eof) => None,
+            Err(e) => return Err(e),
+        };
+        let before = match self.get_previous_capture() {
+            Ok(capture) => Some(distance(&capture)),
+            Err(Error::Eof) => None,
+            Err(e) => return Err(e),
+        };
+        match (before, after) {
+            (None, None) => Err(Error::Eof),
+            //  The playback is positioned on the capture before, the next one is the capture after
+            (None, Some(_)) => Ok(()),
+            (Some(before), Some(after)) if after < before => Ok(()),
+            _ => match self.get_previous_capture() {
+                Ok(_) | Err(Error::Eof) => Ok(()),
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    /// Moves the playback by `count` captures, forward for a positive count and backward for a negative one.
+    /// `Error::Eof` is returned if the recording ends first.
+    ///
+    /// Like the SDK, the playback is positioned between captures: after `get_next_capture` returned a capture,
+    /// `step_captures(-1)` makes `get_next_capture` return the same capture again.
+    pub fn step_captures(&self, count: i64) -> Result<(), Error> {
+        for _ in 0..count.unsigned_abs() {
+            if count > 0 {
+                self.get_next_capture()?;
+            } else {
+                self.get_previous_capture()?;
+            }
+        }
+        Ok(())
     }
 
     /// Get the last valid timestamp in the recording
@@ -218,88 +413,488 @@ impl<'a> Playback<'a> {
         }
     }
 
-    /// Set the image format that color captures will be converted to. By default the conversion format will be the
-    /// same as the image format stored in the recording file, and no conversion will occur.
-    pub fn set_color_conversion(&mut self, format: ImageFormat) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
+    /// Gets the last timestamp in the recording, relative to the start of the recording.
+    pub fn get_last_timestamp_usec(&self) -> u64 {
+        unsafe {
             (self
                 .factory
                 .api_record()
                 .funcs
-                .k4a_playback_set_color_conversion)(self.handle, format.into())
-        })
-        .to_result(())
+                .k4a_playback_get_last_timestamp_usec)(self.handle)
+        }
+    }
+
+    /// Set the image format that color captures will be converted to. By default the conversion format will be the
+    /// same as the image format stored in the recording file, and no conversion will occur.
+    pub fn set_color_conversion(&mut self, format: ImageFormat) -> Result<(), Error> {
+        ErrorContext::new("k4a_playback_set_color_conversion")
+            .arg("format", format!("{:?}", format))
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self
+                        .factory
+                        .api_record()
+                        .funcs
+                        .k4a_playback_set_color_conversion)(
+                        self.handle, format.into()
+                    )
+                })
+                .to_result(())
+            })
     }
 
     /// Get the next data block in the recording.
-    pub fn get_next_data_block(&self, track: &str) -> Result<PlaybackDataBlock, Error> {
+    pub fn get_next_data_block(&self, track: &str) -> Result<PlaybackDataBlock<'_>, Error> {
         let mut block_handle: k4a_playback_data_block_t = ptr::null_mut();
         let track = std::ffi::CString::new(track).unwrap_or_default();
 
-        Error::from_k4a_stream_result_t(unsafe {
-            (self
-                .factory
-                .api_record()
-                .funcs
-                .k4a_playback_get_next_data_block)(
-                self.handle, track.as_ptr(), &mut block_handle
-            )
-        })
-        .to_result_fn(|| PlaybackDataBlock::from_handle(self.factory.api_record(), block_handle))
+        ErrorContext::new("k4a_playback_get_next_data_block")
+            .arg("track", track.to_string_lossy())
+            .call(|| {
+                Error::from_k4a_stream_result_t(unsafe {
+                    (self
+                        .factory
+                        .api_record()
+                        .funcs
+                        .k4a_playback_get_next_data_block)(
+                        self.handle,
+                        track.as_ptr(),
+                        &mut block_handle,
+                    )
+                })
+                .to_result_fn(|| {
+                    PlaybackDataBlock::from_handle(self.factory.api_record(), block_handle)
+                })
+            })
     }
 
     /// Get the previous data block from the recording.
-    pub fn get_previous_data_block(&self, track: &str) -> Result<PlaybackDataBlock, Error> {
+    pub fn get_previous_data_block(&self, track: &str) -> Result<PlaybackDataBlock<'_>, Error> {
         let mut block_handle: k4a_playback_data_block_t = ptr::null_mut();
         let track = std::ffi::CString::new(track).unwrap_or_default();
 
-        Error::from_k4a_stream_result_t(unsafe {
-            (self
-                .factory
-                .api_record()
-                .funcs
-                .k4a_playback_get_previous_data_block)(
-                self.handle,
-                track.as_ptr(),
-                &mut block_handle,
-            )
-        })
-        .to_result_fn(|| PlaybackDataBlock::from_handle(self.factory.api_record(), block_handle))
+        ErrorContext::new("k4a_playback_get_previous_data_block")
+            .arg("track", track.to_string_lossy())
+            .call(|| {
+                Error::from_k4a_stream_result_t(unsafe {
+                    (self
+                        .factory
+                        .api_record()
+                        .funcs
+                        .k4a_playback_get_previous_data_block)(
+                        self.handle,
+                        track.as_ptr(),
+                        &mut block_handle,
+                    )
+                })
+                .to_result_fn(|| {
+                    PlaybackDataBlock::from_handle(self.factory.api_record(), block_handle)
+                })
+            })
+    }
+
+    /// Reads the names and values of all tags in the recording.
+    ///
+    /// The SDK cannot list tags, they are read from the file, which fails for a playback created with
+    /// `from_handle`.
+    pub fn tags(&self) -> Result<Vec<(String, String)>, Error> {
+        self.with_mkv(|mkv| Ok(mkv.tags().to_vec()))
+    }
+
+    /// Reads the names and sizes in bytes of all attachments in the recording, see `tags`.
+    pub fn attachments(&self) -> Result<Vec<(String, u64)>, Error> {
+        self.with_mkv(|mkv| Ok(mkv.attachments()))
+    }
+
+    /// Reads the configuration, tags and attachments of the recording, see `tags`.
+    pub fn get_metadata(&self) -> Result<RecordingMetadata, Error> {
+        let (tags, attachments) =
+            self.with_mkv(|mkv| Ok((mkv.tags().to_vec(), mkv.attachments())))?;
+        Ok(RecordingMetadata::new(
+            self.get_record_configuration()?,
+            tags,
+            attachments,
+        ))
+    }
+
+    /// Counts the blocks of every track without reading their data, see `tags` and
+    /// [`MkvPlayback::block_counts`].
+    pub fn block_counts(&self) -> Result<Vec<(String, u64)>, Error> {
+        self.with_mkv(|mkv| mkv.block_counts())
+    }
+
+    fn with_mkv<T, F: FnOnce(&mut MkvPlayback) -> Result<T, Error>>(
+        &self,
+        f: F,
+    ) -> Result<T, Error> {
+        let mut mkv = self.mkv.borrow_mut();
+        if mkv.is_none() {
+            let path = self
+                .path
+                .as_ref()
+                .ok_or_else(|| ErrorContext::new("Playback::open_mkv"))?;
+            *mkv = Some(MkvPlayback::open(path)?);
+        }
+        f(mkv.as_mut().unwrap())
     }
 
     /// Get the attachment block from the recording.
     pub fn get_attachment(&self, attachment: &str) -> Result<Vec<u8>, Error> {
         let attachment = std::ffi::CString::new(attachment).unwrap_or_default();
-        get_k4a_binary_data(&|data, data_size| unsafe {
-            (self.factory.api_record().funcs.k4a_playback_get_attachment)(
-                self.handle,
-                attachment.as_ptr(),
-                data,
-                data_size,
-            )
-        })
+        ErrorContext::new("k4a_playback_get_attachment")
+            .arg("attachment", attachment.to_string_lossy())
+            .call(|| {
+                get_k4a_binary_data(&|data, data_size| unsafe {
+                    (self.factory.api_record().funcs.k4a_playback_get_attachment)(
+                        self.handle,
+                        attachment.as_ptr(),
+                        data,
+                        data_size,
+                    )
+                })
+            })
     }
 
     /// Get the number of tracks in a playback file.
     pub fn get_track_count(&self) -> usize {
-        unsafe {
-            (self.factory.api_record().funcs.k4a_playback_get_track_count)(self.handle) as usize
-        }
+        unsafe { (self.factory.api_record().funcs.k4a_playback_get_track_count)(self.handle) }
     }
 
     /// Gets the track at a specific index.
-    pub fn get_track(&self, track_index: usize) -> Result<PlaybackTrack, Error> {
-        Ok(PlaybackTrack::new(
-            &self,
-            get_k4a_cstring(&|track_name, track_name_size| unsafe {
-                (self.factory.api_record().funcs.k4a_playback_get_track_name)(
-                    self.handle,
-                    track_index,
-                    track_name,
-                    track_name_size,
-                )
-            })?,
-        ))
+    pub fn get_track(&self, track_index: usize) -> Result<PlaybackTrack<'_>, Error> {
+        let name = ErrorContext::new("k4a_playback_get_track_name")
+            .arg("track_index", track_index)
+            .call(|| {
+                get_k4a_cstring(&|track_name, track_name_size| unsafe {
+                    (self.factory.api_record().funcs.k4a_playback_get_track_name)(
+                        self.handle,
+                        track_index,
+                        track_name,
+                        track_name_size,
+                    )
+                })
+            })?;
+        Ok(PlaybackTrack::new(self, name))
+    }
+
+    /// Iterates over the captures from the current position to the end of the recording.
+    /// The iteration ends at the end of the recording or after the first error.
+    pub fn captures(&self) -> PlaybackCaptures<'_> {
+        PlaybackCaptures {
+            playback: self,
+            forward: true,
+            finished: false,
+        }
+    }
+
+    /// Iterates over the captures from the current position back to the beginning of the recording.
+    pub fn captures_rev(&self) -> PlaybackCaptures<'_> {
+        PlaybackCaptures {
+            forward: false,
+            ..self.captures()
+        }
+    }
+
+    /// Iterates over the IMU samples from the current position to the end of the recording.
+    pub fn imu_samples(&self) -> PlaybackImuSamples<'_> {
+        PlaybackImuSamples {
+            playback: self,
+            forward: true,
+            finished: false,
+        }
+    }
+
+    /// Iterates over the IMU samples from the current position back to the beginning of the recording.
+    pub fn imu_samples_rev(&self) -> PlaybackImuSamples<'_> {
+        PlaybackImuSamples {
+            forward: false,
+            ..self.imu_samples()
+        }
+    }
+
+    /// Iterates over the data blocks of a custom track from the current position to the end of the recording.
+    pub fn data_blocks(&self, track: &str) -> PlaybackDataBlocks<'_> {
+        PlaybackDataBlocks {
+            playback: self,
+            track: track.to_string(),
+            forward: true,
+            finished: false,
+        }
+    }
+
+    /// Iterates over the data blocks of a custom track from the current position back to the beginning
+    /// of the recording.
+    pub fn data_blocks_rev(&self, track: &str) -> PlaybackDataBlocks<'_> {
+        PlaybackDataBlocks {
+            forward: false,
+            ..self.data_blocks(track)
+        }
+    }
+
+    /// Iterates over the captures, IMU samples and data blocks of all custom tracks from the current
+    /// position, merged in the order of their device timestamps. Each stream is read one event ahead,
+    /// so the playback position afterwards is past the last event returned.
+    pub fn events(&self) -> PlaybackEvents<'_> {
+        PlaybackEvents {
+            playback: self,
+            forward: true,
+            sources: None,
+            error: None,
+            finished: false,
+        }
+    }
+
+    /// Iterates over the captures, IMU samples and data blocks from the current position back to the
+    /// beginning of the recording, in the reverse order of `events`.
+    pub fn events_rev(&self) -> PlaybackEvents<'_> {
+        PlaybackEvents {
+            forward: false,
+            ..self.events()
+        }
+    }
+}
+
+/// Reads until an item, the end of the recording or an error that finishes the iteration.
+fn next_until_eof<T, F: FnOnce() -> Result<T, Error>>(
+    finished: &mut bool,
+    read: F,
+) -> Option<Result<T, Error>> {
+    if *finished {
+        return None;
+    }
+    match read() {
+        Ok(item) => Some(Ok(item)),
+        Err(Error::Eof) => {
+            *finished = true;
+            None
+        }
+        Err(e) => {
+            *finished = true;
+            Some(Err(e))
+        }
+    }
+}
+
+/// Iterator over the captures of a recording, created by [`Playback::captures`].
+pub struct PlaybackCaptures<'a> {
+    playback: &'a Playback<'a>,
+    forward: bool,
+    finished: bool,
+}
+
+impl<'a> Iterator for PlaybackCaptures<'a> {
+    type Item = Result<Capture<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let playback = self.playback;
+        let forward = self.forward;
+        next_until_eof(&mut self.finished, || {
+            if forward {
+                playback.get_next_capture()
+            } else {
+                playback.get_previous_capture()
+            }
+        })
+    }
+}
+
+/// Iterator over the IMU samples of a recording, created by [`Playback::imu_samples`].
+pub struct PlaybackImuSamples<'a> {
+    playback: &'a Playback<'a>,
+    forward: bool,
+    finished: bool,
+}
+
+impl Iterator for PlaybackImuSamples<'_> {
+    type Item = Result<ImuSample, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let playback = self.playback;
+        let forward = self.forward;
+        next_until_eof(&mut self.finished, || {
+            if forward {
+                playback.get_next_imu_sample()
+            } else {
+                playback.get_previous_imu_sample()
+            }
+        })
+    }
+}
+
+/// Iterator over the data blocks of a custom track, created by [`Playback::data_blocks`].
+pub struct PlaybackDataBlocks<'a> {
+    playback: &'a Playback<'a>,
+    track: String,
+    forward: bool,
+    finished: bool,
+}
+
+impl<'a> Iterator for PlaybackDataBlocks<'a> {
+    type Item = Result<PlaybackDataBlock<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let playback = self.playback;
+        let forward = self.forward;
+        let track = &self.track;
+        next_until_eof(&mut self.finished, || {
+            if forward {
+                playback.get_next_data_block(track)
+            } else {
+                playback.get_previous_data_block(track)
+            }
+        })
+    }
+}
+
+/// A capture, an IMU sample or a data block of a custom track, read by [`Playback::events`].
+pub enum PlaybackEvent<'a> {
+    Capture(Capture<'a>),
+    ImuSample(ImuSample),
+    DataBlock {
+        track: String,
+        block: PlaybackDataBlock<'a>,
+    },
+}
+
+impl PlaybackEvent<'_> {
+    /// Gets the device timestamp of the event, for a capture the timestamp of its first image.
+    pub fn device_timestamp_usec(&self) -> u64 {
+        match self {
+            PlaybackEvent::Capture(capture) => capture.get_device_timestamp_usec().unwrap_or(0),
+            PlaybackEvent::ImuSample(sample) => sample.acc_timestamp_usec(),
+            PlaybackEvent::DataBlock { block, .. } => block.get_device_timestamp_usec(),
+        }
+    }
+}
+
+enum EventSource {
+    Captures,
+    ImuSamples,
+    DataBlocks(String),
+}
+
+impl EventSource {
+    fn read<'a>(
+        &self,
+        playback: &'a Playback<'a>,
+        forward: bool,
+    ) -> Result<Option<PlaybackEvent<'a>>, Error> {
+        let event = match self {
+            EventSource::Captures if forward => {
+                playback.get_next_capture().map(PlaybackEvent::Capture)
+            }
+            EventSource::Captures => playback.get_previous_capture().map(PlaybackEvent::Capture),
+            EventSource::ImuSamples if forward => {
+                playback.get_next_imu_sample().map(PlaybackEvent::ImuSample)
+            }
+            EventSource::ImuSamples => playback
+                .get_previous_imu_sample()
+                .map(PlaybackEvent::ImuSample),
+            EventSource::DataBlocks(track) => {
+                let block = if forward {
+                    playback.get_next_data_block(track)
+                } else {
+                    playback.get_previous_data_block(track)
+                };
+                block.map(|block| PlaybackEvent::DataBlock {
+                    track: track.clone(),
+                    block,
+                })
+            }
+        };
+        match event {
+            Ok(event) => Ok(Some(event)),
+            Err(Error::Eof) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Iterator over the captures, IMU samples and custom track data blocks of a recording in timestamp
+/// order, created by [`Playback::events`].
+pub struct PlaybackEvents<'a> {
+    playback: &'a Playback<'a>,
+    forward: bool,
+    //  Each source with the event it read ahead, opened on the first call to next.
+    sources: Option<Vec<(EventSource, Option<PlaybackEvent<'a>>)>>,
+    //  An error of the read ahead, returned after the event before it.
+    error: Option<Error>,
+    finished: bool,
+}
+
+impl<'a> PlaybackEvents<'a> {
+    fn open_sources(&self) -> Result<Vec<(EventSource, Option<PlaybackEvent<'a>>)>, Error> {
+        let configuration = self.playback.get_record_configuration()?;
+        let mut sources = Vec::new();
+        if configuration.color_track_enabled()
+            || configuration.depth_track_enabled()
+            || configuration.ir_track_enabled()
+        {
+            sources.push(EventSource::Captures);
+        }
+        if configuration.imu_track_enabled() {
+            sources.push(EventSource::ImuSamples);
+        }
+        for i in 0..self.playback.get_track_count() {
+            let track = self.playback.get_track(i)?;
+            if !track.is_builtin() {
+                sources.push(EventSource::DataBlocks(track.get_name().to_string()));
+            }
+        }
+        sources
+            .into_iter()
+            .map(|source| {
+                let event = source.read(self.playback, self.forward)?;
+                Ok((source, event))
+            })
+            .collect()
+    }
+}
+
+impl<'a> Iterator for PlaybackEvents<'a> {
+    type Item = Result<PlaybackEvent<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        if let Some(e) = self.error.take() {
+            self.finished = true;
+            return Some(Err(e));
+        }
+        if self.sources.is_none() {
+            match self.open_sources() {
+                Ok(sources) => self.sources = Some(sources),
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        let forward = self.forward;
+        let sources = self.sources.as_mut()?;
+
+        //  Events with the same timestamp are read in the order of the sources, or the reverse order
+        let heads = sources.iter().enumerate().filter_map(|(i, (_, event))| {
+            event
+                .as_ref()
+                .map(|event| (event.device_timestamp_usec(), i))
+        });
+        let index = if forward { heads.min() } else { heads.max() }.map(|(_, i)| i);
+        let index = match index {
+            Some(index) => index,
+            None => {
+                self.finished = true;
+                return None;
+            }
+        };
+        let (source, head) = &mut sources[index];
+        let event = head.take();
+        match source.read(self.playback, forward) {
+            Ok(next) => *head = next,
+            Err(e) => self.error = Some(e),
+        }
+        event.map(Ok)
     }
 }
 
@@ -0,0 +1,323 @@
+use crate::capture_source::{CaptureSource, ImuSource, SensorSource};
+use crate::playback::{Playback, RecordConfiguration};
+use crate::*;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Replays a recording like a live [`Camera`]: captures and IMU samples are returned when their
+/// timestamps are reached on the wall clock, and reads time out like `Camera::get_capture`.
+///
+/// Items are never dropped, a reader that falls behind gets them late. `Error::Eof` is returned at the
+/// end of the recording, unless it loops. When it loops, the timestamps of the following passes are
+/// shifted to keep increasing like those of a device, and the items a reader had not read by the end
+/// of a pass are skipped.
+///
+/// The playback is only used with the state locked, so captures and IMU samples can be read and the
+/// replay paused from several threads.
+pub struct PlaybackCamera<'a> {
+    factory: &'a FactoryRecord,
+    configuration: RecordConfiguration,
+    //  Duration of one pass, from the start of the recording to one frame after its last timestamp.
+    pass_usec: u64,
+    state: Mutex<State<'a>>,
+    changed: Condvar,
+}
+
+/// Replay time of the recording, which runs with the wall clock at a speed unless paused.
+struct Clock {
+    instant: Instant,
+    usec: f64,
+    speed: f64,
+    paused: bool,
+}
+
+impl Clock {
+    fn usec_at(&self, now: Instant) -> f64 {
+        if self.paused {
+            self.usec
+        } else {
+            self.usec + now.saturating_duration_since(self.instant).as_secs_f64() * 1e6 * self.speed
+        }
+    }
+
+    /// Wall clock time at which the replay reaches `usec`, None while paused.
+    fn instant_of(&self, usec: u64) -> Option<Instant> {
+        if self.paused {
+            return None;
+        }
+        let wait_usec = (usec as f64 - self.usec).max(0.0) / self.speed;
+        Some(self.instant + Duration::from_secs_f64(wait_usec / 1e6))
+    }
+
+    fn rebase(&mut self, now: Instant) {
+        self.usec = self.usec_at(now);
+        self.instant = now;
+    }
+}
+
+/// The next item of a stream with its replay time and the shift of its timestamps.
+struct Stream<T> {
+    next: Option<(T, u64, u64)>,
+    end: bool,
+}
+
+impl<T> Stream<T> {
+    fn new() -> Stream<T> {
+        Stream {
+            next: None,
+            end: false,
+        }
+    }
+}
+
+struct State<'a> {
+    playback: Playback<'a>,
+    clock: Clock,
+    //  The clock starts at the first item read, so that a playback can be replayed from a seek position.
+    started: bool,
+    looping: bool,
+    //  Number of times the playback restarted from the beginning.
+    pass: u64,
+    captures: Stream<Capture<'a>>,
+    imu_samples: Stream<ImuSample>,
+}
+
+impl<'a> PlaybackCamera<'a> {
+    /// Replays `playback` from its current position at normal speed, without looping. The replay starts
+    /// with the first read.
+    pub fn new(playback: Playback<'a>) -> Result<PlaybackCamera<'a>, Error> {
+        let configuration = playback.get_record_configuration()?;
+        let period_usec = 1_000_000 / configuration.camera_fps().get_u32() as u64;
+        Ok(PlaybackCamera {
+            factory: playback.factory,
+            configuration: RecordConfiguration {
+                value: configuration.value,
+            },
+            pass_usec: playback.get_recording_length_usec() + period_usec,
+            state: Mutex::new(State {
+                playback,
+                clock: Clock {
+                    instant: Instant::now(),
+                    usec: 0.0,
+                    speed: 1.0,
+                    paused: false,
+                },
+                started: false,
+                looping: false,
+                pass: 0,
+                captures: Stream::new(),
+                imu_samples: Stream::new(),
+            }),
+            changed: Condvar::new(),
+        })
+    }
+
+    /// Gets the configuration of the recording.
+    pub fn get_record_configuration(&self) -> &RecordConfiguration {
+        &self.configuration
+    }
+
+    /// Sets the replay speed, e.g. 2.0 replays twice as fast as recorded.
+    pub fn set_speed(&self, speed: f64) {
+        assert!(speed > 0.0, "the speed must be positive");
+        self.update(|clock| clock.speed = speed);
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.lock().clock.speed
+    }
+
+    /// Sets whether the replay restarts from the beginning at the end of the recording.
+    pub fn set_looping(&self, looping: bool) {
+        self.lock().looping = looping;
+        self.changed.notify_all();
+    }
+
+    /// Pauses the replay, reads wait until it is resumed or they time out.
+    pub fn pause(&self) {
+        self.update(|clock| clock.paused = true);
+    }
+
+    pub fn resume(&self) {
+        self.update(|clock| clock.paused = false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.lock().clock.paused
+    }
+
+    /// Stops the replay and returns the playback, positioned after the items read ahead.
+    pub fn into_playback(self) -> Playback<'a> {
+        self.state.into_inner().unwrap().playback
+    }
+
+    /// Reads the next capture once its timestamp is reached, see [`Camera::get_capture`].
+    pub fn get_capture(&self, timeout_in_ms: i32) -> Result<Capture<'_>, Error> {
+        let start_usec = self.configuration.start_timestamp_offset_usec() as u64;
+        self.read(
+            timeout_in_ms,
+            |state| &mut state.captures,
+            |state| {
+                //  The capture is kept in the state, it only borrows the Api of the factory
+                let capture = state.playback.get_next_capture()?;
+                let handle = capture.handle;
+                std::mem::forget(capture);
+                let capture = Capture::from_handle(self.factory.core().api(), handle);
+                let usec = capture
+                    .get_device_timestamp_usec()
+                    .map_or(0, |t| t.saturating_sub(start_usec));
+                Ok((capture, usec))
+            },
+            |capture, shift_usec| {
+                for mut image in [
+                    capture.get_color_image(),
+                    capture.get_depth_image(),
+                    capture.get_ir_image(),
+                ] {
+                    if !image.handle.is_null() {
+                        //  The capture was just read, it is not returned before it is shifted
+                        unsafe {
+                            image.set_device_timestamp_usec(
+                                image.get_device_timestamp_usec() + shift_usec,
+                            );
+                        }
+                    }
+                }
+            },
+        )
+    }
+
+    /// Reads the next IMU sample once its timestamp is reached, see [`Imu::get_imu_sample`].
+    pub fn get_imu_sample(&self, timeout_in_ms: i32) -> Result<ImuSample, Error> {
+        let start_usec = self.configuration.start_timestamp_offset_usec() as u64;
+        self.read(
+            timeout_in_ms,
+            |state| &mut state.imu_samples,
+            |state| {
+                let sample = state.playback.get_next_imu_sample()?;
+                let usec = sample.acc_timestamp_usec().saturating_sub(start_usec);
+                Ok((sample, usec))
+            },
+            |sample, shift_usec| {
+                sample.set_acc_timestamp_usec(sample.acc_timestamp_usec() + shift_usec);
+                sample.set_gyro_timestamp_usec(sample.gyro_timestamp_usec() + shift_usec);
+            },
+        )
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<'a>> {
+        self.state.lock().unwrap()
+    }
+
+    fn update<F: FnOnce(&mut Clock)>(&self, f: F) {
+        let mut state = self.lock();
+        state.clock.rebase(Instant::now());
+        f(&mut state.clock);
+        self.changed.notify_all();
+    }
+
+    /// Waits until the next item of a stream is due. At the end of a looping recording, waits until the
+    /// end of the pass and restarts the playback.
+    fn read<T>(
+        &self,
+        timeout_in_ms: i32,
+        stream: for<'s> fn(&'s mut State<'a>) -> &'s mut Stream<T>,
+        read_next: impl Fn(&mut State<'a>) -> Result<(T, u64), Error>,
+        shift: impl Fn(&mut T, u64),
+    ) -> Result<T, Error> {
+        let deadline = if timeout_in_ms < 0 {
+            None
+        } else {
+            Some(Instant::now() + Duration::from_millis(timeout_in_ms as u64))
+        };
+        let mut state = self.lock();
+        loop {
+            if stream(&mut state).next.is_none() && !stream(&mut state).end {
+                match read_next(&mut state) {
+                    Ok((item, usec)) => {
+                        if !state.started {
+                            state.started = true;
+                            state.clock.instant = Instant::now();
+                            state.clock.usec = usec as f64;
+                        }
+                        let shift_usec = state.pass * self.pass_usec;
+                        stream(&mut state).next = Some((item, usec + shift_usec, shift_usec));
+                    }
+                    Err(Error::Eof) => stream(&mut state).end = true,
+                    Err(e) => return Err(e),
+                }
+            }
+            let next_usec = stream(&mut state).next.as_ref().map(|(_, usec, _)| *usec);
+            let due_usec = match next_usec {
+                Some(usec) => usec,
+                None if state.looping => (state.pass + 1) * self.pass_usec,
+                None => return Err(Error::Eof),
+            };
+
+            let now = Instant::now();
+            if state.clock.usec_at(now) >= due_usec as f64 {
+                match stream(&mut state).next.take() {
+                    Some((mut item, _, shift_usec)) => {
+                        if shift_usec > 0 {
+                            shift(&mut item, shift_usec);
+                        }
+                        return Ok(item);
+                    }
+                    None => {
+                        state.playback.seek_timestamp(0, SeekOrigin::Begin)?;
+                        state.pass += 1;
+                        state.captures.end = false;
+                        state.imu_samples.end = false;
+                        self.changed.notify_all();
+                        continue;
+                    }
+                }
+            }
+
+            if deadline.is_some_and(|deadline| now >= deadline) {
+                return Err(Error::Timeout);
+            }
+            let wake = match (state.clock.instant_of(due_usec), deadline) {
+                (Some(due), Some(deadline)) => Some(due.min(deadline)),
+                (due, deadline) => due.or(deadline),
+            };
+            state = match wake {
+                Some(wake) => {
+                    let wait = wake.saturating_duration_since(now);
+                    self.changed.wait_timeout(state, wait).unwrap().0
+                }
+                None => self.changed.wait(state).unwrap(),
+            };
+        }
+    }
+}
+
+impl CaptureSource for PlaybackCamera<'_> {
+    fn get_capture(&self, timeout_in_ms: i32) -> Result<Capture<'_>, Error> {
+        PlaybackCamera::get_capture(self, timeout_in_ms)
+    }
+}
+
+impl ImuSource for PlaybackCamera<'_> {
+    fn get_imu_sample(&self, timeout_in_ms: i32) -> Result<ImuSample, Error> {
+        PlaybackCamera::get_imu_sample(self, timeout_in_ms)
+    }
+}
+
+impl SensorSource for PlaybackCamera<'_> {
+    fn get_calibration(&self) -> Result<Calibration<'_>, Error> {
+        let calibration = self.lock().playback.get_calibration()?.calibration;
+        Ok(Calibration::from_handle(
+            self.factory.core().api(),
+            calibration,
+        ))
+    }
+
+    fn get_device_configuration(&self) -> Result<DeviceConfiguration, Error> {
+        Ok(self.configuration.device_configuration())
+    }
+
+    fn get_serial_number(&self) -> Result<String, Error> {
+        self.lock().playback.get_tag("K4A_DEVICE_SERIAL_NUMBER")
+    }
+}
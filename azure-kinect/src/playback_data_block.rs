@@ -6,11 +6,15 @@ pub struct PlaybackDataBlock<'a> {
     handle: k4a_playback_data_block_t,
 }
 
+//  A data block is a copy owned by the caller, independent of the playback.
+unsafe impl Send for PlaybackDataBlock<'_> {}
+unsafe impl Sync for PlaybackDataBlock<'_> {}
+
 impl PlaybackDataBlock<'_> {
     pub fn from_handle(
         api_record: &azure_kinect_sys::api::ApiRecord,
         handle: k4a_playback_data_block_t,
-    ) -> PlaybackDataBlock {
+    ) -> PlaybackDataBlock<'_> {
         PlaybackDataBlock { api_record, handle }
     }
 
@@ -30,7 +34,7 @@ impl PlaybackDataBlock<'_> {
             (self
                 .api_record
                 .funcs
-                .k4a_playback_data_block_get_buffer_size)(self.handle) as usize
+                .k4a_playback_data_block_get_buffer_size)(self.handle)
         }
     }
 
@@ -38,6 +42,15 @@ impl PlaybackDataBlock<'_> {
     pub fn get_buffer(&self) -> *const u8 {
         unsafe { (self.api_record.funcs.k4a_playback_data_block_get_buffer)(self.handle) }
     }
+
+    /// Get the data_block buffer as a byte slice
+    pub fn as_bytes(&self) -> &[u8] {
+        let buffer = self.get_buffer();
+        if buffer.is_null() {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(buffer, self.get_buffer_size()) }
+    }
 }
 
 impl Drop for PlaybackDataBlock<'_> {
@@ -47,52 +47,64 @@ impl<'a> PlaybackTrack<'a> {
     /// Gets the video-specific track information for a particular video track.
     pub fn get_video_settings(&self) -> Result<RecordVideoSettings, Error> {
         let mut settings = k4a_record_video_settings_t::default();
-        Error::from_k4a_result_t(unsafe {
-            (self
-                .playback
-                .factory
-                .api_record()
-                .funcs
-                .k4a_playback_track_get_video_settings)(
-                self.playback.handle,
-                self.name.as_ptr(),
-                &mut settings,
-            )
-        })
-        .to_result(RecordVideoSettings { value: settings })
+        ErrorContext::new("k4a_playback_track_get_video_settings")
+            .arg("track", self.get_name())
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self
+                        .playback
+                        .factory
+                        .api_record()
+                        .funcs
+                        .k4a_playback_track_get_video_settings)(
+                        self.playback.handle,
+                        self.name.as_ptr(),
+                        &mut settings,
+                    )
+                })
+                .to_result(RecordVideoSettings { value: settings })
+            })
     }
 
     /// Gets the codec id string for a particular track.
     pub fn get_codec_id(&self) -> Result<String, Error> {
-        get_k4a_string(&|codec_id, codec_id_size| unsafe {
-            (self
-                .playback
-                .factory
-                .api_record()
-                .funcs
-                .k4a_playback_track_get_codec_id)(
-                self.playback.handle,
-                self.name.as_ptr(),
-                codec_id,
-                codec_id_size,
-            )
-        })
+        ErrorContext::new("k4a_playback_track_get_codec_id")
+            .arg("track", self.get_name())
+            .call(|| {
+                get_k4a_string(&|codec_id, codec_id_size| unsafe {
+                    (self
+                        .playback
+                        .factory
+                        .api_record()
+                        .funcs
+                        .k4a_playback_track_get_codec_id)(
+                        self.playback.handle,
+                        self.name.as_ptr(),
+                        codec_id,
+                        codec_id_size,
+                    )
+                })
+            })
     }
 
     /// Gets the codec context for a particular track.
     pub fn get_codec_context(&self) -> Result<Vec<u8>, Error> {
-        get_k4a_binary_data(&|codec_context, codec_context_size| unsafe {
-            (self
-                .playback
-                .factory
-                .api_record()
-                .funcs
-                .k4a_playback_track_get_codec_context)(
-                self.playback.handle,
-                self.name.as_ptr(),
-                codec_context,
-                codec_context_size,
-            )
-        })
+        ErrorContext::new("k4a_playback_track_get_codec_context")
+            .arg("track", self.get_name())
+            .call(|| {
+                get_k4a_binary_data(&|codec_context, codec_context_size| unsafe {
+                    (self
+                        .playback
+                        .factory
+                        .api_record()
+                        .funcs
+                        .k4a_playback_track_get_codec_context)(
+                        self.playback.handle,
+                        self.name.as_ptr(),
+                        codec_context,
+                        codec_context_size,
+                    )
+                })
+            })
     }
 }
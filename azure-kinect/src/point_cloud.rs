@@ -0,0 +1,349 @@
+//  Point clouds from the xyz images of `depth_image_to_point_cloud`, and writers for the file
+//  formats read by MeshLab, CloudCompare and PCL.
+
+use crate::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// File formats of [`PointCloud::write`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PointCloudFormat {
+    /// Polygon File Format, ASCII
+    PlyAscii,
+    /// Polygon File Format, binary little endian
+    PlyBinary,
+    /// PCL Point Cloud Data, ASCII
+    PcdAscii,
+    /// PCL Point Cloud Data, binary
+    PcdBinary,
+    /// One point per line, `x y z` or `x y z r g b`
+    Xyz,
+}
+
+/// Points in millimeters, without the pixels which have no depth.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PointCloud {
+    pub positions: Vec<[f32; 3]>,
+    /// Colors as `[r, g, b]`, one per position
+    pub colors: Option<Vec<[u8; 3]>>,
+}
+
+//  The parts of Image and OwnedImage needed to read the pixels
+struct PixelBuffer<'a> {
+    format: ImageFormat,
+    width: usize,
+    height: usize,
+    stride: usize,
+    bytes: &'a [u8],
+}
+
+impl<'a> PixelBuffer<'a> {
+    fn from_image(image: &'a Image) -> PixelBuffer<'a> {
+        PixelBuffer {
+            format: image.get_format(),
+            width: image.get_width_pixels().max(0) as usize,
+            height: image.get_height_pixels().max(0) as usize,
+            stride: image.get_stride_bytes().max(0) as usize,
+            bytes: image.as_bytes(),
+        }
+    }
+
+    fn from_owned_image(image: &'a OwnedImage) -> PixelBuffer<'a> {
+        PixelBuffer {
+            format: image.get_format(),
+            width: image.get_width_pixels().max(0) as usize,
+            height: image.get_height_pixels().max(0) as usize,
+            stride: image.get_stride_bytes().max(0) as usize,
+            bytes: image.as_bytes(),
+        }
+    }
+
+    fn pixel(&self, x: usize, y: usize, size: usize) -> &[u8] {
+        let offset = y * self.stride + x * size;
+        &self.bytes[offset..offset + size]
+    }
+}
+
+impl PointCloud {
+    /// Creates a point cloud from the CUSTOM image of `Transformation::depth_image_to_point_cloud`,
+    /// colored by a BGRA32 image of the same camera, e.g. of `Transformation::color_image_to_depth_camera`.
+    pub fn from_image(xyz_image: &Image, color_image: Option<&Image>) -> Result<PointCloud, Error> {
+        PointCloud::from_buffers(
+            &PixelBuffer::from_image(xyz_image),
+            color_image.map(PixelBuffer::from_image).as_ref(),
+        )
+    }
+
+    /// Creates a point cloud from the CUSTOM image of `TransformationModel::depth_image_to_point_cloud`,
+    /// colored by a BGRA32 image of the same camera, e.g. of `TransformationModel::color_image_to_depth_camera`.
+    pub fn from_owned_image(
+        xyz_image: &OwnedImage,
+        color_image: Option<&OwnedImage>,
+    ) -> Result<PointCloud, Error> {
+        PointCloud::from_buffers(
+            &PixelBuffer::from_owned_image(xyz_image),
+            color_image.map(PixelBuffer::from_owned_image).as_ref(),
+        )
+    }
+
+    fn from_buffers(xyz: &PixelBuffer, color: Option<&PixelBuffer>) -> Result<PointCloud, Error> {
+        if xyz.format != ImageFormat::Custom {
+            return Err(Error::UnexpectedImageFormat(xyz.format));
+        }
+        if xyz.height > 0 && xyz.bytes.len() < xyz.stride * (xyz.height - 1) + xyz.width * 6 {
+            return Err(Error::TooSmall);
+        }
+        if let Some(color) = color {
+            if color.format != ImageFormat::BGRA32 {
+                return Err(Error::UnexpectedImageFormat(color.format));
+            }
+            if color.width != xyz.width || color.height != xyz.height {
+                return Err(Error::Failed);
+            }
+            if color.height > 0
+                && color.bytes.len() < color.stride * (color.height - 1) + color.width * 4
+            {
+                return Err(Error::TooSmall);
+            }
+        }
+
+        let mut point_cloud = PointCloud {
+            positions: Vec::new(),
+            colors: color.map(|_| Vec::new()),
+        };
+        for y in 0..xyz.height {
+            for x in 0..xyz.width {
+                let p = xyz.pixel(x, y, 6);
+                let v = |i: usize| i16::from_le_bytes([p[i * 2], p[i * 2 + 1]]) as f32;
+                if v(2) == 0.0 {
+                    continue;
+                }
+                point_cloud.positions.push([v(0), v(1), v(2)]);
+                if let (Some(colors), Some(color)) = (point_cloud.colors.as_mut(), color) {
+                    let bgra = color.pixel(x, y, 4);
+                    colors.push([bgra[2], bgra[1], bgra[0]]);
+                }
+            }
+        }
+        Ok(point_cloud)
+    }
+
+    /// Number of points
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Writes the point cloud to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: PointCloudFormat) -> Result<(), Error> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| {
+            ErrorContext::new("File::create")
+                .arg("path", path.display())
+                .with_kind(ErrorKind::Io)
+                .with_source(e)
+        })?;
+        let mut writer = BufWriter::new(file);
+        self.write(&mut writer, format)?;
+        Ok(writer.flush()?)
+    }
+
+    /// Writes the point cloud in a file format.
+    pub fn write<W: Write>(&self, writer: &mut W, format: PointCloudFormat) -> Result<(), Error> {
+        match format {
+            PointCloudFormat::PlyAscii | PointCloudFormat::PlyBinary => {
+                self.write_ply(writer, format == PointCloudFormat::PlyBinary)
+            }
+            PointCloudFormat::PcdAscii | PointCloudFormat::PcdBinary => {
+                self.write_pcd(writer, format == PointCloudFormat::PcdBinary)
+            }
+            PointCloudFormat::Xyz => self.write_xyz(writer),
+        }
+        .map_err(Error::from)
+    }
+
+    fn points(&self) -> impl Iterator<Item = (&[f32; 3], Option<&[u8; 3]>)> {
+        let colors = self.colors.iter().flatten().map(Some);
+        self.positions
+            .iter()
+            .zip(colors.chain(std::iter::repeat(None)))
+    }
+
+    fn write_ply<W: Write>(&self, writer: &mut W, binary: bool) -> std::io::Result<()> {
+        writeln!(writer, "ply")?;
+        if binary {
+            writeln!(writer, "format binary_little_endian 1.0")?;
+        } else {
+            writeln!(writer, "format ascii 1.0")?;
+        }
+        writeln!(writer, "element vertex {}", self.len())?;
+        for axis in &["x", "y", "z"] {
+            writeln!(writer, "property float {}", axis)?;
+        }
+        if self.colors.is_some() {
+            for channel in &["red", "green", "blue"] {
+                writeln!(writer, "property uchar {}", channel)?;
+            }
+        }
+        writeln!(writer, "end_header")?;
+        for (p, c) in self.points() {
+            if binary {
+                for v in p {
+                    writer.write_all(&v.to_le_bytes())?;
+                }
+                if let Some(c) = c {
+                    writer.write_all(c)?;
+                }
+            } else {
+                write!(writer, "{} {} {}", p[0], p[1], p[2])?;
+                if let Some(c) = c {
+                    write!(writer, " {} {} {}", c[0], c[1], c[2])?;
+                }
+                writeln!(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_pcd<W: Write>(&self, writer: &mut W, binary: bool) -> std::io::Result<()> {
+        //  Colors are packed as 0x00RRGGBB into the "rgb" field, as PCL does
+        let colored = self.colors.is_some();
+        writeln!(writer, "# .PCD v0.7 - Point Cloud Data file format")?;
+        writeln!(writer, "VERSION 0.7")?;
+        if colored {
+            writeln!(writer, "FIELDS x y z rgb")?;
+            writeln!(writer, "SIZE 4 4 4 4")?;
+            writeln!(writer, "TYPE F F F U")?;
+            writeln!(writer, "COUNT 1 1 1 1")?;
+        } else {
+            writeln!(writer, "FIELDS x y z")?;
+            writeln!(writer, "SIZE 4 4 4")?;
+            writeln!(writer, "TYPE F F F")?;
+            writeln!(writer, "COUNT 1 1 1")?;
+        }
+        writeln!(writer, "WIDTH {}", self.len())?;
+        writeln!(writer, "HEIGHT 1")?;
+        writeln!(writer, "VIEWPOINT 0 0 0 1 0 0 0")?;
+        writeln!(writer, "POINTS {}", self.len())?;
+        writeln!(writer, "DATA {}", if binary { "binary" } else { "ascii" })?;
+        for (p, c) in self.points() {
+            let rgb = c.map(|c| (c[0] as u32) << 16 | (c[1] as u32) << 8 | c[2] as u32);
+            if binary {
+                for v in p {
+                    writer.write_all(&v.to_le_bytes())?;
+                }
+                if let Some(rgb) = rgb {
+                    writer.write_all(&rgb.to_le_bytes())?;
+                }
+            } else {
+                write!(writer, "{} {} {}", p[0], p[1], p[2])?;
+                if let Some(rgb) = rgb {
+                    write!(writer, " {}", rgb)?;
+                }
+                writeln!(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_xyz<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        for (p, c) in self.points() {
+            write!(writer, "{} {} {}", p[0], p[1], p[2])?;
+            if let Some(c) = c {
+                write!(writer, " {} {} {}", c[0], c[1], c[2])?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //  2x2 pixels, the second pixel has no depth
+    fn xyz_image() -> OwnedImage {
+        let mut buffer = Vec::new();
+        for xyz in &[[1i16, 2, 3], [0, 0, 0], [-4, 5, 6], [7, -8, 900]] {
+            for v in xyz {
+                buffer.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        OwnedImage::new(ImageFormat::Custom, 2, 2, 12, buffer).unwrap()
+    }
+
+    fn color_image() -> OwnedImage {
+        let buffer = vec![
+            10, 20, 30, 255, 0, 0, 0, 0, 40, 50, 60, 255, 70, 80, 90, 255,
+        ];
+        OwnedImage::new(ImageFormat::BGRA32, 2, 2, 8, buffer).unwrap()
+    }
+
+    fn to_string(point_cloud: &PointCloud, format: PointCloudFormat) -> String {
+        let mut out = Vec::new();
+        point_cloud.write(&mut out, format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_from_image() {
+        let point_cloud = PointCloud::from_owned_image(&xyz_image(), None).unwrap();
+        assert_eq!(
+            point_cloud.positions,
+            vec![[1.0, 2.0, 3.0], [-4.0, 5.0, 6.0], [7.0, -8.0, 900.0]]
+        );
+        assert!(point_cloud.colors.is_none());
+
+        let point_cloud = PointCloud::from_owned_image(&xyz_image(), Some(&color_image())).unwrap();
+        assert_eq!(
+            point_cloud.colors,
+            Some(vec![[30, 20, 10], [60, 50, 40], [90, 80, 70]])
+        );
+
+        assert!(PointCloud::from_owned_image(&color_image(), None).is_err());
+        let small = OwnedImage::new(ImageFormat::BGRA32, 1, 1, 4, vec![0; 4]).unwrap();
+        assert!(PointCloud::from_owned_image(&xyz_image(), Some(&small)).is_err());
+    }
+
+    #[test]
+    fn test_write_ascii() {
+        let point_cloud = PointCloud::from_owned_image(&xyz_image(), Some(&color_image())).unwrap();
+        assert_eq!(
+            to_string(&point_cloud, PointCloudFormat::Xyz),
+            "1 2 3 30 20 10\n-4 5 6 60 50 40\n7 -8 900 90 80 70\n"
+        );
+        let ply = to_string(&point_cloud, PointCloudFormat::PlyAscii);
+        assert!(ply.starts_with("ply\nformat ascii 1.0\nelement vertex 3\n"));
+        assert!(ply.ends_with("end_header\n1 2 3 30 20 10\n-4 5 6 60 50 40\n7 -8 900 90 80 70\n"));
+        let pcd = to_string(&point_cloud, PointCloudFormat::PcdAscii);
+        assert!(pcd.contains("FIELDS x y z rgb\n"));
+        assert!(pcd
+            .ends_with("POINTS 3\nDATA ascii\n1 2 3 1971210\n-4 5 6 3945000\n7 -8 900 5918790\n"));
+    }
+
+    #[test]
+    fn test_write_binary() {
+        let point_cloud = PointCloud::from_owned_image(&xyz_image(), None).unwrap();
+        let mut out = Vec::new();
+        point_cloud
+            .write(&mut out, PointCloudFormat::PlyBinary)
+            .unwrap();
+        let header = b"end_header\n";
+        let start = out.windows(header.len()).position(|w| w == header).unwrap() + header.len();
+        assert_eq!(out.len() - start, 3 * 12);
+        assert_eq!(&out[start + 12..start + 16], &(-4.0f32).to_le_bytes());
+
+        let mut out = Vec::new();
+        point_cloud
+            .write(&mut out, PointCloudFormat::PcdBinary)
+            .unwrap();
+        let header = b"DATA binary\n";
+        assert!(out.windows(header.len()).any(|w| w == header));
+        assert_eq!(&out[out.len() - 4..], &900.0f32.to_le_bytes());
+    }
+}
@@ -57,50 +57,71 @@ pub struct Record<'a> {
     pub(crate) handle: k4a_record_t,
 }
 
+//  k4arecord handles are not thread safe, a recording can be moved to another thread but not shared.
+unsafe impl Send for Record<'_> {}
+
 impl Record<'_> {
     pub fn from_handle(
         api_record: &azure_kinect_sys::api::ApiRecord,
         handle: k4a_record_t,
-    ) -> Record {
+    ) -> Record<'_> {
         Record { api_record, handle }
     }
 
     /// Flushes all pending recording data to disk
     pub fn flush(&self) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe { (self.api_record.funcs.k4a_record_flush)(self.handle) })
+        ErrorContext::new("k4a_record_flush").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (self.api_record.funcs.k4a_record_flush)(self.handle)
+            })
             .to_result(())
+        })
     }
 
     /// Adds a tag to the recording
     pub fn add_tag(&self, name: &str, value: &str) -> Result<(), Error> {
         let name = CString::new(name).unwrap_or_default();
         let value = CString::new(value).unwrap_or_default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record.funcs.k4a_record_add_tag)(self.handle, name.as_ptr(), value.as_ptr())
-        })
-        .to_result(())
+        ErrorContext::new("k4a_record_add_tag")
+            .arg("name", name.to_string_lossy())
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api_record.funcs.k4a_record_add_tag)(
+                        self.handle,
+                        name.as_ptr(),
+                        value.as_ptr(),
+                    )
+                })
+                .to_result(())
+            })
     }
 
     /// Adds the track header for recording IMU
     pub fn add_imu_track(&self) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record.funcs.k4a_record_add_imu_track)(self.handle)
+        ErrorContext::new("k4a_record_add_imu_track").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (self.api_record.funcs.k4a_record_add_imu_track)(self.handle)
+            })
+            .to_result(())
         })
-        .to_result(())
     }
 
     /// Adds an attachment to the recording
     pub fn add_attachment(&self, attachment_name: &str, buffer: &[u8]) -> Result<(), Error> {
         let attachment_name = CString::new(attachment_name).unwrap_or_default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record.funcs.k4a_record_add_attachment)(
-                self.handle,
-                attachment_name.as_ptr(),
-                buffer.as_ptr(),
-                buffer.len(),
-            )
-        })
-        .to_result(())
+        ErrorContext::new("k4a_record_add_attachment")
+            .arg("attachment_name", attachment_name.to_string_lossy())
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api_record.funcs.k4a_record_add_attachment)(
+                        self.handle,
+                        attachment_name.as_ptr(),
+                        buffer.as_ptr(),
+                        buffer.len(),
+                    )
+                })
+                .to_result(())
+            })
     }
 
     /// Adds custom video tracks to the recording
@@ -113,17 +134,22 @@ impl Record<'_> {
     ) -> Result<(), Error> {
         let track_name = CString::new(track_name).unwrap_or_default();
         let codec_id = CString::new(codec_id).unwrap_or_default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record.funcs.k4a_record_add_custom_video_track)(
-                self.handle,
-                track_name.as_ptr(),
-                codec_id.as_ptr(),
-                codec_context.as_ptr(),
-                codec_context.len(),
-                &track_settings.value,
-            )
-        })
-        .to_result(())
+        ErrorContext::new("k4a_record_add_custom_video_track")
+            .arg("track_name", track_name.to_string_lossy())
+            .arg("codec_id", codec_id.to_string_lossy())
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api_record.funcs.k4a_record_add_custom_video_track)(
+                        self.handle,
+                        track_name.as_ptr(),
+                        codec_id.as_ptr(),
+                        codec_context.as_ptr(),
+                        codec_context.len(),
+                        &track_settings.value,
+                    )
+                })
+                .to_result(())
+            })
     }
 
     /// Adds custom subtitle tracks to the recording
@@ -136,44 +162,58 @@ impl Record<'_> {
     ) -> Result<(), Error> {
         let track_name = CString::new(track_name).unwrap_or_default();
         let codec_id = CString::new(codec_id).unwrap_or_default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record.funcs.k4a_record_add_custom_subtitle_track)(
-                self.handle,
-                track_name.as_ptr(),
-                codec_id.as_ptr(),
-                codec_context.as_ptr(),
-                codec_context.len(),
-                &track_settings.value,
-            )
-        })
-        .to_result(())
+        ErrorContext::new("k4a_record_add_custom_subtitle_track")
+            .arg("track_name", track_name.to_string_lossy())
+            .arg("codec_id", codec_id.to_string_lossy())
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api_record.funcs.k4a_record_add_custom_subtitle_track)(
+                        self.handle,
+                        track_name.as_ptr(),
+                        codec_id.as_ptr(),
+                        codec_context.as_ptr(),
+                        codec_context.len(),
+                        &track_settings.value,
+                    )
+                })
+                .to_result(())
+            })
     }
 
     /// Writes the recording header and metadata to file
     pub fn write_header(&self) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record.funcs.k4a_record_write_header)(self.handle)
+        ErrorContext::new("k4a_record_write_header").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (self.api_record.funcs.k4a_record_write_header)(self.handle)
+            })
+            .to_result(())
         })
-        .to_result(())
     }
 
     /// Writes a camera capture to file
     pub fn write_capture(&self, capture: &Capture) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record.funcs.k4a_record_write_capture)(self.handle, capture.handle as _)
+        ErrorContext::new("k4a_record_write_capture").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (self.api_record.funcs.k4a_record_write_capture)(self.handle, capture.handle as _)
+            })
+            .to_result(())
         })
-        .to_result(())
     }
 
     /// Writes an imu sample to file
     pub fn write_imu_sample(&self, imu_sample: &ImuSample) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record.funcs.k4a_record_write_imu_sample)(
-                self.handle,
-                std::mem::transmute::<k4a_imu_sample_t, _>(imu_sample.value),
-            )
+        ErrorContext::new("k4a_record_write_imu_sample").call(|| {
+            Error::from_k4a_result_t(unsafe {
+                (self.api_record.funcs.k4a_record_write_imu_sample)(
+                    self.handle,
+                    std::mem::transmute::<
+                        k4a_imu_sample_t,
+                        azure_kinect_sys::k4arecord::k4a_imu_sample_t,
+                    >(imu_sample.value),
+                )
+            })
+            .to_result(())
         })
-        .to_result(())
     }
 
     pub fn write_custom_track_data(
@@ -183,16 +223,21 @@ impl Record<'_> {
         custom_data: &[u8],
     ) -> Result<(), Error> {
         let track_name = CString::new(track_name).unwrap_or_default();
-        Error::from_k4a_result_t(unsafe {
-            (self.api_record.funcs.k4a_record_write_custom_track_data)(
-                self.handle,
-                track_name.as_ptr(),
-                device_timestamp_usec,
-                custom_data.as_ptr() as *mut u8,
-                custom_data.len(),
-            )
-        })
-        .to_result(())
+        ErrorContext::new("k4a_record_write_custom_track_data")
+            .arg("track_name", track_name.to_string_lossy())
+            .arg("device_timestamp_usec", device_timestamp_usec)
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self.api_record.funcs.k4a_record_write_custom_track_data)(
+                        self.handle,
+                        track_name.as_ptr(),
+                        device_timestamp_usec,
+                        custom_data.as_ptr() as *mut u8,
+                        custom_data.len(),
+                    )
+                })
+                .to_result(())
+            })
     }
 }
 
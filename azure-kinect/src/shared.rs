@@ -0,0 +1,130 @@
+//! [`SharedDevice`], [`SharedCamera`] and [`SharedCapture`] keep the [`Factory`] alive with an `Arc`
+//! instead of borrowing it, so that they have no lifetime parameter and can be moved into threads,
+//! channels and async tasks. `Image`, `Imu` and `Playback` have no owned handle: images are read
+//! from the [`SharedCapture`] they belong to, the IMU is started from [`SharedDevice::device`].
+//!
+//! Thread safety of the handles, following the SDK:
+//! * `Factory`, `FactoryRecord`, `Device`, `Capture` and `Calibration` are `Send` and `Sync`.
+//!   The k4a device functions are thread safe, captures are reference counted.
+//! * `Image` is `Send` only. Clones of an image or a capture share one SDK handle, so writing its
+//!   buffer or its metadata is `unsafe` whatever the thread.
+//! * `Camera` and `Imu` are `Send` and `Sync` as they only borrow the `Device`.
+//! * `Transformation`, `Playback` and `Record` are `Send` only, their handles must not be used by
+//!   several threads at once.
+//! * `PlaybackCamera` is `Send` and `Sync`, it locks its playback.
+//!
+//! ```ignore
+//! let factory = Arc::new(Factory::new()?);
+//! let device = SharedDevice::open(&factory, 0)?;
+//! let camera = device.start_cameras(&DeviceConfiguration::default())?;
+//! let (sender, receiver) = std::sync::mpsc::channel();
+//! std::thread::spawn(move || {
+//!     while let Ok(capture) = camera.get_capture(1000) {
+//!         sender.send(capture).unwrap();
+//!     }
+//! });
+//! ```
+
+use crate::*;
+use std::sync::Arc;
+
+//  The Api lives as long as the Factory in the Arc, which is kept next to the handles using it.
+//  Fields are declared before the Arc so that the handles are dropped first.
+unsafe fn extend_api(factory: &Arc<Factory>) -> &'static azure_kinect_sys::api::Api {
+    &*(factory.api() as *const azure_kinect_sys::api::Api)
+}
+
+/// A [`Device`] that keeps its [`Factory`] alive.
+pub struct SharedDevice {
+    device: Device<'static>,
+    factory: Arc<Factory>,
+}
+
+impl SharedDevice {
+    /// Opens a k4a device.
+    pub fn open(factory: &Arc<Factory>, index: u32) -> Result<Arc<SharedDevice>, Error> {
+        let device = factory.device_open(index)?;
+        let handle = device.handle;
+        std::mem::forget(device);
+        Ok(Arc::new(SharedDevice {
+            device: Device::from_handle(unsafe { extend_api(factory) }, handle),
+            factory: factory.clone(),
+        }))
+    }
+
+    pub fn device(&self) -> &Device<'_> {
+        &self.device
+    }
+
+    pub fn factory(&self) -> &Arc<Factory> {
+        &self.factory
+    }
+
+    /// Starts the K4A device's cameras, they are stopped when the returned camera is dropped.
+    pub fn start_cameras(
+        self: &Arc<Self>,
+        configuration: &DeviceConfiguration,
+    ) -> Result<SharedCamera, Error> {
+        //  The Device stays at the same address in the Arc
+        let device = unsafe { &*(&self.device as *const Device<'static>) };
+        Ok(SharedCamera {
+            camera: device.start_cameras(configuration)?,
+            device: self.clone(),
+        })
+    }
+}
+
+/// A [`Camera`] that keeps its device alive.
+pub struct SharedCamera {
+    camera: Camera<'static>,
+    device: Arc<SharedDevice>,
+}
+
+impl SharedCamera {
+    pub fn camera(&self) -> &Camera<'_> {
+        &self.camera
+    }
+
+    pub fn device(&self) -> &Arc<SharedDevice> {
+        &self.device
+    }
+
+    /// Reads a sensor capture, see [`Camera::get_capture`].
+    pub fn get_capture(&self, timeout_in_ms: i32) -> Result<SharedCapture, Error> {
+        let capture = self.camera.get_capture(timeout_in_ms)?;
+        Ok(SharedCapture::new(self.device.factory(), capture))
+    }
+}
+
+/// A [`Capture`] that keeps its [`Factory`] alive.
+pub struct SharedCapture {
+    capture: Capture<'static>,
+    factory: Arc<Factory>,
+}
+
+impl SharedCapture {
+    /// Takes a capture created by the Api of `factory`.
+    pub fn new(factory: &Arc<Factory>, capture: Capture) -> SharedCapture {
+        assert!(std::ptr::eq(capture.api, factory.api()));
+        let handle = capture.handle;
+        std::mem::forget(capture);
+        SharedCapture {
+            capture: Capture::from_handle(unsafe { extend_api(factory) }, handle),
+            factory: factory.clone(),
+        }
+    }
+
+    pub fn capture(&self) -> &Capture<'_> {
+        &self.capture
+    }
+
+    pub fn factory(&self) -> &Arc<Factory> {
+        &self.factory
+    }
+}
+
+impl Clone for SharedCapture {
+    fn clone(&self) -> Self {
+        SharedCapture::new(&self.factory, self.capture.clone())
+    }
+}
@@ -86,3 +86,123 @@ impl Display for HardwareVersion {
             self.rgb(), self.depth(), self.audio(), self.depth_sensor(), self.firmware_build(), self.firmware_signature())
     }
 }
+
+#[doc = " Intrinsic parameters of a camera, in pixels for the mode specific resolution."]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct IntrinsicParameters {
+    pub cx: f32,
+    pub cy: f32,
+    pub fx: f32,
+    pub fy: f32,
+    pub k1: f32,
+    pub k2: f32,
+    pub k3: f32,
+    pub k4: f32,
+    pub k5: f32,
+    pub k6: f32,
+    pub codx: f32,
+    pub cody: f32,
+    pub p1: f32,
+    pub p2: f32,
+}
+
+#[doc = " Intrinsic calibration of the depth or color camera."]
+#[derive(Copy, Clone)]
+pub struct Intrinsics {
+    pub(crate) value: k4a_calibration_camera_t,
+}
+
+impl Intrinsics {
+    #[doc = "< Type of calibration model used"]
+    pub fn model_type(&self) -> CalibrationModelType {
+        CalibrationModelType::from_primitive(self.value.intrinsics.type_)
+    }
+    #[doc = "< Resolution width of the calibration sensor."]
+    pub fn resolution_width(&self) -> i32 {
+        self.value.resolution_width
+    }
+    #[doc = "< Resolution height of the calibration sensor."]
+    pub fn resolution_height(&self) -> i32 {
+        self.value.resolution_height
+    }
+    #[doc = "< Calibration parameters"]
+    pub fn parameters(&self) -> IntrinsicParameters {
+        let p = unsafe { self.value.intrinsics.parameters.param };
+        IntrinsicParameters {
+            cx: p.cx,
+            cy: p.cy,
+            fx: p.fx,
+            fy: p.fy,
+            k1: p.k1,
+            k2: p.k2,
+            k3: p.k3,
+            k4: p.k4,
+            k5: p.k5,
+            k6: p.k6,
+            codx: p.codx,
+            cody: p.cody,
+            p1: p.p1,
+            p2: p.p2,
+        }
+    }
+    #[doc = "< Max FOV of the camera."]
+    pub fn metric_radius(&self) -> f32 {
+        self.value.metric_radius
+    }
+
+    /// Horizontal field of view in degrees, between the left and right edges of the image through the principal point.
+    pub fn horizontal_fov(&self) -> f32 {
+        let p = self.parameters();
+        let width = self.resolution_width() as f32;
+        self.fov(
+            Float2::new(-0.5, p.cy),
+            Float2::new(width - 0.5, p.cy),
+            (width / 2.0, p.fx),
+        )
+    }
+
+    /// Vertical field of view in degrees, between the top and bottom edges of the image through the principal point.
+    pub fn vertical_fov(&self) -> f32 {
+        let p = self.parameters();
+        let height = self.resolution_height() as f32;
+        self.fov(
+            Float2::new(p.cx, -0.5),
+            Float2::new(p.cx, height - 0.5),
+            (height / 2.0, p.fy),
+        )
+    }
+
+    fn fov(&self, from: Float2, to: Float2, (half_size, focal_length): (f32, f32)) -> f32 {
+        let rays = CameraModel::from_native(&self.value)
+            .ok()
+            .and_then(|model| Some((model.unproject_ray(&from)?, model.unproject_ray(&to)?)));
+        match rays {
+            Some(((x0, y0), (x1, y1))) => {
+                let dot = x0 * x1 + y0 * y1 + 1.0;
+                let norm = ((x0 * x0 + y0 * y0 + 1.0) * (x1 * x1 + y1 * y1 + 1.0)).sqrt();
+                (dot / norm).clamp(-1.0, 1.0).acos().to_degrees()
+            }
+            //  Lens model not supported or edges outside of the metric radius, ignore distortion.
+            None => 2.0 * (half_size / focal_length).atan().to_degrees(),
+        }
+    }
+}
+
+#[doc = " Extrinsic calibration, the transformation from a source to a target coordinate system."]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Extrinsics {
+    pub(crate) value: k4a_calibration_extrinsics_t,
+}
+
+impl Extrinsics {
+    #[doc = "< 3x3 Rotation matrix, rows of columns"]
+    pub fn rotation(&self) -> [[f32; 3]; 3] {
+        let r = &self.value.rotation;
+        [[r[0], r[1], r[2]], [r[3], r[4], r[5]], [r[6], r[7], r[8]]]
+    }
+    #[doc = "< Translation vector, x,y,z (in millimeters)"]
+    pub fn translation(&self) -> Float3 {
+        let t = &self.value.translation;
+        Float3::new(t[0], t[1], t[2])
+    }
+}
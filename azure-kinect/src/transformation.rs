@@ -13,8 +13,12 @@ pub struct Transformation<'a> {
     depth_resolution: Dimension,
 }
 
+//  A transformation keeps intermediate buffers of the last call, it can be moved to another thread
+//  but not used by several at once.
+unsafe impl Send for Transformation<'_> {}
+
 impl<'a> Transformation<'a> {
-    #[deprecated(since = "0.2", note = "Factory::transformation_create")]
+    #[deprecated(since = "0.2.0", note = "Factory::transformation_create")]
     pub fn new(factory: &'a Factory, calibration: &'a Calibration) -> Transformation<'a> {
         factory.transformation_create(calibration)
     }
@@ -55,21 +59,25 @@ impl<'a> Transformation<'a> {
         depth_image: &Image,
         transformed_depth_image: &mut Image,
     ) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
-            (self
-                .factory
-                .api()
-                .funcs
-                .k4a_transformation_depth_image_to_color_camera)(
-                self.handle,
-                depth_image.handle,
-                transformed_depth_image.handle,
-            )
-        })
-        .to_result(())
+        ErrorContext::new("k4a_transformation_depth_image_to_color_camera")
+            .arg("depth_format", format!("{:?}", depth_image.get_format()))
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self
+                        .factory
+                        .api()
+                        .funcs
+                        .k4a_transformation_depth_image_to_color_camera)(
+                        self.handle,
+                        depth_image.handle,
+                        transformed_depth_image.handle,
+                    )
+                })
+                .to_result(())
+            })
     }
 
-    pub fn depth_image_to_color_camera(&self, depth_image: &Image) -> Result<Image, Error> {
+    pub fn depth_image_to_color_camera(&self, depth_image: &Image) -> Result<Image<'_>, Error> {
         let mut transformed_depth_image = self.factory.image_create(
             ImageFormat::Depth16,
             self.color_resolution.width,
@@ -89,22 +97,27 @@ impl<'a> Transformation<'a> {
         interpolation_type: TransformationInterpolationType,
         invalid_custom_value: u32,
     ) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
-            (self
-                .factory
-                .api()
-                .funcs
-                .k4a_transformation_depth_image_to_color_camera_custom)(
-                self.handle,
-                depth_image.handle,
-                custom_image.handle,
-                transformed_depth_image.handle,
-                transformed_custom_image.handle,
-                interpolation_type.into(),
-                invalid_custom_value,
-            )
-        })
-        .to_result(())
+        ErrorContext::new("k4a_transformation_depth_image_to_color_camera_custom")
+            .arg("depth_format", format!("{:?}", depth_image.get_format()))
+            .arg("custom_format", format!("{:?}", custom_image.get_format()))
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self
+                        .factory
+                        .api()
+                        .funcs
+                        .k4a_transformation_depth_image_to_color_camera_custom)(
+                        self.handle,
+                        depth_image.handle,
+                        custom_image.handle,
+                        transformed_depth_image.handle,
+                        transformed_custom_image.handle,
+                        interpolation_type.into(),
+                        invalid_custom_value,
+                    )
+                })
+                .to_result(())
+            })
     }
 
     pub fn depth_image_to_color_camera_custom(
@@ -113,7 +126,7 @@ impl<'a> Transformation<'a> {
         custom_image: &Image,
         interpolation_type: TransformationInterpolationType,
         invalid_custom_value: u32,
-    ) -> Result<(Image, Image), Error> {
+    ) -> Result<(Image<'_>, Image<'_>), Error> {
         let bytes_per_pixel: usize = match custom_image.get_format() {
             ImageFormat::Custom8 => std::mem::size_of::<i8>(),
             ImageFormat::Custom16 => std::mem::size_of::<i16>(),
@@ -139,7 +152,7 @@ impl<'a> Transformation<'a> {
             custom_image,
             &mut transformed_depth_image,
             &mut transformed_custom_image,
-            interpolation_type.into(),
+            interpolation_type,
             invalid_custom_value,
         )?;
         Ok((transformed_depth_image, transformed_custom_image))
@@ -151,26 +164,30 @@ impl<'a> Transformation<'a> {
         color_image: &Image,
         transformed_color_image: &mut Image,
     ) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
-            (self
-                .factory
-                .api()
-                .funcs
-                .k4a_transformation_color_image_to_depth_camera)(
-                self.handle,
-                depth_image.handle,
-                color_image.handle,
-                transformed_color_image.handle,
-            )
-        })
-        .to_result(())
+        ErrorContext::new("k4a_transformation_color_image_to_depth_camera")
+            .arg("color_format", format!("{:?}", color_image.get_format()))
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self
+                        .factory
+                        .api()
+                        .funcs
+                        .k4a_transformation_color_image_to_depth_camera)(
+                        self.handle,
+                        depth_image.handle,
+                        color_image.handle,
+                        transformed_color_image.handle,
+                    )
+                })
+                .to_result(())
+            })
     }
 
     pub fn color_image_to_depth_camera(
         &self,
         depth_image: &Image,
         color_image: &Image,
-    ) -> Result<Image, Error> {
+    ) -> Result<Image<'_>, Error> {
         let mut transformed_color_image = self.factory.image_create(
             ImageFormat::BGRA32,
             self.color_resolution.width,
@@ -192,26 +209,30 @@ impl<'a> Transformation<'a> {
         camera: CalibrationType,
         xyz_image: &mut Image,
     ) -> Result<(), Error> {
-        Error::from_k4a_result_t(unsafe {
-            (self
-                .factory
-                .api()
-                .funcs
-                .k4a_transformation_depth_image_to_point_cloud)(
-                self.handle,
-                depth_image.handle,
-                camera.into(),
-                xyz_image.handle,
-            )
-        })
-        .to_result(())
+        ErrorContext::new("k4a_transformation_depth_image_to_point_cloud")
+            .arg("depth_format", format!("{:?}", depth_image.get_format()))
+            .call(|| {
+                Error::from_k4a_result_t(unsafe {
+                    (self
+                        .factory
+                        .api()
+                        .funcs
+                        .k4a_transformation_depth_image_to_point_cloud)(
+                        self.handle,
+                        depth_image.handle,
+                        camera.into(),
+                        xyz_image.handle,
+                    )
+                })
+                .to_result(())
+            })
     }
 
     pub fn depth_image_to_point_cloud(
         &self,
         depth_image: &Image,
         camera: CalibrationType,
-    ) -> Result<Image, Error> {
+    ) -> Result<Image<'_>, Error> {
         let mut xyz_image = self.factory.image_create(
             ImageFormat::Custom,
             self.color_resolution.width,
@@ -0,0 +1,474 @@
+//  Image transformations of the SDK (transformation.c, rgbz.c) evaluated in Rust on owned images.
+//  Rays of the pixels are looked up in per-camera tables, images are processed in parallel with rayon.
+
+use crate::*;
+use rayon::prelude::*;
+use std::sync::OnceLock;
+
+//  Rows of the color image rasterized by one task of depth_image_to_color_camera
+const BAND_ROWS: usize = 32;
+
+/// Per-pixel rays of a camera, NaN for pixels which can not be unprojected.
+struct XyTable {
+    width: usize,
+    height: usize,
+    xy: Vec<[f32; 2]>,
+}
+
+impl XyTable {
+    fn new(camera: &CameraModel) -> XyTable {
+        let width = camera.resolution_width().max(0) as usize;
+        let height = camera.resolution_height().max(0) as usize;
+        let mut xy = vec![[f32::NAN; 2]; width * height];
+        if width > 0 {
+            xy.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+                for (x, xy) in row.iter_mut().enumerate() {
+                    if let Some((rx, ry)) = camera.unproject_ray(&Float2::new(x as f32, y as f32)) {
+                        *xy = [rx, ry];
+                    }
+                }
+            });
+        }
+        XyTable { width, height, xy }
+    }
+}
+
+/// A vertex of the depth mesh projected into the color camera, `z` is 0 if invalid.
+#[derive(Copy, Clone, Default)]
+struct Vertex {
+    u: f32,
+    v: f32,
+    z: f32,
+}
+
+/// The transformations of [`Transformation`] implemented in Rust on [`OwnedImage`]s,
+/// usable without a [`Factory`].
+pub struct TransformationModel {
+    calibration: CalibrationModel,
+    depth_table: XyTable,
+    //  Only needed for point clouds of the color camera, which has many more pixels
+    color_table: OnceLock<XyTable>,
+}
+
+impl TransformationModel {
+    pub fn new(calibration: &CalibrationModel) -> Result<TransformationModel, Error> {
+        Ok(TransformationModel {
+            calibration: *calibration,
+            depth_table: XyTable::new(calibration.camera(CalibrationType::Depth)?),
+            color_table: OnceLock::new(),
+        })
+    }
+
+    /// Transforms the depth map into the geometry of the color camera.
+    pub fn depth_image_to_color_camera(
+        &self,
+        depth_image: &OwnedImage,
+    ) -> Result<OwnedImage, Error> {
+        let depth = self.read_depth(depth_image)?;
+        let (transformed_depth, _) = self.depth_to_color(&depth, None)?;
+        let mut image = self.color_image(ImageFormat::Depth16, 2, |v, b| {
+            b.copy_from_slice(&transformed_depth[v].to_le_bytes())
+        })?;
+        image.set_device_timestamp_usec(depth_image.get_device_timestamp_usec());
+        Ok(image)
+    }
+
+    /// Transforms the depth map and a custom image of the depth camera into the geometry of the color camera.
+    /// The custom image must be CUSTOM8 or CUSTOM16, pixels without a value are set to `invalid_custom_value`.
+    pub fn depth_image_to_color_camera_custom(
+        &self,
+        depth_image: &OwnedImage,
+        custom_image: &OwnedImage,
+        interpolation_type: TransformationInterpolationType,
+        invalid_custom_value: u32,
+    ) -> Result<(OwnedImage, OwnedImage), Error> {
+        let depth = self.read_depth(depth_image)?;
+        let format = custom_image.get_format();
+        let bytes_per_pixel = match format {
+            ImageFormat::Custom8 => 1,
+            ImageFormat::Custom16 => 2,
+            _ => return Err(Error::UnexpectedImageFormat(format)),
+        };
+        let custom = read_pixels(custom_image, &self.depth_table, bytes_per_pixel)?;
+        let (transformed_depth, transformed_custom) = self.depth_to_color(
+            &depth,
+            Some((&custom, interpolation_type, invalid_custom_value)),
+        )?;
+        let transformed_custom = transformed_custom.unwrap_or_default();
+        let mut depth_image_out = self.color_image(ImageFormat::Depth16, 2, |v, b| {
+            b.copy_from_slice(&transformed_depth[v].to_le_bytes())
+        })?;
+        let mut custom_image_out = self.color_image(format, bytes_per_pixel, |v, b| {
+            b.copy_from_slice(&transformed_custom[v].to_le_bytes()[..bytes_per_pixel])
+        })?;
+        depth_image_out.set_device_timestamp_usec(depth_image.get_device_timestamp_usec());
+        custom_image_out.set_device_timestamp_usec(custom_image.get_device_timestamp_usec());
+        Ok((depth_image_out, custom_image_out))
+    }
+
+    /// Transforms a BGRA32 color image into the geometry of the depth camera.
+    /// Pixels without depth or outside of the color image are set to 0.
+    pub fn color_image_to_depth_camera(
+        &self,
+        depth_image: &OwnedImage,
+        color_image: &OwnedImage,
+    ) -> Result<OwnedImage, Error> {
+        let depth = self.read_depth(depth_image)?;
+        let color_camera = self.calibration.camera(CalibrationType::Color)?;
+        let (color_width, color_height) = (
+            color_camera.resolution_width(),
+            color_camera.resolution_height(),
+        );
+        check_image(color_image, ImageFormat::BGRA32, color_width, color_height)?;
+        let color = color_image.as_bytes();
+        let color_stride = color_image.get_stride_bytes() as usize;
+        let vertices = self.depth_to_color_vertices(&depth);
+
+        let width = self.depth_table.width;
+        let mut buffer = vec![0u8; width * self.depth_table.height * 4];
+        if width > 0 {
+            buffer
+                .par_chunks_mut(width * 4)
+                .zip(vertices.par_chunks(width))
+                .for_each(|(row, vertices)| {
+                    for (pixel, vertex) in row.chunks_exact_mut(4).zip(vertices) {
+                        if vertex.z == 0.0 {
+                            continue;
+                        }
+                        let (u, v) = (vertex.u.round() as i32, vertex.v.round() as i32);
+                        if u >= 0 && v >= 0 && u < color_width && v < color_height {
+                            let offset = v as usize * color_stride + u as usize * 4;
+                            pixel.copy_from_slice(&color[offset..offset + 4]);
+                        }
+                    }
+                });
+        }
+        let mut image = OwnedImage::new(
+            ImageFormat::BGRA32,
+            width as i32,
+            self.depth_table.height as i32,
+            width as i32 * 4,
+            buffer,
+        )?;
+        image.set_device_timestamp_usec(color_image.get_device_timestamp_usec());
+        Ok(image)
+    }
+
+    /// Transforms a depth image of the depth or color camera into a point cloud of that camera.
+    /// The result is a CUSTOM image with x, y and z of each pixel as `i16` in millimeters.
+    pub fn depth_image_to_point_cloud(
+        &self,
+        depth_image: &OwnedImage,
+        camera: CalibrationType,
+    ) -> Result<OwnedImage, Error> {
+        let table = match camera {
+            CalibrationType::Depth => &self.depth_table,
+            CalibrationType::Color => {
+                let color_camera = self.calibration.camera(CalibrationType::Color)?;
+                self.color_table.get_or_init(|| XyTable::new(color_camera))
+            }
+            _ => return Err(Error::Failed),
+        };
+        check_image(
+            depth_image,
+            ImageFormat::Depth16,
+            table.width as i32,
+            table.height as i32,
+        )?;
+        let depth = read_pixels(depth_image, table, 2)?;
+
+        let width = table.width;
+        let mut buffer = vec![0u8; width * table.height * 6];
+        if width > 0 {
+            buffer
+                .par_chunks_mut(width * 6)
+                .zip(depth.par_chunks(width).zip(table.xy.par_chunks(width)))
+                .for_each(|(row, (depth, xy))| {
+                    for (pixel, (&d, xy)) in row.chunks_exact_mut(6).zip(depth.iter().zip(xy)) {
+                        if d == 0 || xy[0].is_nan() {
+                            continue;
+                        }
+                        let d = d as f32;
+                        let xyz = [xy[0] * d, xy[1] * d, d];
+                        for (b, v) in pixel.chunks_exact_mut(2).zip(&xyz) {
+                            let v = v.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+                            b.copy_from_slice(&v.to_le_bytes());
+                        }
+                    }
+                });
+        }
+        let mut image = OwnedImage::new(
+            ImageFormat::Custom,
+            width as i32,
+            table.height as i32,
+            width as i32 * 6,
+            buffer,
+        )?;
+        image.set_device_timestamp_usec(depth_image.get_device_timestamp_usec());
+        Ok(image)
+    }
+
+    fn read_depth(&self, depth_image: &OwnedImage) -> Result<Vec<u32>, Error> {
+        check_image(
+            depth_image,
+            ImageFormat::Depth16,
+            self.depth_table.width as i32,
+            self.depth_table.height as i32,
+        )?;
+        read_pixels(depth_image, &self.depth_table, 2)
+    }
+
+    /// Creates an image of the color camera, calling `write(pixel index, pixel bytes)` for each pixel.
+    fn color_image<F: Fn(usize, &mut [u8]) + Sync>(
+        &self,
+        format: ImageFormat,
+        bytes_per_pixel: usize,
+        write: F,
+    ) -> Result<OwnedImage, Error> {
+        let color_camera = self.calibration.camera(CalibrationType::Color)?;
+        let width = color_camera.resolution_width().max(0) as usize;
+        let height = color_camera.resolution_height().max(0) as usize;
+        let mut buffer = vec![0u8; width * height * bytes_per_pixel];
+        if width > 0 {
+            buffer
+                .par_chunks_mut(width * bytes_per_pixel)
+                .enumerate()
+                .for_each(|(y, row)| {
+                    for (x, pixel) in row.chunks_exact_mut(bytes_per_pixel).enumerate() {
+                        write(y * width + x, pixel);
+                    }
+                });
+        }
+        OwnedImage::new(
+            format,
+            width as i32,
+            height as i32,
+            (width * bytes_per_pixel) as i32,
+            buffer,
+        )
+    }
+
+    /// Projects every depth pixel into the color camera.
+    fn depth_to_color_vertices(&self, depth: &[u32]) -> Vec<Vertex> {
+        let calibration = &self.calibration;
+        depth
+            .par_iter()
+            .zip(self.depth_table.xy.par_iter())
+            .map(|(&d, xy)| {
+                if d == 0 || xy[0].is_nan() {
+                    return Vertex::default();
+                }
+                let d = d as f32;
+                let point3d = Float3::new(xy[0] * d, xy[1] * d, d);
+                let point3d = match calibration.convert_3d_to_3d(
+                    &point3d,
+                    CalibrationType::Depth,
+                    CalibrationType::Color,
+                ) {
+                    Ok(point3d) => point3d,
+                    Err(_) => return Vertex::default(),
+                };
+                match calibration.convert_3d_to_2d(
+                    &point3d,
+                    CalibrationType::Color,
+                    CalibrationType::Color,
+                ) {
+                    Ok((point2d, true)) => Vertex {
+                        u: point2d.x(),
+                        v: point2d.y(),
+                        z: point3d.z(),
+                    },
+                    _ => Vertex::default(),
+                }
+            })
+            .collect()
+    }
+
+    /// Renders the depth image as a mesh of two triangles per 2x2 pixels into the color camera,
+    /// keeping the nearest surface. The custom values are interpolated over the triangles.
+    fn depth_to_color(
+        &self,
+        depth: &[u32],
+        custom: Option<(&[u32], TransformationInterpolationType, u32)>,
+    ) -> Result<(Vec<u16>, Option<Vec<u32>>), Error> {
+        let (width, height) = (self.depth_table.width, self.depth_table.height);
+        let color_camera = self.calibration.camera(CalibrationType::Color)?;
+        let color_width = color_camera.resolution_width().max(0) as usize;
+        let color_height = color_camera.resolution_height().max(0) as usize;
+        let mut transformed_depth = vec![0u16; color_width * color_height];
+        let mut transformed_custom =
+            custom.map(|(_, _, invalid)| vec![invalid; color_width * color_height]);
+        if width < 2 || height < 2 || color_width == 0 {
+            return Ok((transformed_depth, transformed_custom));
+        }
+        let vertices = self.depth_to_color_vertices(depth);
+
+        //  Triangles as indices of their vertices, sorted into the bands of rows they cover
+        let band_count = color_height.div_ceil(BAND_ROWS);
+        let mut bands: Vec<Vec<[usize; 3]>> = vec![Vec::new(); band_count];
+        for y in 0..height - 1 {
+            for x in 0..width - 1 {
+                let top_left = y * width + x;
+                let bottom_left = top_left + width;
+                for triangle in &[
+                    [top_left, top_left + 1, bottom_left],
+                    [top_left + 1, bottom_left + 1, bottom_left],
+                ] {
+                    let [a, b, c] = triangle.map(|i| vertices[i]);
+                    if a.z == 0.0 || b.z == 0.0 || c.z == 0.0 {
+                        continue;
+                    }
+                    let min_v = a.v.min(b.v).min(c.v).ceil().max(0.0) as usize;
+                    let max_v = a.v.max(b.v).max(c.v).floor();
+                    if max_v < 0.0 || min_v >= color_height {
+                        continue;
+                    }
+                    let max_v = (max_v as usize).min(color_height - 1);
+                    for band in bands
+                        .iter_mut()
+                        .take(max_v / BAND_ROWS + 1)
+                        .skip(min_v / BAND_ROWS)
+                    {
+                        band.push(*triangle);
+                    }
+                }
+            }
+        }
+
+        let band_size = BAND_ROWS * color_width;
+        let mut custom_bands: Vec<Option<&mut [u32]>> = match transformed_custom.as_mut() {
+            Some(c) => c.chunks_mut(band_size).map(Some).collect(),
+            None => (0..band_count).map(|_| None).collect(),
+        };
+        transformed_depth
+            .par_chunks_mut(band_size)
+            .zip(custom_bands.par_iter_mut())
+            .zip(bands.par_iter())
+            .enumerate()
+            .for_each(|(band, ((depth_band, custom_band), triangles))| {
+                let first_row = band * BAND_ROWS;
+                let rows = depth_band.len() / color_width;
+                for triangle in triangles {
+                    let [a, b, c] = triangle.map(|i| vertices[i]);
+                    let area = (b.u - a.u) * (c.v - a.v) - (c.u - a.u) * (b.v - a.v);
+                    if area.abs() < f32::EPSILON {
+                        continue;
+                    }
+                    let min_u = a.u.min(b.u).min(c.u).ceil().max(0.0) as usize;
+                    let max_u = a.u.max(b.u).max(c.u).floor();
+                    let min_v = a.v.min(b.v).min(c.v).ceil().max(first_row as f32) as usize;
+                    let max_v = a.v.max(b.v).max(c.v).floor();
+                    if max_u < 0.0 || max_v < first_row as f32 {
+                        continue;
+                    }
+                    let max_u = (max_u as usize).min(color_width - 1);
+                    let max_v = (max_v as usize).min(first_row + rows - 1);
+                    for v in min_v..=max_v {
+                        for u in min_u..=max_u {
+                            let (pu, pv) = (u as f32, v as f32);
+                            let w0 = ((b.u - pu) * (c.v - pv) - (c.u - pu) * (b.v - pv)) / area;
+                            let w1 = ((c.u - pu) * (a.v - pv) - (a.u - pu) * (c.v - pv)) / area;
+                            let w2 = 1.0 - w0 - w1;
+                            if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                                continue;
+                            }
+                            let z = (w0 * a.z + w1 * b.z + w2 * c.z)
+                                .round()
+                                .clamp(1.0, u16::MAX as f32)
+                                as u16;
+                            let index = (v - first_row) * color_width + u;
+                            let current = depth_band[index];
+                            if current != 0 && z >= current {
+                                continue;
+                            }
+                            depth_band[index] = z;
+                            if let (
+                                Some(custom_band),
+                                Some((custom, interpolation_type, invalid)),
+                            ) = (custom_band.as_mut(), custom)
+                            {
+                                let values = triangle.map(|i| custom[i]);
+                                custom_band[index] =
+                                    interpolate(values, [w0, w1, w2], interpolation_type, invalid);
+                            }
+                        }
+                    }
+                }
+            });
+        Ok((transformed_depth, transformed_custom))
+    }
+}
+
+fn interpolate(
+    values: [u32; 3],
+    weights: [f32; 3],
+    interpolation_type: TransformationInterpolationType,
+    invalid_value: u32,
+) -> u32 {
+    match interpolation_type {
+        TransformationInterpolationType::Nearest => {
+            let nearest = (0..3)
+                .max_by(|&i, &j| weights[i].total_cmp(&weights[j]))
+                .unwrap_or(0);
+            values[nearest]
+        }
+        TransformationInterpolationType::Linear => {
+            if values.contains(&invalid_value) {
+                return invalid_value;
+            }
+            let value: f32 = values
+                .iter()
+                .zip(&weights)
+                .map(|(&v, &w)| v as f32 * w)
+                .sum();
+            value.round() as u32
+        }
+    }
+}
+
+fn check_image(
+    image: &OwnedImage,
+    format: ImageFormat,
+    width: i32,
+    height: i32,
+) -> Result<(), Error> {
+    if image.get_format() != format {
+        return Err(Error::UnexpectedImageFormat(image.get_format()));
+    }
+    if image.get_width_pixels() != width || image.get_height_pixels() != height {
+        return Err(Error::Failed);
+    }
+    Ok(())
+}
+
+/// Reads the little endian pixels of an image with the size of `table` into a packed buffer.
+fn read_pixels(
+    image: &OwnedImage,
+    table: &XyTable,
+    bytes_per_pixel: usize,
+) -> Result<Vec<u32>, Error> {
+    if image.get_width_pixels() != table.width as i32
+        || image.get_height_pixels() != table.height as i32
+    {
+        return Err(Error::Failed);
+    }
+    let stride = image.get_stride_bytes() as usize;
+    let bytes = image.as_bytes();
+    let mut pixels = vec![0u32; table.width * table.height];
+    if table.width > 0 {
+        pixels
+            .par_chunks_mut(table.width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                let offset = y * stride;
+                let source = &bytes[offset..offset + table.width * bytes_per_pixel];
+                for (p, b) in row.iter_mut().zip(source.chunks_exact(bytes_per_pixel)) {
+                    *p = b
+                        .iter()
+                        .rev()
+                        .fold(0u32, |value, &byte| (value << 8) | byte as u32);
+                }
+            });
+    }
+    Ok(pixels)
+}
@@ -15,13 +15,14 @@ pub(crate) fn get_k4a_cstring(
             k4a_buffer_result_t_K4A_BUFFER_RESULT_SUCCEEDED => Ok(CString::default()),
             k4a_buffer_result_t_K4A_BUFFER_RESULT_TOO_SMALL => {
                 if buffer > 1 {
-                    let mut retbuf = Vec::<u8>::with_capacity(buffer);
-                    retbuf.set_len(buffer - 1);
-                    Error::from_k4a_buffer_result_t((f)(
+                    let mut retbuf = vec![0u8; buffer];
+                    let r = (f)(
                         retbuf.as_mut_ptr() as *mut ::std::os::raw::c_char,
                         &mut buffer,
-                    ))
-                    .to_result(CString::from_vec_unchecked(retbuf))
+                    );
+                    retbuf.truncate(retbuf.len() - 1);
+                    Error::from_k4a_buffer_result_t(r)
+                        .to_result_fn(|| CString::from_vec_unchecked(retbuf))
                 } else {
                     Err(Error::from_k4a_buffer_result_t(r))
                 }
@@ -41,13 +42,14 @@ pub(crate) fn get_k4a_string(
             k4a_buffer_result_t_K4A_BUFFER_RESULT_SUCCEEDED => Ok(String::new()),
             k4a_buffer_result_t_K4A_BUFFER_RESULT_TOO_SMALL => {
                 if buffer > 1 {
-                    let mut retstr = String::with_capacity(buffer);
-                    retstr.as_mut_vec().set_len(buffer - 1);
-                    Error::from_k4a_buffer_result_t((f)(
-                        retstr.as_mut_ptr() as *mut ::std::os::raw::c_char,
+                    let mut retbuf = vec![0u8; buffer];
+                    let r = (f)(
+                        retbuf.as_mut_ptr() as *mut ::std::os::raw::c_char,
                         &mut buffer,
-                    ))
-                    .to_result(retstr)
+                    );
+                    retbuf.truncate(retbuf.len() - 1);
+                    Error::from_k4a_buffer_result_t(r)
+                        .to_result_fn(|| String::from_utf8_unchecked(retbuf))
                 } else {
                     Err(Error::from_k4a_buffer_result_t(r))
                 }
@@ -60,23 +62,20 @@ pub(crate) fn get_k4a_string(
 pub(crate) fn get_k4a_binary_data(
     f: &dyn Fn(*mut u8, *mut usize) -> k4a_buffer_result_t,
 ) -> Result<Vec<u8>, Error> {
-    unsafe {
-        let mut buffer: usize = 0;
-        let r = (f)(ptr::null_mut(), &mut buffer);
-        match r {
-            k4a_buffer_result_t_K4A_BUFFER_RESULT_SUCCEEDED => Ok(Vec::<u8>::new()),
-            k4a_buffer_result_t_K4A_BUFFER_RESULT_TOO_SMALL => {
-                if buffer > 1 {
-                    let mut retbuf = Vec::<u8>::with_capacity(buffer);
-                    retbuf.set_len(buffer);
-                    Error::from_k4a_buffer_result_t((f)(retbuf.as_mut_ptr(), &mut buffer))
-                        .to_result(retbuf)
-                } else {
-                    Err(Error::from_k4a_buffer_result_t(r))
-                }
+    let mut buffer: usize = 0;
+    let r = (f)(ptr::null_mut(), &mut buffer);
+    match r {
+        k4a_buffer_result_t_K4A_BUFFER_RESULT_SUCCEEDED => Ok(Vec::<u8>::new()),
+        k4a_buffer_result_t_K4A_BUFFER_RESULT_TOO_SMALL => {
+            if buffer > 1 {
+                let mut retbuf = vec![0u8; buffer];
+                Error::from_k4a_buffer_result_t((f)(retbuf.as_mut_ptr(), &mut buffer))
+                    .to_result(retbuf)
+            } else {
+                Err(Error::from_k4a_buffer_result_t(r))
             }
-            _ => Err(Error::from_k4a_buffer_result_t(r)),
         }
+        _ => Err(Error::from_k4a_buffer_result_t(r)),
     }
 }
 #[cfg(test)]
@@ -91,7 +90,7 @@ mod tests {
         let f: &dyn Fn(*mut ::std::os::raw::c_char, *mut usize) -> k4a_buffer_result_t =
             &|s, len| unsafe {
                 *len = t1.len() + 1;
-                if s == std::ptr::null_mut() {
+                if s.is_null() {
                     k4a_buffer_result_t_K4A_BUFFER_RESULT_TOO_SMALL
                 } else {
                     std::ptr::copy_nonoverlapping(ct1.as_ptr(), s, t1.len() + 1);
@@ -0,0 +1,290 @@
+use azure_kinect::*;
+
+mod common;
+
+//  The fake calibration has no lens distortion, so both implementations must agree.
+#[test]
+fn test_camera_model_matches_sdk() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory();
+    let device = factory.device_open(0)?;
+    let calibration = device.get_calibration(DepthMode::NFovUnbinned, ColorResolution::_1080p)?;
+    let model = calibration.model()?;
+
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+    for &(u, v) in &[(0.0, 0.0), (320.0, 288.0), (600.5, 10.25)] {
+        let point2d = Float2::new(u, v);
+        let (sdk, sdk_valid) = calibration.convert_2d_to_3d(
+            &point2d,
+            1500.0,
+            CalibrationType::Depth,
+            CalibrationType::Color,
+        )?;
+        let (rust, valid) = model.convert_2d_to_3d(
+            &point2d,
+            1500.0,
+            CalibrationType::Depth,
+            CalibrationType::Color,
+        )?;
+        assert_eq!(valid, sdk_valid);
+        assert!(close(rust.x(), sdk.x()) && close(rust.y(), sdk.y()) && close(rust.z(), sdk.z()));
+
+        let (sdk, sdk_valid) = calibration.convert_2d_to_2d(
+            &point2d,
+            1500.0,
+            CalibrationType::Depth,
+            CalibrationType::Color,
+        )?;
+        let (rust, valid) = model.convert_2d_to_2d(
+            &point2d,
+            1500.0,
+            CalibrationType::Depth,
+            CalibrationType::Color,
+        )?;
+        assert_eq!(valid, sdk_valid);
+        assert!(close(rust.x(), sdk.x()) && close(rust.y(), sdk.y()));
+    }
+
+    let point3d = Float3::new(10.0, -20.0, 800.0);
+    let sdk =
+        calibration.convert_3d_to_3d(&point3d, CalibrationType::Gyro, CalibrationType::Depth)?;
+    let rust = model.convert_3d_to_3d(&point3d, CalibrationType::Gyro, CalibrationType::Depth)?;
+    assert!(close(rust.x(), sdk.x()) && close(rust.y(), sdk.y()) && close(rust.z(), sdk.z()));
+    assert!(model
+        .convert_3d_to_2d(&point3d, CalibrationType::Depth, CalibrationType::Accel)
+        .is_err());
+    Ok(())
+}
+
+//  The bindgen enums are i32 on Windows, the model type is compared as u32.
+#[allow(clippy::unnecessary_cast)]
+#[test]
+fn test_intrinsics_and_extrinsics() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory();
+    let device = factory.device_open(0)?;
+    let calibration = device.get_calibration(DepthMode::NFovUnbinned, ColorResolution::_1080p)?;
+    let native = calibration.get_native();
+    assert_eq!(calibration.depth_mode(), DepthMode::NFovUnbinned);
+    assert_eq!(calibration.color_resolution(), ColorResolution::_1080p);
+
+    let depth = calibration.intrinsics(CalibrationType::Depth)?;
+    let p = depth.parameters();
+    assert_eq!(depth.resolution_width(), 640);
+    assert_eq!(depth.resolution_height(), 576);
+    assert_eq!(
+        depth.model_type() as u32,
+        native.depth_camera_calibration.intrinsics.type_ as u32
+    );
+    //  Without distortion, the angles from the principal point to both edges of the image
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-2;
+    let fov = |c: f32, size: f32, f: f32| ((c + 0.5) / f).atan() + ((size - 0.5 - c) / f).atan();
+    assert!(close(
+        depth.horizontal_fov(),
+        fov(p.cx, 640.0, p.fx).to_degrees()
+    ));
+    assert!(close(
+        depth.vertical_fov(),
+        fov(p.cy, 576.0, p.fy).to_degrees()
+    ));
+    assert!(calibration.intrinsics(CalibrationType::Gyro).is_err());
+
+    let extrinsics = calibration.extrinsics(CalibrationType::Depth, CalibrationType::Color)?;
+    let native_extrinsics = &native.extrinsics[0][1];
+    let rotation = extrinsics.rotation();
+    for i in 0..9 {
+        assert_eq!(rotation[i / 3][i % 3], native_extrinsics.rotation[i]);
+    }
+    let translation = extrinsics.translation();
+    assert_eq!(
+        [translation.x(), translation.y(), translation.z()],
+        native_extrinsics.translation
+    );
+    assert!(calibration
+        .extrinsics(CalibrationType::Unknown, CalibrationType::Color)
+        .is_err());
+    Ok(())
+}
+
+fn to_f64(point3d: &Float3) -> [f64; 3] {
+    [point3d.x(), point3d.y(), point3d.z()].map(f64::from)
+}
+
+//  Pixels of the depth camera in NFOV unbinned mode: a grid, the edges of the image and beyond them.
+fn depth_pixels() -> Vec<(f32, f32)> {
+    let mut pixels = vec![
+        (-0.5, -0.5),
+        (639.5, 575.5),
+        (-0.5, 288.0),
+        (639.5, 288.0),
+        (320.0, -0.5),
+        (320.0, 575.5),
+        (-0.6, 288.0),
+        (639.6, 288.0),
+        (320.0, 575.6),
+    ];
+    for v in (0..576).step_by(48) {
+        for u in (0..640).step_by(40) {
+            pixels.push((u as f32 + 0.25, v as f32 + 0.75));
+        }
+    }
+    pixels
+}
+
+#[test]
+fn test_distorted_3d_to_2d() -> Result<(), Box<dyn std::error::Error>> {
+    let native = common::distorted_factory_calibration()
+        .to_native(DepthMode::NFovUnbinned, ColorResolution::_1080p)?;
+    let model = CalibrationModel::from_native(&native)?;
+    let mut checked = [0, 0];
+    for z in &[500.0f32, 1500.0, 4000.0] {
+        for y in -24..=24 {
+            for x in -24..=24 {
+                //  Up to a field of view of twice the metric radius
+                let point3d = Float3::new(x as f32 * z / 12.0, y as f32 * z / 12.0, *z);
+                for &(target, camera) in &[
+                    (CalibrationType::Depth, &native.depth_camera_calibration),
+                    (CalibrationType::Color, &native.color_camera_calibration),
+                ] {
+                    let (point2d, valid) =
+                        model.convert_3d_to_2d(&point3d, CalibrationType::Depth, target)?;
+                    let target_point3d = match target {
+                        CalibrationType::Depth => to_f64(&point3d),
+                        _ => common::reference_transform(&native, to_f64(&point3d)),
+                    };
+                    let (expected, expected_valid) =
+                        common::reference_project(camera, target_point3d);
+                    assert_eq!(valid, expected_valid, "{:?} {:?}", to_f64(&point3d), target);
+                    if valid {
+                        assert!((f64::from(point2d.x()) - expected[0]).abs() < 1e-2);
+                        assert!((f64::from(point2d.y()) - expected[1]).abs() < 1e-2);
+                    }
+                    checked[valid as usize] += 1;
+                }
+            }
+        }
+    }
+    //  Both sides of the edge of the valid area are covered
+    assert!(checked[0] > 0 && checked[1] > 0);
+    Ok(())
+}
+
+#[test]
+fn test_distorted_2d_to_3d() -> Result<(), Box<dyn std::error::Error>> {
+    let native = common::distorted_factory_calibration()
+        .to_native(DepthMode::NFovUnbinned, ColorResolution::_1080p)?;
+    let model = CalibrationModel::from_native(&native)?;
+    let depth = &native.depth_camera_calibration;
+    for (u, v) in depth_pixels() {
+        let point2d = Float2::new(u, v);
+        let (point3d, valid) = model.convert_2d_to_3d(
+            &point2d,
+            1500.0,
+            CalibrationType::Depth,
+            CalibrationType::Depth,
+        )?;
+        let inside = u >= -0.5 && v >= -0.5 && u <= 639.5 && v <= 575.5;
+        assert_eq!(valid, inside, "{} {}", u, v);
+        if !valid {
+            continue;
+        }
+        //  The 3d point is on the ray of the pixel
+        assert_eq!(point3d.z(), 1500.0);
+        let (expected, _) = common::reference_project(depth, to_f64(&point3d));
+        assert!((expected[0] - f64::from(u)).abs() < 1e-2, "{} {}", u, v);
+        assert!((expected[1] - f64::from(v)).abs() < 1e-2, "{} {}", u, v);
+
+        let (color_point3d, valid) = model.convert_2d_to_3d(
+            &point2d,
+            1500.0,
+            CalibrationType::Depth,
+            CalibrationType::Color,
+        )?;
+        assert!(valid);
+        let expected = common::reference_transform(&native, to_f64(&point3d));
+        for (a, b) in to_f64(&color_point3d).iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-2);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_distorted_2d_to_2d() -> Result<(), Box<dyn std::error::Error>> {
+    let native = common::distorted_factory_calibration()
+        .to_native(DepthMode::NFovUnbinned, ColorResolution::_720p)?;
+    let model = CalibrationModel::from_native(&native)?;
+    let mut checked = [0, 0];
+    for (u, v) in depth_pixels() {
+        for &depth in &[300.0, 1500.0] {
+            let point2d = Float2::new(u, v);
+            let (color_point2d, valid) = model.convert_2d_to_2d(
+                &point2d,
+                depth,
+                CalibrationType::Depth,
+                CalibrationType::Color,
+            )?;
+            let (point3d, unprojected) = model.convert_2d_to_3d(
+                &point2d,
+                depth,
+                CalibrationType::Depth,
+                CalibrationType::Depth,
+            )?;
+            let (expected, projected) = common::reference_project(
+                &native.color_camera_calibration,
+                common::reference_transform(&native, to_f64(&point3d)),
+            );
+            assert_eq!(valid, unprojected && projected, "{} {} {}", u, v, depth);
+            if valid {
+                assert!((f64::from(color_point2d.x()) - expected[0]).abs() < 1e-2);
+                assert!((f64::from(color_point2d.y()) - expected[1]).abs() < 1e-2);
+            }
+            checked[valid as usize] += 1;
+        }
+    }
+    //  Close to the camera, the edges of the depth image are outside of the color image
+    assert!(checked[0] > 0 && checked[1] > 0);
+    Ok(())
+}
+
+//  In WFOV mode the corners of the depth image are beyond the metric radius, the valid area ends
+//  inside of the image.
+#[test]
+fn test_distorted_metric_radius() -> Result<(), Box<dyn std::error::Error>> {
+    let native = common::distorted_factory_calibration()
+        .to_native(DepthMode::WFovUnbinned, ColorResolution::Off)?;
+    let model = CalibrationModel::from_native(&native)?;
+    let depth = &native.depth_camera_calibration;
+    let mut last_valid = None;
+    for i in 0..=512 {
+        let point2d = Float2::new(511.5 - i as f32, 511.5 - i as f32);
+        let (point3d, valid) = model.convert_2d_to_3d(
+            &point2d,
+            1000.0,
+            CalibrationType::Depth,
+            CalibrationType::Depth,
+        )?;
+        if !valid {
+            break;
+        }
+        let (expected, expected_valid) = common::reference_project(depth, to_f64(&point3d));
+        assert!(expected_valid);
+        assert!((expected[0] - f64::from(point2d.x())).abs() < 1e-2);
+        last_valid = Some(i);
+    }
+    let last_valid = last_valid.unwrap();
+    assert!(last_valid > 256 && last_valid < 512);
+    //  Once invalid, every pixel further out is invalid
+    for i in last_valid + 1..=512 {
+        let point2d = Float2::new(511.5 - i as f32, 511.5 - i as f32);
+        assert!(
+            !model
+                .convert_2d_to_3d(
+                    &point2d,
+                    1000.0,
+                    CalibrationType::Depth,
+                    CalibrationType::Depth
+                )?
+                .1
+        );
+    }
+    Ok(())
+}
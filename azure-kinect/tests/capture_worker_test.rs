@@ -0,0 +1,132 @@
+use azure_kinect::*;
+
+mod common;
+
+#[test]
+fn capture_worker_test() {
+    assert_eq!(capture_worker_test_main().unwrap(), ());
+}
+
+fn capture_worker_test_main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory();
+    let device = factory.device_open(0)?;
+    let camera_config = DeviceConfiguration::builder()
+        .depth_mode(DepthMode::NFov2x2Binned)
+        .camera_fps(Fps::_30fps)
+        .build();
+    let camera = device.start_cameras(&camera_config)?;
+
+    let (received, statistics) =
+        camera.with_capture_worker(1, QueuePolicy::DropNewest, |worker| {
+            let mut received = 0;
+            let mut last_timestamp_usec = 0;
+            for _ in 0..5 {
+                let capture = worker.get_capture(1000)?;
+                let timestamp_usec = capture.get_depth_image().get_device_timestamp_usec();
+                assert!(timestamp_usec > last_timestamp_usec);
+                last_timestamp_usec = timestamp_usec;
+                received += 1;
+            }
+            Ok::<_, Error>((received, worker.statistics()))
+        })?;
+
+    //  A consumer which keeps up with the camera gets every capture
+    assert_eq!(statistics.delivered, received);
+    Ok(())
+}
+
+const PERIOD_USEC: u64 = 33_333;
+
+//  Longer than the 2 captures the fake SDK queues, so that a blocked worker misses frames
+const SLOW_CONSUMER: std::time::Duration = std::time::Duration::from_millis(300);
+
+fn timestamp_usec(capture: &Capture) -> u64 {
+    capture.get_depth_image().get_device_timestamp_usec()
+}
+
+/// Frames missing between two captures.
+fn frames_between(first_usec: u64, second_usec: u64) -> u64 {
+    ((second_usec - first_usec + PERIOD_USEC / 2) / PERIOD_USEC).saturating_sub(1)
+}
+
+/// Takes a capture, waits for the queue of one capture to fill up, then takes three more. Returns the
+/// timestamps and the statistics after the last one.
+fn slow_consumer(policy: QueuePolicy) -> Result<([u64; 4], CaptureStatistics), Error> {
+    let factory = common::mock_factory();
+    let device = factory.device_open(0)?;
+    let camera = device.start_cameras(
+        &DeviceConfiguration::builder()
+            .depth_mode(DepthMode::NFov2x2Binned)
+            .camera_fps(Fps::_30fps)
+            .build(),
+    )?;
+    camera.with_capture_worker(1, policy, |worker| {
+        let first = timestamp_usec(&worker.get_capture(1000)?);
+        std::thread::sleep(SLOW_CONSUMER);
+        let second = timestamp_usec(&worker.get_capture(1000)?);
+        let third = timestamp_usec(&worker.get_capture(1000)?);
+        let fourth = timestamp_usec(&worker.get_capture(1000)?);
+        Ok(([first, second, third, fourth], worker.statistics()))
+    })
+}
+
+#[test]
+fn drop_newest_test() -> Result<(), Error> {
+    let ([first, second, third, _], statistics) = slow_consumer(QueuePolicy::DropNewest)?;
+
+    //  The capture queued during the wait is the one after the first, the next ones are dropped
+    assert!(frames_between(first, second) <= statistics.gap_frames);
+    let missing = frames_between(second, third);
+    assert!(
+        missing >= 3,
+        "{} frames between {} and {}",
+        missing,
+        second,
+        third
+    );
+    assert!(statistics.dropped >= 1);
+    assert!(statistics.dropped + statistics.gap_frames >= missing);
+    assert_eq!(statistics.delivered, 4);
+    Ok(())
+}
+
+#[test]
+fn drop_oldest_test() -> Result<(), Error> {
+    let ([first, second, third, _], statistics) = slow_consumer(QueuePolicy::DropOldest)?;
+
+    //  The capture queued last during the wait is kept, the older ones are dropped
+    let missing = frames_between(first, second);
+    assert!(
+        missing >= 3,
+        "{} frames between {} and {}",
+        missing,
+        first,
+        second
+    );
+    assert!(statistics.dropped >= 1);
+    assert!(statistics.dropped + statistics.gap_frames >= missing);
+    assert!(frames_between(second, third) <= statistics.gap_frames);
+    assert_eq!(statistics.delivered, 4);
+    Ok(())
+}
+
+#[test]
+fn block_test() -> Result<(), Error> {
+    let (timestamps, statistics) = slow_consumer(QueuePolicy::Block)?;
+
+    //  No capture is dropped by the queue. The worker waits with the capture after the queued one,
+    //  the SDK drops the frames meanwhile and the next capture comes after a gap.
+    assert_eq!(statistics.dropped, 0);
+    let missing: Vec<u64> = timestamps
+        .windows(2)
+        .map(|t| frames_between(t[0], t[1]))
+        .collect();
+    assert!(
+        missing[2] >= 3,
+        "frames missing between captures {:?}",
+        missing
+    );
+    assert!(missing.iter().sum::<u64>() <= statistics.gap_frames);
+    assert_eq!(statistics.delivered, 4);
+    Ok(())
+}
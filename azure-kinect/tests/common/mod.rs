@@ -0,0 +1,86 @@
+//! Setup shared by the integration tests. They run against the in-process fake SDK
+//! (`azure_kinect_sys::mock`), no device is required.
+#![allow(dead_code)]
+
+use azure_kinect::*;
+use azure_kinect_sys::k4a::{k4a_calibration_camera_t, k4a_calibration_t};
+
+pub fn mock_factory() -> Factory {
+    Factory::with_api(azure_kinect_sys::mock::api())
+}
+
+pub fn mock_factory_record() -> FactoryRecord {
+    FactoryRecord::with_api(
+        azure_kinect_sys::mock::api(),
+        azure_kinect_sys::mock::api_record(),
+    )
+}
+
+/// The factory calibration of the fake device with the lens distortion of a real one, Rational 6KT
+/// for both cameras. The fake SDK ignores lens distortion, use it with the Rust models only.
+pub fn distorted_factory_calibration() -> FactoryCalibration {
+    let factory = mock_factory();
+    let device = factory.device_open(0).unwrap();
+    let mut calibration =
+        FactoryCalibration::from_raw(&device.get_raw_calibration().unwrap()).unwrap();
+    for camera in &mut calibration.calibration_information.cameras {
+        let (parameters, metric_radius) = if camera.location == "CALIBRATION_CameraLocationD0" {
+            (
+                [
+                    0.500_7, 0.505_1, 0.492_5, 0.492_6, 3.23, 2.07, 0.10, 3.56, 3.12, 0.54, 0.0,
+                    0.0, -6.5e-5, 5.4e-5,
+                ],
+                1.74,
+            )
+        } else {
+            (
+                [
+                    0.499_6, 0.504_7, 0.478_4, 0.637_8, 0.48, -2.61, 1.55, 0.36, -2.44, 1.48, 0.0,
+                    0.0, 6.5e-4, -1.7e-4,
+                ],
+                1.7,
+            )
+        };
+        camera.intrinsics.model_parameters = parameters.to_vec();
+        camera.intrinsics.model_type = "CALIBRATION_LensDistortionModelRational6KT".to_string();
+        camera.metric_radius = metric_radius;
+    }
+    calibration
+}
+
+//  The lens model of the SDK (intrinsic_transformations.c) evaluated in double precision, with the
+//  metric radius and image bounds checks. Rational 6KT only.
+pub fn reference_project(camera: &k4a_calibration_camera_t, point3d: [f64; 3]) -> ([f64; 2], bool) {
+    let p = unsafe { camera.intrinsics.parameters.param };
+    let [cx, cy, fx, fy] = [p.cx, p.cy, p.fx, p.fy].map(f64::from);
+    let [k1, k2, k3, k4, k5, k6] = [p.k1, p.k2, p.k3, p.k4, p.k5, p.k6].map(f64::from);
+    let [codx, cody, p1, p2] = [p.codx, p.cody, p.p1, p.p2].map(f64::from);
+    let xp = point3d[0] / point3d[2] - codx;
+    let yp = point3d[1] / point3d[2] - cody;
+    let rs = xp * xp + yp * yp;
+    let d = (1.0 + k1 * rs + k2 * rs * rs + k3 * rs * rs * rs)
+        / (1.0 + k4 * rs + k5 * rs * rs + k6 * rs * rs * rs);
+    let xp_d = xp * d + (rs + 2.0 * xp * xp) * p2 + xp * yp * p1;
+    let yp_d = yp * d + (rs + 2.0 * yp * yp) * p1 + xp * yp * p2;
+    let uv = [(xp_d + codx) * fx + cx, (yp_d + cody) * fy + cy];
+    let radius = f64::from(camera.metric_radius);
+    let valid = point3d[2] > 0.0
+        && rs <= radius * radius
+        && uv[0] >= -0.5
+        && uv[1] >= -0.5
+        && uv[0] <= f64::from(camera.resolution_width) - 0.5
+        && uv[1] <= f64::from(camera.resolution_height) - 0.5;
+    (uv, valid)
+}
+
+/// From the depth to the color camera, in double precision.
+pub fn reference_transform(calibration: &k4a_calibration_t, point3d: [f64; 3]) -> [f64; 3] {
+    let e = &calibration.extrinsics[0][1];
+    let r = e.rotation.map(f64::from);
+    let t = e.translation.map(f64::from);
+    [
+        r[0] * point3d[0] + r[1] * point3d[1] + r[2] * point3d[2] + t[0],
+        r[3] * point3d[0] + r[4] * point3d[1] + r[5] * point3d[2] + t[1],
+        r[6] * point3d[0] + r[7] * point3d[1] + r[8] * point3d[2] + t[2],
+    ]
+}
@@ -0,0 +1,85 @@
+use azure_kinect::*;
+
+mod common;
+
+#[test]
+fn error_context_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory_record();
+
+    //  SDK messages are kept without a debug message handler
+    let e = factory.core().device_open(100).err().unwrap();
+    assert_eq!(e.kind(), ErrorKind::Failed);
+    let context = e.context().unwrap();
+    assert_eq!(context.operation(), Some("k4a_device_open"));
+    assert_eq!(context.argument("index"), Some("100"));
+    assert!(context.messages()[0].starts_with("device index 100 is out of range"));
+    assert!(e
+        .to_string()
+        .starts_with("k4a_device_open(index = 100) failed: "));
+
+    let e = factory.playback_open("error_test.mkv").err().unwrap();
+    assert_eq!(
+        e.context().unwrap().argument("path"),
+        Some("error_test.mkv")
+    );
+
+    //  Timeout stays matchable
+    let device = factory.core().device_open(0)?;
+    let config = DeviceConfiguration::builder()
+        .depth_mode(DepthMode::NFovUnbinned)
+        .camera_fps(Fps::_5fps)
+        .build();
+    let camera = device.start_cameras(&config)?;
+    assert!(matches!(camera.get_capture(0), Err(Error::Timeout)));
+    let e = device.start_cameras(&config).err().unwrap();
+    assert_eq!(
+        e.context().unwrap().messages(),
+        ["cameras are already started"]
+    );
+
+    let e = PointCloud::default()
+        .save("/not_found/error_test.ply", PointCloudFormat::PlyAscii)
+        .err()
+        .unwrap();
+    assert_eq!(e.kind(), ErrorKind::Io);
+    assert!(std::error::Error::source(&e).is_some());
+    Ok(())
+}
+
+#[test]
+fn debug_message_handlers_test() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::{Arc, Mutex};
+
+    let handler = |received: &Arc<Mutex<Vec<String>>>| -> Box<DebugMessageHandler> {
+        let received = received.clone();
+        Box::new(move |_level, _file, _line, message| {
+            if message.starts_with("device index 101") {
+                received.lock().unwrap().push(message.to_string());
+            }
+        })
+    };
+    let first_received = Arc::new(Mutex::new(Vec::new()));
+    let second_received = Arc::new(Mutex::new(Vec::new()));
+    let first =
+        common::mock_factory().set_debug_message_handler(handler(&first_received), LogLevel::Error);
+    let second =
+        common::mock_factory().set_debug_message_handler(handler(&second_received), LogLevel::Info);
+
+    //  Every Factory receives the messages, not only the last one created
+    assert!(first.device_open(101).is_err());
+    assert_eq!(first_received.lock().unwrap().len(), 1);
+    assert_eq!(second_received.lock().unwrap().len(), 1);
+
+    //  Dropping a Factory keeps the handlers of the others and the messages of the errors
+    drop(second);
+    let e = first.device_open(101).err().unwrap();
+    assert!(e.context().unwrap().messages()[0].starts_with("device index 101 is out of range"));
+    assert_eq!(first_received.lock().unwrap().len(), 2);
+    assert_eq!(second_received.lock().unwrap().len(), 1);
+
+    drop(first);
+    let e = common::mock_factory().device_open(101).err().unwrap();
+    assert!(e.context().unwrap().messages()[0].starts_with("device index 101 is out of range"));
+    assert_eq!(first_received.lock().unwrap().len(), 2);
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use azure_kinect::*;
+
+mod common;
+
+#[test]
+fn test_factory_calibration() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory();
+    let device = factory.device_open(0)?;
+    let raw = device.get_raw_calibration()?;
+    let parsed = FactoryCalibration::from_raw(&raw)?;
+    assert_eq!(parsed.serial_number(), device.get_serialnum()?);
+    assert_eq!(parsed.calibration_information.cameras.len(), 2);
+    assert_eq!(
+        parsed.calibration_information.cameras[0]
+            .intrinsics
+            .model_type(),
+        CalibrationModelType::BrownConrady
+    );
+
+    //  Serializing again gives a blob which the SDK accepts and which parses to the same values.
+    let written = parsed.to_raw()?;
+    assert_eq!(written.last(), Some(&0));
+    assert_eq!(FactoryCalibration::from_raw(&written)?, parsed);
+
+    for &(depth_mode, color_resolution) in &[
+        (DepthMode::NFovUnbinned, ColorResolution::_720p),
+        (DepthMode::NFov2x2Binned, ColorResolution::_3072p),
+        (DepthMode::WFovUnbinned, ColorResolution::Off),
+        (DepthMode::PassiveIr, ColorResolution::_1536p),
+    ] {
+        let sdk = factory.calibration_get_from_raw(&written, depth_mode, color_resolution)?;
+        let native = parsed.to_native(depth_mode, color_resolution)?;
+        let sdk = sdk.get_native();
+        for (a, b) in [
+            (
+                &native.depth_camera_calibration,
+                &sdk.depth_camera_calibration,
+            ),
+            (
+                &native.color_camera_calibration,
+                &sdk.color_camera_calibration,
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(a.resolution_width, b.resolution_width);
+            assert_eq!(a.resolution_height, b.resolution_height);
+            assert_eq!(a.intrinsics.type_, b.intrinsics.type_);
+            assert_eq!(unsafe { a.intrinsics.parameters.v }, unsafe {
+                b.intrinsics.parameters.v
+            });
+        }
+        for (a, b) in native
+            .extrinsics
+            .iter()
+            .flatten()
+            .zip(sdk.extrinsics.iter().flatten())
+        {
+            assert_eq!(a.rotation, b.rotation);
+            assert_eq!(a.translation, b.translation);
+        }
+        assert!(parsed.to_model(depth_mode, color_resolution).is_ok());
+    }
+
+    assert!(FactoryCalibration::from_raw(b"{}\0").is_err());
+    Ok(())
+}
+
+//  The principal point of the fake device is in the middle of the sensor, so it is moved by the
+//  crop offset of the mode (and by half a pixel, the SDK puts the center of the first pixel at 0).
+#[test]
+fn test_mode_specific_principal_point() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory();
+    let device = factory.device_open(0)?;
+    let parsed = FactoryCalibration::from_raw(&device.get_raw_calibration()?)?;
+    for &(depth_mode, cx, cy) in &[
+        (DepthMode::NFov2x2Binned, 159.5, 165.5),
+        (DepthMode::NFovUnbinned, 319.5, 331.5),
+        (DepthMode::WFov2x2Binned, 255.5, 255.5),
+        (DepthMode::WFovUnbinned, 511.5, 511.5),
+    ] {
+        for native in &[
+            parsed.to_native(depth_mode, ColorResolution::Off)?,
+            *factory
+                .calibration_get_from_raw(
+                    &device.get_raw_calibration()?,
+                    depth_mode,
+                    ColorResolution::Off,
+                )?
+                .get_native(),
+        ] {
+            let p = unsafe { native.depth_camera_calibration.intrinsics.parameters.param };
+            assert_eq!((p.cx, p.cy), (cx, cy), "{:?}", depth_mode);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_unknown_fields() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory();
+    let device = factory.device_open(0)?;
+    let raw = device.get_raw_calibration()?;
+    let mut json: serde_json::Value =
+        serde_json::from_slice(&raw[..raw.iter().position(|c| *c == 0).unwrap()])?;
+    json["Extra"] = serde_json::json!({"Nested": [1, 2]});
+    json["CalibrationInformation"]["Extra"] = serde_json::json!("information");
+    json["CalibrationInformation"]["Cameras"][0]["Extra"] = serde_json::json!(0.5);
+    json["CalibrationInformation"]["Cameras"][1]["Rt"]["Extra"] = serde_json::json!(true);
+    json["CalibrationInformation"]["InertialSensors"][0]["Extra"] = serde_json::json!(null);
+    json["CalibrationInformation"]["Metadata"]["Version"]["Extra"] = serde_json::json!(3);
+
+    let parsed = FactoryCalibration::from_raw(&serde_json::to_vec(&json)?)?;
+    assert_eq!(parsed.extra["Extra"]["Nested"][1], 2);
+    let written = parsed.to_raw()?;
+    assert_eq!(FactoryCalibration::from_raw(&written)?, parsed);
+    //  The known fields are written back as floats, compare the unknown ones
+    let written: serde_json::Value = serde_json::from_slice(&written[..written.len() - 1])?;
+    for pointer in &[
+        "/Extra",
+        "/CalibrationInformation/Extra",
+        "/CalibrationInformation/Cameras/0/Extra",
+        "/CalibrationInformation/Cameras/1/Rt/Extra",
+        "/CalibrationInformation/InertialSensors/0/Extra",
+        "/CalibrationInformation/Metadata/Version/Extra",
+    ] {
+        assert_eq!(
+            written.pointer(pointer),
+            json.pointer(pointer),
+            "{}",
+            pointer
+        );
+        assert!(written.pointer(pointer).is_some());
+    }
+    Ok(())
+}
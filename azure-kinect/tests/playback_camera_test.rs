@@ -0,0 +1,117 @@
+use azure_kinect::playback_camera::PlaybackCamera;
+use azure_kinect::*;
+use std::time::{Duration, Instant};
+
+mod common;
+
+const START_USEC: u64 = 1_000_000;
+const PERIOD_USEC: u64 = 33_333;
+
+fn write_recording(factory: &FactoryRecord, path: &str) -> Result<(), Error> {
+    let config = DeviceConfiguration::builder()
+        .color_resolution(ColorResolution::Off)
+        .depth_mode(DepthMode::NFov2x2Binned)
+        .camera_fps(Fps::_30fps)
+        .build();
+    let record = factory.record_create_without_device(path, &config)?;
+    record.write_header()?;
+    for i in 0..10 {
+        let mut depth = factory
+            .core()
+            .image_create(ImageFormat::Depth16, 320, 288, 640)?;
+        let mut capture = factory.core().capture_create()?;
+        unsafe {
+            depth.set_device_timestamp_usec(START_USEC + i * PERIOD_USEC);
+            capture.set_depth_image(&depth);
+        }
+        record.write_capture(&capture)?;
+    }
+    record.flush()
+}
+
+fn timestamp(capture: &Capture) -> u64 {
+    capture.get_device_timestamp_usec().unwrap()
+}
+
+/// Reads until the end of the source, works with a device or a recording.
+fn read_timestamps<S: CaptureSource>(source: &S, count: usize) -> Result<Vec<u64>, Error> {
+    let mut timestamps = Vec::new();
+    while timestamps.len() < count {
+        match source.get_capture(-1) {
+            Ok(capture) => timestamps.push(timestamp(&capture)),
+            Err(Error::Eof) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(timestamps)
+}
+
+#[test]
+fn paced_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory_record();
+    write_recording(&factory, "playback_camera_paced_test.mkv")?;
+    let camera = PlaybackCamera::new(factory.playback_open("playback_camera_paced_test.mkv")?)?;
+    camera.set_speed(4.0);
+
+    //  The first capture is due immediately, the second one a frame later
+    let started = Instant::now();
+    assert_eq!(timestamp(&camera.get_capture(0)?), START_USEC);
+    assert!(matches!(camera.get_capture(0), Err(Error::Timeout)));
+    let timestamps = read_timestamps(&camera, usize::MAX)?;
+    assert!(started.elapsed() >= Duration::from_micros(9 * PERIOD_USEC / 4));
+    assert_eq!(timestamps.len(), 9);
+    assert_eq!(timestamps[8], START_USEC + 9 * PERIOD_USEC);
+    assert!(matches!(camera.get_capture(0), Err(Error::Eof)));
+
+    //  The replay starts at the position of the playback
+    let playback = camera.into_playback();
+    playback.seek_capture(5)?;
+    let camera = PlaybackCamera::new(playback)?;
+    assert_eq!(
+        timestamp(&camera.get_capture(0)?),
+        START_USEC + 5 * PERIOD_USEC
+    );
+    assert!(matches!(camera.get_capture(0), Err(Error::Timeout)));
+
+    //  A live camera is read with the same code
+    let device = factory.core().device_open(1)?;
+    let live = device.start_cameras(
+        &DeviceConfiguration::builder()
+            .depth_mode(DepthMode::NFov2x2Binned)
+            .build(),
+    )?;
+    assert_eq!(read_timestamps(&live, 2)?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn pause_and_loop_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory_record();
+    write_recording(&factory, "playback_camera_loop_test.mkv")?;
+    let camera = PlaybackCamera::new(factory.playback_open("playback_camera_loop_test.mkv")?)?;
+    camera.set_speed(10.0);
+    camera.set_looping(true);
+
+    let mut timestamps = vec![timestamp(&camera.get_capture(-1)?)];
+    camera.pause();
+    assert!(camera.is_paused());
+    assert!(matches!(camera.get_capture(20), Err(Error::Timeout)));
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(Duration::from_millis(20));
+            camera.resume();
+        });
+        //  Waits until the other thread resumes the replay
+        timestamps.push(timestamp(&camera.get_capture(-1).unwrap()));
+    });
+
+    //  The timestamps of the second pass follow those of the first one
+    timestamps.extend(read_timestamps(&camera, 13)?);
+    assert!(timestamps.windows(2).all(|t| t[0] < t[1]));
+    assert_eq!(timestamps[1], START_USEC + PERIOD_USEC);
+    assert_eq!(timestamps[10], START_USEC + 10 * PERIOD_USEC);
+    assert_eq!(timestamps[14], START_USEC + 14 * PERIOD_USEC);
+    let playback = camera.into_playback();
+    assert!(playback.get_next_capture().is_ok());
+    Ok(())
+}
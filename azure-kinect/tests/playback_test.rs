@@ -0,0 +1,216 @@
+use azure_kinect::*;
+
+mod common;
+
+const START_USEC: u64 = 1_000_000;
+const PERIOD_USEC: u64 = 33_333;
+
+fn timestamp(capture: &Capture) -> u64 {
+    capture.get_device_timestamp_usec().unwrap()
+}
+
+#[test]
+fn seek_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory_record();
+    let config = DeviceConfiguration::builder()
+        .color_resolution(ColorResolution::Off)
+        .depth_mode(DepthMode::NFov2x2Binned)
+        .camera_fps(Fps::_30fps)
+        .build();
+    let record = factory.record_create_without_device("playback_test.mkv", &config)?;
+    record.write_header()?;
+    for i in 0..10 {
+        let mut depth = factory
+            .core()
+            .image_create(ImageFormat::Depth16, 320, 288, 640)?;
+        let mut capture = factory.core().capture_create()?;
+        unsafe {
+            depth.set_device_timestamp_usec(START_USEC + i * PERIOD_USEC);
+            capture.set_depth_image(&depth);
+        }
+        record.write_capture(&capture)?;
+    }
+    record.flush()?;
+    std::mem::drop(record);
+
+    let playback = factory.playback_open("playback_test.mkv")?;
+    assert_eq!(playback.get_last_timestamp_usec(), 9 * PERIOD_USEC);
+
+    playback.seek_timestamp(-1, SeekOrigin::End)?;
+    assert_eq!(
+        timestamp(&playback.get_next_capture()?),
+        START_USEC + 9 * PERIOD_USEC
+    );
+    playback.seek_timestamp((START_USEC + PERIOD_USEC) as i64, SeekOrigin::DeviceTime)?;
+    assert_eq!(
+        timestamp(&playback.get_next_capture()?),
+        START_USEC + PERIOD_USEC
+    );
+
+    playback.seek_capture(4)?;
+    assert_eq!(
+        timestamp(&playback.get_next_capture()?),
+        START_USEC + 4 * PERIOD_USEC
+    );
+
+    //  Stepping back by one returns the same capture again
+    playback.step_captures(-1)?;
+    assert_eq!(
+        timestamp(&playback.get_next_capture()?),
+        START_USEC + 4 * PERIOD_USEC
+    );
+    playback.step_captures(3)?;
+    playback.step_captures(-1)?;
+    assert_eq!(
+        timestamp(&playback.get_next_capture()?),
+        START_USEC + 7 * PERIOD_USEC
+    );
+    playback.seek_capture(10)?;
+    assert!(matches!(playback.get_next_capture(), Err(Error::Eof)));
+    assert!(matches!(playback.seek_capture(11), Err(Error::Eof)));
+    playback.seek_capture(0)?;
+    assert!(matches!(playback.step_captures(-1), Err(Error::Eof)));
+
+    playback.seek_nearest_capture(START_USEC + 5 * PERIOD_USEC - 100)?;
+    assert_eq!(
+        timestamp(&playback.get_next_capture()?),
+        START_USEC + 5 * PERIOD_USEC
+    );
+    playback.seek_nearest_capture(START_USEC + 5 * PERIOD_USEC + 100)?;
+    assert_eq!(
+        timestamp(&playback.get_next_capture()?),
+        START_USEC + 5 * PERIOD_USEC
+    );
+    playback.seek_nearest_capture(0)?;
+    assert_eq!(timestamp(&playback.get_next_capture()?), START_USEC);
+    playback.seek_nearest_capture(u64::MAX)?;
+    assert_eq!(
+        timestamp(&playback.get_next_capture()?),
+        START_USEC + 9 * PERIOD_USEC
+    );
+    assert!(matches!(playback.get_next_capture(), Err(Error::Eof)));
+    Ok(())
+}
+
+#[test]
+fn events_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory_record();
+    let device = factory.core().device_open(0)?;
+    let config = DeviceConfiguration::builder()
+        .color_resolution(ColorResolution::Off)
+        .depth_mode(DepthMode::NFov2x2Binned)
+        .camera_fps(Fps::_30fps)
+        .build();
+    let camera = device.start_cameras(&config)?;
+    let imu = camera.start_imu()?;
+    let record = factory.record_create("playback_events_test.mkv", &device, &config)?;
+    record.add_imu_track()?;
+    record.add_custom_subtitle_track(
+        "NOTES",
+        "S_TEXT/UTF8",
+        &[],
+        &record::RecordSubtitleSettings::new(false),
+    )?;
+    record.write_header()?;
+    for i in 0..6 {
+        let capture = camera.get_capture(1000)?;
+        record.write_capture(&capture)?;
+        for _ in 0..3 {
+            record.write_imu_sample(&imu.get_imu_sample(1000)?)?;
+        }
+        if i % 2 == 0 {
+            let timestamp = timestamp(&capture) + 1;
+            record.write_custom_track_data("NOTES", timestamp, format!("{}", i).as_bytes())?;
+        }
+    }
+    record.flush()?;
+    std::mem::drop(record);
+
+    let playback = factory.playback_open("playback_events_test.mkv")?;
+    let captures = playback.captures().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(captures.len(), 6);
+    assert_eq!(playback.captures_rev().count(), 6);
+    assert_eq!(playback.imu_samples().count(), 18);
+    let notes = playback
+        .data_blocks("NOTES")
+        .map(|block| block.map(|b| String::from_utf8_lossy(b.as_bytes()).to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(notes, ["0", "2", "4"]);
+    assert!(playback.data_blocks("NOTES").next().is_none());
+    assert_eq!(playback.data_blocks_rev("NOTES").count(), 3);
+
+    playback.seek_timestamp(0, SeekOrigin::Begin)?;
+    let events = playback.events().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(events.len(), 6 + 18 + 3);
+    let timestamps: Vec<u64> = events.iter().map(|e| e.device_timestamp_usec()).collect();
+    assert!(timestamps.windows(2).all(|t| t[0] <= t[1]));
+    assert!(events.iter().any(|e| matches!(
+        e,
+        playback::PlaybackEvent::DataBlock { track, .. } if track == "NOTES"
+    )));
+
+    playback.seek_timestamp(0, SeekOrigin::End)?;
+    let mut reversed: Vec<u64> = playback
+        .events_rev()
+        .map(|e| e.map(|e| e.device_timestamp_usec()))
+        .collect::<Result<_, _>>()?;
+    reversed.reverse();
+    assert_eq!(reversed, timestamps);
+    Ok(())
+}
+
+#[test]
+fn metadata_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory_record();
+    let config = DeviceConfiguration::builder()
+        .color_resolution(ColorResolution::Off)
+        .depth_mode(DepthMode::NFov2x2Binned)
+        .build();
+    let path = std::env::temp_dir().join("playback_metadata_test.mkv");
+    let path = path.to_str().unwrap();
+
+    //  The fake SDK keeps its recordings in memory, the tags and attachments are read from the file
+    let mut mkv = mkv::MkvRecord::create(path, &config)?;
+    mkv.add_tag("TOOL_VERSION", "1.2")?;
+    mkv.add_attachment("notes.txt", b"notes")?;
+    mkv.add_custom_subtitle_track(
+        "NOTES",
+        "S_TEXT/UTF8",
+        &[],
+        &record::RecordSubtitleSettings::new(false),
+    )?;
+    mkv.write_header()?;
+    for i in 0..3 {
+        mkv.write_custom_track_data("NOTES", 1000 + i * 1000, b"note")?;
+    }
+    mkv.close()?;
+    let record = factory.record_create_without_device(path, &config)?;
+    record.write_header()?;
+    record.flush()?;
+    std::mem::drop(record);
+
+    let playback = factory.playback_open(path)?;
+    assert!(playback
+        .tags()?
+        .contains(&("TOOL_VERSION".to_string(), "1.2".to_string())));
+    assert_eq!(playback.attachments()?, [("notes.txt".to_string(), 5)]);
+    let metadata = playback.get_metadata()?;
+    assert_eq!(
+        metadata.configuration().depth_mode(),
+        DepthMode::NFov2x2Binned
+    );
+    assert_eq!(metadata.tag("TOOL_VERSION"), Some("1.2"));
+    assert_eq!(metadata.custom_tags().count(), 1);
+    assert_eq!(metadata.device_serial_number(), None);
+    assert!(playback.block_counts()?.contains(&("NOTES".to_string(), 3)));
+
+    //  The file is parsed once
+    std::fs::remove_file(path)?;
+    assert_eq!(playback.attachments()?.len(), 1);
+    let record = factory.record_create_without_device("playback_metadata_test.mkv", &config)?;
+    record.write_header()?;
+    std::mem::drop(record);
+    let playback = factory.playback_open("playback_metadata_test.mkv")?;
+    assert_eq!(playback.tags().err().unwrap().kind(), ErrorKind::Io);
+    Ok(())
+}
@@ -1,18 +1,14 @@
 use azure_kinect::*;
 
-//  Copy depthengine_2_0.dll and k4a.dll, k4arecord.dll to current directory.
+mod common;
+
 #[test]
 fn record_test() {
     assert_eq!(record_test_main().unwrap(), ());
 }
 
 fn record_test_main() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    let factory = FactoryRecord::with_library_directory(
-        std::env::current_dir()?
-            .to_str()
-            .ok_or(error::Error::Failed)?,
-    )?
-    .set_debug_message_handler(
+    let factory = common::mock_factory_record().set_debug_message_handler(
         Box::new(move |level, file, line, message| {
             println!("{:?}, {}, {}, {}", level, file, line, message);
         }),
@@ -0,0 +1,322 @@
+//! Compares the pure-Rust implementations with the installed Azure Kinect SDK, on data made by a real
+//! device. The tests are ignored by default, run them on a machine with the SDK:
+//!
+//! ```text
+//! K4A_TEST_RECORDING=recording.mkv cargo test --test sdk_test -- --ignored
+//! ```
+//!
+//! `K4A_TEST_RECORDING` names a recording written by the SDK, with an IMU track and both cameras.
+//! Its calibration holds the lens distortion of the device.
+use azure_kinect::mkv::MkvPlayback;
+use azure_kinect::*;
+
+fn recording_path() -> String {
+    std::env::var("K4A_TEST_RECORDING").expect("K4A_TEST_RECORDING must name an SDK recording")
+}
+
+#[test]
+#[ignore]
+fn mkv_reader_matches_sdk() -> Result<(), Box<dyn std::error::Error>> {
+    let path = recording_path();
+    let factory = FactoryRecord::new()?;
+    let sdk = factory.playback_open(&path)?;
+    let mut mkv = MkvPlayback::open(&path)?;
+    assert_eq!(
+        mkv.get_record_configuration()?
+            .start_timestamp_offset_usec(),
+        sdk.get_record_configuration()?
+            .start_timestamp_offset_usec()
+    );
+
+    let mut captures = 0;
+    loop {
+        let expected = match sdk.get_next_capture() {
+            Ok(capture) => capture,
+            Err(Error::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let capture = mkv.get_next_capture()?;
+        let timestamp = |image: Option<&OwnedImage>| image.map(|i| i.get_device_timestamp_usec());
+        let expected_timestamp = |image: Image| {
+            Some(image.get_device_timestamp_usec())
+                .filter(|_| !unsafe { image.get_native_handle() }.is_null())
+        };
+        assert_eq!(
+            timestamp(capture.get_color_image()),
+            expected_timestamp(expected.get_color_image())
+        );
+        assert_eq!(
+            timestamp(capture.get_depth_image()),
+            expected_timestamp(expected.get_depth_image())
+        );
+        captures += 1;
+    }
+    assert!(captures > 0);
+    assert!(matches!(mkv.get_next_capture(), Err(Error::Eof)));
+
+    //  The payload of IMU samples holds device timestamps, the block timestamps are relative
+    let mut samples = 0;
+    loop {
+        let expected = match sdk.get_next_imu_sample() {
+            Ok(sample) => sample,
+            Err(Error::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let sample = mkv.get_next_imu_sample()?;
+        assert_eq!(sample.acc_timestamp_usec(), expected.acc_timestamp_usec());
+        assert_eq!(sample.gyro_timestamp_usec(), expected.gyro_timestamp_usec());
+        assert_eq!(sample.acc_sample().x(), expected.acc_sample().x());
+        assert_eq!(sample.gyro_sample().z(), expected.gyro_sample().z());
+        samples += 1;
+    }
+    assert!(samples > 0);
+    Ok(())
+}
+
+//  Pixels of an image: a grid, the edges and beyond them.
+fn pixels(width: i32, height: i32) -> Vec<Float2> {
+    let (w, h) = (width as f32, height as f32);
+    let mut pixels = vec![
+        Float2::new(-0.5, -0.5),
+        Float2::new(w - 0.5, h - 0.5),
+        Float2::new(-0.6, h / 2.0),
+        Float2::new(w - 0.4, h / 2.0),
+        Float2::new(w / 2.0, h - 0.4),
+    ];
+    for v in (0..height).step_by(16) {
+        for u in (0..width).step_by(16) {
+            pixels.push(Float2::new(u as f32 + 0.25, v as f32 + 0.75));
+        }
+    }
+    pixels
+}
+
+#[test]
+#[ignore]
+fn camera_model_matches_sdk() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = FactoryRecord::new()?;
+    let playback = factory.playback_open(&recording_path())?;
+    let calibration = playback.get_calibration()?;
+    let model = calibration.model()?;
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-2;
+
+    for &(source, target) in &[
+        (CalibrationType::Depth, CalibrationType::Color),
+        (CalibrationType::Color, CalibrationType::Depth),
+    ] {
+        let camera = model.camera(source)?;
+        for point2d in pixels(camera.resolution_width(), camera.resolution_height()) {
+            for &depth in &[300.0, 1500.0] {
+                let (expected, expected_valid) =
+                    calibration.convert_2d_to_3d(&point2d, depth, source, target)?;
+                let (point3d, valid) = model.convert_2d_to_3d(&point2d, depth, source, target)?;
+                assert_eq!(valid, expected_valid);
+                if valid {
+                    assert!(close(point3d.x(), expected.x()) && close(point3d.y(), expected.y()));
+                    assert!(close(point3d.z(), expected.z()));
+                }
+
+                let (expected, expected_valid) =
+                    calibration.convert_2d_to_2d(&point2d, depth, source, target)?;
+                let (point2d, valid) = model.convert_2d_to_2d(&point2d, depth, source, target)?;
+                assert_eq!(valid, expected_valid);
+                if valid {
+                    assert!(close(point2d.x(), expected.x()) && close(point2d.y(), expected.y()));
+                }
+            }
+        }
+
+        for y in -24..=24 {
+            for x in -24..=24 {
+                let point3d = Float3::new(x as f32 * 125.0, y as f32 * 125.0, 1500.0);
+                let (expected, expected_valid) =
+                    calibration.convert_3d_to_2d(&point3d, source, target)?;
+                let (point2d, valid) = model.convert_3d_to_2d(&point3d, source, target)?;
+                assert_eq!(valid, expected_valid);
+                if valid {
+                    assert!(close(point2d.x(), expected.x()) && close(point2d.y(), expected.y()));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+//  Same keys and values, numbers compared in single precision as they are parsed.
+fn assert_same_json(a: &serde_json::Value, b: &serde_json::Value, path: &str) {
+    use serde_json::Value;
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            assert_eq!(
+                a.keys().collect::<Vec<_>>(),
+                b.keys().collect::<Vec<_>>(),
+                "{}",
+                path
+            );
+            for (key, value) in a {
+                assert_same_json(value, &b[key], &format!("{}/{}", path, key));
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            assert_eq!(a.len(), b.len(), "{}", path);
+            for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                assert_same_json(a, b, &format!("{}/{}", path, i));
+            }
+        }
+        (Value::Number(a), Value::Number(b)) => {
+            assert_eq!(
+                a.as_f64().map(|f| f as f32),
+                b.as_f64().map(|f| f as f32),
+                "{}",
+                path
+            );
+        }
+        _ => assert_eq!(a, b, "{}", path),
+    }
+}
+
+#[test]
+#[ignore]
+fn factory_calibration_matches_sdk() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = FactoryRecord::new()?;
+    let playback = factory.playback_open(&recording_path())?;
+    let raw = playback.get_raw_calibration()?;
+    let parsed = FactoryCalibration::from_raw(&raw)?;
+
+    //  Every field of the device is written back
+    let json = |raw: &[u8]| -> Result<serde_json::Value, serde_json::Error> {
+        let end = raw.iter().position(|c| *c == 0).unwrap_or(raw.len());
+        serde_json::from_slice(&raw[..end])
+    };
+    assert_same_json(&json(&parsed.to_raw()?)?, &json(&raw)?, "");
+
+    let close = |a: f32, b: f32| (a - b).abs() <= 1e-4 * b.abs().max(1.0);
+    for &depth_mode in &[
+        DepthMode::NFov2x2Binned,
+        DepthMode::NFovUnbinned,
+        DepthMode::WFov2x2Binned,
+        DepthMode::WFovUnbinned,
+        DepthMode::PassiveIr,
+    ] {
+        for &color_resolution in &[
+            ColorResolution::_720p,
+            ColorResolution::_1080p,
+            ColorResolution::_1440p,
+            ColorResolution::_1536p,
+            ColorResolution::_2160p,
+            ColorResolution::_3072p,
+        ] {
+            let sdk =
+                factory
+                    .core()
+                    .calibration_get_from_raw(&raw, depth_mode, color_resolution)?;
+            let sdk = sdk.get_native();
+            let native = parsed.to_native(depth_mode, color_resolution)?;
+            for (a, b) in &[
+                (
+                    &native.depth_camera_calibration,
+                    &sdk.depth_camera_calibration,
+                ),
+                (
+                    &native.color_camera_calibration,
+                    &sdk.color_camera_calibration,
+                ),
+            ] {
+                assert_eq!(a.resolution_width, b.resolution_width);
+                assert_eq!(a.resolution_height, b.resolution_height);
+                assert_eq!(a.intrinsics.type_, b.intrinsics.type_);
+                assert_eq!(a.metric_radius, b.metric_radius);
+                let (a, b) = unsafe { (a.intrinsics.parameters.v, b.intrinsics.parameters.v) };
+                for (i, (a, b)) in a.iter().zip(&b).enumerate() {
+                    assert!(
+                        close(*a, *b),
+                        "{:?} {:?} parameter {}: {} != {}",
+                        depth_mode,
+                        color_resolution,
+                        i,
+                        a,
+                        b
+                    );
+                }
+            }
+            for (a, b) in native
+                .extrinsics
+                .iter()
+                .flatten()
+                .zip(sdk.extrinsics.iter().flatten())
+            {
+                for (a, b) in a.rotation.iter().zip(&b.rotation) {
+                    assert!(close(*a, *b));
+                }
+                for (a, b) in a.translation.iter().zip(&b.translation) {
+                    assert!(close(*a, *b));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[ignore]
+fn transformation_model_matches_sdk() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = FactoryRecord::new()?;
+    let mut playback = factory.playback_open(&recording_path())?;
+    playback.set_color_conversion(ImageFormat::BGRA32)?;
+    let calibration = playback.get_calibration()?;
+    let transformation = factory.core().transformation_create(&calibration);
+    let model = TransformationModel::new(&calibration.model()?)?;
+
+    let capture = loop {
+        let capture = playback.get_next_capture()?;
+        let has_image = |image: Image| !unsafe { image.get_native_handle() }.is_null();
+        if has_image(capture.get_color_image()) && has_image(capture.get_depth_image()) {
+            break capture;
+        }
+    };
+    let depth = capture.get_depth_image();
+    let color = capture.get_color_image();
+    let (width, height) = (depth.get_width_pixels(), depth.get_height_pixels());
+
+    //  Both render a mesh of the depth image, the depth may differ by rounding and the coverage
+    //  at the edges of surfaces
+    let expected = transformation.depth_image_to_color_camera(&depth)?;
+    let transformed = model.depth_image_to_color_camera(&OwnedImage::from_image(&depth))?;
+    let (mut same, mut different) = (0, 0);
+    for (row, expected_row) in transformed.depth16()?.zip(expected.depth16()?) {
+        for (&d, &expected_d) in row.iter().zip(expected_row) {
+            if d == 0 && expected_d == 0 {
+                continue;
+            }
+            if d != 0 && expected_d != 0 && (d as i32 - expected_d as i32).abs() <= 2 {
+                same += 1;
+            } else {
+                different += 1;
+            }
+        }
+    }
+    assert!(same > 0 && different * 100 < same);
+
+    let mut expected =
+        factory
+            .core()
+            .image_create(ImageFormat::BGRA32, width, height, width * 4)?;
+    transformation.color_image_to_depth_camera_exist_image(&depth, &color, &mut expected)?;
+    let transformed = model.color_image_to_depth_camera(
+        &OwnedImage::from_image(&depth),
+        &OwnedImage::from_image(&color),
+    )?;
+    let (mut same, mut different) = (0, 0);
+    for (row, expected_row) in transformed.bgra32()?.zip(expected.bgra32()?) {
+        for (pixel, expected_pixel) in row.iter().zip(expected_row) {
+            if pixel == expected_pixel {
+                same += 1;
+            } else {
+                different += 1;
+            }
+        }
+    }
+    //  Pixels on the border between two color pixels may round the other way
+    assert!(different * 100 < same);
+    Ok(())
+}
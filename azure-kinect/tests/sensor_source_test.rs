@@ -0,0 +1,88 @@
+use azure_kinect::playback_camera::PlaybackCamera;
+use azure_kinect::*;
+
+mod common;
+
+/// What a tool reads from a source, the same for a device and a recording of it.
+struct Summary {
+    serial_number: String,
+    depth_mode: DepthMode,
+    calibration_depth_mode: DepthMode,
+    captures: usize,
+    imu_samples: usize,
+}
+
+fn summarize<S: SensorSource>(source: &S, count: usize) -> Result<Summary, Error> {
+    let mut captures = 0;
+    let mut imu_samples = 0;
+    while captures < count {
+        match source.get_capture(1000) {
+            Ok(_) => captures += 1,
+            Err(Error::Eof) => break,
+            Err(e) => return Err(e),
+        }
+        match source.get_imu_sample(1000) {
+            Ok(_) => imu_samples += 1,
+            Err(Error::Eof) => (),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(Summary {
+        serial_number: source.get_serial_number()?,
+        depth_mode: source.get_device_configuration()?.depth_mode(),
+        calibration_depth_mode: source.get_calibration()?.depth_mode(),
+        captures,
+        imu_samples,
+    })
+}
+
+#[test]
+fn sensor_source_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory_record();
+    let device = factory.core().device_open(0)?;
+    let config = DeviceConfiguration::builder()
+        .color_resolution(ColorResolution::Off)
+        .depth_mode(DepthMode::NFov2x2Binned)
+        .camera_fps(Fps::_30fps)
+        .build();
+    let camera = device.start_cameras(&config)?;
+    let imu = camera.start_imu()?;
+    let record = factory.record_create("sensor_source_test.mkv", &device, &config)?;
+    record.add_imu_track()?;
+    record.write_header()?;
+    for _ in 0..5 {
+        record.write_capture(&camera.get_capture(1000)?)?;
+        record.write_imu_sample(&imu.get_imu_sample(1000)?)?;
+    }
+    record.flush()?;
+    std::mem::drop(record);
+
+    let live = summarize(&DeviceSource::new(&camera, Some(&imu)), 5)?;
+    assert_eq!(live.serial_number, device.get_serialnum()?);
+    assert_eq!(live.depth_mode, DepthMode::NFov2x2Binned);
+    assert_eq!(live.calibration_depth_mode, DepthMode::NFov2x2Binned);
+    assert_eq!((live.captures, live.imu_samples), (5, 5));
+
+    let playback = factory.playback_open("sensor_source_test.mkv")?;
+    let recorded = summarize(&playback, usize::MAX)?;
+    assert_eq!(recorded.serial_number, live.serial_number);
+    assert_eq!(recorded.depth_mode, live.depth_mode);
+    assert_eq!(recorded.calibration_depth_mode, live.calibration_depth_mode);
+    assert_eq!((recorded.captures, recorded.imu_samples), (5, 5));
+
+    playback.seek_timestamp(0, SeekOrigin::Begin)?;
+    let replay = PlaybackCamera::new(playback)?;
+    replay.set_speed(10.0);
+    let replayed = summarize(&replay, usize::MAX)?;
+    assert_eq!(replayed.serial_number, live.serial_number);
+    assert_eq!(replayed.calibration_depth_mode, live.calibration_depth_mode);
+    assert_eq!(replayed.captures, 5);
+
+    //  Without an IMU, reading samples from a device fails
+    let source = DeviceSource::new(&camera, None);
+    assert_eq!(
+        source.get_imu_sample(0).err().unwrap().kind(),
+        ErrorKind::Failed
+    );
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use azure_kinect::playback::Playback;
+use azure_kinect::record::Record;
+use azure_kinect::*;
+use std::sync::Arc;
+
+mod common;
+
+fn assert_send_sync<T: Send + Sync>() {}
+fn assert_send<T: Send>() {}
+
+#[test]
+fn send_sync_test() {
+    assert_send_sync::<Factory>();
+    assert_send_sync::<FactoryRecord>();
+    assert_send_sync::<Device>();
+    assert_send_sync::<Camera>();
+    assert_send_sync::<Imu>();
+    assert_send_sync::<Capture>();
+    assert_send_sync::<Calibration>();
+    assert_send_sync::<Error>();
+    assert_send_sync::<SharedDevice>();
+    assert_send_sync::<SharedCamera>();
+    assert_send_sync::<SharedCapture>();
+    assert_send_sync::<azure_kinect::playback_camera::PlaybackCamera>();
+    assert_send::<Image>();
+    assert_send::<Transformation>();
+    assert_send::<Playback>();
+    assert_send::<Record>();
+}
+
+#[test]
+fn shared_handle_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory();
+    let mut capture = factory.capture_create()?;
+    let depth = factory.image_create(ImageFormat::Depth16, 320, 288, 640)?;
+    unsafe { capture.set_depth_image(&depth) };
+
+    //  The images of a capture and their clones are the same SDK image, which is why the setters are
+    //  unsafe: the timestamp set here is read by the other thread.
+    let mut image = capture.get_depth_image();
+    unsafe { image.set_device_timestamp_usec(1000) };
+    let clone = capture.clone();
+    let timestamp = std::thread::scope(|scope| {
+        scope
+            .spawn(move || clone.get_depth_image().get_device_timestamp_usec())
+            .join()
+            .unwrap()
+    });
+    assert_eq!(timestamp, 1000);
+    assert_eq!(depth.get_device_timestamp_usec(), 1000);
+    Ok(())
+}
+
+#[test]
+fn shared_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = Arc::new(common::mock_factory());
+    let device = SharedDevice::open(&factory, 0)?;
+    let camera = device.start_cameras(
+        &DeviceConfiguration::builder()
+            .color_format(ImageFormat::BGRA32)
+            .color_resolution(ColorResolution::_720p)
+            .depth_mode(DepthMode::NFov2x2Binned)
+            .build(),
+    )?;
+    std::mem::drop(device);
+    std::mem::drop(factory);
+
+    //  Captures are read on one thread and received on another
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let reader = std::thread::spawn(move || -> Result<(), Error> {
+        for _ in 0..3 {
+            sender.send(camera.get_capture(1000)?).unwrap();
+        }
+        //  The device is shared with this thread
+        camera.device().device().set_color_control(
+            ColorControlCommand::Brightness,
+            ColorControlMode::Manual,
+            100,
+        )?;
+        Ok(())
+    });
+    let captures: Vec<SharedCapture> = receiver.iter().collect();
+    reader.join().unwrap()?;
+    assert_eq!(captures.len(), 3);
+    for capture in &captures {
+        let color = capture.capture().get_color_image();
+        assert_eq!(color.get_format(), ImageFormat::BGRA32);
+        assert_eq!(color.get_width_pixels(), 1280);
+    }
+    let capture = captures[0].clone();
+    std::mem::drop(captures);
+    assert_eq!(
+        capture.capture().get_depth_image().get_format(),
+        ImageFormat::Depth16
+    );
+    Ok(())
+}
@@ -1,18 +1,14 @@
 use azure_kinect::*;
 
-//  Copy depthengine_2_0.dll and k4a.dll to current directory.
+mod common;
+
 #[test]
 fn test() {
     assert_eq!(test_main().unwrap(), ());
 }
 
 fn test_main() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    let factory = Factory::with_library_directory(
-        std::env::current_dir()?
-            .to_str()
-            .ok_or(error::Error::Failed)?,
-    )?
-    .set_debug_message_handler(
+    let factory = common::mock_factory().set_debug_message_handler(
         Box::new(move |level, file, line, message| {
             println!("{:?}, {}, {}, {}", level, file, line, message);
         }),
@@ -38,10 +34,19 @@ fn test_main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         let imu = camera.start_imu()?;
         let imu_sample = imu.get_imu_sample_wait_infinite()?;
         println!("imu = {}", imu_sample);
+        for imu_sample in imu.samples().take(3) {
+            assert!(imu_sample?.acc_timestamp_usec() > 0);
+        }
     }
 
-    for i in 0..20 {
-        if let Ok(capture) = camera.get_capture(100) {
+    for (i, capture) in camera
+        .captures()
+        .timeout(100)
+        .on_timeout(TimeoutBehavior::Yield)
+        .take(20)
+        .enumerate()
+    {
+        if let Ok(capture) = capture {
             let image = capture.get_color_image();
             println!(
                 "[{}] format = {:?}, width = {}, height = {}, temparature = {}",
@@ -0,0 +1,293 @@
+use azure_kinect::*;
+use azure_kinect_sys::k4a::k4a_calibration_t;
+
+mod common;
+
+//  The fake SDK splats depth pixels instead of rendering triangles, so only the point cloud and the
+//  color image in the depth camera are expected to match it exactly.
+
+fn to_image<'a>(factory: &'a Factory, owned: &OwnedImage) -> Result<Image<'a>, Error> {
+    let mut image = factory.image_create(
+        owned.get_format(),
+        owned.get_width_pixels(),
+        owned.get_height_pixels(),
+        owned.get_stride_bytes(),
+    )?;
+    //  The image was just created, there is no other handle to it.
+    unsafe { image.as_bytes_mut() }.copy_from_slice(owned.as_bytes());
+    Ok(image)
+}
+
+//  A slanted plane with a hole in the middle
+fn depth_image(width: i32, height: i32) -> Result<OwnedImage, Error> {
+    let mut buffer = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let hole = (x - width / 2).abs() < 20 && (y - height / 2).abs() < 20;
+            let d = if hole { 0 } else { 1000 + x + y / 2 } as u16;
+            buffer.extend_from_slice(&d.to_le_bytes());
+        }
+    }
+    OwnedImage::new(ImageFormat::Depth16, width, height, width * 2, buffer)
+}
+
+#[test]
+fn test_transformation_model() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = common::mock_factory();
+    let device = factory.device_open(0)?;
+    let calibration = device.get_calibration(DepthMode::NFovUnbinned, ColorResolution::_720p)?;
+    let transformation = factory.transformation_create(&calibration);
+    let model = TransformationModel::new(&calibration.model()?)?;
+    let (width, height) = (640, 576);
+    let (color_width, color_height) = (1280, 720);
+
+    let depth = depth_image(width, height)?;
+    let native_depth = to_image(&factory, &depth)?;
+
+    //  Point cloud of the depth camera
+    let point_cloud = model.depth_image_to_point_cloud(&depth, CalibrationType::Depth)?;
+    assert_eq!(point_cloud.get_format(), ImageFormat::Custom);
+    let mut native_point_cloud =
+        factory.image_create(ImageFormat::Custom, width, height, width * 6)?;
+    transformation.depth_image_to_point_cloud_exist_image(
+        &native_depth,
+        CalibrationType::Depth,
+        &mut native_point_cloud,
+    )?;
+    let values = |b: &[u8]| -> Vec<i16> {
+        b.chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect()
+    };
+    for (a, b) in values(point_cloud.as_bytes())
+        .iter()
+        .zip(values(native_point_cloud.as_bytes()).iter())
+    {
+        assert!((a - b).abs() <= 1);
+    }
+
+    //  Color image in the depth camera
+    let mut color = Vec::new();
+    for y in 0..color_height {
+        for x in 0..color_width {
+            color.extend_from_slice(&[x as u8, y as u8, (x / 256 + y / 256 * 8) as u8, 255]);
+        }
+    }
+    let color = OwnedImage::new(
+        ImageFormat::BGRA32,
+        color_width,
+        color_height,
+        color_width * 4,
+        color,
+    )?;
+    let transformed_color = model.color_image_to_depth_camera(&depth, &color)?;
+    let mut native_transformed_color =
+        factory.image_create(ImageFormat::BGRA32, width, height, width * 4)?;
+    transformation.color_image_to_depth_camera_exist_image(
+        &native_depth,
+        &to_image(&factory, &color)?,
+        &mut native_transformed_color,
+    )?;
+    assert_eq!(
+        transformed_color.as_bytes(),
+        native_transformed_color.as_bytes()
+    );
+
+    //  Depth in the color camera, compared where both have a surface. The mesh does not reach
+    //  half a pixel beyond the edges of the surface, where the fake SDK splats a pixel.
+    let transformed_depth = model.depth_image_to_color_camera(&depth)?;
+    assert_eq!(transformed_depth.get_width_pixels(), color_width);
+    let native_transformed_depth = transformation.depth_image_to_color_camera(&native_depth)?;
+    let (mut covered, mut missed) = (0, 0);
+    for (row, native_row) in transformed_depth
+        .depth16()?
+        .zip(native_transformed_depth.depth16()?)
+    {
+        for (&d, &native_d) in row.iter().zip(native_row) {
+            if native_d != 0 {
+                if d == 0 {
+                    missed += 1;
+                    continue;
+                }
+                assert!((d as i32 - native_d as i32).abs() <= 2);
+                covered += 1;
+            }
+        }
+    }
+    assert!(covered > 0 && missed * 100 < covered);
+
+    //  Custom image in the color camera
+    let custom = OwnedImage::new(
+        ImageFormat::Custom16,
+        width,
+        height,
+        width * 2,
+        [7u8, 0].repeat((width * height) as usize),
+    )?;
+    for &interpolation_type in &[
+        TransformationInterpolationType::Nearest,
+        TransformationInterpolationType::Linear,
+    ] {
+        let (depth_out, custom_out) = model.depth_image_to_color_camera_custom(
+            &depth,
+            &custom,
+            interpolation_type,
+            0xffff,
+        )?;
+        assert_eq!(depth_out.as_bytes(), transformed_depth.as_bytes());
+        for (row, custom_row) in depth_out.depth16()?.zip(custom_out.custom16()?) {
+            for (&d, &c) in row.iter().zip(custom_row) {
+                assert_eq!(c, if d == 0 { 0xffff } else { 7 });
+            }
+        }
+    }
+
+    assert!(model
+        .depth_image_to_point_cloud(&color, CalibrationType::Depth)
+        .is_err());
+    assert!(model
+        .depth_image_to_point_cloud(&depth, CalibrationType::Gyro)
+        .is_err());
+    Ok(())
+}
+
+//  The fake SDK ignores lens distortion, so on a distorted calibration the model is compared with
+//  the lens model evaluated in double precision.
+
+fn distorted_model() -> Result<(k4a_calibration_t, CalibrationModel), Error> {
+    let native = common::distorted_factory_calibration()
+        .to_native(DepthMode::NFovUnbinned, ColorResolution::_720p)?;
+    Ok((native, CalibrationModel::from_native(&native)?))
+}
+
+fn depth_rows(image: &OwnedImage) -> Result<Vec<Vec<u16>>, Error> {
+    Ok(image.depth16()?.map(|row| row.to_vec()).collect())
+}
+
+//  Where a pixel of the depth image is seen by the color camera, and its depth there
+fn reference_color_pixel(
+    native: &k4a_calibration_t,
+    model: &CalibrationModel,
+    x: i32,
+    y: i32,
+    depth: u16,
+) -> Result<Option<([f64; 2], f64)>, Error> {
+    let (point3d, valid) = model.convert_2d_to_3d(
+        &Float2::new(x as f32, y as f32),
+        depth as f32,
+        CalibrationType::Depth,
+        CalibrationType::Depth,
+    )?;
+    if !valid {
+        return Ok(None);
+    }
+    let point3d = common::reference_transform(
+        native,
+        [point3d.x(), point3d.y(), point3d.z()].map(f64::from),
+    );
+    let (uv, valid) = common::reference_project(&native.color_camera_calibration, point3d);
+    Ok(Some((uv, point3d[2])).filter(|_| valid))
+}
+
+#[test]
+fn test_distorted_depth_image_to_color_camera() -> Result<(), Box<dyn std::error::Error>> {
+    let (native, model) = distorted_model()?;
+    let transformation = TransformationModel::new(&model)?;
+    let (width, height) = (640, 576);
+    let depth = depth_image(width, height)?;
+    let transformed_depth = depth_rows(&transformation.depth_image_to_color_camera(&depth)?)?;
+    let depth = depth_rows(&depth)?;
+
+    let mut checked = 0;
+    for y in (1..height - 1).step_by(7) {
+        for x in (1..width - 1).step_by(7) {
+            //  Away from the hole, where the surface continues around the pixel
+            if (x - width / 2).abs() < 22 && (y - height / 2).abs() < 22 {
+                continue;
+            }
+            //  The mesh ends at the pixels whose neighbors are outside of the color image
+            let mut pixels = Vec::new();
+            for (dx, dy) in &[(0, 0), (-1, -1), (1, -1), (-1, 1), (1, 1)] {
+                let d = depth[(y + dy) as usize][(x + dx) as usize];
+                pixels.push(reference_color_pixel(&native, &model, x + dx, y + dy, d)?);
+            }
+            if pixels.iter().any(Option::is_none) {
+                continue;
+            }
+            let (uv, z) = pixels[0].unwrap();
+            let (u, v) = (uv[0].round() as usize, uv[1].round() as usize);
+            //  The plane rises by about a millimeter per pixel
+            let transformed = transformed_depth[v][u];
+            assert!(
+                transformed != 0 && (f64::from(transformed) - z).abs() <= 2.0,
+                "{} {}: {} {}",
+                x,
+                y,
+                transformed,
+                z
+            );
+            checked += 1;
+        }
+    }
+    assert!(checked > 3000);
+    Ok(())
+}
+
+#[test]
+fn test_distorted_color_image_to_depth_camera() -> Result<(), Box<dyn std::error::Error>> {
+    let (native, model) = distorted_model()?;
+    let transformation = TransformationModel::new(&model)?;
+    let (width, height) = (640, 576);
+    let (color_width, color_height) = (1280, 720);
+    let depth = depth_image(width, height)?;
+
+    //  Every pixel holds its coordinates
+    let mut color = Vec::new();
+    for y in 0..color_height {
+        for x in 0..color_width {
+            let c = (x + y * 2048) as u32;
+            color.extend_from_slice(&[c as u8, (c >> 8) as u8, (c >> 16) as u8, 255]);
+        }
+    }
+    let color = OwnedImage::new(
+        ImageFormat::BGRA32,
+        color_width,
+        color_height,
+        color_width * 4,
+        color,
+    )?;
+    let transformed_color = transformation.color_image_to_depth_camera(&depth, &color)?;
+    let depth_values = depth_rows(&depth)?;
+
+    let (mut colored, mut black) = (0, 0);
+    for (y, row) in transformed_color.bgra32()?.enumerate() {
+        for (x, pixel) in row.iter().enumerate() {
+            let d = depth_values[y][x];
+            let expected = match reference_color_pixel(&native, &model, x as i32, y as i32, d)? {
+                _ if d == 0 => None,
+                Some((uv, _)) => {
+                    //  Skip the pixels which fall on the border between two color pixels
+                    if uv.iter().any(|c| (c - c.floor() - 0.5).abs() < 1e-2) {
+                        continue;
+                    }
+                    Some([uv[0].round() as u32, uv[1].round() as u32])
+                }
+                None => None,
+            };
+            match expected {
+                Some([u, v]) if u < color_width as u32 && v < color_height as u32 => {
+                    let c = u32::from_le_bytes([pixel[0], pixel[1], pixel[2], 0]);
+                    assert_eq!([c % 2048, c / 2048], [u, v], "{} {}", x, y);
+                    colored += 1;
+                }
+                _ => {
+                    assert_eq!(pixel, &[0, 0, 0, 0], "{} {}", x, y);
+                    black += 1;
+                }
+            }
+        }
+    }
+    //  The hole and the edges of the depth image outside of the color camera
+    assert!(colored > 0 && black > 0);
+    Ok(())
+}
@@ -0,0 +1,314 @@
+use crate::param::Parameter;
+use crate::recorder::Error;
+use azure_kinect::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Recording settings read by `--config`, a TOML file or a JSON file with the extension `.json`.
+/// Every field is optional, command line flags override the file.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecorderConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub devices: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_resolution: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_fps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synchronized_images_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth_delay_off_color_usec: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wired_sync_mode: Option<String>,
+    /// One value for all subordinates, or one value for each device.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subordinate_delay_off_master_usec: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_streaming_indicator: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imu: Option<bool>,
+    /// Recording length in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_length: Option<u64>,
+    /// Output file, `{serial}`, `{index}` and `{datetime}` are replaced for each device.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Color controls by the snake case name of the `ColorControlCommand`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_controls: Option<BTreeMap<String, ColorControlSetting>>,
+    /// Tags written to each recording.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ColorControlSetting {
+    /// `auto` or `manual`
+    pub mode: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<i32>,
+}
+
+impl RecorderConfig {
+    pub fn load(path: &str) -> Result<RecorderConfig, Error<'static>> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Error(format!("Unable to read config file {}: {}", path, e)))?;
+        let is_json = Path::new(path)
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        if is_json {
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&text).map_err(|e| e.to_string())
+        }
+        .map_err(|e| Error::Error(format!("Invalid config file {}: {}", path, e)))
+    }
+
+    /// The effective settings of `param`, as printed by `--dump-config`.
+    pub fn from_parameter(param: &Parameter) -> RecorderConfig {
+        let c = &param.device_config;
+        let mut color_controls = BTreeMap::new();
+        let mut color_control = |command, value: Option<i32>| {
+            color_controls.insert(
+                color_control_command_name(command).to_string(),
+                ColorControlSetting {
+                    mode: color_control_mode_name(if value.is_some() {
+                        ColorControlMode::Manual
+                    } else {
+                        ColorControlMode::Auto
+                    })
+                    .to_string(),
+                    value,
+                },
+            );
+        };
+        color_control(
+            ColorControlCommand::ExposureTimeAbsolute,
+            param.absolute_exposure_value,
+        );
+        color_control(ColorControlCommand::Gain, param.gain);
+        for (command, mode, value) in param.color_controls.iter() {
+            color_controls.insert(
+                color_control_command_name(*command).to_string(),
+                ColorControlSetting {
+                    mode: color_control_mode_name(*mode).to_string(),
+                    value: Some(*value).filter(|_| *mode == ColorControlMode::Manual),
+                },
+            );
+        }
+        RecorderConfig {
+            devices: Some(param.devices.clone()),
+            color_format: Some(color_format_name(c.color_format()).to_string()),
+            color_resolution: Some(color_resolution_name(c.color_resolution()).to_string()),
+            depth_mode: Some(depth_mode_name(c.depth_mode()).to_string()),
+            camera_fps: Some(c.camera_fps().get_u32()),
+            synchronized_images_only: Some(c.synchronized_images_only()),
+            depth_delay_off_color_usec: Some(c.depth_delay_off_color_usec()),
+            wired_sync_mode: Some(wired_sync_mode_name(c.wired_sync_mode()).to_string()),
+            subordinate_delay_off_master_usec: Some(param.sync_delays.clone()),
+            disable_streaming_indicator: Some(c.disable_streaming_indicator()),
+            imu: Some(param.record_imu),
+            record_length: param.recording_length.map(|d| d.as_secs()),
+            output: Some(param.recording_filename.clone()).filter(|o| !o.is_empty()),
+            color_controls: Some(color_controls),
+            tags: Some(param.tags.iter().cloned().collect()),
+        }
+    }
+
+    pub fn to_toml(&self) -> Result<String, Error<'static>> {
+        toml::to_string(self).map_err(|e| Error::Error(format!("Unable to write config: {}", e)))
+    }
+}
+
+pub fn to_color_format<'a>(value: &str) -> Result<ImageFormat, Error<'a>> {
+    match value.to_ascii_uppercase().as_str() {
+        "MJPG" => Ok(ImageFormat::MJPG),
+        "NV12" => Ok(ImageFormat::NV12),
+        "YUY2" => Ok(ImageFormat::YUY2),
+        "BGRA32" => Ok(ImageFormat::BGRA32),
+        _ => Err(Error::Error(format!(
+            "Unknown color format specified: {}",
+            value
+        ))),
+    }
+}
+
+pub fn to_color_resolution<'a>(value: &str) -> Result<ColorResolution, Error<'a>> {
+    match value.to_ascii_lowercase().as_str() {
+        "3072p" => Ok(ColorResolution::_3072p),
+        "2160p" => Ok(ColorResolution::_2160p),
+        "1536p" => Ok(ColorResolution::_1536p),
+        "1440p" => Ok(ColorResolution::_1440p),
+        "1080p" => Ok(ColorResolution::_1080p),
+        "720p" => Ok(ColorResolution::_720p),
+        "off" => Ok(ColorResolution::Off),
+        _ => Err(Error::Error(format!(
+            "Unknown color resolution specified: {}",
+            value
+        ))),
+    }
+}
+
+pub fn to_color_control<'a>(
+    name: &str,
+    setting: &ColorControlSetting,
+) -> Result<(ColorControlCommand, ColorControlMode, i32), Error<'a>> {
+    let command = COLOR_CONTROL_COMMANDS
+        .iter()
+        .find(|c| color_control_command_name(**c) == name.to_ascii_lowercase())
+        .copied()
+        .ok_or_else(|| Error::Error(format!("Unknown color control specified: {}", name)))?;
+    match (setting.mode.to_ascii_lowercase().as_str(), setting.value) {
+        ("auto", _) => Ok((command, ColorControlMode::Auto, 0)),
+        ("manual", Some(value)) => Ok((command, ColorControlMode::Manual, value)),
+        ("manual", None) => Err(Error::Error(format!(
+            "The manual color control {} needs a value",
+            name
+        ))),
+        (mode, _) => Err(Error::Error(format!(
+            "Unknown color control mode specified: {}",
+            mode
+        ))),
+    }
+}
+
+const COLOR_CONTROL_COMMANDS: [ColorControlCommand; 10] = [
+    ColorControlCommand::ExposureTimeAbsolute,
+    ColorControlCommand::AutoExposurePriority,
+    ColorControlCommand::Brightness,
+    ColorControlCommand::Contrast,
+    ColorControlCommand::Saturation,
+    ColorControlCommand::Sharpness,
+    ColorControlCommand::WhiteBalance,
+    ColorControlCommand::BacklightCompensation,
+    ColorControlCommand::Gain,
+    ColorControlCommand::PowerlineFrequency,
+];
+
+fn color_control_command_name(command: ColorControlCommand) -> &'static str {
+    match command {
+        ColorControlCommand::ExposureTimeAbsolute => "exposure_time_absolute",
+        ColorControlCommand::AutoExposurePriority => "auto_exposure_priority",
+        ColorControlCommand::Brightness => "brightness",
+        ColorControlCommand::Contrast => "contrast",
+        ColorControlCommand::Saturation => "saturation",
+        ColorControlCommand::Sharpness => "sharpness",
+        ColorControlCommand::WhiteBalance => "white_balance",
+        ColorControlCommand::BacklightCompensation => "backlight_compensation",
+        ColorControlCommand::Gain => "gain",
+        ColorControlCommand::PowerlineFrequency => "powerline_frequency",
+    }
+}
+
+fn color_control_mode_name(mode: ColorControlMode) -> &'static str {
+    match mode {
+        ColorControlMode::Auto => "auto",
+        ColorControlMode::Manual => "manual",
+    }
+}
+
+fn color_format_name(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::NV12 => "NV12",
+        ImageFormat::YUY2 => "YUY2",
+        ImageFormat::BGRA32 => "BGRA32",
+        _ => "MJPG",
+    }
+}
+
+fn color_resolution_name(resolution: ColorResolution) -> &'static str {
+    match resolution {
+        ColorResolution::Off => "OFF",
+        ColorResolution::_720p => "720p",
+        ColorResolution::_1080p => "1080p",
+        ColorResolution::_1440p => "1440p",
+        ColorResolution::_1536p => "1536p",
+        ColorResolution::_2160p => "2160p",
+        ColorResolution::_3072p => "3072p",
+    }
+}
+
+fn depth_mode_name(depth_mode: DepthMode) -> &'static str {
+    match depth_mode {
+        DepthMode::Off => "OFF",
+        DepthMode::NFov2x2Binned => "NFOV_2X2BINNED",
+        DepthMode::NFovUnbinned => "NFOV_UNBINNED",
+        DepthMode::WFov2x2Binned => "WFOV_2X2BINNED",
+        DepthMode::WFovUnbinned => "WFOV_UNBINNED",
+        DepthMode::PassiveIr => "PASSIVE_IR",
+    }
+}
+
+fn wired_sync_mode_name(mode: WiredSyncMode) -> &'static str {
+    match mode {
+        WiredSyncMode::Standalone => "Standalone",
+        WiredSyncMode::Master => "Master",
+        WiredSyncMode::Subordinate => "Subordinate",
+    }
+}
+
+#[test]
+fn config_test() {
+    let config: RecorderConfig = toml::from_str(
+        r#"
+        devices = ["0", "000001000001"]
+        color_format = "BGRA32"
+        color_resolution = "720p"
+        camera_fps = 15
+        imu = false
+        output = "take-{serial}.mkv"
+
+        [color_controls]
+        brightness = { mode = "manual", value = 100 }
+        white_balance = { mode = "auto" }
+
+        [tags]
+        SITE = "lab"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.camera_fps, Some(15));
+    assert_eq!(config.depth_mode, None);
+    assert_eq!(config.tags.as_ref().unwrap()["SITE"], "lab");
+
+    let json: RecorderConfig = serde_json::from_str(
+        r#"{"color_format": "BGRA32", "color_resolution": "720p", "camera_fps": 15}"#,
+    )
+    .unwrap();
+    assert_eq!(json.color_format, config.color_format);
+    assert!(toml::from_str::<RecorderConfig>("frame_rate = 30").is_err());
+
+    let controls = config.color_controls.as_ref().unwrap();
+    assert_eq!(
+        to_color_control("brightness", &controls["brightness"]).unwrap(),
+        (
+            ColorControlCommand::Brightness,
+            ColorControlMode::Manual,
+            100
+        )
+    );
+    assert_eq!(
+        to_color_control("white_balance", &controls["white_balance"])
+            .unwrap()
+            .1,
+        ColorControlMode::Auto
+    );
+    let manual = ColorControlSetting {
+        mode: "manual".to_string(),
+        value: None,
+    };
+    assert!(to_color_control("contrast", &manual).is_err());
+    assert!(to_color_control("zoom", &controls["brightness"]).is_err());
+
+    assert_eq!(to_color_format("bgra32").unwrap(), ImageFormat::BGRA32);
+    assert!(to_color_format("RGB").is_err());
+    assert_eq!(to_color_resolution("OFF").unwrap(), ColorResolution::Off);
+    assert!(to_color_resolution("4k").is_err());
+}
@@ -0,0 +1,491 @@
+use crate::param::{ConcatParameter, TrimParameter};
+use crate::recorder::Error;
+use azure_kinect::playback::Playback;
+use azure_kinect::playback_data_block::PlaybackDataBlock;
+use azure_kinect::record::{Record, RecordSubtitleSettings};
+use azure_kinect::*;
+
+//  Tags the SDK recorder only writes for recordings created with a device, the other K4A_ tags
+//  are written from the configuration.
+const DEVICE_TAGS: [&str; 4] = [
+    "K4A_CALIBRATION_FILE",
+    "K4A_COLOR_FIRMWARE_VERSION",
+    "K4A_DEPTH_FIRMWARE_VERSION",
+    "K4A_DEVICE_SERIAL_NUMBER",
+];
+
+pub(crate) fn do_trim(
+    factory: &FactoryRecord,
+    param: &TrimParameter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let playback = open(factory, &param.path)?;
+    let (record, tracks) = create_record(factory, &param.output, &playback)?;
+    let start_offset_usec = playback
+        .get_record_configuration()?
+        .start_timestamp_offset_usec() as u64;
+    let start_usec = param.start.map_or(0, |s| s.as_micros() as u64);
+    if start_usec > 0 {
+        playback.seek_timestamp(start_usec as i64, SeekOrigin::Begin)?;
+    }
+    let end_usec = param.end.map(|e| start_offset_usec + e.as_micros() as u64);
+    let written = copy(
+        &playback,
+        &record,
+        &tracks,
+        start_offset_usec + start_usec,
+        end_usec,
+        0,
+    )?;
+    record.flush()?;
+    println!("Wrote {} captures to {}", written.captures, param.output);
+    Ok(())
+}
+
+pub(crate) fn do_concat(
+    factory: &FactoryRecord,
+    param: &ConcatParameter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let playbacks = param
+        .paths
+        .iter()
+        .map(|path| open(factory, path))
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = match playbacks.first() {
+        Some(first) => first,
+        None => return Err(Box::new(Error::ErrorStr("No recordings to concatenate."))),
+    };
+    let configuration = first.get_record_configuration()?;
+    for (playback, path) in playbacks.iter().zip(param.paths.iter()).skip(1) {
+        if !is_same_device(first, playback)? {
+            return Err(Box::new(Error::Error(format!(
+                "Recording {} is not from the same device or has another configuration than {}",
+                path, param.paths[0]
+            ))));
+        }
+    }
+
+    let (record, tracks) = create_record(factory, &param.output, first)?;
+    let frame_period_usec = 1_000_000 / configuration.camera_fps().get_u32() as i64;
+    let mut captures = 0;
+    let mut last_timestamp_usec: Option<u64> = None;
+    for playback in playbacks.iter() {
+        let start_usec = playback
+            .get_record_configuration()?
+            .start_timestamp_offset_usec() as u64;
+        //  Each recording starts one frame after the end of the previous one.
+        let offset_usec = last_timestamp_usec.map_or(0, |last| {
+            last as i64 + frame_period_usec - start_usec as i64
+        });
+        let written = copy(playback, &record, &tracks, start_usec, None, offset_usec)?;
+        captures += written.captures;
+        last_timestamp_usec = written.last_timestamp_usec.or(last_timestamp_usec);
+    }
+    record.flush()?;
+    println!(
+        "Wrote {} captures of {} recordings to {}",
+        captures,
+        playbacks.len(),
+        param.output
+    );
+    Ok(())
+}
+
+fn open<'a>(
+    factory: &'a FactoryRecord,
+    path: &str,
+) -> Result<Playback<'a>, Box<dyn std::error::Error>> {
+    match factory.playback_open(path) {
+        Ok(playback) => Ok(playback),
+        Err(_) => Err(Box::new(Error::Error(format!(
+            "Unable to open recording file: {}",
+            path
+        )))),
+    }
+}
+
+fn is_same_device(a: &Playback, b: &Playback) -> Result<bool, Box<dyn std::error::Error>> {
+    let (ca, cb) = (a.get_record_configuration()?, b.get_record_configuration()?);
+    let same_configuration = ca.color_format() == cb.color_format()
+        && ca.color_resolution() == cb.color_resolution()
+        && ca.depth_mode() == cb.depth_mode()
+        && ca.camera_fps() == cb.camera_fps()
+        && ca.color_track_enabled() == cb.color_track_enabled()
+        && ca.depth_track_enabled() == cb.depth_track_enabled()
+        && ca.ir_track_enabled() == cb.ir_track_enabled()
+        && ca.imu_track_enabled() == cb.imu_track_enabled();
+    let custom_tracks = |playback: &Playback| -> Result<Vec<String>, azure_kinect::Error> {
+        let mut names = Vec::new();
+        for i in 0..playback.get_track_count() {
+            let track = playback.get_track(i)?;
+            if !track.is_builtin() {
+                names.push(track.get_name().to_string());
+            }
+        }
+        Ok(names)
+    };
+    Ok(same_configuration
+        && a.get_tag("K4A_DEVICE_SERIAL_NUMBER").ok() == b.get_tag("K4A_DEVICE_SERIAL_NUMBER").ok()
+        && custom_tracks(a)? == custom_tracks(b)?)
+}
+
+/// Creates a recording with the configuration, tags, attachments and custom tracks of `playback`
+/// and writes its header. Returns the names of the custom tracks.
+fn create_record<'a>(
+    factory: &'a FactoryRecord,
+    path: &str,
+    playback: &Playback,
+) -> Result<(Record<'a>, Vec<String>), Box<dyn std::error::Error>> {
+    let configuration = playback.get_record_configuration()?;
+    let record =
+        match factory.record_create_without_device(path, &configuration.device_configuration()) {
+            Ok(record) => record,
+            Err(_) => {
+                return Err(Box::new(Error::Error(format!(
+                    "Unable to create recording file: {}",
+                    path
+                ))))
+            }
+        };
+    let (tags, attachments) = match (playback.tags(), playback.attachments()) {
+        (Ok(tags), Ok(attachments)) => (
+            tags.into_iter()
+                .filter(|(name, _)| {
+                    DEVICE_TAGS.contains(&name.as_str()) || !name.starts_with("K4A_")
+                })
+                .collect(),
+            attachments.into_iter().map(|(name, _)| name).collect(),
+        ),
+        //  Without the file only the tags and attachments with known names can be read
+        _ => {
+            let tags: Vec<(String, String)> = DEVICE_TAGS
+                .iter()
+                .filter_map(|name| {
+                    playback
+                        .get_tag(name)
+                        .ok()
+                        .map(|value| (name.to_string(), value))
+                })
+                .collect();
+            let attachments: Vec<String> = tags
+                .iter()
+                .filter(|(name, _)| name == "K4A_CALIBRATION_FILE")
+                .map(|(_, value)| value.clone())
+                .collect();
+            (tags, attachments)
+        }
+    };
+    for (name, value) in &tags {
+        record.add_tag(name, value)?;
+    }
+    for name in &attachments {
+        record.add_attachment(name, &playback.get_attachment(name)?)?;
+    }
+    if configuration.imu_track_enabled() {
+        record.add_imu_track()?;
+    }
+
+    let mut tracks = Vec::new();
+    for i in 0..playback.get_track_count() {
+        let track = playback.get_track(i)?;
+        if track.is_builtin() {
+            continue;
+        }
+        let codec_id = track.get_codec_id()?;
+        let codec_context = track.get_codec_context().unwrap_or_default();
+        match track.get_video_settings() {
+            Ok(settings) => record.add_custom_video_track(
+                track.get_name(),
+                &codec_id,
+                &codec_context,
+                &settings,
+            )?,
+            //  Playback does not tell whether a subtitle track was written as high frequency data,
+            //  which only changes how the blocks are stored.
+            Err(_) => record.add_custom_subtitle_track(
+                track.get_name(),
+                &codec_id,
+                &codec_context,
+                &RecordSubtitleSettings::new(false),
+            )?,
+        }
+        tracks.push(track.get_name().to_string());
+    }
+    record.write_header()?;
+    Ok((record, tracks))
+}
+
+struct Written {
+    captures: u64,
+    last_timestamp_usec: Option<u64>,
+}
+
+/// Copies the captures, IMU samples and custom track data with device timestamps from `start_usec` to
+/// `end_usec`, shifted by `offset_usec`. The streams are merged in timestamp order, as the recording
+/// is written in clusters of increasing time.
+fn copy(
+    playback: &Playback,
+    record: &Record,
+    tracks: &[String],
+    start_usec: u64,
+    end_usec: Option<u64>,
+    offset_usec: i64,
+) -> Result<Written, Box<dyn std::error::Error>> {
+    let shift = |timestamp: u64| (timestamp as i64 + offset_usec) as u64;
+    let imu_enabled = playback.get_record_configuration()?.imu_track_enabled();
+    let mut capture = next_capture(playback, start_usec)?;
+    let mut imu_sample = if imu_enabled {
+        next_imu_sample(playback, start_usec)?
+    } else {
+        None
+    };
+    let mut blocks = tracks
+        .iter()
+        .map(|track| next_data_block(playback, track, start_usec))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut written = Written {
+        captures: 0,
+        last_timestamp_usec: None,
+    };
+    loop {
+        let capture_timestamp = capture.as_ref().map(|(t, _)| *t);
+        let imu_timestamp = imu_sample.as_ref().map(|s| s.acc_timestamp_usec());
+        let block = blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| b.as_ref().map(|b| (b.get_device_timestamp_usec(), i)))
+            .min();
+        let timestamp = match [capture_timestamp, imu_timestamp, block.map(|b| b.0)]
+            .iter()
+            .flatten()
+            .min()
+        {
+            Some(&t) if end_usec.is_none_or(|end| t <= end) => t,
+            _ => break,
+        };
+
+        if capture_timestamp == Some(timestamp) {
+            if let Some((_, c)) = capture.as_ref() {
+                let mut images = [c.get_color_image(), c.get_depth_image(), c.get_ir_image()];
+                for image in images.iter_mut() {
+                    //  The capture was read by this loop only
+                    if !unsafe { image.get_native_handle() }.is_null() {
+                        let timestamp = shift(image.get_device_timestamp_usec());
+                        unsafe { image.set_device_timestamp_usec(timestamp) };
+                    }
+                }
+                record.write_capture(c)?;
+            }
+            written.captures += 1;
+            capture = next_capture(playback, start_usec)?;
+        } else if imu_timestamp == Some(timestamp) {
+            if let Some(mut sample) = imu_sample.take() {
+                sample.set_acc_timestamp_usec(shift(sample.acc_timestamp_usec()));
+                sample.set_gyro_timestamp_usec(shift(sample.gyro_timestamp_usec()));
+                record.write_imu_sample(&sample)?;
+            }
+            imu_sample = next_imu_sample(playback, start_usec)?;
+        } else if let Some((_, i)) = block {
+            if let Some(b) = blocks[i].as_ref() {
+                record.write_custom_track_data(&tracks[i], shift(timestamp), b.as_bytes())?;
+            }
+            blocks[i] = next_data_block(playback, &tracks[i], start_usec)?;
+        }
+        written.last_timestamp_usec = Some(shift(timestamp));
+    }
+    Ok(written)
+}
+
+//  Each reader skips what lies before `start_usec`, seeking may stop a little earlier.
+
+fn next_capture<'a>(
+    playback: &'a Playback,
+    start_usec: u64,
+) -> Result<Option<(u64, Capture<'a>)>, Box<dyn std::error::Error>> {
+    loop {
+        let capture = match playback.get_next_capture() {
+            Ok(capture) => capture,
+            Err(azure_kinect::Error::Eof) => return Ok(None),
+            Err(e) => return Err(Box::new(e)),
+        };
+        let timestamp = [
+            capture.get_color_image(),
+            capture.get_depth_image(),
+            capture.get_ir_image(),
+        ]
+        .iter()
+        .filter(|image| !unsafe { image.get_native_handle() }.is_null())
+        .map(|image| image.get_device_timestamp_usec())
+        .min();
+        match timestamp {
+            Some(t) if t >= start_usec => return Ok(Some((t, capture))),
+            _ => continue,
+        }
+    }
+}
+
+fn next_imu_sample(
+    playback: &Playback,
+    start_usec: u64,
+) -> Result<Option<ImuSample>, Box<dyn std::error::Error>> {
+    loop {
+        match playback.get_next_imu_sample() {
+            Ok(sample) if sample.acc_timestamp_usec() >= start_usec => return Ok(Some(sample)),
+            Ok(_) => continue,
+            Err(azure_kinect::Error::Eof) => return Ok(None),
+            Err(e) => return Err(Box::new(e)),
+        }
+    }
+}
+
+fn next_data_block<'a>(
+    playback: &'a Playback,
+    track: &str,
+    start_usec: u64,
+) -> Result<Option<PlaybackDataBlock<'a>>, Box<dyn std::error::Error>> {
+    loop {
+        match playback.get_next_data_block(track) {
+            Ok(block) if block.get_device_timestamp_usec() >= start_usec => return Ok(Some(block)),
+            Ok(_) => continue,
+            Err(azure_kinect::Error::Eof) => return Ok(None),
+            Err(e) => return Err(Box::new(e)),
+        }
+    }
+}
+
+#[test]
+fn trim_and_concat_test() -> Result<(), Box<dyn std::error::Error>> {
+    //  Recordings of the fake SDK are kept in memory by path
+    let factory = FactoryRecord::with_api(
+        azure_kinect_sys::mock::api(),
+        azure_kinect_sys::mock::api_record(),
+    );
+    let device = factory.core().device_open(0)?;
+    let config = DeviceConfiguration::builder()
+        .color_format(ImageFormat::BGRA32)
+        .color_resolution(ColorResolution::_720p)
+        .depth_mode(DepthMode::NFov2x2Binned)
+        .camera_fps(Fps::_30fps)
+        .build();
+    let camera = device.start_cameras(&config)?;
+    let imu = camera.start_imu()?;
+    let recording = factory.record_create("edit_test.mkv", &device, &config)?;
+    recording.add_imu_track()?;
+    recording.add_custom_subtitle_track(
+        "NOTES",
+        "S_TEXT/UTF8",
+        &[],
+        &RecordSubtitleSettings::new(false),
+    )?;
+    recording.write_header()?;
+    //  Frames may be dropped when the test runs slowly, so the window is taken from the captures
+    let mut captured = Vec::new();
+    for i in 0..10 {
+        let capture = camera.get_capture(1000)?;
+        let timestamp = capture.get_color_image().get_device_timestamp_usec();
+        captured.push(timestamp);
+        recording.write_capture(&capture)?;
+        recording.write_imu_sample(&imu.get_imu_sample(1000)?)?;
+        recording.write_custom_track_data("NOTES", timestamp, format!("{}", i).as_bytes())?;
+    }
+    recording.flush()?;
+    std::mem::drop(recording);
+
+    let source = factory.playback_open("edit_test.mkv")?;
+    let start_offset_usec = source
+        .get_record_configuration()?
+        .start_timestamp_offset_usec() as u64;
+    let period_usec = 1_000_000 / 30;
+
+    //  Captures 3 to 6
+    do_trim(
+        &factory,
+        &TrimParameter {
+            path: "edit_test.mkv".to_string(),
+            output: "edit_test_trim.mkv".to_string(),
+            start: Some(std::time::Duration::from_micros(
+                captured[3] - start_offset_usec,
+            )),
+            end: Some(std::time::Duration::from_micros(
+                captured[6] - start_offset_usec,
+            )),
+        },
+    )?;
+    let trimmed = factory.playback_open("edit_test_trim.mkv")?;
+    let trimmed_configuration = trimmed.get_record_configuration()?;
+    assert_eq!(
+        trimmed_configuration.start_timestamp_offset_usec() as u64,
+        captured[3]
+    );
+    assert!(trimmed_configuration.imu_track_enabled());
+    assert_eq!(trimmed_configuration.color_format(), ImageFormat::BGRA32);
+    assert_eq!(
+        trimmed.get_raw_calibration()?,
+        source.get_raw_calibration()?
+    );
+    assert_eq!(
+        trimmed.get_tag("K4A_DEVICE_SERIAL_NUMBER")?,
+        source.get_tag("K4A_DEVICE_SERIAL_NUMBER")?
+    );
+    let mut captures = 0;
+    while trimmed.get_next_capture().is_ok() {
+        captures += 1;
+    }
+    assert_eq!(captures, 4);
+    let mut trimmed_imu_samples = 0;
+    while trimmed.get_next_imu_sample().is_ok() {
+        trimmed_imu_samples += 1;
+    }
+    let block = trimmed.get_next_data_block("NOTES")?;
+    assert_eq!(block.as_bytes(), b"3");
+
+    do_concat(
+        &factory,
+        &ConcatParameter {
+            paths: vec![
+                "edit_test.mkv".to_string(),
+                "edit_test_trim.mkv".to_string(),
+            ],
+            output: "edit_test_concat.mkv".to_string(),
+        },
+    )?;
+    let joined = factory.playback_open("edit_test_concat.mkv")?;
+    let mut timestamps = Vec::new();
+    while let Ok(capture) = joined.get_next_capture() {
+        timestamps.push(capture.get_color_image().get_device_timestamp_usec());
+    }
+    assert_eq!(timestamps.len(), 14);
+    assert!(timestamps.windows(2).all(|t| t[0] < t[1]));
+    assert_eq!(timestamps[10] - timestamps[9], period_usec);
+    let mut imu_samples = 0;
+    while joined.get_next_imu_sample().is_ok() {
+        imu_samples += 1;
+    }
+    assert_eq!(imu_samples, 10 + trimmed_imu_samples);
+    let mut notes = Vec::new();
+    while let Ok(block) = joined.get_next_data_block("NOTES") {
+        notes.push(String::from_utf8(block.as_bytes().to_vec())?);
+    }
+    assert_eq!(notes.concat(), "01234567893456");
+
+    //  Recordings of another configuration can not be joined
+    let other_config = DeviceConfiguration::builder()
+        .depth_mode(DepthMode::NFovUnbinned)
+        .build();
+    let other = factory.record_create("edit_test_other.mkv", &device, &other_config)?;
+    other.write_header()?;
+    other.flush()?;
+    std::mem::drop(other);
+    assert!(do_concat(
+        &factory,
+        &ConcatParameter {
+            paths: vec![
+                "edit_test.mkv".to_string(),
+                "edit_test_other.mkv".to_string(),
+            ],
+            output: "edit_test_concat_other.mkv".to_string(),
+        },
+    )
+    .is_err());
+    Ok(())
+}
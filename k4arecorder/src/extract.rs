@@ -0,0 +1,416 @@
+use crate::param::{ExtractParameter, Parameter};
+use crate::recorder::{open_devices, set_color_controls, Error};
+use azure_kinect::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+//  Timeout of a read from a device, a recording returns at once.
+const READ_TIMEOUT_MS: i32 = 1000;
+
+pub(crate) fn do_extract(
+    factory: &FactoryRecord,
+    param: &ExtractParameter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut playback = match factory.playback_open(&param.path) {
+        Ok(playback) => playback,
+        Err(_) => {
+            return Err(Box::new(Error::Error(format!(
+                "Unable to open recording file: {}",
+                param.path
+            ))))
+        }
+    };
+    let configuration = playback.get_record_configuration()?;
+    let start_usec = param.start.map_or(0, |s| s.as_micros() as u64);
+
+    //  Only MJPG can be written without encoding, everything else is converted to BGRA and written as PNG.
+    let color_jpeg = !param.color_png && configuration.color_format() == ImageFormat::MJPG;
+    if param.color && configuration.color_track_enabled() && !color_jpeg {
+        playback.set_color_conversion(ImageFormat::BGRA32)?;
+    }
+    if start_usec > 0 {
+        playback.seek_timestamp(start_usec as i64, SeekOrigin::Begin)?;
+    }
+
+    extract(
+        &playback,
+        param,
+        color_jpeg,
+        param.imu && configuration.imu_track_enabled(),
+        Some(configuration.start_timestamp_offset_usec() as u64),
+    )
+}
+
+/// Extracts from the device of `recorder` started with its recording settings, from the first capture
+/// until `--end`.
+pub(crate) fn do_extract_live(
+    factory: &FactoryRecord,
+    recorder: &Parameter,
+    param: &ExtractParameter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if recorder.devices.len() != 1 {
+        return Err(Box::new(Error::ErrorStr(
+            "Only one device can be extracted from.",
+        )));
+    }
+    let config = &recorder.device_config;
+    let color_jpeg = !param.color_png && config.color_format() == ImageFormat::MJPG;
+    if param.color
+        && config.color_resolution() != ColorResolution::Off
+        && !color_jpeg
+        && config.color_format() != ImageFormat::BGRA32
+    {
+        return Err(Box::new(Error::ErrorStr(
+            "Color frames of a device can only be extracted from BGRA32, or from MJPG as jpg.",
+        )));
+    }
+
+    let devices = open_devices(factory.core(), &recorder.devices)?;
+    set_color_controls(&devices[0], recorder);
+    let camera = devices[0].start_cameras(config)?;
+    let imu = if param.imu {
+        Some(camera.start_imu()?)
+    } else {
+        None
+    };
+    extract(
+        &DeviceSource::new(&camera, imu.as_ref()),
+        param,
+        color_jpeg,
+        param.imu,
+        None,
+    )
+}
+
+/// Writes the captures and IMU samples of a recording or a device. Timestamps are filtered relative to
+/// `start_offset_usec`, or to the first item read if it is None.
+fn extract<S: SensorSource>(
+    source: &S,
+    param: &ExtractParameter,
+    color_jpeg: bool,
+    imu: bool,
+    mut start_offset_usec: Option<u64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let start_usec = param.start.map_or(0, |s| s.as_micros() as u64);
+    let end_usec = param.end.map(|e| e.as_micros() as u64);
+    let mut relative = |device_timestamp_usec: u64| {
+        device_timestamp_usec
+            .saturating_sub(*start_offset_usec.get_or_insert(device_timestamp_usec))
+    };
+    let after_end = |t: u64| end_usec.is_some_and(|end| t > end);
+
+    let output_dir = Path::new(&param.output_dir);
+    std::fs::create_dir_all(output_dir)?;
+    let mut frames_csv = None;
+    if param.color || param.depth || param.ir {
+        let mut csv = BufWriter::new(File::create(output_dir.join("frames.csv"))?);
+        writeln!(csv, "frame,stream,device_timestamp_usec,file")?;
+        frames_csv = Some(csv);
+    }
+    let mut imu_csv = None;
+    if imu {
+        let mut csv = BufWriter::new(File::create(output_dir.join("imu.csv"))?);
+        writeln!(
+            csv,
+            "acc_timestamp_usec,acc_x,acc_y,acc_z,gyro_timestamp_usec,gyro_x,gyro_y,gyro_z,temperature"
+        )?;
+        imu_csv = Some(csv);
+    }
+
+    let mut frame = 0u64;
+    let mut written_captures = 0u64;
+    let mut written_samples = 0u64;
+    let mut captures_done = frames_csv.is_none();
+    let mut imu_done = imu_csv.is_none();
+    while !captures_done || !imu_done {
+        if let Some(csv) = frames_csv.as_mut().filter(|_| !captures_done) {
+            let capture = match source.get_capture(READ_TIMEOUT_MS) {
+                Ok(capture) => Some(capture),
+                Err(azure_kinect::Error::Timeout) => None,
+                Err(azure_kinect::Error::Eof) => {
+                    captures_done = true;
+                    None
+                }
+                Err(e) => return Err(Box::new(e)),
+            };
+            if let Some(capture) = capture {
+                let images = [
+                    ("color", param.color, capture.get_color_image()),
+                    ("depth", param.depth, capture.get_depth_image()),
+                    ("ir", param.ir, capture.get_ir_image()),
+                ];
+                let timestamp = images
+                    .iter()
+                    .filter(|(_, _, image)| !unsafe { image.get_native_handle() }.is_null())
+                    .map(|(_, _, image)| relative(image.get_device_timestamp_usec()))
+                    .min();
+                match timestamp {
+                    Some(t) if after_end(t) => captures_done = true,
+                    Some(t) if t >= start_usec => {
+                        let index = frame;
+                        frame += 1;
+                        if index.is_multiple_of(param.stride as u64) {
+                            write_images(csv, output_dir, index, &images, color_jpeg)?;
+                            written_captures += 1;
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        //  A device queues few IMU samples, they are read between captures. A recording returns all of
+        //  them at once.
+        while let Some(csv) = imu_csv.as_mut().filter(|_| !imu_done) {
+            let timeout_in_ms = if captures_done { READ_TIMEOUT_MS } else { 0 };
+            let sample = match source.get_imu_sample(timeout_in_ms) {
+                Ok(sample) => sample,
+                Err(azure_kinect::Error::Timeout) => break,
+                Err(azure_kinect::Error::Eof) => {
+                    imu_done = true;
+                    break;
+                }
+                Err(e) => return Err(Box::new(e)),
+            };
+            let t = relative(sample.acc_timestamp_usec());
+            if after_end(t) {
+                imu_done = true;
+                break;
+            } else if t < start_usec {
+                continue;
+            }
+            let (acc, gyro) = (sample.acc_sample(), sample.gyro_sample());
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{}",
+                sample.acc_timestamp_usec(),
+                acc.x(),
+                acc.y(),
+                acc.z(),
+                sample.gyro_timestamp_usec(),
+                gyro.x(),
+                gyro.y(),
+                gyro.z(),
+                sample.temperature()
+            )?;
+            written_samples += 1;
+        }
+    }
+
+    if let Some(mut csv) = frames_csv {
+        csv.flush()?;
+        println!("Extracted {} captures", written_captures);
+    }
+    if let Some(mut csv) = imu_csv {
+        csv.flush()?;
+        println!("Extracted {} IMU samples", written_samples);
+    }
+    Ok(())
+}
+
+/// Writes the enabled images of a capture and lists them in frames.csv.
+fn write_images(
+    csv: &mut impl Write,
+    output_dir: &Path,
+    index: u64,
+    images: &[(&str, bool, Image); 3],
+    color_jpeg: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for (stream, enabled, image) in images.iter() {
+        if !enabled || unsafe { image.get_native_handle() }.is_null() {
+            continue;
+        }
+        let file = if *stream == "color" && color_jpeg {
+            let file = format!("{}_{:06}.jpg", stream, index);
+            std::fs::write(output_dir.join(&file), image.as_bytes())?;
+            file
+        } else {
+            let file = format!("{}_{:06}.png", stream, index);
+            write_png(&output_dir.join(&file), image)?;
+            file
+        };
+        writeln!(
+            csv,
+            "{},{},{},{}",
+            index,
+            stream,
+            image.get_device_timestamp_usec(),
+            file
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes a BGRA32 image as 8-bit RGBA, DEPTH16 and IR16 images as 16-bit grayscale.
+fn write_png(path: &Path, image: &Image) -> Result<(), Box<dyn std::error::Error>> {
+    let width = image.get_width_pixels() as u32;
+    let height = image.get_height_pixels() as u32;
+    let (color_type, bit_depth, data) = match image.get_format() {
+        ImageFormat::BGRA32 => (
+            png::ColorType::Rgba,
+            png::BitDepth::Eight,
+            image
+                .bgra32()?
+                .flat_map(|row| row.iter().flat_map(|p| [p[2], p[1], p[0], p[3]]))
+                .collect::<Vec<_>>(),
+        ),
+        ImageFormat::Depth16 | ImageFormat::IR16 => {
+            let rows = if image.get_format() == ImageFormat::Depth16 {
+                image.depth16()?
+            } else {
+                image.ir16()?
+            };
+            (
+                png::ColorType::Grayscale,
+                png::BitDepth::Sixteen,
+                rows.flat_map(|row| row.iter().flat_map(|v| v.to_be_bytes()))
+                    .collect::<Vec<_>>(),
+            )
+        }
+        format => {
+            return Err(Box::new(Error::Error(format!(
+                "Unsupported image format: {:?}",
+                format
+            ))))
+        }
+    };
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+    encoder.write_header()?.write_image_data(&data)?;
+    Ok(())
+}
+
+#[test]
+fn extract_test() -> Result<(), Box<dyn std::error::Error>> {
+    //  Recordings of the fake SDK are kept in memory by path
+    let factory = FactoryRecord::with_api(
+        azure_kinect_sys::mock::api(),
+        azure_kinect_sys::mock::api_record(),
+    );
+    let device = factory.core().device_open(0)?;
+    let config = DeviceConfiguration::builder()
+        .color_format(ImageFormat::BGRA32)
+        .color_resolution(ColorResolution::_720p)
+        .depth_mode(DepthMode::NFov2x2Binned)
+        .build();
+    let camera = device.start_cameras(&config)?;
+    let imu = camera.start_imu()?;
+    let recording = factory.record_create("extract_test.mkv", &device, &config)?;
+    recording.add_imu_track()?;
+    recording.write_header()?;
+    for _ in 0..4 {
+        recording.write_capture(&camera.get_capture(1000)?)?;
+        recording.write_imu_sample(&imu.get_imu_sample(1000)?)?;
+    }
+    recording.flush()?;
+    std::mem::drop(recording);
+
+    let output_dir = std::env::temp_dir().join("k4arecorder_extract_test");
+    let _ = std::fs::remove_dir_all(&output_dir);
+    let param = ExtractParameter {
+        path: "extract_test.mkv".to_string(),
+        live: false,
+        output_dir: output_dir.to_string_lossy().to_string(),
+        start: None,
+        end: None,
+        stride: 2,
+        color: true,
+        depth: true,
+        ir: false,
+        imu: true,
+        color_png: false,
+    };
+    do_extract(&factory, &param)?;
+
+    let frames = std::fs::read_to_string(output_dir.join("frames.csv"))?;
+    let lines: Vec<&str> = frames.lines().collect();
+    assert_eq!(lines.len(), 1 + 2 * 2);
+    assert!(lines[1].starts_with("0,color,"));
+    assert!(lines[1].ends_with(",color_000000.png"));
+    assert!(lines[4].starts_with("2,depth,"));
+    assert!(output_dir.join("depth_000002.png").exists());
+    assert!(!output_dir.join("ir_000000.png").exists());
+    let imu_samples = std::fs::read_to_string(output_dir.join("imu.csv"))?;
+    assert_eq!(imu_samples.lines().count(), 1 + 4);
+
+    let decoder = png::Decoder::new(File::open(output_dir.join("depth_000000.png"))?);
+    let reader = decoder.read_info()?;
+    assert_eq!(reader.info().bit_depth, png::BitDepth::Sixteen);
+    assert_eq!((reader.info().width, reader.info().height), (320, 288));
+    std::fs::remove_dir_all(&output_dir)?;
+    Ok(())
+}
+
+#[test]
+fn extract_live_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = FactoryRecord::with_api(
+        azure_kinect_sys::mock::api(),
+        azure_kinect_sys::mock::api_record(),
+    );
+    let recorder = Parameter {
+        list_device: false,
+        dump_config: false,
+        info: None,
+        extract: None,
+        trim: None,
+        concat: None,
+        devices: vec!["1".to_string()],
+        recording_filename: String::new(),
+        recording_length: None,
+        device_config: DeviceConfiguration::builder()
+            .color_format(ImageFormat::BGRA32)
+            .color_resolution(ColorResolution::_720p)
+            .depth_mode(DepthMode::NFov2x2Binned)
+            .camera_fps(Fps::_30fps)
+            .build(),
+        sync_delays: vec![0],
+        record_imu: true,
+        absolute_exposure_value: None,
+        gain: None,
+        color_controls: Vec::new(),
+        tags: Vec::new(),
+    };
+    let output_dir = std::env::temp_dir().join("k4arecorder_extract_live_test");
+    let _ = std::fs::remove_dir_all(&output_dir);
+    let param = ExtractParameter {
+        path: String::new(),
+        live: true,
+        output_dir: output_dir.to_string_lossy().to_string(),
+        start: None,
+        end: Some(std::time::Duration::from_millis(100)),
+        stride: 1,
+        color: false,
+        depth: true,
+        ir: false,
+        imu: true,
+        color_png: false,
+    };
+    do_extract_live(&factory, &recorder, &param)?;
+
+    //  The captures of the first 100 ms are written, the same as from a recording. How many depends on
+    //  the scheduling of the device thread, the timestamps are relative to the first capture.
+    let frames = std::fs::read_to_string(output_dir.join("frames.csv"))?;
+    let timestamps: Vec<u64> = frames
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').nth(2).unwrap().parse().unwrap())
+        .collect();
+    assert!(!timestamps.is_empty());
+    assert!(frames.lines().nth(1).unwrap().starts_with("0,depth,"));
+    assert!(output_dir.join("depth_000000.png").exists());
+    assert!(timestamps.windows(2).all(|t| t[0] < t[1]));
+    let window_end = timestamps[0] + 100_000;
+    assert!(timestamps.iter().all(|t| *t <= window_end));
+    let imu_samples = std::fs::read_to_string(output_dir.join("imu.csv"))?;
+    let acc_timestamps: Vec<u64> = imu_samples
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').next().unwrap().parse().unwrap())
+        .collect();
+    assert!(!acc_timestamps.is_empty());
+    assert!(acc_timestamps.iter().all(|t| *t <= window_end));
+    std::fs::remove_dir_all(&output_dir)?;
+    Ok(())
+}
@@ -0,0 +1,405 @@
+use crate::param::InfoParameter;
+use crate::recorder::Error;
+use azure_kinect::playback::{Playback, RecordConfiguration};
+use azure_kinect::record::RecordVideoSettings;
+use azure_kinect::*;
+use serde_json::{json, Value};
+
+//  Tags written by the SDK recorder, read by name when the tags of the file cannot be listed.
+const K4A_TAGS: [&str; 16] = [
+    "K4A_COLOR_MODE",
+    "K4A_DEPTH_MODE",
+    "K4A_IR_MODE",
+    "K4A_IMU_MODE",
+    "K4A_CALIBRATION_FILE",
+    "K4A_DEPTH_DELAY_NS",
+    "K4A_WIRED_SYNC_MODE",
+    "K4A_SUBORDINATE_DELAY_NS",
+    "K4A_COLOR_FIRMWARE_VERSION",
+    "K4A_DEPTH_FIRMWARE_VERSION",
+    "K4A_DEVICE_SERIAL_NUMBER",
+    "K4A_START_OFFSET_NS",
+    "K4A_COLOR_TRACK",
+    "K4A_DEPTH_TRACK",
+    "K4A_IR_TRACK",
+    "K4A_IMU_TRACK",
+];
+
+pub(crate) struct TrackInfo {
+    pub name: String,
+    pub builtin: bool,
+    pub codec_id: String,
+    pub video_settings: Option<RecordVideoSettings>,
+    pub frame_count: u64,
+}
+
+pub(crate) struct RecordingInfo {
+    pub path: String,
+    pub configuration: RecordConfiguration,
+    pub recording_length_usec: u64,
+    pub tracks: Vec<TrackInfo>,
+    pub tags: Vec<(String, String)>,
+    pub attachments: Vec<String>,
+}
+
+pub(crate) fn print_info(
+    factory: &FactoryRecord,
+    param: &InfoParameter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let info = read_info(factory, &param.path)?;
+    if param.json {
+        println!("{}", serde_json::to_string_pretty(&to_json(&info))?);
+    } else {
+        print_text(&info);
+    }
+    Ok(())
+}
+
+pub(crate) fn read_info(
+    factory: &FactoryRecord,
+    path: &str,
+) -> Result<RecordingInfo, Box<dyn std::error::Error>> {
+    let playback = match factory.playback_open(path) {
+        Ok(playback) => playback,
+        Err(_) => {
+            return Err(Box::new(Error::Error(format!(
+                "Unable to open recording file: {}",
+                path
+            ))))
+        }
+    };
+    let configuration = playback.get_record_configuration()?;
+
+    let mut tracks = Vec::new();
+    for i in 0..playback.get_track_count() {
+        let track = playback.get_track(i)?;
+        tracks.push(TrackInfo {
+            name: track.get_name().to_string(),
+            builtin: track.is_builtin(),
+            codec_id: track.get_codec_id().unwrap_or_default(),
+            video_settings: track.get_video_settings().ok(),
+            frame_count: 0,
+        });
+    }
+    //  A block holds an image or a data block, counted from the index of the file. Without the file
+    //  the playback is read through.
+    match playback.block_counts() {
+        Ok(counts) => {
+            for track in tracks.iter_mut() {
+                track.frame_count = match (track.builtin, track.name.as_str()) {
+                    (true, "IMU") => count_imu_samples(&playback)?,
+                    (_, name) => counts
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .map_or(0, |(_, count)| *count),
+                };
+            }
+        }
+        Err(_) => {
+            let [color, depth, ir] = count_images(&playback)?;
+            for track in tracks.iter_mut() {
+                track.frame_count = match (track.builtin, track.name.as_str()) {
+                    (true, "COLOR") => color,
+                    (true, "DEPTH") => depth,
+                    (true, "IR") => ir,
+                    (true, "IMU") => count_imu_samples(&playback)?,
+                    (true, _) => 0,
+                    (false, name) => count_data_blocks(&playback, name)?,
+                };
+            }
+        }
+    }
+
+    let (tags, attachments) = match (playback.tags(), playback.attachments()) {
+        (Ok(tags), Ok(attachments)) => (
+            tags,
+            attachments.into_iter().map(|(name, _)| name).collect(),
+        ),
+        _ => read_known_tags(&playback),
+    };
+
+    Ok(RecordingInfo {
+        path: path.to_string(),
+        configuration,
+        recording_length_usec: playback.get_recording_length_usec(),
+        tracks,
+        tags,
+        attachments,
+    })
+}
+
+/// Reads the tags written by the SDK recorder and the calibration attachment by name.
+fn read_known_tags(playback: &Playback) -> (Vec<(String, String)>, Vec<String>) {
+    let tags: Vec<(String, String)> = K4A_TAGS
+        .iter()
+        .filter_map(|name| {
+            playback
+                .get_tag(name)
+                .ok()
+                .map(|value| (name.to_string(), value))
+        })
+        .collect();
+    let attachments = tags
+        .iter()
+        .filter(|(name, _)| name == "K4A_CALIBRATION_FILE")
+        .filter(|(_, value)| playback.get_attachment(value).is_ok())
+        .map(|(_, value)| value.clone())
+        .collect();
+    (tags, attachments)
+}
+
+fn count_images(playback: &Playback) -> Result<[u64; 3], Box<dyn std::error::Error>> {
+    let mut counts = [0u64; 3];
+    loop {
+        let capture = match playback.get_next_capture() {
+            Ok(capture) => capture,
+            Err(azure_kinect::Error::Eof) => break,
+            Err(e) => return Err(Box::new(e)),
+        };
+        let images = [
+            capture.get_color_image(),
+            capture.get_depth_image(),
+            capture.get_ir_image(),
+        ];
+        for (count, image) in counts.iter_mut().zip(images.iter()) {
+            if !unsafe { image.get_native_handle() }.is_null() {
+                *count += 1;
+            }
+        }
+    }
+    Ok(counts)
+}
+
+fn count_imu_samples(playback: &Playback) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut count = 0;
+    loop {
+        match playback.get_next_imu_sample() {
+            Ok(_) => count += 1,
+            Err(azure_kinect::Error::Eof) => return Ok(count),
+            Err(e) => return Err(Box::new(e)),
+        }
+    }
+}
+
+fn count_data_blocks(playback: &Playback, track: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut count = 0;
+    loop {
+        match playback.get_next_data_block(track) {
+            Ok(_) => count += 1,
+            Err(azure_kinect::Error::Eof) => return Ok(count),
+            Err(e) => return Err(Box::new(e)),
+        }
+    }
+}
+
+fn color_mode(configuration: &RecordConfiguration) -> String {
+    if !configuration.color_track_enabled() {
+        return "OFF".to_string();
+    }
+    let resolution = match configuration.color_resolution() {
+        ColorResolution::Off => "OFF",
+        ColorResolution::_720p => "720p",
+        ColorResolution::_1080p => "1080p",
+        ColorResolution::_1440p => "1440p",
+        ColorResolution::_1536p => "1536p",
+        ColorResolution::_2160p => "2160p",
+        ColorResolution::_3072p => "3072p",
+    };
+    match configuration.color_format() {
+        ImageFormat::MJPG => resolution.to_string(),
+        format => format!("{}_{:?}", resolution, format),
+    }
+}
+
+fn depth_mode(configuration: &RecordConfiguration) -> &'static str {
+    if !configuration.depth_track_enabled() && !configuration.ir_track_enabled() {
+        return "OFF";
+    }
+    match configuration.depth_mode() {
+        DepthMode::Off => "OFF",
+        DepthMode::NFov2x2Binned => "NFOV_2X2BINNED",
+        DepthMode::NFovUnbinned => "NFOV_UNBINNED",
+        DepthMode::WFov2x2Binned => "WFOV_2X2BINNED",
+        DepthMode::WFovUnbinned => "WFOV_UNBINNED",
+        DepthMode::PassiveIr => "PASSIVE_IR",
+    }
+}
+
+fn wired_sync_mode(configuration: &RecordConfiguration) -> &'static str {
+    match configuration.wired_sync_mode() {
+        WiredSyncMode::Standalone => "Standalone",
+        WiredSyncMode::Master => "Master",
+        WiredSyncMode::Subordinate => "Subordinate",
+    }
+}
+
+pub(crate) fn to_json(info: &RecordingInfo) -> Value {
+    let c = &info.configuration;
+    json!({
+        "path": info.path,
+        "configuration": {
+            "color_mode": color_mode(c),
+            "depth_mode": depth_mode(c),
+            "camera_fps": c.camera_fps().get_u32(),
+            "color_track_enabled": c.color_track_enabled(),
+            "depth_track_enabled": c.depth_track_enabled(),
+            "ir_track_enabled": c.ir_track_enabled(),
+            "imu_track_enabled": c.imu_track_enabled(),
+            "depth_delay_off_color_usec": c.depth_delay_off_color_usec(),
+            "wired_sync_mode": wired_sync_mode(c),
+            "subordinate_delay_off_master_usec": c.subordinate_delay_off_master_usec(),
+            "start_timestamp_offset_usec": c.start_timestamp_offset_usec(),
+        },
+        "recording_length_usec": info.recording_length_usec,
+        "tracks": info.tracks.iter().map(|t| json!({
+            "name": t.name,
+            "builtin": t.builtin,
+            "codec_id": t.codec_id,
+            "video_settings": t.video_settings.as_ref().map(|v| json!({
+                "width": v.width(),
+                "height": v.height(),
+                "frame_rate": v.frame_rate(),
+            })),
+            "frame_count": t.frame_count,
+        })).collect::<Vec<_>>(),
+        "tags": info.tags.iter().map(|(name, value)| (name.clone(), json!(value)))
+            .collect::<serde_json::Map<_, _>>(),
+        "attachments": info.attachments,
+    })
+}
+
+fn print_text(info: &RecordingInfo) {
+    let c = &info.configuration;
+    println!("File: {}", info.path);
+    println!(
+        "Recording length: {:.3} s",
+        info.recording_length_usec as f64 / 1_000_000.0
+    );
+    println!("Color mode: {}", color_mode(c));
+    println!("Depth mode: {}", depth_mode(c));
+    println!("Frame rate: {}", c.camera_fps().get_u32());
+    println!(
+        "Tracks: color {}, depth {}, IR {}, IMU {}",
+        on_off(c.color_track_enabled()),
+        on_off(c.depth_track_enabled()),
+        on_off(c.ir_track_enabled()),
+        on_off(c.imu_track_enabled())
+    );
+    println!(
+        "Depth delay off color: {} us",
+        c.depth_delay_off_color_usec()
+    );
+    println!("External sync mode: {}", wired_sync_mode(c));
+    println!(
+        "Subordinate delay off master: {} us",
+        c.subordinate_delay_off_master_usec()
+    );
+    println!(
+        "Start timestamp offset: {} us",
+        c.start_timestamp_offset_usec()
+    );
+
+    println!("Tracks ({}):", info.tracks.len());
+    for t in &info.tracks {
+        print!(
+            "\t{}{}: codec {}, {} frames",
+            t.name,
+            if t.builtin { " (builtin)" } else { "" },
+            t.codec_id,
+            t.frame_count
+        );
+        if let Some(v) = t.video_settings.as_ref() {
+            print!(", {}x{} @ {} fps", v.width(), v.height(), v.frame_rate());
+        }
+        println!();
+    }
+    println!("Tags ({}):", info.tags.len());
+    for (name, value) in &info.tags {
+        println!("\t{} = {}", name, value);
+    }
+    println!("Attachments ({}):", info.attachments.len());
+    for name in &info.attachments {
+        println!("\t{}", name);
+    }
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "ON"
+    } else {
+        "OFF"
+    }
+}
+
+#[test]
+fn read_info_test() -> Result<(), Box<dyn std::error::Error>> {
+    //  Recordings of the fake SDK are kept in memory by path
+    let factory = FactoryRecord::with_api(
+        azure_kinect_sys::mock::api(),
+        azure_kinect_sys::mock::api_record(),
+    );
+    let device = factory.core().device_open(0)?;
+    let config = DeviceConfiguration::builder()
+        .color_resolution(ColorResolution::_720p)
+        .depth_mode(DepthMode::NFovUnbinned)
+        .build();
+    let camera = device.start_cameras(&config)?;
+    let recording = factory.record_create("info_test.mkv", &device, &config)?;
+    recording.add_imu_track()?;
+    recording.write_header()?;
+    for _ in 0..3 {
+        recording.write_capture(&camera.get_capture(1000)?)?;
+    }
+    recording.flush()?;
+    std::mem::drop(recording);
+
+    let info = read_info(&factory, "info_test.mkv")?;
+    assert_eq!(color_mode(&info.configuration), "720p");
+    assert_eq!(depth_mode(&info.configuration), "NFOV_UNBINNED");
+    let color = info.tracks.iter().find(|t| t.name == "COLOR").unwrap();
+    assert!(color.builtin);
+    assert_eq!(color.frame_count, 3);
+    assert_eq!(color.video_settings.as_ref().unwrap().width(), 1280);
+    assert!(info.tags.iter().any(|(name, _)| name == "K4A_DEPTH_MODE"));
+
+    let json = to_json(&info);
+    assert_eq!(json["configuration"]["depth_mode"], "NFOV_UNBINNED");
+    assert_eq!(json["tags"]["K4A_DEPTH_MODE"], "NFOV_UNBINNED");
+    assert!(read_info(&factory, "not_found.mkv").is_err());
+    Ok(())
+}
+
+#[test]
+fn read_info_block_counts_test() -> Result<(), Box<dyn std::error::Error>> {
+    let factory = FactoryRecord::with_api(
+        azure_kinect_sys::mock::api(),
+        azure_kinect_sys::mock::api_record(),
+    );
+    let device = factory.core().device_open(1)?;
+    let config = DeviceConfiguration::builder()
+        .color_resolution(ColorResolution::Off)
+        .depth_mode(DepthMode::NFov2x2Binned)
+        .build();
+    let camera = device.start_cameras(&config)?;
+    let path = std::env::temp_dir().join("info_block_counts_test.mkv");
+    let path = path.to_str().unwrap();
+
+    //  The fake SDK opens its recording in memory, the frames are counted in the file
+    let mut mkv = azure_kinect::mkv::MkvRecord::create(path, &config)?;
+    mkv.write_header()?;
+    for _ in 0..5 {
+        mkv.write_capture(&OwnedCapture::from_capture(&camera.get_capture(1000)?))?;
+    }
+    mkv.close()?;
+    let recording = factory.record_create(path, &device, &config)?;
+    recording.write_header()?;
+    recording.write_capture(&camera.get_capture(1000)?)?;
+    recording.flush()?;
+    std::mem::drop(recording);
+
+    let info = read_info(&factory, path)?;
+    std::fs::remove_file(path)?;
+    let depth = info.tracks.iter().find(|t| t.name == "DEPTH").unwrap();
+    assert_eq!(depth.frame_count, 5);
+    Ok(())
+}
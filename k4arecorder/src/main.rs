@@ -1,3 +1,7 @@
+mod config;
+mod edit;
+mod extract;
+mod info;
 mod param;
 mod recorder;
 use crate::recorder::do_recording;
@@ -16,10 +20,27 @@ fn main() {
 
 fn main2() -> Result<(), Box<dyn std::error::Error>> {
     let param = param::Parameter::new()?;
+    if param.dump_config {
+        print!(
+            "{}",
+            config::RecorderConfig::from_parameter(&param).to_toml()?
+        );
+        return Ok(());
+    }
 
     let factory = FactoryRecord::new()?;
     if param.list_device {
         list_devices(&factory);
+    } else if let Some(info) = param.info.as_ref() {
+        info::print_info(&factory, info)?;
+    } else if let Some(extract) = param.extract.as_ref().filter(|e| e.live) {
+        extract::do_extract_live(&factory, &param, extract)?;
+    } else if let Some(extract) = param.extract.as_ref() {
+        extract::do_extract(&factory, extract)?;
+    } else if let Some(trim) = param.trim.as_ref() {
+        edit::do_trim(&factory, trim)?;
+    } else if let Some(concat) = param.concat.as_ref() {
+        edit::do_concat(&factory, concat)?;
     } else {
         let request_abort1 = std::sync::Arc::new(atomic::AtomicBool::new(false));
         let request_abort2 = request_abort1.clone();
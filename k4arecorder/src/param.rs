@@ -1,17 +1,57 @@
+use crate::config::{to_color_control, to_color_format, to_color_resolution, RecorderConfig};
 use crate::recorder::Error;
 use azure_kinect::*;
-use clap::{App, Arg, ArgMatches};
+use clap::{App, Arg, ArgMatches, SubCommand};
 use std::time::Duration;
 
+pub struct InfoParameter {
+    pub path: String,
+    pub json: bool,
+}
+
+pub struct ExtractParameter {
+    pub path: String,
+    pub live: bool,
+    pub output_dir: String,
+    pub start: Option<Duration>,
+    pub end: Option<Duration>,
+    pub stride: u32,
+    pub color: bool,
+    pub depth: bool,
+    pub ir: bool,
+    pub imu: bool,
+    pub color_png: bool,
+}
+
+pub struct TrimParameter {
+    pub path: String,
+    pub output: String,
+    pub start: Option<Duration>,
+    pub end: Option<Duration>,
+}
+
+pub struct ConcatParameter {
+    pub paths: Vec<String>,
+    pub output: String,
+}
+
 pub struct Parameter {
     pub list_device: bool,
-    pub device_index: u32,
+    pub dump_config: bool,
+    pub info: Option<InfoParameter>,
+    pub extract: Option<ExtractParameter>,
+    pub trim: Option<TrimParameter>,
+    pub concat: Option<ConcatParameter>,
+    pub devices: Vec<String>,
     pub recording_filename: String,
     pub recording_length: Option<Duration>,
     pub device_config: DeviceConfiguration,
+    pub sync_delays: Vec<u32>,
     pub record_imu: bool,
     pub absolute_exposure_value: Option<i32>,
     pub gain: Option<i32>,
+    pub color_controls: Vec<(ColorControlCommand, ColorControlMode, i32)>,
+    pub tags: Vec<(String, String)>,
 }
 
 impl<'a> Parameter {
@@ -19,10 +59,17 @@ impl<'a> Parameter {
         let p = Parameter::from(create_app().get_matches());
 
         if let Ok(r) = p.as_ref() {
-            if !r.list_device && r.recording_filename.len() == 0 {
+            if !r.list_device
+                && !r.dump_config
+                && r.info.is_none()
+                && r.extract.is_none()
+                && r.trim.is_none()
+                && r.concat.is_none()
+                && r.recording_filename.is_empty()
+            {
                 create_app()
                     .print_help()
-                    .or_else(|_| Err(Error::ErrorStr("err")))?;
+                    .map_err(|_| Error::ErrorStr("err"))?;
                 std::process::exit(1);
             }
         }
@@ -31,42 +78,153 @@ impl<'a> Parameter {
     }
 
     fn from(args: ArgMatches) -> Result<Parameter, Error> {
-        let format_resolution = to_format_and_resolution(args.value_of("color-mode").unwrap())?;
+        let config = match args.value_of("config") {
+            Some(path) => RecorderConfig::load(path)?,
+            None => RecorderConfig::default(),
+        };
+        //  Flags given on the command line override the config file, which overrides the defaults.
+        let flag = |name: &str| {
+            if args.occurrences_of(name) > 0 {
+                args.value_of(name)
+            } else {
+                None
+            }
+        };
+        let value = |name: &str, config: Option<String>| -> String {
+            flag(name)
+                .map(|v| v.to_string())
+                .or(config)
+                .unwrap_or_else(|| args.value_of(name).unwrap_or("").to_string())
+        };
+
+        let format_resolution = match flag("color-mode") {
+            Some(color_mode) => to_format_and_resolution(color_mode)?,
+            None => {
+                let (format, resolution) =
+                    to_format_and_resolution(args.value_of("color-mode").unwrap())?;
+                (
+                    match config.color_format.as_ref() {
+                        Some(format) => to_color_format(format)?,
+                        None => format,
+                    },
+                    match config.color_resolution.as_ref() {
+                        Some(resolution) => to_color_resolution(resolution)?,
+                        None => resolution,
+                    },
+                )
+            }
+        };
+        let sync_delays = to_sync_delays(&value(
+            "sync-delay",
+            config
+                .subordinate_delay_off_master_usec
+                .as_ref()
+                .map(|delays| {
+                    delays
+                        .iter()
+                        .map(|d| d.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                }),
+        ))?;
+
+        let mut absolute_exposure_value = None;
+        let mut gain = None;
+        let mut color_controls = Vec::new();
+        for (name, setting) in config.color_controls.iter().flatten() {
+            let (command, mode, value) = to_color_control(name, setting)?;
+            let manual_value = Some(value).filter(|_| mode == ColorControlMode::Manual);
+            match command {
+                ColorControlCommand::ExposureTimeAbsolute => absolute_exposure_value = manual_value,
+                ColorControlCommand::Gain => gain = manual_value,
+                _ => color_controls.push((command, mode, value)),
+            }
+        }
+        if flag("exposure-control").is_some() {
+            absolute_exposure_value = correct_param_range(flag("exposure-control"), 2, 200000);
+        }
+        if flag("gain").is_some() {
+            gain = correct_param_range(flag("gain"), 0, 255);
+        }
+
         let param = Parameter {
             list_device: args.is_present("list"),
-            device_index: args.value_of("device").unwrap_or("0").parse().unwrap_or(0),
-            recording_filename: args.value_of("OUTPUT").unwrap_or("").to_string(),
-            recording_length: correct_param::<u64, _, _>(args.value_of("record-length"), |value| {
-                Duration::from_secs(std::cmp::max(0, value))
+            dump_config: args.is_present("dump-config"),
+            info: args.subcommand_matches("info").map(|args| InfoParameter {
+                path: args.value_of("FILE").unwrap_or("").to_string(),
+                json: args.is_present("json"),
             }),
+            extract: match args.subcommand_matches("extract") {
+                Some(args) => Some(to_extract_parameter(args)?),
+                None => None,
+            },
+            trim: match args.subcommand_matches("trim") {
+                Some(args) => Some(to_trim_parameter(args)?),
+                None => None,
+            },
+            concat: args
+                .subcommand_matches("concat")
+                .map(|args| ConcatParameter {
+                    paths: args
+                        .values_of("FILE")
+                        .map(|files| files.map(|f| f.to_string()).collect())
+                        .unwrap_or_default(),
+                    output: args.value_of("output").unwrap_or("").to_string(),
+                }),
+            devices: to_devices(&value(
+                "device",
+                config.devices.as_ref().map(|devices| devices.join(",")),
+            ))?,
+            recording_filename: args
+                .value_of("OUTPUT")
+                .map(|output| output.to_string())
+                .or_else(|| config.output.clone())
+                .unwrap_or_default(),
+            recording_length: correct_param::<u64, _, _>(
+                Some(&value(
+                    "record-length",
+                    config.record_length.map(|l| l.to_string()),
+                )),
+                |value| Duration::from_secs(std::cmp::max(0, value)),
+            ),
             device_config: DeviceConfiguration::builder()
                 .color_format(format_resolution.0)
                 .color_resolution(format_resolution.1)
-                .depth_mode(to_depth_mode(args.value_of("depth-mode").unwrap())?)
-                .camera_fps(to_frame_rate(args.value_of("rate").unwrap())?)
-                .synchronized_images_only(false)
+                .depth_mode(to_depth_mode(&value(
+                    "depth-mode",
+                    config.depth_mode.clone(),
+                ))?)
+                .camera_fps(to_frame_rate(&value(
+                    "rate",
+                    config.camera_fps.map(|fps| fps.to_string()),
+                ))?)
+                .synchronized_images_only(config.synchronized_images_only.unwrap_or(false))
                 .depth_delay_off_color_usec(
-                    args.value_of("depth-delay")
-                        .unwrap_or("0")
-                        .parse()
-                        .unwrap_or(0),
-                )
-                .wired_sync_mode(to_external_sync(args.value_of("external-sync").unwrap())?)
-                .subordinate_delay_off_master_usec(
-                    args.value_of("sync-delay")
-                        .unwrap_or("0")
-                        .parse()
-                        .unwrap_or(0),
+                    value(
+                        "depth-delay",
+                        config.depth_delay_off_color_usec.map(|d| d.to_string()),
+                    )
+                    .parse()
+                    .unwrap_or(0),
                 )
-                .disable_streaming_indicator(false)
+                .wired_sync_mode(to_external_sync(&value(
+                    "external-sync",
+                    config.wired_sync_mode.clone(),
+                ))?)
+                .subordinate_delay_off_master_usec(sync_delays[0])
+                .disable_streaming_indicator(config.disable_streaming_indicator.unwrap_or(false))
                 .build(),
-            record_imu: to_imu_mode(args.value_of("imu").unwrap_or("ON"))?,
-            absolute_exposure_value: correct_param_range(
-                args.value_of("exposure-control"),
-                2,
-                200000,
-            ),
-            gain: correct_param_range(args.value_of("gain"), 0, 255),
+            sync_delays,
+            record_imu: to_imu_mode(&value(
+                "imu",
+                config
+                    .imu
+                    .map(|imu| if imu { "ON" } else { "OFF" }.to_string()),
+            ))?,
+            absolute_exposure_value,
+            gain,
+            color_controls,
+            tags: config.tags.clone().into_iter().flatten().collect(),
         };
 
         if param.device_config.camera_fps() == Fps::_30fps
@@ -78,7 +236,22 @@ impl<'a> Parameter {
             ));
         }
 
-        if param.device_config.subordinate_delay_off_master_usec() > 0
+        if param.devices.len() > 1 {
+            if param.device_config.wired_sync_mode() != WiredSyncMode::Standalone {
+                return Err(Error::ErrorStr(
+                    "--external-sync can not be set for several devices, the sync cables decide the master.",
+                ));
+            }
+            if param.sync_delays.len() != 1 && param.sync_delays.len() != param.devices.len() {
+                return Err(Error::ErrorStr(
+                    "--sync-delay must have one value, or one value for each device.",
+                ));
+            }
+        } else if param.sync_delays.len() > 1 {
+            return Err(Error::ErrorStr(
+                "--sync-delay must have one value for a single device.",
+            ));
+        } else if param.device_config.subordinate_delay_off_master_usec() > 0
             && param.device_config.wired_sync_mode() != WiredSyncMode::Subordinate
         {
             return Err(Error::ErrorStr(
@@ -98,7 +271,7 @@ fn create_app<'a, 'b>() -> App<'a, 'b> {
         )
         .arg(Arg::with_name("device")
             .long("device")
-            .help("Specify the device index to use")
+            .help("Specify the device index or serial number to use\nSeveral comma separated devices are recorded synchronized, one file each.\nThe device with only the sync out cable connected is the master.")
             .default_value("0"))
         .arg(Arg::with_name("record-length")
             .long("record-length")
@@ -134,18 +307,145 @@ fn create_app<'a, 'b>() -> App<'a, 'b> {
             .default_value("Standalone"))
         .arg(Arg::with_name("sync-delay")
             .long("sync-delay")
-            .help("Set the external sync delay off the master camera in microseconds\nThis setting is only valid if the camera is in Subordinate mode.")
+            .help("Set the external sync delay off the master camera in microseconds\nThis setting is only valid if the camera is in Subordinate mode.\nFor several devices, one value applies to all subordinates, or give comma separated values in the order of --device.")
             .default_value("0"))
         .arg(Arg::with_name("exposure-control")
             .long("exposure-control")
             .short("e")
-            .help("Set manual exposure value from 2 us to 200,000us for the RGB camera (default: auto exposure). This control also supports MFC settings of -11 to 1)."))
+            .help("Set manual exposure value from 2 us to 200,000us for the RGB camera (default: auto exposure). This control also supports MFC settings of -11 to 1).")
+            .takes_value(true))
         .arg(Arg::with_name("gain")
             .long("gain")
             .short("g")
-            .help("Set cameras manual gain. The valid range is 0 to 255. (default: auto)"))
+            .help("Set cameras manual gain. The valid range is 0 to 255. (default: auto)")
+            .takes_value(true))
+        .arg(Arg::with_name("config")
+            .long("config")
+            .help("Read the recording settings from a TOML file, or a JSON file with the extension .json\nFlags on the command line override the file.")
+            .takes_value(true))
+        .arg(Arg::with_name("dump-config")
+            .long("dump-config")
+            .help("Print the effective recording settings as TOML and exit"))
         .arg(Arg::with_name("OUTPUT")
-            .help("Sets the output file"))
+            .help("Sets the output file\n{serial}, {index} and {datetime} are replaced for each device."))
+        .subcommand(SubCommand::with_name("info")
+            .about("Print the configuration, tracks, tags and attachments of a recording")
+            .arg(Arg::with_name("json")
+                .long("json")
+                .help("Print as JSON"))
+            .arg(Arg::with_name("FILE")
+                .help("Recording to inspect")
+                .required(true)))
+        .subcommand(SubCommand::with_name("extract")
+            .about("Write the frames of a recording to image files and the timestamps and IMU samples to CSV files")
+            .arg(Arg::with_name("output-dir")
+                .long("output-dir")
+                .short("o")
+                .help("Directory to write the files to")
+                .default_value("."))
+            .arg(Arg::with_name("start")
+                .long("start")
+                .help("Skip the first N seconds of the recording")
+                .takes_value(true))
+            .arg(Arg::with_name("end")
+                .long("end")
+                .help("Stop at N seconds from the start of the recording")
+                .takes_value(true))
+            .arg(Arg::with_name("stride")
+                .long("stride")
+                .help("Extract every N-th capture")
+                .default_value("1"))
+            .arg(Arg::with_name("streams")
+                .long("streams")
+                .help("Comma separated streams to extract, Available options:\ncolor, depth, ir, imu")
+                .default_value("color,depth,ir,imu"))
+            .arg(Arg::with_name("color-format")
+                .long("color-format")
+                .help("Set the file format of color frames (jpg, png)\nOnly MJPG recordings can be written as jpg, others are always written as png.")
+                .default_value("jpg"))
+            .arg(Arg::with_name("live")
+                .long("live")
+                .help("Extract from the device given by --device, started with the recording settings, instead of a recording\n--end is required.")
+                .conflicts_with("FILE"))
+            .arg(Arg::with_name("FILE")
+                .help("Recording to extract")
+                .required_unless("live")))
+        .subcommand(SubCommand::with_name("trim")
+            .about("Write a time window of a recording to a new recording")
+            .arg(Arg::with_name("output")
+                .long("output")
+                .short("o")
+                .help("Sets the output file")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("start")
+                .long("start")
+                .help("Skip the first N seconds of the recording")
+                .takes_value(true))
+            .arg(Arg::with_name("end")
+                .long("end")
+                .help("Stop at N seconds from the start of the recording")
+                .takes_value(true))
+            .arg(Arg::with_name("FILE")
+                .help("Recording to trim")
+                .required(true)))
+        .subcommand(SubCommand::with_name("concat")
+            .about("Join recordings of the same device into one recording, each one starting after the previous one")
+            .arg(Arg::with_name("output")
+                .long("output")
+                .short("o")
+                .help("Sets the output file")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("FILE")
+                .help("Recordings to join, in order")
+                .multiple(true)
+                .min_values(2)
+                .required(true)))
+}
+
+fn to_extract_parameter<'a>(args: &ArgMatches) -> Result<ExtractParameter, Error<'a>> {
+    let streams = to_streams(args.value_of("streams").unwrap_or(""))?;
+    let param = ExtractParameter {
+        path: args.value_of("FILE").unwrap_or("").to_string(),
+        live: args.is_present("live"),
+        output_dir: args.value_of("output-dir").unwrap_or(".").to_string(),
+        start: to_seconds(args.value_of("start"))?,
+        end: to_seconds(args.value_of("end"))?,
+        stride: match args.value_of("stride").unwrap_or("1").parse() {
+            Ok(stride) if stride > 0 => stride,
+            _ => return Err(Error::ErrorStr("--stride must be a positive integer.")),
+        },
+        color: streams[0],
+        depth: streams[1],
+        ir: streams[2],
+        imu: streams[3],
+        color_png: to_color_png(args.value_of("color-format").unwrap_or("jpg"))?,
+    };
+    if let (Some(start), Some(end)) = (param.start, param.end) {
+        if end < start {
+            return Err(Error::ErrorStr("--end must not be before --start."));
+        }
+    }
+    if param.live && param.end.is_none() {
+        return Err(Error::ErrorStr("--end is required with --live."));
+    }
+    Ok(param)
+}
+
+fn to_trim_parameter<'a>(args: &ArgMatches) -> Result<TrimParameter, Error<'a>> {
+    let param = TrimParameter {
+        path: args.value_of("FILE").unwrap_or("").to_string(),
+        output: args.value_of("output").unwrap_or("").to_string(),
+        start: to_seconds(args.value_of("start"))?,
+        end: to_seconds(args.value_of("end"))?,
+    };
+    if let (Some(start), Some(end)) = (param.start, param.end) {
+        if end < start {
+            return Err(Error::ErrorStr("--end must not be before --start."));
+        }
+    }
+    Ok(param)
 }
 
 fn correct_param<T: Ord + core::str::FromStr, U: Ord, F: Fn(T) -> U>(
@@ -238,6 +538,84 @@ fn to_external_sync<'a>(value: &str) -> Result<WiredSyncMode, Error<'a>> {
     }
 }
 
+fn to_devices<'a>(value: &str) -> Result<Vec<String>, Error<'a>> {
+    let devices: Vec<String> = value
+        .split(',')
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+        .collect();
+    if devices.is_empty() {
+        return Err(Error::ErrorStr("No device specified."));
+    }
+    if let Some(d) = devices
+        .iter()
+        .enumerate()
+        .find(|(i, d)| devices[..*i].contains(d))
+        .map(|(_, d)| d)
+    {
+        return Err(Error::Error(format!(
+            "Device specified more than once: {}",
+            d
+        )));
+    }
+    Ok(devices)
+}
+
+fn to_sync_delays<'a>(value: &str) -> Result<Vec<u32>, Error<'a>> {
+    value
+        .split(',')
+        .map(|d| d.trim())
+        .map(|d| {
+            d.parse()
+                .map_err(|_| Error::Error(format!("Invalid sync delay specified: {}", d)))
+        })
+        .collect()
+}
+
+fn to_seconds<'a>(value: Option<&str>) -> Result<Option<Duration>, Error<'a>> {
+    match value.map(|v| v.parse::<f64>()) {
+        None => Ok(None),
+        Some(Ok(seconds)) if seconds >= 0.0 && seconds.is_finite() => {
+            Ok(Some(Duration::from_secs_f64(seconds)))
+        }
+        Some(_) => Err(Error::Error(format!(
+            "Invalid time specified: {}",
+            value.unwrap_or("")
+        ))),
+    }
+}
+
+//  color, depth, ir, imu
+fn to_streams<'a>(value: &str) -> Result<[bool; 4], Error<'a>> {
+    let mut streams = [false; 4];
+    for stream in value.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        match stream.to_ascii_lowercase().as_str() {
+            "color" => streams[0] = true,
+            "depth" => streams[1] = true,
+            "ir" => streams[2] = true,
+            "imu" => streams[3] = true,
+            _ => {
+                return Err(Error::Error(format!(
+                    "Unknown stream specified: {}",
+                    stream
+                )))
+            }
+        }
+    }
+    Ok(streams)
+}
+
+fn to_color_png<'a>(value: &str) -> Result<bool, Error<'a>> {
+    match value.to_ascii_lowercase().as_str() {
+        "jpg" | "jpeg" => Ok(false),
+        "png" => Ok(true),
+        _ => Err(Error::Error(format!(
+            "Unknown color file format specified: {}",
+            value
+        ))),
+    }
+}
+
 #[test]
 fn conv_param_test() {
     assert_eq!(
@@ -322,4 +700,138 @@ fn conv_param_test() {
         WiredSyncMode::Standalone
     );
     assert!(to_external_sync("as098kasd").is_err());
+
+    assert_eq!(to_streams("color,ir").unwrap(), [true, false, true, false]);
+    assert_eq!(
+        to_streams("DEPTH, imu").unwrap(),
+        [false, true, false, true]
+    );
+    assert!(to_streams("color,audio").is_err());
+
+    assert!(!to_color_png("jpg").unwrap());
+    assert!(to_color_png("PNG").unwrap());
+    assert!(to_color_png("bmp").is_err());
+
+    assert_eq!(to_seconds(None).unwrap(), None);
+    assert_eq!(
+        to_seconds(Some("1.5")).unwrap(),
+        Some(Duration::from_millis(1500))
+    );
+    assert!(to_seconds(Some("-1")).is_err());
+
+    assert_eq!(to_devices("0").unwrap(), vec!["0"]);
+    assert_eq!(
+        to_devices("1, 000123456789").unwrap(),
+        vec!["1", "000123456789"]
+    );
+    assert!(to_devices("0,1,0").is_err());
+    assert!(to_devices(",").is_err());
+    assert_eq!(to_sync_delays("0").unwrap(), vec![0]);
+    assert_eq!(to_sync_delays("0,160,320").unwrap(), vec![0, 160, 320]);
+    assert!(to_sync_delays("0,abc").is_err());
+
+    let args = create_app().get_matches_from(vec![
+        "k4arecorder",
+        "extract",
+        "--start",
+        "1.5",
+        "--end",
+        "3",
+        "x.mkv",
+    ]);
+    let extract = to_extract_parameter(args.subcommand_matches("extract").unwrap()).unwrap();
+    assert_eq!(extract.start, Some(Duration::from_millis(1500)));
+    assert_eq!(extract.end, Some(Duration::from_secs(3)));
+    assert_eq!(extract.path, "x.mkv");
+    assert!(!extract.live);
+
+    let args = create_app().get_matches_from(vec!["k4arecorder", "extract", "--live"]);
+    assert!(to_extract_parameter(args.subcommand_matches("extract").unwrap()).is_err());
+    let args =
+        create_app().get_matches_from(vec!["k4arecorder", "extract", "--live", "--end", "2"]);
+    assert!(
+        to_extract_parameter(args.subcommand_matches("extract").unwrap())
+            .unwrap()
+            .live
+    );
+    assert!(create_app()
+        .get_matches_from_safe(vec!["k4arecorder", "extract"])
+        .is_err());
+}
+
+#[test]
+fn config_param_test() {
+    let path = std::env::temp_dir().join("k4arecorder_config_param_test.toml");
+    std::fs::write(
+        &path,
+        r#"
+        color_format = "BGRA32"
+        color_resolution = "720p"
+        depth_mode = "WFOV_2X2BINNED"
+        camera_fps = 15
+        imu = false
+        record_length = 10
+        output = "take-{datetime}.mkv"
+
+        [color_controls]
+        exposure_time_absolute = { mode = "manual", value = 8330 }
+        brightness = { mode = "manual", value = 100 }
+
+        [tags]
+        SITE = "lab"
+        "#,
+    )
+    .unwrap();
+    let config = path.to_string_lossy().to_string();
+
+    let param = Parameter::from(create_app().get_matches_from(vec![
+        "k4arecorder",
+        "--config",
+        &config,
+        "--rate",
+        "5",
+        "--gain",
+        "10",
+    ]))
+    .unwrap();
+    assert_eq!(param.device_config.color_format(), ImageFormat::BGRA32);
+    assert_eq!(param.device_config.depth_mode(), DepthMode::WFov2x2Binned);
+    assert_eq!(param.device_config.camera_fps(), Fps::_5fps);
+    assert!(!param.record_imu);
+    assert_eq!(param.recording_length, Some(Duration::from_secs(10)));
+    assert_eq!(param.recording_filename, "take-{datetime}.mkv");
+    assert_eq!(param.absolute_exposure_value, Some(8330));
+    assert_eq!(param.gain, Some(10));
+    assert_eq!(
+        param.color_controls,
+        vec![(
+            ColorControlCommand::Brightness,
+            ColorControlMode::Manual,
+            100
+        )]
+    );
+    assert_eq!(param.tags, vec![("SITE".to_string(), "lab".to_string())]);
+
+    //  The dumped settings read back to the same parameters
+    let dumped = RecorderConfig::from_parameter(&param);
+    std::fs::write(&path, dumped.to_toml().unwrap()).unwrap();
+    let reloaded = Parameter::from(create_app().get_matches_from(vec![
+        "k4arecorder",
+        "--config",
+        &config,
+        "--color-mode",
+        "1080p",
+    ]))
+    .unwrap();
+    assert_eq!(reloaded.device_config.camera_fps(), Fps::_5fps);
+    assert_eq!(reloaded.device_config.color_format(), ImageFormat::MJPG);
+    assert_eq!(
+        reloaded.device_config.color_resolution(),
+        ColorResolution::_1080p
+    );
+    assert_eq!(reloaded.gain, Some(10));
+    assert_eq!(reloaded.tags, param.tags);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(RecorderConfig::load("not_found.toml").is_err());
 }
@@ -1,6 +1,6 @@
 use crate::param::Parameter;
 use azure_kinect::*;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[derive(Debug)]
 pub enum Error<'a> {
@@ -50,42 +50,34 @@ pub(crate) fn do_recording<F: Fn() -> bool>(
     param: &Parameter,
     request_abort: F,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let installed_devices = factory.core().device_get_installed_count();
-    if param.device_index >= installed_devices {
-        return Err(Box::new(Error::ErrorStr("Device not found.")));
-    }
-
-    let mut device = match factory.core().device_open(param.device_index) {
-        Ok(device) => device,
-        Err(_) => {
-            return Err(Box::new(Error::ErrorStr(
-                "Runtime error: k4a_device_open() failed ",
-            )))
-        }
-    };
+    let devices = open_devices(factory.core(), &param.devices)?;
 
-    let serial_number = device.get_serialnum()?;
-    println!("Device serial number: {}", serial_number);
-    let version_info = device.get_version()?;
-    print!(
-        "Device version: {}",
-        if version_info.firmware_build() == FirmwareBuildType::Release {
-            "Rel"
-        } else {
-            "Dbg"
-        }
-    );
-    print!("; C: {}", version_info.rgb());
-    print!(
-        "; D: {}[{}.{}]",
-        version_info.depth(),
-        version_info.depth_sensor().major(),
-        version_info.depth_sensor().minor()
-    );
-    println!("; A: {}", version_info.audio());
+    let mut serial_numbers = Vec::new();
+    for device in devices.iter() {
+        let serial_number = device.get_serialnum()?;
+        println!("Device serial number: {}", serial_number);
+        let version_info = device.get_version()?;
+        print!(
+            "Device version: {}",
+            if version_info.firmware_build() == FirmwareBuildType::Release {
+                "Rel"
+            } else {
+                "Dbg"
+            }
+        );
+        print!("; C: {}", version_info.rgb());
+        print!(
+            "; D: {}[{}.{}]",
+            version_info.depth(),
+            version_info.depth_sensor().major(),
+            version_info.depth_sensor().minor()
+        );
+        println!("; A: {}", version_info.audio());
+        serial_numbers.push(serial_number);
+    }
 
     let camera_fps = param.device_config.camera_fps().get_u32();
-    if camera_fps <= 0
+    if camera_fps == 0
         || (param.device_config.color_resolution() == ColorResolution::Off
             && param.device_config.depth_mode() == DepthMode::Off)
     {
@@ -94,97 +86,108 @@ pub(crate) fn do_recording<F: Fn() -> bool>(
         )));
     }
 
-    if let Some(absolute_exposure_value) = param.absolute_exposure_value {
-        if let Err(_) = device.set_color_control(
-            ColorControlCommand::ExposureTimeAbsolute,
-            ColorControlMode::Manual,
-            absolute_exposure_value,
-        ) {
-            eprintln!("Runtime error: k4a_device_set_color_control() for manual exposure failed ");
-        }
-    } else {
-        if let Err(_) = device.set_color_control(
-            ColorControlCommand::ExposureTimeAbsolute,
-            ColorControlMode::Auto,
-            0,
-        ) {
-            eprintln!("Runtime error: k4a_device_set_color_control() for auto exposure failed ");
-        }
+    for device in devices.iter() {
+        set_color_controls(device, param);
     }
 
-    if let Some(gain) = param.gain {
-        if let Err(_) =
-            device.set_color_control(ColorControlCommand::Gain, ColorControlMode::Manual, gain)
-        {
-            eprintln!("Runtime error: k4a_device_set_color_control() for manual gain failed ");
-        }
-    } else {
-        if let Err(_) =
-            device.set_color_control(ColorControlCommand::Gain, ColorControlMode::Auto, 0)
-        {
-            eprintln!("Runtime error: k4a_device_set_color_control() for auto gain failed ");
-        }
+    let device_configs = device_configurations(&devices, &serial_numbers, param)?;
+    let recording_filenames = recording_filenames(
+        &param.recording_filename,
+        &serial_numbers,
+        SystemTime::now(),
+    );
+
+    //  Subordinates wait for the sync signal, so the master is started last.
+    let mut start_order: Vec<usize> = (0..devices.len()).collect();
+    start_order.sort_by_key(|&i| device_configs[i].wired_sync_mode() == WiredSyncMode::Master);
+    let mut started: Vec<Option<Camera>> = devices.iter().map(|_| None).collect();
+    for &i in start_order.iter() {
+        started[i] = Some(devices[i].start_cameras(&device_configs[i])?);
     }
+    let cameras: Vec<Camera> = started.into_iter().flatten().collect();
+    let imus = cameras
+        .iter()
+        .map(|camera| {
+            if param.record_imu {
+                camera.start_imu().map(Option::Some)
+            } else {
+                Ok(Option::None)
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let camera = device.start_cameras(&param.device_config)?;
-    let imu = if param.record_imu {
-        Option::Some(camera.start_imu()?)
+    if devices.len() == 1 {
+        println!("Device started");
     } else {
-        Option::None
-    };
-
-    println!("Device started");
-
-    let recording =
-        match factory.record_create(param.recording_filename.as_str(), &device, unsafe {
-            std::mem::transmute(&param.device_config)
-        }) {
-            Ok(recording) => recording,
-            Err(_) => {
-                return Err(Box::new(Error::Error(format!(
-                    "Unable to create recording file: {}",
-                    param.recording_filename
-                ))))
-            }
-        };
+        println!("{} devices started", devices.len());
+    }
 
-    if imu.is_some() {
-        recording.add_imu_track()?;
+    let session_id = session_id();
+    let mut recordings = Vec::new();
+    for (i, device) in devices.iter().enumerate() {
+        let recording =
+            match factory.record_create(&recording_filenames[i], device, &device_configs[i]) {
+                Ok(recording) => recording,
+                Err(_) => {
+                    return Err(Box::new(Error::Error(format!(
+                        "Unable to create recording file: {}",
+                        recording_filenames[i]
+                    ))))
+                }
+            };
+        if imus[i].is_some() {
+            recording.add_imu_track()?;
+        }
+        for (name, value) in param.tags.iter() {
+            recording.add_tag(name, value)?;
+        }
+        if devices.len() > 1 {
+            recording.add_tag(SESSION_ID_TAG, &session_id)?;
+        }
+        recording.write_header()?;
+        recordings.push(recording);
     }
-    recording.write_header()?;
 
     // Wait for the first capture before starting recording.
-    let timeout_sec_for_first_capture =
-        if param.device_config.wired_sync_mode() == WiredSyncMode::Subordinate {
-            println!("[subordinate mode] Waiting for signal from master");
-            360u64
-        } else {
-            60u64
-        };
+    let timeout_sec_for_first_capture = if device_configs
+        .iter()
+        .any(|c| c.wired_sync_mode() == WiredSyncMode::Subordinate)
+    {
+        println!("[subordinate mode] Waiting for signal from master");
+        360u64
+    } else {
+        60u64
+    };
 
     let first_capture = Processing::new(Some(Duration::from_secs(timeout_sec_for_first_capture)));
-    let mut first_captured = false;
-    while first_capture.is_processing() && !request_abort() {
-        match camera.get_capture(100) {
-            Err(azure_kinect::Error::Timeout) => continue,
-            Err(e) => {
-                return Err(Box::new(Error::Error(format!(
-                    "Runtime error: k4a_device_get_capture() returned error: {}",
-                    e
-                ))))
-            }
-            _ => (),
-        };
-        first_captured = true;
-        break;
-    }
+    for camera in cameras.iter() {
+        let mut first_captured = false;
+        let mut captures = camera
+            .captures()
+            .timeout(100)
+            .on_timeout(TimeoutBehavior::Yield);
+        while first_capture.is_processing() && !request_abort() {
+            match captures.next() {
+                Some(Err(azure_kinect::Error::Timeout)) => continue,
+                Some(Err(e)) => {
+                    return Err(Box::new(Error::Error(format!(
+                        "Runtime error: k4a_device_get_capture() returned error: {}",
+                        e
+                    ))))
+                }
+                _ => (),
+            };
+            first_captured = true;
+            break;
+        }
 
-    if request_abort() {
-        return Ok(());
-    } else if !first_captured {
-        return Err(Box::new(Error::ErrorStr(
-            "Timed out waiting for first capture.",
-        )));
+        if request_abort() {
+            return Ok(());
+        } else if !first_captured {
+            return Err(Box::new(Error::ErrorStr(
+                "Timed out waiting for first capture.",
+            )));
+        }
     }
 
     println!("Started recording");
@@ -192,53 +195,52 @@ pub(crate) fn do_recording<F: Fn() -> bool>(
         println!("Press Ctrl-C to stop recording.");
     }
 
-    let camera_timeout_ms = 1000 / camera_fps;
+    //  The devices are polled in turn, within one frame period.
+    let camera_timeout_ms = 1000 / camera_fps / cameras.len() as u32;
 
     let recording_process = Processing::new(param.recording_length);
     while recording_process.is_processing() && !request_abort() {
-        let capture = match camera.get_capture(camera_timeout_ms as i32) {
-            Ok(c) => c,
-            Err(azure_kinect::Error::Timeout) => continue,
-            Err(e) => {
-                return Err(Box::new(Error::Error(format!(
-                    "Runtime error: k4a_device_get_capture() returned {}",
-                    e
-                ))))
-            }
-        };
+        for ((camera, imu), recording) in cameras.iter().zip(imus.iter()).zip(recordings.iter()) {
+            let capture = match camera.get_capture(camera_timeout_ms as i32) {
+                Ok(c) => c,
+                Err(azure_kinect::Error::Timeout) => continue,
+                Err(e) => {
+                    return Err(Box::new(Error::Error(format!(
+                        "Runtime error: k4a_device_get_capture() returned {}",
+                        e
+                    ))))
+                }
+            };
 
-        match recording.write_capture(&capture) {
-            Err(e) => {
+            if let Err(e) = recording.write_capture(&capture) {
                 return Err(Box::new(Error::Error(format!(
-                    "Runtime error: k4a_record_write_imu_sample() returned {}",
+                    "Runtime error: k4a_record_write_capture() returned {}",
                     e
-                ))))
+                ))));
             }
-            _ => (),
-        };
 
-        if imu.is_some() {
-            while recording_process.is_processing() && !request_abort() {
-                let sample = match imu.as_ref().unwrap().get_imu_sample(0) {
-                    Ok(s) => s,
-                    Err(azure_kinect::Error::Timeout) => break,
-                    Err(e) => {
-                        return Err(Box::new(Error::Error(format!(
-                            "Runtime error: k4a_imu_get_sample() returned {}",
-                            e
-                        ))))
-                    }
-                };
+            if let Some(imu) = imu.as_ref() {
+                //  Checked before each read, a sample taken from the device is always written
+                let mut samples = imu.samples().timeout(0).on_timeout(TimeoutBehavior::Yield);
+                while recording_process.is_processing() && !request_abort() {
+                    let sample = match samples.next() {
+                        Some(Ok(s)) => s,
+                        Some(Err(azure_kinect::Error::Timeout)) | None => break,
+                        Some(Err(e)) => {
+                            return Err(Box::new(Error::Error(format!(
+                                "Runtime error: k4a_imu_get_sample() returned {}",
+                                e
+                            ))))
+                        }
+                    };
 
-                match recording.write_imu_sample(&sample) {
-                    Err(e) => {
+                    if let Err(e) = recording.write_imu_sample(&sample) {
                         return Err(Box::new(Error::Error(format!(
                             "Runtime error: k4a_record_write_imu_sample() returned {}",
                             e
-                        ))))
+                        ))));
                     }
-                    _ => (),
-                };
+                }
             }
         }
     }
@@ -247,13 +249,340 @@ pub(crate) fn do_recording<F: Fn() -> bool>(
         println!("Stopping recording...");
     }
 
-    std::mem::drop(imu);
-    std::mem::drop(camera);
+    std::mem::drop(imus);
+    std::mem::drop(cameras);
 
     println!("Saving recording...");
-    recording.flush()?;
-    std::mem::drop(recording);
+    for recording in recordings.iter() {
+        recording.flush()?;
+    }
+    std::mem::drop(recordings);
 
     println!("Done");
     Ok(())
 }
+
+/// Opens the devices given by index or serial number.
+pub(crate) fn open_devices<'a>(
+    factory: &'a Factory,
+    selectors: &[String],
+) -> Result<Vec<Device<'a>>, Box<dyn std::error::Error>> {
+    let installed_devices = factory.device_get_installed_count();
+    let mut indices = Vec::new();
+    for selector in selectors {
+        let index = match selector.parse::<u32>() {
+            Ok(index) if index < installed_devices => Some(index),
+            _ => (0..installed_devices).find(|&i| {
+                factory
+                    .device_open(i)
+                    .and_then(|device| device.get_serialnum())
+                    .is_ok_and(|serial_number| serial_number == *selector)
+            }),
+        };
+        match index {
+            Some(index) if indices.contains(&index) => {
+                return Err(Box::new(Error::Error(format!(
+                    "Device specified more than once: {}",
+                    selector
+                ))))
+            }
+            Some(index) => indices.push(index),
+            None => {
+                return Err(Box::new(Error::Error(format!(
+                    "Device not found: {}",
+                    selector
+                ))))
+            }
+        }
+    }
+
+    let mut devices = Vec::new();
+    for index in indices {
+        match factory.device_open(index) {
+            Ok(device) => devices.push(device),
+            Err(_) => {
+                return Err(Box::new(Error::ErrorStr(
+                    "Runtime error: k4a_device_open() failed ",
+                )))
+            }
+        }
+    }
+    Ok(devices)
+}
+
+pub(crate) fn set_color_controls(device: &Device, param: &Parameter) {
+    if let Some(absolute_exposure_value) = param.absolute_exposure_value {
+        if device
+            .set_color_control(
+                ColorControlCommand::ExposureTimeAbsolute,
+                ColorControlMode::Manual,
+                absolute_exposure_value,
+            )
+            .is_err()
+        {
+            eprintln!("Runtime error: k4a_device_set_color_control() for manual exposure failed ");
+        }
+    } else {
+        if device
+            .set_color_control(
+                ColorControlCommand::ExposureTimeAbsolute,
+                ColorControlMode::Auto,
+                0,
+            )
+            .is_err()
+        {
+            eprintln!("Runtime error: k4a_device_set_color_control() for auto exposure failed ");
+        }
+    }
+
+    if let Some(gain) = param.gain {
+        if device
+            .set_color_control(ColorControlCommand::Gain, ColorControlMode::Manual, gain)
+            .is_err()
+        {
+            eprintln!("Runtime error: k4a_device_set_color_control() for manual gain failed ");
+        }
+    } else {
+        if device
+            .set_color_control(ColorControlCommand::Gain, ColorControlMode::Auto, 0)
+            .is_err()
+        {
+            eprintln!("Runtime error: k4a_device_set_color_control() for auto gain failed ");
+        }
+    }
+
+    for &(command, mode, value) in param.color_controls.iter() {
+        if device.set_color_control(command, mode, value).is_err() {
+            eprintln!(
+                "Runtime error: k4a_device_set_color_control() for {:?} failed ",
+                command
+            );
+        }
+    }
+}
+
+/// Configuration of each device. With several devices, the one with only the sync out cable
+/// connected is the master and all others are subordinates.
+fn device_configurations(
+    devices: &[Device],
+    serial_numbers: &[String],
+    param: &Parameter,
+) -> Result<Vec<DeviceConfiguration>, Box<dyn std::error::Error>> {
+    if devices.len() == 1 {
+        return Ok(vec![param.device_config]);
+    }
+    let sync_jacks = devices
+        .iter()
+        .map(|device| device.is_sync_connected())
+        .collect::<Result<Vec<_>, _>>()?;
+    let masters: Vec<usize> = (0..devices.len())
+        .filter(|&i| !sync_jacks[i].0 && sync_jacks[i].1)
+        .collect();
+    if masters.len() != 1 {
+        return Err(Box::new(Error::Error(format!(
+            "Exactly one device must have only the sync out cable connected, found {}.",
+            masters.len()
+        ))));
+    }
+
+    let mut configs = Vec::new();
+    for (i, &(sync_in, _)) in sync_jacks.iter().enumerate() {
+        let delay = if param.sync_delays.len() == 1 {
+            param.sync_delays[0]
+        } else {
+            param.sync_delays[i]
+        };
+        let (mode, delay) = if i == masters[0] {
+            if param.sync_delays.len() > 1 && delay > 0 {
+                return Err(Box::new(Error::Error(format!(
+                    "--sync-delay of the master device {} must be 0.",
+                    serial_numbers[i]
+                ))));
+            }
+            (WiredSyncMode::Master, 0)
+        } else if sync_in {
+            (WiredSyncMode::Subordinate, delay)
+        } else {
+            return Err(Box::new(Error::Error(format!(
+                "The sync in cable of device {} is not connected.",
+                serial_numbers[i]
+            ))));
+        };
+        let c = &param.device_config;
+        configs.push(
+            DeviceConfiguration::builder()
+                .color_format(c.color_format())
+                .color_resolution(c.color_resolution())
+                .depth_mode(c.depth_mode())
+                .camera_fps(c.camera_fps())
+                .synchronized_images_only(c.synchronized_images_only())
+                .depth_delay_off_color_usec(c.depth_delay_off_color_usec())
+                .wired_sync_mode(mode)
+                .subordinate_delay_off_master_usec(delay)
+                .disable_streaming_indicator(c.disable_streaming_indicator())
+                .build(),
+        );
+    }
+    Ok(configs)
+}
+
+//  Tag shared by the recordings of one run with several devices.
+const SESSION_ID_TAG: &str = "K4ARECORDER_SESSION_ID";
+
+fn session_id() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{:x}-{:x}", now.as_nanos(), std::process::id())
+}
+
+/// Expands `{serial}`, `{index}` and `{datetime}` (UTC) in the output file of each device.
+/// Several devices without `{serial}` or `{index}` get the serial number appended.
+fn recording_filenames(
+    template: &str,
+    serial_numbers: &[String],
+    start_time: SystemTime,
+) -> Vec<String> {
+    let template = template.replace("{datetime}", &utc_datetime(start_time));
+    let per_device = template.contains("{serial}") || template.contains("{index}");
+    serial_numbers
+        .iter()
+        .enumerate()
+        .map(|(index, serial_number)| {
+            let filename = template
+                .replace("{serial}", serial_number)
+                .replace("{index}", &index.to_string());
+            if serial_numbers.len() > 1 && !per_device {
+                device_filename(&filename, serial_number)
+            } else {
+                filename
+            }
+        })
+        .collect()
+}
+
+//  YYYYMMDD-hhmmss
+fn utc_datetime(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, secs_of_day) = ((secs / 86400) as i64, secs % 86400);
+    //  Civil date from days since 1970-01-01, http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+/// `output.mkv` becomes `output-<serial number>.mkv`.
+fn device_filename(recording_filename: &str, serial_number: &str) -> String {
+    let path = std::path::Path::new(recording_filename);
+    let stem = path
+        .file_stem()
+        .map_or(String::new(), |s| s.to_string_lossy().to_string());
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, serial_number, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, serial_number),
+    };
+    path.with_file_name(file_name).to_string_lossy().to_string()
+}
+
+#[test]
+fn record_synchronized_test() -> Result<(), Box<dyn std::error::Error>> {
+    //  Recordings of the fake SDK are kept in memory by path, device 0 is cabled as the master of device 1
+    let factory = FactoryRecord::with_api(
+        azure_kinect_sys::mock::api(),
+        azure_kinect_sys::mock::api_record(),
+    );
+    let serial_numbers = ["000001000000", "000001000001"];
+    let mut param = Parameter {
+        list_device: false,
+        dump_config: false,
+        info: None,
+        extract: None,
+        trim: None,
+        concat: None,
+        devices: vec!["1".to_string(), serial_numbers[0].to_string()],
+        recording_filename: "sync_test.mkv".to_string(),
+        recording_length: Some(Duration::from_millis(200)),
+        device_config: DeviceConfiguration::builder()
+            .color_resolution(ColorResolution::_720p)
+            .depth_mode(DepthMode::NFovUnbinned)
+            .camera_fps(Fps::_30fps)
+            .build(),
+        sync_delays: vec![160, 0],
+        record_imu: true,
+        absolute_exposure_value: None,
+        gain: None,
+        color_controls: vec![(
+            ColorControlCommand::Brightness,
+            ColorControlMode::Manual,
+            100,
+        )],
+        tags: vec![("SITE".to_string(), "lab".to_string())],
+    };
+    do_recording(&factory, &param, || false)?;
+
+    let master = factory.playback_open(&device_filename("sync_test.mkv", serial_numbers[0]))?;
+    let subordinate =
+        factory.playback_open(&device_filename("sync_test.mkv", serial_numbers[1]))?;
+    let master_config = master.get_record_configuration()?;
+    let subordinate_config = subordinate.get_record_configuration()?;
+    assert_eq!(master_config.wired_sync_mode(), WiredSyncMode::Master);
+    assert_eq!(
+        subordinate_config.wired_sync_mode(),
+        WiredSyncMode::Subordinate
+    );
+    assert_eq!(subordinate_config.subordinate_delay_off_master_usec(), 160);
+    assert!(master_config.imu_track_enabled());
+    assert_eq!(
+        master.get_tag("K4A_DEVICE_SERIAL_NUMBER")?,
+        serial_numbers[0]
+    );
+    assert_eq!(
+        master.get_tag(SESSION_ID_TAG)?,
+        subordinate.get_tag(SESSION_ID_TAG)?
+    );
+    assert_eq!(subordinate.get_tag("SITE")?, "lab");
+    assert!(master.get_next_capture().is_ok());
+    assert!(subordinate.get_next_capture().is_ok());
+
+    //  The master can not be delayed
+    param.sync_delays = vec![0, 160];
+    assert!(do_recording(&factory, &param, || false).is_err());
+    param.devices = vec!["0".to_string(), "123".to_string()];
+    assert!(do_recording(&factory, &param, || false).is_err());
+
+    assert_eq!(device_filename("out.mkv", "0001"), "out-0001.mkv");
+    assert_eq!(device_filename("dir/out", "0001"), "dir/out-0001");
+    let serial_numbers = vec!["0001".to_string(), "0002".to_string()];
+    let start_time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    assert_eq!(
+        recording_filenames("take-{datetime}.mkv", &serial_numbers[..1], start_time),
+        vec!["take-20231114-221320.mkv"]
+    );
+    assert_eq!(
+        recording_filenames("{index}_{serial}.mkv", &serial_numbers, start_time),
+        vec!["0_0001.mkv", "1_0002.mkv"]
+    );
+    assert_eq!(
+        recording_filenames("out.mkv", &serial_numbers, start_time),
+        vec!["out-0001.mkv", "out-0002.mkv"]
+    );
+    Ok(())
+}